@@ -1,5 +1,5 @@
 //! Example client for X server.
-//! Currenly can only connect to unix socket without any setup.
+//! Currenly can only connect to unix socket.
 //! In real life scenario i would recomment to use anyhow as
 //! a main resulting type
 
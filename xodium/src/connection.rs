@@ -1,17 +1,83 @@
+mod address;
+mod atom;
+mod color;
+mod cursor;
+mod damage;
+mod dpms;
+mod event;
+mod ewmh;
+mod extension;
+mod font;
+mod grab;
+mod graphics;
+mod host;
+mod id;
+mod image;
+mod keyboard;
+mod misc;
+mod pipeline;
+mod pixmap;
+mod pointer;
+mod present;
+mod property;
+mod render;
+mod screen_saver;
+mod selection;
+mod shape;
+mod window;
+mod xfixes;
+mod xinerama;
+mod xinput;
+mod xkb;
+mod xtest;
+
+use self::address::{Address, DeadlineSocket};
+use self::atom::AtomCache;
+pub use self::event::Events;
+pub use self::font::Font;
+use self::grab::PassiveGrab;
+pub use self::graphics::DrawBatch;
+use self::id::IdAllocator;
+pub use self::misc::ServerGrab;
+pub use self::pipeline::{Cookie, Pipeline, Replies};
+pub use self::window::WindowNode;
 use crate::display::{Display, DisplayError};
 use crate::framed::Framed;
-use crate::protocol::SetupCodec;
+use crate::protocol::{
+    ByteOrder, Deserialize, Event, Extension, Message, MessageCodec, Screen, Serialize, Setup,
+    SetupCodec, SetupRequest, SetupResponse, Trace, XError,
+};
 use crate::utils::StreamMarker;
+use crate::xauthority;
+use std::cell::Cell;
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
 use std::fmt;
 use std::io;
+#[cfg(unix)]
+use std::os::unix::io::OwnedFd;
 // TODO: Support other platforms
 use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
 
 /// Xodium socket connection error
 #[derive(Debug)]
 pub enum ConnectionError {
     DisplayNotAvailable(DisplayError),
     Io(io::Error),
+    /// X server refused the connection, contains reason sent by server.
+    SetupFailed(String),
+    /// X server requires further authentication, contains reason sent by server.
+    SetupAuthenticate(String),
+    /// None of the addresses display could be reached at,
+    /// contains every address tried paired with the error.
+    Unreachable(Vec<(String, io::Error)>),
+    /// X server has no screen requested by display, e.g. screen 1 for `:0.1`.
+    NoSuchScreen(u16),
+    /// X server accepted the connection, but did not answer setup request in time.
+    Timeout,
 }
 
 impl From<DisplayError> for ConnectionError {
@@ -30,8 +96,22 @@ impl From<ConnectionError> for io::Error {
     fn from(e: ConnectionError) -> io::Error {
         match e {
             ConnectionError::Io(io) => io,
-            ConnectionError::DisplayNotAvailable(disp) => {
-                io::Error::new(io::ErrorKind::Other, disp.to_string())
+            ConnectionError::DisplayNotAvailable(disp) => io::Error::other(disp.to_string()),
+            ConnectionError::SetupFailed(reason) => {
+                io::Error::new(io::ErrorKind::ConnectionRefused, reason)
+            }
+            ConnectionError::SetupAuthenticate(reason) => {
+                io::Error::new(io::ErrorKind::PermissionDenied, reason)
+            }
+            unreachable @ ConnectionError::Unreachable(_) => {
+                io::Error::new(io::ErrorKind::NotFound, unreachable.to_string())
+            }
+            ConnectionError::Timeout => io::Error::new(
+                io::ErrorKind::TimedOut,
+                ConnectionError::Timeout.to_string(),
+            ),
+            no_screen @ ConnectionError::NoSuchScreen(_) => {
+                io::Error::new(io::ErrorKind::NotFound, no_screen.to_string())
             }
         }
     }
@@ -46,51 +126,1220 @@ impl fmt::Display for ConnectionError {
             ConnectionError::Io(e) => {
                 write!(f, "X Connection failed: {}", e)
             }
+            ConnectionError::SetupFailed(reason) => {
+                write!(f, "X Connection refused: {}", reason)
+            }
+            ConnectionError::SetupAuthenticate(reason) => {
+                write!(f, "X Connection requires authentication: {}", reason)
+            }
+            ConnectionError::Unreachable(failures) => {
+                write!(f, "X Connection failed, tried:")?;
+                for (address, e) in failures {
+                    write!(f, " {} ({})", address, e)?;
+                }
+                Ok(())
+            }
+            ConnectionError::NoSuchScreen(screen) => {
+                write!(f, "X Connection failed: no screen {}", screen)
+            }
+            ConnectionError::Timeout => {
+                write!(f, "X Connection failed: setup timed out")
+            }
+        }
+    }
+}
+
+impl Error for ConnectionError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ConnectionError::DisplayNotAvailable(e) => Some(e),
+            ConnectionError::Io(e) => Some(e),
+            // Display lists errors of all addresses, none of them is the sole cause
+            ConnectionError::Unreachable(_) => None,
+            ConnectionError::SetupFailed(_)
+            | ConnectionError::SetupAuthenticate(_)
+            | ConnectionError::NoSuchScreen(_)
+            | ConnectionError::Timeout => None,
         }
     }
 }
 
 /// Connect to X server using DISPLAY environment variable.
 /// Function will try to parse `DISPLAY` variable and connect using parsed data.
+///
+/// Local display is tried at UNIX socket, then at abstract socket (Linux only),
+/// then at TCP port `6000 + display` of localhost.
 pub fn connect_default() -> Result<Connection, ConnectionError> {
     let env_display = Display::from_env()?;
     connect_to_display(env_display)
 }
 
-const DEFAULT_UNIX_X_SERVER_SOCKET_PATH: &str = "/tmp/.X11-unix/X";
+/// Same as [connect_default], but also returns index of the default screen
+/// taken from `DISPLAY`, 0 if it has no screen specified.
+///
+/// ```no_run
+/// # fn main() -> Result<(), xodium::ConnectionError> {
+/// let (connection, screen) = xodium::connect()?;
+/// let root = connection.screen(screen).unwrap().root;
+/// # Ok(())
+/// # }
+/// ```
+pub fn connect() -> Result<(Connection, usize), ConnectionError> {
+    let connection = connect_default()?;
+    let screen = connection.default_screen_index();
+    Ok((connection, screen))
+}
 
 // TODO: Support other platforms
 /// Connect to your specified address using [Display]
+/// First address the display is reachable at is used, see [connect_default].
+///
+/// Local display socket is looked up in `/tmp/.X11-unix`,
+/// or in `$XDG_RUNTIME_DIR/.X11-unix` if the former does not exist.
+/// Xauthority entries matching the display are tried in order until X server accepts one.
 pub fn connect_to_display(display: Display) -> Result<Connection, ConnectionError> {
-    if display.hostname.is_some() {
-        unimplemented!("hostname connections are not supported at the moment");
+    connect_to_display_with_socket_dir(display, &address::default_socket_dir())
+}
+
+/// Same as [connect_to_display], but local display socket is looked up in `socket_dir`,
+/// e.g. in sandbox which has X server sockets mounted elsewhere.
+pub fn connect_to_display_with_socket_dir(
+    display: Display,
+    socket_dir: &Path,
+) -> Result<Connection, ConnectionError> {
+    let authorizations = authorizations_for(&display);
+    connect_with_authorizations(display, socket_dir, &authorizations, None)
+}
+
+/// Same as [connect_to_display], but fails with [Timeout](ConnectionError::Timeout)
+/// if X server does not answer setup request within `setup_timeout`,
+/// e.g. for health checks of X server which accepts connections but hangs.
+/// Connecting the socket itself is not limited, neither are requests after setup.
+pub fn connect_to_display_with_timeout(
+    display: Display,
+    setup_timeout: Duration,
+) -> Result<Connection, ConnectionError> {
+    let authorizations = authorizations_for(&display);
+    connect_with_authorizations(
+        display,
+        &address::default_socket_dir(),
+        &authorizations,
+        Some(setup_timeout),
+    )
+}
+
+/// Same as [connect_to_display], but specified authorization is sent
+/// instead of the one found in Xauthority file, e.g. cookie passed by launcher
+/// when Xauthority file is not available.
+/// Use [Connection::with_stream] to authorize over already open stream.
+pub fn connect_to_display_with_auth(
+    display: Display,
+    auth_name: &str,
+    auth_data: &[u8],
+) -> Result<Connection, ConnectionError> {
+    let authorizations = [(auth_name.to_owned(), auth_data.to_vec())];
+    connect_with_authorizations(
+        display,
+        &address::default_socket_dir(),
+        &authorizations,
+        None,
+    )
+}
+
+/// Connect to the display trying authorizations in order,
+/// see [setup_with_authorizations].
+fn connect_with_authorizations(
+    display: Display,
+    socket_dir: &Path,
+    authorizations: &[(String, Vec<u8>)],
+    setup_timeout: Option<Duration>,
+) -> Result<Connection, ConnectionError> {
+    let candidates = Address::candidates(&display, socket_dir)?;
+    let mut connection = setup_with_authorizations(&candidates, authorizations, setup_timeout)?;
+
+    // Screen does not affect the socket, it only selects the default screen
+    if let Some(screen) = display.screen {
+        if connection.screen(screen.into()).is_none() {
+            return Err(ConnectionError::NoSuchScreen(screen));
+        }
+    }
+
+    connection.display = Some(display);
+    Ok(connection)
+}
+
+/// Local displays with X server socket found in the socket directory
+/// (see [connect_to_display]) or, on Linux, with abstract socket,
+/// ordered by display number. Lets connect without `DISPLAY` set,
+/// e.g. from a service:
+///
+/// ```no_run
+/// # fn main() -> Result<(), xodium::ConnectionError> {
+/// if let Some(display) = xodium::discover_local_displays().into_iter().next() {
+///     let connection = xodium::connect_to_display(display)?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Sockets are not checked to be accepting connections, the stale ones are listed as well.
+pub fn discover_local_displays() -> Vec<Display> {
+    #[allow(unused_mut)]
+    let mut numbers = address::socket_dir_displays(&address::default_socket_dir());
+
+    #[cfg(target_os = "linux")]
+    {
+        let proc_net_unix = std::fs::read_to_string("/proc/net/unix").unwrap_or_default();
+        numbers.extend(address::abstract_socket_displays(&proc_net_unix));
     }
 
-    if display.screen.is_some() {
-        unimplemented!("screen connections are not supported at the moment");
+    displays_from_numbers(numbers)
+}
+
+/// Same as [discover_local_displays], but only sockets in `socket_dir` are looked up.
+pub fn discover_local_displays_in(socket_dir: &Path) -> Vec<Display> {
+    displays_from_numbers(address::socket_dir_displays(socket_dir))
+}
+
+fn displays_from_numbers(mut numbers: Vec<u16>) -> Vec<Display> {
+    numbers.sort_unstable();
+    numbers.dedup();
+    numbers
+        .into_iter()
+        .map(|number| Display::new(None, number, None))
+        .collect()
+}
+
+/// Find authorizations for the display in default Xauthority file, in file order.
+/// Missing or unreadable Xauthority means no authorization is used.
+fn authorizations_for(display: &Display) -> Vec<(String, Vec<u8>)> {
+    let entries = xauthority::read_default().unwrap_or_default();
+
+    let authorizations: Vec<_> = xauthority::find_entries(&entries, display)
+        .into_iter()
+        .map(|entry| (entry.protocol_name.clone(), entry.protocol_data.clone()))
+        .collect();
+
+    if authorizations.is_empty() {
+        return vec![(String::new(), vec![])];
     }
+    authorizations
+}
+
+/// Perform setup handshake trying authorizations in order, each one on a fresh socket
+/// connected to the first address available. If X server refuses all of them,
+/// error of the last one is returned.
+///
+/// If `setup_timeout` is set, all handshakes together must complete within it,
+/// the limit is lifted once setup succeeds.
+fn setup_with_authorizations(
+    candidates: &[Address],
+    authorizations: &[(String, Vec<u8>)],
+    setup_timeout: Option<Duration>,
+) -> Result<Connection, ConnectionError> {
+    let mut failure = None;
+    let deadline = setup_timeout.map(|timeout| Instant::now() + timeout);
 
-    let connection = UnixStream::connect(format!(
-        "{}{}",
-        DEFAULT_UNIX_X_SERVER_SOCKET_PATH, display.display
-    ))?;
+    for (auth_name, auth_data) in authorizations {
+        let socket = address::connect_any(candidates)?;
+        // Socket is boxed by setup, so options are changed through another handle
+        let options = match deadline {
+            Some(_) => Some(socket.try_clone()?),
+            None => None,
+        };
+        let socket_deadline = Rc::new(Cell::new(deadline));
+        let socket = DeadlineSocket::new(socket, socket_deadline.clone());
 
-    Connection::setup(Box::new(connection))
+        match Connection::setup_with_auth(Box::new(socket), auth_name, auth_data) {
+            Ok(connection) => {
+                if let Some(options) = options {
+                    socket_deadline.set(None);
+                    options.set_read_timeout(None)?;
+                }
+                return Ok(connection);
+            }
+            // Read timeout is reported as WouldBlock on Unix and TimedOut on Windows
+            Err(ConnectionError::Io(e))
+                if deadline.is_some()
+                    && matches!(
+                        e.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) =>
+            {
+                return Err(ConnectionError::Timeout)
+            }
+            // E.g. stale cookie, the next entry may hold the valid one
+            Err(e @ ConnectionError::SetupFailed(_))
+            | Err(e @ ConnectionError::SetupAuthenticate(_)) => failure = Some(e),
+            Err(e) => return Err(e),
+        }
+    }
+
+    Err(failure.unwrap_or_else(|| ConnectionError::SetupFailed("No authorization to try".into())))
 }
 
 /// Xodium connection to X server.
 /// Works over any type implementing [Read](std::io::Read) + [Write](std::io::Write).
 /// Use [connect_default] and [connect_to_display] to open the connection.
 pub struct Connection {
-    _framed: Framed<SetupCodec>,
+    framed: Framed<MessageCodec>,
+    sequence_number: u16,
+    /// Byte order announced in setup request.
+    byte_order: ByteOrder,
+    setup: Setup,
+    /// Display connection was opened for, if known.
+    display: Option<Display>,
+    ids: IdAllocator,
+    atoms: AtomCache,
+    /// Extensions queried so far, `None` for unsupported ones.
+    extensions: HashMap<String, Option<Extension>>,
+    /// Events received while waiting for replies.
+    events: VecDeque<Event>,
+    /// Errors generated by requests nobody waits a reply for.
+    errors: VecDeque<XError>,
+    /// Idle time after which [poll_event](Connection::poll_event) sends keep-alive.
+    keep_alive_interval: Option<Duration>,
+    /// Time requests were last sent to X server.
+    last_flush: Instant,
+    /// Passive grabs to release on shutdown, `None` unless grabs are tracked.
+    grabs: Option<Vec<PassiveGrab>>,
+}
+
+/// Whether request failed because of X error, e.g. window was destroyed meanwhile.
+fn is_x_error(error: &io::Error) -> bool {
+    error.get_ref().is_some_and(|inner| inner.is::<XError>())
+}
+
+/// Buffered requests are sent when connection is dropped,
+/// so requests without reply are not lost.
+/// Errors are ignored, use [disconnect](Connection::disconnect) to handle them.
+impl Drop for Connection {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
 }
 
 impl Connection {
     /// Setup connection over any type implementing [Read](std::io::Read) + [Write](std::io::Write).
+    /// No authorization is sent to X server, use [with_stream](Connection::with_stream) to send it.
     /// Unless you open socket connection yourself, use [connect_default] or [connect_to_display]
     pub fn setup(stream: Box<dyn StreamMarker>) -> Result<Self, ConnectionError> {
-        let setup_codec = SetupCodec::default();
-        let _framed = Framed::new(stream, setup_codec);
-        Ok(Connection { _framed })
+        Connection::setup_with_auth(stream, "", b"")
+    }
+
+    /// Setup connection over any stream using specified authorization,
+    /// e.g. over TLS or SSH channel. Stream is used for both handshake and requests.
+    pub fn with_stream<S: StreamMarker + 'static>(
+        stream: S,
+        auth_name: &str,
+        auth_data: &[u8],
+    ) -> Result<Self, ConnectionError> {
+        Connection::setup_with_auth(Box::new(stream), auth_name, auth_data)
+    }
+
+    /// Perform setup handshake using specified authorization.
+    pub(crate) fn setup_with_auth(
+        stream: Box<dyn StreamMarker>,
+        auth_name: &str,
+        auth_data: &[u8],
+    ) -> Result<Self, ConnectionError> {
+        let request = SetupRequest::new(auth_name, auth_data).map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidInput, "Authorization is too long")
+        })?;
+
+        let byte_order = ByteOrder::NATIVE;
+        let mut framed = Framed::new(stream, SetupCodec::new(byte_order));
+        framed.send(request)?;
+        framed.flush()?;
+
+        let setup = match framed.next()? {
+            SetupResponse::Success(setup) => setup,
+            SetupResponse::Failed { reason, .. } => {
+                return Err(ConnectionError::SetupFailed(reason))
+            }
+            SetupResponse::Authenticate { reason } => {
+                return Err(ConnectionError::SetupAuthenticate(reason))
+            }
+        };
+
+        Ok(Connection {
+            framed: framed.map_codec(|_| MessageCodec::default()),
+            sequence_number: 0,
+            byte_order,
+            ids: IdAllocator::new(setup.resource_id_base, setup.resource_id_mask),
+            setup,
+            display: None,
+            atoms: AtomCache::default(),
+            extensions: HashMap::new(),
+            events: VecDeque::new(),
+            errors: VecDeque::new(),
+            keep_alive_interval: None,
+            last_flush: Instant::now(),
+            grabs: None,
+        })
+    }
+
+    /// Setup connection over already open socket, e.g. inherited from launcher.
+    /// Authorization is looked up for `DISPLAY` environment variable if it is set.
+    ///
+    /// Connection takes ownership of the descriptor and closes it when dropped.
+    /// Since crate forbids unsafe code, raw descriptor has to be converted by caller
+    /// using [FromRawFd](std::os::unix::io::FromRawFd), which transfers the ownership:
+    /// ```no_run
+    /// use std::os::unix::io::{FromRawFd, OwnedFd};
+    ///
+    /// // Descriptor 3 is inherited from parent process
+    /// let fd = unsafe { OwnedFd::from_raw_fd(3) };
+    /// let connection = xodium::Connection::from_fd(fd);
+    /// ```
+    /// Descriptor may be either UNIX or TCP socket,
+    /// both are only read from and written to.
+    #[cfg(unix)]
+    pub fn from_fd(fd: OwnedFd) -> Result<Self, ConnectionError> {
+        let display = Display::from_env().ok();
+        // Socket can not be reopened, so only the first authorization is tried
+        let (auth_name, auth_data) = display
+            .as_ref()
+            .and_then(|display| authorizations_for(display).into_iter().next())
+            .unwrap_or_default();
+
+        let stream = Box::new(UnixStream::from(fd));
+        let mut connection = Connection::setup_with_auth(stream, &auth_name, &auth_data)?;
+        connection.display = display;
+        Ok(connection)
+    }
+
+    /// Information sent by X server during connection setup.
+    pub fn setup_info(&self) -> &Setup {
+        &self.setup
+    }
+
+    /// Byte order of the connection, announced in setup request.
+    /// Always native, X server swaps bytes if its own order differs.
+    pub fn byte_order(&self) -> ByteOrder {
+        self.byte_order
+    }
+
+    /// Screen with specified index, `None` if X server has no such screen.
+    pub fn screen(&self, index: usize) -> Option<&Screen> {
+        self.setup.roots.get(index)
+    }
+
+    /// Index of screen specified by display connection was opened for,
+    /// e.g. 1 for `:0.1`. If display has no screen specified
+    /// or X server has no such screen, the first screen is used.
+    pub fn default_screen_index(&self) -> usize {
+        let index = self
+            .display
+            .as_ref()
+            .and_then(|display| display.screen)
+            .map_or(0, usize::from);
+
+        if index < self.setup.roots.len() {
+            index
+        } else {
+            0
+        }
+    }
+
+    /// Screen with [default_screen_index](Connection::default_screen_index).
+    pub fn default_screen(&self) -> &Screen {
+        &self.setup.roots[self.default_screen_index()]
+    }
+
+    /// Root window of the [default screen](Connection::default_screen).
+    pub fn root(&self) -> u32 {
+        self.default_screen().root
+    }
+
+    /// Colormap of the [default screen](Connection::default_screen),
+    /// e.g. to allocate colors of windows with the root visual.
+    pub fn default_colormap(&self) -> u32 {
+        self.default_screen().default_colormap
+    }
+
+    /// White pixel value of the [default screen](Connection::default_screen).
+    pub fn white_pixel(&self) -> u32 {
+        self.default_screen().white_pixel
+    }
+
+    /// Black pixel value of the [default screen](Connection::default_screen).
+    pub fn black_pixel(&self) -> u32 {
+        self.default_screen().black_pixel
+    }
+
+    /// Set callback invoked with raw bytes of every request buffered
+    /// and every message read from X server, `None` disables tracing.
+    pub fn set_trace(&mut self, trace: Option<Trace>) {
+        self.framed.codec_mut().trace = trace;
+    }
+
+    /// Set maximum size of reply in bytes, including its 32 byte header,
+    /// 4 MiB by default. Larger reply is skipped instead of being buffered,
+    /// so it can not exhaust memory, and waiting for it fails with `InvalidData`.
+    ///
+    /// Helpers of requests with large replies, e.g. [get_image](Connection::get_image),
+    /// raise the limit to the expected reply size while waiting for it.
+    pub fn set_max_reply_len(&mut self, max_reply_len: usize) {
+        self.framed.codec_mut().max_reply_len = max_reply_len;
+    }
+
+    /// Flush buffered requests and close the connection.
+    /// Unlike dropping the connection, reports errors of sending requests.
+    pub fn disconnect(mut self) -> io::Result<()> {
+        // Socket is closed once connection is dropped on return
+        self.flush()
+    }
+
+    /// Send all buffered requests to X server.
+    ///
+    /// On a non-blocking socket it may fail with `WouldBlock` having sent
+    /// only a part of requests, the rest stays buffered until it is called again.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.framed.flush()?;
+        self.last_flush = Instant::now();
+        Ok(())
+    }
+
+    /// Buffer request and return its sequence number.
+    /// Requests are not sent until [flush](Connection::flush) is called.
+    ///
+    /// Together with [wait_for_reply](Connection::wait_for_reply) allows to send
+    /// requests which have no helper method.
+    pub fn send_request<R: Serialize>(&mut self, request: R) -> io::Result<u16> {
+        self.framed.send(request)?;
+        self.sequence_number = self.sequence_number.wrapping_add(1);
+        Ok(self.sequence_number)
+    }
+
+    /// Flush buffered requests and wait for the reply to request with sequence number.
+    /// Events and errors of other requests received meanwhile are queued,
+    /// error of the request itself is returned as [XError] wrapped in `io::Error`.
+    pub fn wait_for_reply<T: Deserialize>(&mut self, sequence_number: u16) -> io::Result<T> {
+        self.flush()?;
+
+        loop {
+            match self.framed.next()? {
+                Message::Reply {
+                    sequence_number: reply_sequence_number,
+                    data,
+                } if reply_sequence_number == sequence_number => {
+                    return T::deserialize(&data[..])?.ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidData, "Incomplete reply")
+                    });
+                }
+                Message::ReplyTooLong {
+                    sequence_number: reply_sequence_number,
+                } if reply_sequence_number == sequence_number => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "Reply is too long",
+                    ));
+                }
+                // Nobody waits for this reply anymore
+                Message::Reply { .. } | Message::ReplyTooLong { .. } => {}
+                Message::Error(error) if error.sequence_number == sequence_number => {
+                    return Err(error.into());
+                }
+                Message::Error(error) => self.errors.push_back(error),
+                Message::Event(event) => self.events.push_back(event),
+            }
+        }
+    }
+
+    /// Same as [wait_for_reply](Connection::wait_for_reply), but replies up to
+    /// `max_reply_len` bytes are accepted meanwhile, for request with reply
+    /// expected to exceed the [limit](Connection::set_max_reply_len), e.g. GetImage.
+    pub(crate) fn wait_for_large_reply<T: Deserialize>(
+        &mut self,
+        sequence_number: u16,
+        max_reply_len: usize,
+    ) -> io::Result<T> {
+        let codec = self.framed.codec_mut();
+        let limit = codec.max_reply_len;
+        codec.max_reply_len = limit.max(max_reply_len);
+
+        let result = self.wait_for_reply(sequence_number);
+        self.framed.codec_mut().max_reply_len = limit;
+        result
+    }
+
+    /// Flush buffered requests and wait for the next event.
+    pub fn wait_event(&mut self) -> io::Result<Event> {
+        if let Some(event) = self.events.pop_front() {
+            return Ok(event);
+        }
+
+        self.flush()?;
+
+        loop {
+            match self.framed.next()? {
+                Message::Event(event) => return Ok(event),
+                Message::Error(error) => self.errors.push_back(error),
+                // Nobody waits for this reply anymore
+                Message::Reply { .. } | Message::ReplyTooLong { .. } => {}
+            }
+        }
+    }
+
+    /// Return next queued error of request which had no reply waited for, never blocks.
+    /// Errors are queued only while waiting for replies or events.
+    pub fn poll_error(&mut self) -> Option<XError> {
+        self.errors.pop_front()
+    }
+
+    /// Flush buffered requests and wait until X server processes them,
+    /// so errors of all of them are queued.
+    pub fn sync(&mut self) -> io::Result<()> {
+        self.get_input_focus()?;
+        Ok(())
+    }
+
+    /// Flush buffered requests, wait until X server processes them and
+    /// return all queued errors, e.g. at the end of a drawing batch.
+    pub fn check(&mut self) -> io::Result<Vec<XError>> {
+        self.sync()?;
+        Ok(self.errors.drain(..).collect())
+    }
+
+    /// Return next event if it was already received, never blocks.
+    /// Sends keep-alive request if it is enabled with
+    /// [set_keep_alive](Connection::set_keep_alive) and connection was idle long enough.
+    pub fn poll_event(&mut self) -> io::Result<Option<Event>> {
+        self.keep_alive_if_idle()?;
+
+        self.next_received_event()
+    }
+
+    /// Whether event was already received, so [poll_event](Connection::poll_event)
+    /// returns it without reading the socket. Lets connection be driven by
+    /// external poll loop, which only has to wait for the socket to become readable
+    /// while this is false.
+    pub fn has_buffered_event(&self) -> bool {
+        !self.events.is_empty()
+            || self
+                .framed
+                .codec()
+                .has_complete_event(self.framed.buffered_input())
+    }
+
+    /// Number of bytes of requests buffered and not sent yet,
+    /// they are sent by [flush](Connection::flush).
+    pub fn pending_requests(&self) -> usize {
+        self.framed.buffered_output_len()
+    }
+
+    /// Return next queued or already read event, never blocks.
+    pub(crate) fn next_received_event(&mut self) -> io::Result<Option<Event>> {
+        if let Some(event) = self.events.pop_front() {
+            return Ok(Some(event));
+        }
+
+        while let Some(message) = self.framed.next_buffered()? {
+            match message {
+                Message::Event(event) => return Ok(Some(event)),
+                Message::Error(error) => self.errors.push_back(error),
+                Message::Reply { .. } | Message::ReplyTooLong { .. } => {}
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::address::Address;
+    use super::{
+        connect_to_display_with_socket_dir, connect_with_authorizations,
+        discover_local_displays_in, setup_with_authorizations, Connection, ConnectionError,
+    };
+    use crate::display::{Display, DisplayError};
+    use crate::protocol::{
+        DestroyNotifyEvent, Direction, Event, NoOperation, QueryPointer, QueryPointerReply, XError,
+    };
+    use crate::test_utils::{
+        mock_connection, mock_connection_with_screens, read_setup_request, setup_response,
+    };
+    use std::cell::RefCell;
+    use std::env;
+    use std::error::Error;
+    use std::fs;
+    use std::io::{self, Write};
+    use std::net::TcpListener;
+    use std::os::unix::io::OwnedFd;
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::Path;
+    use std::rc::Rc;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_connection_error_source() {
+        let error = ConnectionError::from(DisplayError::DisplayNotSet);
+        let source = error.source().unwrap();
+        assert!(matches!(
+            source.downcast_ref::<DisplayError>(),
+            Some(DisplayError::DisplayNotSet)
+        ));
+
+        let error = ConnectionError::from(io::Error::from(io::ErrorKind::ConnectionReset));
+        let source = error.source().unwrap();
+        assert_eq!(
+            source.downcast_ref::<io::Error>().unwrap().kind(),
+            io::ErrorKind::ConnectionReset
+        );
+
+        let unreachable = vec![(":0".to_owned(), io::Error::from(io::ErrorKind::NotFound))];
+        for error in [
+            ConnectionError::SetupFailed("No protocol specified".to_owned()),
+            ConnectionError::SetupAuthenticate("unknown".to_owned()),
+            ConnectionError::Unreachable(unreachable),
+            ConnectionError::NoSuchScreen(1),
+        ] {
+            assert!(error.source().is_none());
+        }
+    }
+
+    #[test]
+    fn test_from_fd() {
+        let (client, mut server) = UnixStream::pair().unwrap();
+
+        let server = thread::spawn(move || {
+            read_setup_request(&mut server).unwrap();
+            server.write_all(&setup_response(1)).unwrap();
+        });
+
+        let connection = Connection::from_fd(OwnedFd::from(client)).unwrap();
+        server.join().unwrap();
+
+        assert_eq!(connection.setup_info().roots.len(), 1);
+        assert_eq!(connection.setup_info().vendor, "xodium");
+    }
+
+    #[test]
+    fn test_with_stream() {
+        let (client, mut server) = UnixStream::pair().unwrap();
+
+        let server = thread::spawn(move || {
+            let auth = read_setup_request(&mut server).unwrap();
+            server.write_all(&setup_response(2)).unwrap();
+            auth
+        });
+
+        let connection =
+            Connection::with_stream(client, "MIT-MAGIC-COOKIE-1", b"\xab\xcd").unwrap();
+        let (auth_name, auth_data) = server.join().unwrap();
+
+        assert_eq!(auth_name, b"MIT-MAGIC-COOKIE-1");
+        assert_eq!(auth_data, b"\xab\xcd");
+        assert_eq!(connection.setup_info().roots.len(), 2);
+    }
+
+    #[test]
+    fn test_connect_with_socket_dir() {
+        let socket_dir = env::temp_dir().join("xodium-tests-socket-dir");
+        fs::create_dir_all(&socket_dir).unwrap();
+        let socket_path = socket_dir.join("X7");
+        let _ = fs::remove_file(&socket_path);
+        let listener = UnixListener::bind(&socket_path).unwrap();
+
+        let server = thread::spawn(move || {
+            let (mut server, _) = listener.accept().unwrap();
+            read_setup_request(&mut server).unwrap();
+            server.write_all(&setup_response(1)).unwrap();
+        });
+
+        let display = Display::from_str(":7").unwrap();
+        let connection = connect_to_display_with_socket_dir(display, &socket_dir).unwrap();
+        server.join().unwrap();
+        fs::remove_file(&socket_path).unwrap();
+
+        assert_eq!(connection.setup_info().vendor, "xodium");
+    }
+
+    #[test]
+    fn test_connect_with_explicit_authorization() {
+        let socket_dir = env::temp_dir().join("xodium-tests-explicit-auth");
+        fs::create_dir_all(&socket_dir).unwrap();
+        let socket_path = socket_dir.join("X9");
+        let _ = fs::remove_file(&socket_path);
+        let listener = UnixListener::bind(&socket_path).unwrap();
+
+        let server = thread::spawn(move || {
+            let (mut server, _) = listener.accept().unwrap();
+            let auth = read_setup_request(&mut server).unwrap();
+            server.write_all(&setup_response(1)).unwrap();
+            auth
+        });
+
+        let display = Display::from_str(":9").unwrap();
+        let authorizations = [("MIT-MAGIC-COOKIE-1".to_owned(), b"\x12\x34\x56".to_vec())];
+        let connection =
+            connect_with_authorizations(display, &socket_dir, &authorizations, None).unwrap();
+        let (auth_name, auth_data) = server.join().unwrap();
+        fs::remove_file(&socket_path).unwrap();
+
+        assert_eq!(auth_name, b"MIT-MAGIC-COOKIE-1");
+        assert_eq!(auth_data, b"\x12\x34\x56");
+        assert_eq!(connection.display, Some(Display::from_str(":9").unwrap()));
+    }
+
+    #[test]
+    fn test_setup_with_next_authorization() {
+        let socket_path = env::temp_dir().join("xodium-tests-next-auth");
+        let _ = fs::remove_file(&socket_path);
+        let listener = UnixListener::bind(&socket_path).unwrap();
+
+        let server = thread::spawn(move || {
+            let mut auth_data = vec![];
+            for response in &[
+                &b"\x00\x05\x0b\x00\x00\x00\x02\x00stale\0\0\0"[..],
+                &setup_response(1),
+            ] {
+                let (mut server, _) = listener.accept().unwrap();
+                auth_data.push(read_setup_request(&mut server).unwrap().1);
+                server.write_all(response).unwrap();
+            }
+            auth_data
+        });
+
+        let candidates = [Address::Unix(socket_path.clone())];
+        let authorizations = [
+            ("MIT-MAGIC-COOKIE-1".to_owned(), b"\x01".to_vec()),
+            ("MIT-MAGIC-COOKIE-1".to_owned(), b"\x02".to_vec()),
+        ];
+        let connection = setup_with_authorizations(&candidates, &authorizations, None).unwrap();
+        let auth_data = server.join().unwrap();
+        fs::remove_file(&socket_path).unwrap();
+
+        assert_eq!(connection.setup_info().vendor, "xodium");
+        assert_eq!(auth_data, [b"\x01", b"\x02"]);
+    }
+
+    #[test]
+    fn test_setup_with_all_authorizations_rejected() {
+        let socket_path = env::temp_dir().join("xodium-tests-rejected-auth");
+        let _ = fs::remove_file(&socket_path);
+        let listener = UnixListener::bind(&socket_path).unwrap();
+
+        let server = thread::spawn(move || {
+            for response in &[
+                &b"\x00\x05\x0b\x00\x00\x00\x02\x00stale\0\0\0"[..],
+                &b"\x02\0\0\0\0\0\x02\0unknown\0"[..],
+            ] {
+                let (mut server, _) = listener.accept().unwrap();
+                read_setup_request(&mut server).unwrap();
+                server.write_all(response).unwrap();
+            }
+        });
+
+        let candidates = [Address::Unix(socket_path.clone())];
+        let authorizations = [
+            ("MIT-MAGIC-COOKIE-1".to_owned(), b"\x01".to_vec()),
+            ("XDM-AUTHORIZATION-1".to_owned(), b"\x02".to_vec()),
+        ];
+        let result = setup_with_authorizations(&candidates, &authorizations, None);
+        server.join().unwrap();
+        fs::remove_file(&socket_path).unwrap();
+
+        match result {
+            Err(ConnectionError::SetupAuthenticate(reason)) => assert_eq!(reason, "unknown"),
+            Err(other) => panic!("Unexpected error: {}", other),
+            Ok(_) => panic!("All authorizations should be rejected"),
+        }
+    }
+
+    #[test]
+    fn test_discover_local_displays() {
+        let socket_dir = env::temp_dir().join("xodium-tests-discover");
+        let _ = fs::remove_dir_all(&socket_dir);
+        fs::create_dir_all(&socket_dir).unwrap();
+        let _x12 = UnixListener::bind(socket_dir.join("X12")).unwrap();
+        let _x3 = UnixListener::bind(socket_dir.join("X3")).unwrap();
+        for name in &["X", "X1-lock", "Xa", "X+4", "X99999", "Y5"] {
+            fs::write(socket_dir.join(name), b"").unwrap();
+        }
+
+        let displays = discover_local_displays_in(&socket_dir);
+        fs::remove_dir_all(&socket_dir).unwrap();
+
+        assert_eq!(
+            displays,
+            [
+                Display::from_str(":3").unwrap(),
+                Display::from_str(":12").unwrap()
+            ]
+        );
+        assert!(discover_local_displays_in(&socket_dir).is_empty());
+    }
+
+    #[test]
+    fn test_connect_with_screen() {
+        let socket_dir = env::temp_dir().join("xodium-tests-screen");
+        fs::create_dir_all(&socket_dir).unwrap();
+        let socket_path = socket_dir.join("X8");
+        let _ = fs::remove_file(&socket_path);
+        let listener = UnixListener::bind(&socket_path).unwrap();
+
+        let server = thread::spawn(move || {
+            for _ in 0..2 {
+                let (mut server, _) = listener.accept().unwrap();
+                read_setup_request(&mut server).unwrap();
+                server.write_all(&setup_response(2)).unwrap();
+            }
+        });
+
+        let display = Display::from_str(":8.1").unwrap();
+        let connection = connect_to_display_with_socket_dir(display, &socket_dir).unwrap();
+        assert_eq!(connection.default_screen().root, 0x101);
+
+        let display = Display::from_str(":8.2").unwrap();
+        match connect_to_display_with_socket_dir(display, &socket_dir) {
+            Err(ConnectionError::NoSuchScreen(2)) => {}
+            Err(other) => panic!("Unexpected error: {}", other),
+            Ok(_) => panic!("Screen 2 does not exist"),
+        }
+
+        server.join().unwrap();
+        fs::remove_file(&socket_path).unwrap();
+    }
+
+    #[test]
+    fn test_connect_tcp_with_screen() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server = thread::spawn(move || {
+            let (mut server, _) = listener.accept().unwrap();
+            read_setup_request(&mut server).unwrap();
+            server.write_all(&setup_response(3)).unwrap();
+        });
+
+        // Hostname makes TCP the only candidate address
+        let display = Display::from_str(&format!("127.0.0.1:{}.2", port - 6000)).unwrap();
+        let authorizations = [(String::new(), vec![])];
+        let connection =
+            connect_with_authorizations(display, Path::new("/nonexistent"), &authorizations, None)
+                .unwrap();
+        server.join().unwrap();
+
+        assert_eq!(connection.default_screen_index(), 2);
+        assert_eq!(connection.default_screen().root, 0x102);
+    }
+
+    #[test]
+    fn test_setup_timeout() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (done, wait_done) = mpsc::channel::<()>();
+
+        // Hung server reads setup request, but never replies nor closes socket
+        let server = thread::spawn(move || {
+            let (mut server, _) = listener.accept().unwrap();
+            read_setup_request(&mut server).unwrap();
+            wait_done.recv().unwrap();
+        });
+
+        let display = Display::from_str(&format!("127.0.0.1:{}", port - 6000)).unwrap();
+        let authorizations = [(String::new(), vec![])];
+        let started = Instant::now();
+        let result = connect_with_authorizations(
+            display,
+            Path::new("/nonexistent"),
+            &authorizations,
+            Some(Duration::from_millis(100)),
+        );
+        done.send(()).unwrap();
+        server.join().unwrap();
+
+        assert!(matches!(result, Err(ConnectionError::Timeout)));
+        assert!(started.elapsed() >= Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_setup_timeout_slow_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (done, wait_done) = mpsc::channel::<()>();
+
+        // Server replies a byte at a time, each read completes well within the timeout
+        let server = thread::spawn(move || {
+            let (mut server, _) = listener.accept().unwrap();
+            read_setup_request(&mut server).unwrap();
+            for byte in setup_response(1) {
+                if server.write_all(&[byte]).is_err() || wait_done.try_recv().is_ok() {
+                    break;
+                }
+                thread::sleep(Duration::from_millis(20));
+            }
+        });
+
+        let display = Display::from_str(&format!("127.0.0.1:{}", port - 6000)).unwrap();
+        let authorizations = [(String::new(), vec![])];
+        let started = Instant::now();
+        let result = connect_with_authorizations(
+            display,
+            Path::new("/nonexistent"),
+            &authorizations,
+            Some(Duration::from_millis(100)),
+        );
+        let elapsed = started.elapsed();
+        done.send(()).unwrap();
+        server.join().unwrap();
+
+        assert!(matches!(result, Err(ConnectionError::Timeout)));
+        assert!(elapsed < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_setup_failed() {
+        let (client, mut server) = UnixStream::pair().unwrap();
+
+        let server = thread::spawn(move || {
+            read_setup_request(&mut server).unwrap();
+            server
+                .write_all(b"\x00\x06\x0b\x00\x00\x00\x02\x00reason\0\0")
+                .unwrap();
+        });
+
+        let result = Connection::setup(Box::new(client));
+        server.join().unwrap();
+
+        match result {
+            Err(ConnectionError::SetupFailed(reason)) => assert_eq!(reason, "reason"),
+            Err(other) => panic!("Unexpected error: {}", other),
+            Ok(_) => panic!("Setup should fail"),
+        }
+    }
+
+    #[test]
+    fn test_wait_event() {
+        let mut input = b"\x11\x00\x00\x00\x01\x02\x00\x00\x03\x04\x00\x00".to_vec();
+        input.resize(32, 0);
+
+        let (mut connection, _) = mock_connection(input);
+
+        assert_eq!(
+            connection.wait_event().unwrap(),
+            Event::DestroyNotify(DestroyNotifyEvent {
+                event: 0x201,
+                window: 0x403,
+            })
+        );
+        assert!(connection.poll_event().unwrap().is_none());
+        assert!(connection.wait_event().is_err());
+    }
+
+    #[test]
+    fn test_trace() {
+        let mut reply = b"\x01\x00\x01\x00\x00\x00\x00\x00\x2a\x01\x00\x00".to_vec();
+        reply.extend(vec![0; 20]);
+
+        let (mut connection, _) = mock_connection(reply.clone());
+        let traced = Rc::new(RefCell::new(vec![]));
+        let trace_traced = traced.clone();
+        connection.set_trace(Some(Box::new(move |direction, bytes| {
+            trace_traced.borrow_mut().push((direction, bytes.to_vec()))
+        })));
+
+        connection.intern_atom("UTF8_STRING", false).unwrap();
+
+        assert_eq!(
+            *traced.borrow(),
+            vec![
+                (
+                    Direction::Out,
+                    b"\x10\x00\x05\x00\x0b\x00\x00\x00UTF8_STRING\0".to_vec()
+                ),
+                (Direction::In, reply),
+            ]
+        );
+
+        connection.set_trace(None);
+        connection.intern_atom("WM_NAME", false).ok();
+        assert_eq!(traced.borrow().len(), 2);
+    }
+
+    #[test]
+    fn test_disconnect_flushes() {
+        let (mut connection, written) = mock_connection(vec![]);
+
+        connection.grab_server().unwrap();
+        assert!(written.borrow().is_empty());
+
+        connection.disconnect().unwrap();
+        assert_eq!(*written.borrow(), b"\x24\x00\x01\x00");
+    }
+
+    #[test]
+    fn test_drop_flushes() {
+        let (mut connection, written) = mock_connection(vec![]);
+
+        connection.ungrab_server().unwrap();
+        drop(connection);
+        assert_eq!(*written.borrow(), b"\x25\x00\x01\x00");
+    }
+
+    #[test]
+    fn test_wait_for_reply_queues_events_and_errors() {
+        // DestroyNotify event, Window error of NoOperation and QueryPointer reply
+        let mut input = b"\x11\x00\x00\x00\x01\x02\x00\x00\x03\x04\x00\x00".to_vec();
+        input.resize(32, 0);
+        input.extend(b"\x00\x03\x01\x00\x01\x02\x00\x00\x00\x00\x7f\x00");
+        input.resize(64, 0);
+        input.extend(b"\x01\x01\x02\x00\x00\x00\x00\x00\x00\x01\x00\x00\x00\x00\x00\x00");
+        input.extend(b"\x0a\x00\x14\x00\x0a\x00\x14\x00\x00\x00");
+        input.resize(96, 0);
+
+        let (mut connection, _) = mock_connection(input);
+        connection.send_request(NoOperation::default()).unwrap();
+        let sequence_number = connection
+            .send_request(QueryPointer { window: 0x100 })
+            .unwrap();
+        let reply: QueryPointerReply = connection.wait_for_reply(sequence_number).unwrap();
+
+        assert_eq!(reply.root, 0x100);
+        assert_eq!((reply.root_x, reply.root_y), (10, 20));
+
+        let error = connection.poll_error().unwrap();
+        assert_eq!((error.code, error.sequence_number), (3, 1));
+        assert!(connection.poll_error().is_none());
+
+        assert_eq!(
+            connection.poll_event().unwrap(),
+            Some(Event::DestroyNotify(DestroyNotifyEvent {
+                event: 0x201,
+                window: 0x403,
+            }))
+        );
+    }
+
+    #[test]
+    fn test_check() {
+        let mut input = vec![];
+        // Drawable, GContext and Window errors of 3 requests
+        for &(code, sequence_number) in &[(9, 1), (13, 2), (3, 3)] {
+            input.extend(&[0, code, sequence_number, 0]);
+            input.resize(input.len() + 28, 0);
+        }
+        input.extend(b"\x01\x01\x04\x00\x00\x00\x00\x00\x01\x00\x00\x00");
+        input.resize(input.len() + 20, 0);
+
+        let (mut connection, written) = mock_connection(input);
+        for _ in 0..3 {
+            connection.send_request(NoOperation::default()).unwrap();
+        }
+
+        let errors = connection.check().unwrap();
+        let codes: Vec<_> = errors
+            .iter()
+            .map(|error| (error.code, error.sequence_number))
+            .collect();
+        assert_eq!(codes, [(9, 1), (13, 2), (3, 3)]);
+        assert!(connection.poll_error().is_none());
+
+        // Requests are followed by GetInputFocus
+        assert_eq!(&written.borrow()[12..], b"\x2b\x00\x01\x00");
+    }
+
+    #[test]
+    fn test_has_buffered_event() {
+        let mut input = b"\x01\x01\x02\x00\x00\x00\x00\x00\x01\x00\x00\x00".to_vec();
+        input.resize(32, 0);
+        input.extend(b"\x14\x00\x00\x00\x01\x02\x00\x00\x03\x04\x00\x00");
+        input.resize(64, 0);
+        // Beginning of the next event
+        input.extend(b"\x14\x00\x00\x00\x01\x02\x00\x00");
+
+        // Mock stream is read along with setup response
+        let (mut connection, _) = mock_connection(input);
+        assert!(connection.has_buffered_event());
+
+        connection.send_request(NoOperation::default()).unwrap();
+        assert_eq!(connection.pending_requests(), 4);
+        connection.flush().unwrap();
+        assert_eq!(connection.pending_requests(), 0);
+
+        assert!(matches!(
+            connection.poll_event().unwrap(),
+            Some(Event::MapRequest(_))
+        ));
+        assert!(!connection.has_buffered_event());
+        assert!(connection.poll_event().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_wait_for_reply_error() {
+        let mut input = b"\x00\x03\x01\x00\x01\x02\x00\x00\x00\x00\x26\x00".to_vec();
+        input.resize(32, 0);
+
+        let (mut connection, _) = mock_connection(input);
+        let sequence_number = connection
+            .send_request(QueryPointer { window: 0x201 })
+            .unwrap();
+        let error = connection
+            .wait_for_reply::<QueryPointerReply>(sequence_number)
+            .unwrap_err();
+
+        let error = error.get_ref().unwrap().downcast_ref::<XError>().unwrap();
+        assert_eq!(error.name(), "Window");
+        assert_eq!(error.bad_value, 0x201);
+        assert!(connection.poll_error().is_none());
+    }
+
+    #[test]
+    fn test_wait_for_reply_too_long() {
+        // Reply with 8 bytes of data, then reply to GetInputFocus
+        let mut input = b"\x01\x00\x01\x00\x02\x00\x00\x00".to_vec();
+        input.resize(40, 0);
+        input.extend(b"\x01\x01\x02\x00\x00\x00\x00\x00\x01\x00\x00\x00");
+        input.resize(72, 0);
+
+        let (mut connection, _) = mock_connection(input);
+        connection.set_max_reply_len(32);
+        let sequence_number = connection
+            .send_request(QueryPointer { window: 0x201 })
+            .unwrap();
+        let error = connection
+            .wait_for_reply::<QueryPointerReply>(sequence_number)
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        // Data of the long reply was skipped, so the connection is still usable
+        connection.sync().unwrap();
+        assert!(connection.poll_error().is_none());
+    }
+
+    #[test]
+    fn test_default_screen() {
+        let (mut connection, _) = mock_connection_with_screens(2, vec![]);
+        assert_eq!(connection.default_screen().root, 0x100);
+        assert_eq!(connection.screen(1).unwrap().root, 0x101);
+        assert!(connection.screen(2).is_none());
+
+        connection.display = Some(Display::from_str(":0.1").unwrap());
+        assert_eq!(connection.default_screen_index(), 1);
+        assert_eq!(connection.default_screen().root, 0x101);
+
+        connection.display = Some(Display::from_str(":0").unwrap());
+        assert_eq!(connection.default_screen().root, 0x100);
+
+        connection.display = Some(Display::from_str(":0.5").unwrap());
+        assert_eq!(connection.default_screen_index(), 0);
+    }
+
+    #[test]
+    fn test_default_screen_accessors() {
+        let (mut connection, _) = mock_connection_with_screens(2, vec![]);
+        assert_eq!(connection.root(), 0x100);
+        assert_eq!(connection.default_colormap(), 0x20);
+
+        connection.display = Some(Display::from_str(":0.1").unwrap());
+        assert_eq!(connection.root(), 0x101);
+        assert_eq!(connection.default_colormap(), 0x21);
+        assert_eq!(connection.white_pixel(), 0x00ff_ffff);
+        assert_eq!(connection.black_pixel(), 0);
     }
 }
@@ -0,0 +1,373 @@
+use super::ConnectionError;
+use crate::display::{Display, DisplayError, Endpoint, DEFAULT_UNIX_X_SERVER_SOCKET_DIR};
+use std::cell::Cell;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+#[cfg(target_os = "linux")]
+use std::os::linux::net::SocketAddrExt;
+#[cfg(target_os = "linux")]
+use std::os::unix::net::SocketAddr;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// Address X server may be listening on.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Address {
+    Unix(PathBuf),
+    /// Linux abstract socket, name without leading nul byte.
+    #[cfg(target_os = "linux")]
+    Abstract(String),
+    Tcp(String, u16),
+}
+
+impl Address {
+    /// Addresses to try for the display in order of preference, starting with
+    /// the [resolved](Display::resolve_in) one, UNIX socket of local display
+    /// is looked up in `socket_dir`.
+    pub(crate) fn candidates(
+        display: &Display,
+        socket_dir: &Path,
+    ) -> Result<Vec<Address>, DisplayError> {
+        let socket_path = match display.resolve_in(socket_dir)? {
+            Endpoint::Unix(path) => path,
+            Endpoint::Tcp(hostname, port) => return Ok(vec![Address::Tcp(hostname, port)]),
+        };
+
+        // launchd sockets are only available on the file system
+        if display.is_unix_socket_path() {
+            return Ok(vec![Address::Unix(socket_path)]);
+        }
+
+        // Abstract socket name does not depend on socket directory
+        #[cfg(target_os = "linux")]
+        let abstract_name = display
+            .unix_socket_path()
+            .map(|path| path.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let mut candidates = vec![
+            Address::Unix(socket_path),
+            #[cfg(target_os = "linux")]
+            Address::Abstract(abstract_name),
+        ];
+        // Display number may be out of TCP port range, sockets work regardless
+        if let Ok(port) = display.tcp_port() {
+            candidates.push(Address::Tcp("localhost".to_owned(), port));
+        }
+        Ok(candidates)
+    }
+
+    pub(crate) fn connect(&self) -> io::Result<Socket> {
+        Ok(match self {
+            Address::Unix(path) => Socket::Unix(UnixStream::connect(path)?),
+            #[cfg(target_os = "linux")]
+            Address::Abstract(name) => {
+                let address = SocketAddr::from_abstract_name(name.as_bytes())?;
+                Socket::Unix(UnixStream::connect_addr(&address)?)
+            }
+            Address::Tcp(host, port) => {
+                let stream = TcpStream::connect((host.as_str(), *port))?;
+                // Requests are buffered by connection itself
+                stream.set_nodelay(true)?;
+                Socket::Tcp(stream)
+            }
+        })
+    }
+}
+
+/// Socket connected to X server, keeps access to socket options
+/// which are lost once it is boxed as a stream.
+#[derive(Debug)]
+pub(crate) enum Socket {
+    Unix(UnixStream),
+    Tcp(TcpStream),
+}
+
+impl Socket {
+    /// Another handle of the same socket, options set through it apply to both.
+    pub(crate) fn try_clone(&self) -> io::Result<Socket> {
+        Ok(match self {
+            Socket::Unix(stream) => Socket::Unix(stream.try_clone()?),
+            Socket::Tcp(stream) => Socket::Tcp(stream.try_clone()?),
+        })
+    }
+
+    /// Fail reads blocking longer than `timeout`, `None` blocks indefinitely.
+    pub(crate) fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        match self {
+            Socket::Unix(stream) => stream.set_read_timeout(timeout),
+            Socket::Tcp(stream) => stream.set_read_timeout(timeout),
+        }
+    }
+}
+
+impl Read for Socket {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Socket::Unix(stream) => stream.read(buf),
+            Socket::Tcp(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Socket {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Socket::Unix(stream) => stream.write(buf),
+            Socket::Tcp(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Socket::Unix(stream) => stream.flush(),
+            Socket::Tcp(stream) => stream.flush(),
+        }
+    }
+}
+
+/// Socket whose reads fail with [TimedOut](io::ErrorKind::TimedOut) once `deadline`
+/// passes, each read blocks at most for the time remaining.
+/// Deadline is shared with its creator, which clears it to lift the limit.
+pub(crate) struct DeadlineSocket {
+    socket: Socket,
+    deadline: Rc<Cell<Option<Instant>>>,
+}
+
+impl DeadlineSocket {
+    pub(crate) fn new(socket: Socket, deadline: Rc<Cell<Option<Instant>>>) -> DeadlineSocket {
+        DeadlineSocket { socket, deadline }
+    }
+}
+
+impl Read for DeadlineSocket {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if let Some(deadline) = self.deadline.get() {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            // Zero timeout is rejected by the socket
+            if remaining == Duration::ZERO {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "Read deadline has passed",
+                ));
+            }
+            self.socket.set_read_timeout(Some(remaining))?;
+        }
+        self.socket.read(buf)
+    }
+}
+
+impl Write for DeadlineSocket {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.socket.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.socket.flush()
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Address::Unix(path) => write!(f, "unix:{}", path.display()),
+            #[cfg(target_os = "linux")]
+            Address::Abstract(name) => write!(f, "unix:@{}", name),
+            Address::Tcp(host, port) => write!(f, "tcp:{}:{}", host, port),
+        }
+    }
+}
+
+/// Directory to look up local X server sockets in.
+/// Default one is used if it exists, otherwise `.X11-unix` in `XDG_RUNTIME_DIR`.
+pub(crate) fn default_socket_dir() -> PathBuf {
+    let default = PathBuf::from(DEFAULT_UNIX_X_SERVER_SOCKET_DIR);
+    if default.exists() {
+        return default;
+    }
+
+    match env::var_os("XDG_RUNTIME_DIR") {
+        Some(runtime_dir) => PathBuf::from(runtime_dir).join(".X11-unix"),
+        None => default,
+    }
+}
+
+/// Display number of X server socket named `X<number>`.
+fn socket_display_number(name: &str) -> Option<u16> {
+    let number = name.strip_prefix('X')?;
+    if number.is_empty() || !number.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    number.parse().ok()
+}
+
+/// Display numbers of X server sockets found in `socket_dir`,
+/// none if it does not exist or can not be read.
+pub(crate) fn socket_dir_displays(socket_dir: &Path) -> Vec<u16> {
+    let entries = match fs::read_dir(socket_dir) {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| socket_display_number(entry.file_name().to_str()?))
+        .collect()
+}
+
+/// Display numbers of X server abstract sockets listed in `/proc/net/unix`,
+/// abstract names are paths in the default socket directory prefixed with `@`.
+#[cfg(target_os = "linux")]
+pub(crate) fn abstract_socket_displays(proc_net_unix: &str) -> Vec<u16> {
+    let prefix = format!("@{}/", DEFAULT_UNIX_X_SERVER_SOCKET_DIR);
+
+    proc_net_unix
+        .lines()
+        .skip(1) // header
+        .filter_map(|line| line.split_whitespace().nth(7))
+        .filter_map(|path| socket_display_number(path.strip_prefix(prefix.as_str())?))
+        .collect()
+}
+
+/// Connect to the first address available.
+/// If none of them is, error lists every address tried.
+pub(crate) fn connect_any(addresses: &[Address]) -> Result<Socket, ConnectionError> {
+    let mut failures = vec![];
+
+    for address in addresses {
+        match address.connect() {
+            Ok(stream) => return Ok(stream),
+            Err(e) => failures.push((address.to_string(), e)),
+        }
+    }
+
+    Err(ConnectionError::Unreachable(failures))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{connect_any, Address};
+    use crate::connection::{Connection, ConnectionError};
+    use crate::display::Display;
+    use crate::test_utils::{read_setup_request, setup_response};
+    use std::io::Write;
+    use std::net::TcpListener;
+    use std::path::{Path, PathBuf};
+    use std::thread;
+
+    #[test]
+    fn test_candidates_local() {
+        let display = Display::from_str(":1").unwrap();
+
+        assert_eq!(
+            Address::candidates(&display, Path::new("/run/user/1000/.X11-unix")).unwrap(),
+            vec![
+                Address::Unix(PathBuf::from("/run/user/1000/.X11-unix/X1")),
+                #[cfg(target_os = "linux")]
+                Address::Abstract("/tmp/.X11-unix/X1".to_owned()),
+                Address::Tcp("localhost".to_owned(), 6001),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_candidates_unix_hostname() {
+        let display = Display::from_str("unix:60000").unwrap();
+
+        assert_eq!(
+            Address::candidates(&display, Path::new("/tmp/.X11-unix")).unwrap(),
+            vec![
+                Address::Unix(PathBuf::from("/tmp/.X11-unix/X60000")),
+                #[cfg(target_os = "linux")]
+                Address::Abstract("/tmp/.X11-unix/X60000".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_candidates_remote() {
+        let display = Display::from_str("example.com:2").unwrap();
+
+        assert_eq!(
+            Address::candidates(&display, Path::new("/tmp/.X11-unix")).unwrap(),
+            vec![Address::Tcp("example.com".to_owned(), 6002)]
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_abstract_socket_displays() {
+        use super::abstract_socket_displays;
+
+        const PROC_NET_UNIX: &str = "\
+Num       RefCount Protocol Flags    Type St Inode Path
+0000000000000000: 00000002 00000000 00010000 0001 01 23131 @/tmp/.X11-unix/X0
+0000000000000000: 00000002 00000000 00010000 0001 01 23132 /tmp/.X11-unix/X0
+0000000000000000: 00000003 00000000 00000000 0001 03 23654 @/tmp/.X11-unix/X0
+0000000000000000: 00000002 00000000 00010000 0001 01 31310 @/tmp/.X11-unix/X3
+0000000000000000: 00000002 00000000 00010000 0001 01 18934 @/tmp/.ICE-unix/1024
+0000000000000000: 00000003 00000000 00000000 0001 03 24012
+";
+
+        assert_eq!(abstract_socket_displays(PROC_NET_UNIX), [0, 0, 3]);
+    }
+
+    #[test]
+    fn test_fallback_to_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server = thread::spawn(move || {
+            let (mut server, _) = listener.accept().unwrap();
+            read_setup_request(&mut server).unwrap();
+            server.write_all(&setup_response(1)).unwrap();
+        });
+
+        let addresses = [
+            Address::Unix(PathBuf::from("/nonexistent/.X11-unix/X0")),
+            Address::Tcp("127.0.0.1".to_owned(), port),
+        ];
+        let stream = connect_any(&addresses).unwrap();
+        let connection = Connection::setup(Box::new(stream)).unwrap();
+        server.join().unwrap();
+
+        assert_eq!(connection.setup_info().vendor, "xodium");
+    }
+
+    #[test]
+    fn test_all_addresses_failed() {
+        // Grab free port and release it, so nobody listens there
+        let port = {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap().port()
+        };
+
+        let addresses = [
+            Address::Unix(PathBuf::from("/nonexistent/.X11-unix/X0")),
+            Address::Tcp("127.0.0.1".to_owned(), port),
+        ];
+
+        match connect_any(&addresses) {
+            Err(ConnectionError::Unreachable(failures)) => {
+                let tried: Vec<_> = failures
+                    .iter()
+                    .map(|(address, _)| address.clone())
+                    .collect();
+                assert_eq!(
+                    tried,
+                    [
+                        "unix:/nonexistent/.X11-unix/X0".to_owned(),
+                        format!("tcp:127.0.0.1:{}", port),
+                    ]
+                );
+            }
+            Err(other) => panic!("Unexpected error: {}", other),
+            Ok(_) => panic!("Connection should fail"),
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use super::Connection;
+use crate::protocol::{GetAtomName, GetAtomNameReply, InternAtom, InternAtomReply};
+use std::collections::HashMap;
+use std::io;
+
+/// Bidirectional cache of atoms known to the connection.
+/// Atoms are never destroyed by X server, so cached values stay valid.
+#[derive(Default)]
+pub(crate) struct AtomCache {
+    atoms: HashMap<String, u32>,
+    names: HashMap<u32, String>,
+}
+
+impl AtomCache {
+    pub fn atom(&self, name: &str) -> Option<u32> {
+        self.atoms.get(name).copied()
+    }
+
+    pub fn name(&self, atom: u32) -> Option<&str> {
+        self.names.get(&atom).map(String::as_str)
+    }
+
+    pub fn insert(&mut self, name: &str, atom: u32) {
+        self.atoms.insert(name.to_string(), atom);
+        self.names.insert(atom, name.to_string());
+    }
+}
+
+impl Connection {
+    /// Get atom for the name. Returns 0 (None) if `only_if_exists`
+    /// is set and atom does not exist yet.
+    /// Known atoms are served from cache without a round-trip.
+    pub fn intern_atom(&mut self, name: &str, only_if_exists: bool) -> io::Result<u32> {
+        if let Some(atom) = self.atoms.atom(name) {
+            return Ok(atom);
+        }
+
+        let sequence_number = self.send_request(InternAtom {
+            only_if_exists,
+            name: name.to_string(),
+        })?;
+        let reply: InternAtomReply = self.wait_for_reply(sequence_number)?;
+
+        if reply.atom != 0 {
+            self.atoms.insert(name, reply.atom);
+        }
+
+        Ok(reply.atom)
+    }
+
+    /// Get atoms for several names at once, in the same order as names.
+    /// All requests are sent together and replies are read afterwards,
+    /// so there is only a single round-trip regardless of names count.
+    pub fn intern_atoms(&mut self, names: &[&str], only_if_exists: bool) -> io::Result<Vec<u32>> {
+        let mut atoms = vec![0; names.len()];
+        let mut pending = vec![];
+
+        for (i, name) in names.iter().enumerate() {
+            match self.atoms.atom(name) {
+                Some(atom) => atoms[i] = atom,
+                None => {
+                    let sequence_number = self.send_request(InternAtom {
+                        only_if_exists,
+                        name: name.to_string(),
+                    })?;
+                    pending.push((i, sequence_number));
+                }
+            }
+        }
+
+        self.flush()?;
+
+        for (i, sequence_number) in pending {
+            let reply: InternAtomReply = self.wait_for_reply(sequence_number)?;
+            if reply.atom != 0 {
+                self.atoms.insert(names[i], reply.atom);
+            }
+            atoms[i] = reply.atom;
+        }
+
+        Ok(atoms)
+    }
+
+    /// Atom for the name if it is already cached, never touches the socket.
+    /// Cheap enough for hot event loops, see [prime_atoms](Connection::prime_atoms).
+    pub fn known_atom(&self, name: &str) -> Option<u32> {
+        self.atoms.atom(name)
+    }
+
+    /// Intern atoms in a single round-trip, e.g. at startup,
+    /// so [known_atom](Connection::known_atom) finds them later.
+    pub fn prime_atoms(&mut self, names: &[&str]) -> io::Result<()> {
+        self.intern_atoms(names, false)?;
+        Ok(())
+    }
+
+    /// Get name of the atom.
+    /// Known atoms are served from cache without a round-trip.
+    pub fn get_atom_name(&mut self, atom: u32) -> io::Result<String> {
+        if let Some(name) = self.atoms.name(atom) {
+            return Ok(name.to_string());
+        }
+
+        let sequence_number = self.send_request(GetAtomName { atom })?;
+        let reply: GetAtomNameReply = self.wait_for_reply(sequence_number)?;
+        self.atoms.insert(&reply.name, atom);
+
+        Ok(reply.name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::mock_connection;
+
+    #[test]
+    fn test_get_atom_name_cached() {
+        let mut reply = b"\x01\x00\x01\x00\x02\x00\x00\x00\x07\x00".to_vec();
+        reply.extend(vec![0; 22]);
+        reply.extend(b"WM_NAME\0");
+
+        let (mut connection, written) = mock_connection(reply);
+        assert_eq!(connection.get_atom_name(39).unwrap(), "WM_NAME");
+        assert_eq!(connection.get_atom_name(39).unwrap(), "WM_NAME");
+        assert_eq!(connection.intern_atom("WM_NAME", false).unwrap(), 39);
+
+        // Only the first call reaches X server
+        assert_eq!(&written.borrow()[..], b"\x11\x00\x02\x00\x27\x00\x00\x00");
+    }
+
+    #[test]
+    fn test_intern_atoms_batched() {
+        let mut input = vec![];
+        for (sequence_number, atom) in &[(1u8, 0x12au16), (2, 0), (3, 0x12b), (4, 0x1f)] {
+            input.extend(&[1, 0, *sequence_number, 0, 0, 0, 0, 0]);
+            input.extend(&atom.to_le_bytes());
+            input.extend(vec![0; 22]);
+        }
+
+        let (mut connection, written) = mock_connection(input);
+        assert_eq!(connection.intern_atom("WM_NAME", false).unwrap(), 0x12a);
+        written.borrow_mut().clear();
+
+        let atoms = connection
+            .intern_atoms(&["UTF8_STRING", "WM_NAME", "_NET_WM_NAME", "STRING"], true)
+            .unwrap();
+        assert_eq!(atoms, vec![0, 0x12a, 0x12b, 0x1f]);
+
+        // Cached WM_NAME is not requested again
+        let mut expected = b"\x10\x01\x05\x00\x0b\x00\x00\x00UTF8_STRING\0".to_vec();
+        expected.extend(b"\x10\x01\x05\x00\x0c\x00\x00\x00_NET_WM_NAME");
+        expected.extend(b"\x10\x01\x04\x00\x06\x00\x00\x00STRING\0\0");
+        assert_eq!(&written.borrow()[..], &expected[..]);
+        assert_eq!(connection.intern_atom("_NET_WM_NAME", true).unwrap(), 0x12b);
+    }
+
+    #[test]
+    fn test_intern_atom_cached() {
+        let mut reply = b"\x01\x00\x01\x00\x00\x00\x00\x00\x2a\x01\x00\x00".to_vec();
+        reply.extend(vec![0; 20]);
+
+        let (mut connection, written) = mock_connection(reply);
+        assert_eq!(connection.intern_atom("UTF8_STRING", false).unwrap(), 0x12a);
+        assert_eq!(connection.get_atom_name(0x12a).unwrap(), "UTF8_STRING");
+        assert_eq!(written.borrow().len(), 20);
+    }
+
+    #[test]
+    fn test_known_atom() {
+        let mut input = vec![];
+        for (sequence_number, atom) in &[(1u8, 0x12au16), (2, 0x12b)] {
+            input.extend(&[1, 0, *sequence_number, 0, 0, 0, 0, 0]);
+            input.extend(&atom.to_le_bytes());
+            input.extend(vec![0; 22]);
+        }
+
+        let (mut connection, written) = mock_connection(input);
+        assert_eq!(connection.known_atom("UTF8_STRING"), None);
+        assert!(written.borrow().is_empty());
+
+        connection
+            .prime_atoms(&["UTF8_STRING", "_NET_WM_NAME"])
+            .unwrap();
+        written.borrow_mut().clear();
+
+        for _ in 0..1000 {
+            assert_eq!(connection.known_atom("UTF8_STRING"), Some(0x12a));
+            assert_eq!(connection.known_atom("_NET_WM_NAME"), Some(0x12b));
+        }
+        connection.flush().unwrap();
+        assert!(written.borrow().is_empty());
+    }
+}
@@ -0,0 +1,100 @@
+use super::Connection;
+use crate::protocol::{
+    AllocColorCells, AllocColorCellsReply, AllocColorPlanes, ColorPlanes, FreeColors, QueryColors,
+    QueryColorsReply, Rgb,
+};
+use std::io;
+
+impl Connection {
+    /// Allocate read/write colormap cells, returns `colors` pixels and `planes` plane masks.
+    /// Every pixel combined with any subset of masks is allocated.
+    /// Masks are contiguous bits if `contiguous` is set.
+    pub fn alloc_color_cells(
+        &mut self,
+        contiguous: bool,
+        cmap: u32,
+        colors: u16,
+        planes: u16,
+    ) -> io::Result<(Vec<u32>, Vec<u32>)> {
+        let sequence_number = self.send_request(AllocColorCells {
+            contiguous,
+            cmap,
+            colors,
+            planes,
+        })?;
+        let reply: AllocColorCellsReply = self.wait_for_reply(sequence_number)?;
+        Ok((reply.pixels, reply.masks))
+    }
+
+    /// Allocate read/write colormap cells with `reds`, `greens` and `blues`
+    /// planes for the respective channel, e.g. for palette animation.
+    pub fn alloc_color_planes(
+        &mut self,
+        contiguous: bool,
+        cmap: u32,
+        colors: u16,
+        reds: u16,
+        greens: u16,
+        blues: u16,
+    ) -> io::Result<ColorPlanes> {
+        let sequence_number = self.send_request(AllocColorPlanes {
+            contiguous,
+            cmap,
+            colors,
+            reds,
+            greens,
+            blues,
+        })?;
+        self.wait_for_reply(sequence_number)
+    }
+
+    /// Query color values of colormap cells.
+    pub fn query_colors(&mut self, cmap: u32, pixels: &[u32]) -> io::Result<Vec<Rgb>> {
+        let sequence_number = self.send_request(QueryColors {
+            cmap,
+            pixels: pixels.to_vec(),
+        })?;
+        let reply: QueryColorsReply = self.wait_for_reply(sequence_number)?;
+        Ok(reply.colors)
+    }
+
+    /// Free colormap cells. Planes specified by `plane_mask`
+    /// are combined with every pixel to select freed cells.
+    pub fn free_colors(&mut self, cmap: u32, plane_mask: u32, pixels: &[u32]) -> io::Result<()> {
+        self.send_request(FreeColors {
+            cmap,
+            plane_mask,
+            pixels: pixels.to_vec(),
+        })?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::protocol::Rgb;
+    use crate::test_utils::mock_connection;
+
+    #[test]
+    fn test_query_colors() {
+        let mut reply = b"\x01\x00\x01\x00\x02\x00\x00\x00\x01\x00".to_vec();
+        reply.extend(vec![0; 22]);
+        reply.extend(b"\x01\x00\x02\x00\x03\x00\x00\x00");
+
+        let (mut connection, written) = mock_connection(reply);
+        let colors = connection.query_colors(0x20, &[7]).unwrap();
+
+        assert_eq!(
+            colors,
+            vec![Rgb {
+                red: 1,
+                green: 2,
+                blue: 3
+            }]
+        );
+        assert_eq!(
+            &written.borrow()[..],
+            b"\x5b\x00\x03\x00\x20\x00\x00\x00\x07\x00\x00\x00"
+        );
+    }
+}
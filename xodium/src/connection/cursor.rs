@@ -0,0 +1,84 @@
+use super::Connection;
+use crate::protocol::{CreateCursor, RecolorCursor, Rgb};
+use std::io;
+
+impl Connection {
+    /// Create cursor from 1-bit `source` and `mask` pixmaps, e.g. filled with
+    /// [put_image](Connection::put_image), with hotspot at `x`, `y`.
+    /// Mask 0 (None) shows all pixels of source. Returns id of the cursor,
+    /// pixmaps can be freed right after.
+    pub fn create_cursor(
+        &mut self,
+        source: u32,
+        mask: u32,
+        fore: Rgb,
+        back: Rgb,
+        x: u16,
+        y: u16,
+    ) -> io::Result<u32> {
+        let cid = self.generate_id()?;
+        self.send_request(CreateCursor {
+            cid,
+            source,
+            mask,
+            fore_rgb: fore,
+            back_rgb: back,
+            x,
+            y,
+        })?;
+        Ok(cid)
+    }
+
+    /// Change foreground and background colors of cursor, e.g. to match theme.
+    /// Colors are approximated by what hardware supports.
+    pub fn recolor_cursor(&mut self, cursor: u32, fore: Rgb, back: Rgb) -> io::Result<()> {
+        self.send_request(RecolorCursor {
+            cursor,
+            fore_red: fore.red,
+            fore_green: fore.green,
+            fore_blue: fore.blue,
+            back_red: back.red,
+            back_green: back.green,
+            back_blue: back.blue,
+        })?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::protocol::{ImageFormat, Rectangle, Rgb};
+    use crate::test_utils::mock_connection;
+
+    #[test]
+    fn test_create_cursor_from_bitmap() {
+        const GC: u32 = 0x0400_0100;
+        let (mut connection, written) = mock_connection(vec![]);
+
+        let source = connection.create_pixmap(1, 0x100, 8, 2).unwrap();
+        let rectangle = Rectangle::from((0, 0, 8, 2));
+        let data = b"\x18\x00\x00\x00\x3c\x00\x00\x00".to_vec();
+        connection
+            .put_image(ImageFormat::XYPixmap, source, GC, rectangle, 1, data)
+            .unwrap();
+        let cursor = connection
+            .create_cursor(source, 0, Rgb::default(), Rgb::default(), 4, 1)
+            .unwrap();
+        connection.free_pixmap(source).unwrap();
+        assert_eq!(cursor, 0x0400_0001);
+
+        // CreatePixmap, PutImage, CreateCursor without mask and FreePixmap
+        connection.flush().unwrap();
+        let written = written.borrow();
+        assert_eq!(written.len(), 16 + 32 + 32 + 8);
+        assert_eq!(&written[..8], b"\x35\x01\x04\x00\x00\x00\x00\x04");
+        assert_eq!(&written[16..20], b"\x48\x01\x08\x00");
+        assert_eq!(
+            &written[48..60],
+            b"\x5d\x00\x08\x00\x01\x00\x00\x04\x00\x00\x00\x04"
+        );
+        assert_eq!(&written[60..64], b"\x00\x00\x00\x00");
+        assert_eq!(&written[76..80], b"\x04\x00\x01\x00");
+        assert_eq!(&written[80..], b"\x36\x00\x02\x00\x00\x00\x00\x04");
+    }
+}
@@ -0,0 +1,109 @@
+use super::Connection;
+use crate::protocol::{
+    DamageCreate, DamageDestroy, DamageNotifyEvent, DamageQueryVersion, DamageQueryVersionReply,
+    DamageReportLevel, DamageSubtract, Event, DAMAGE_EXTENSION_NAME,
+};
+use std::io;
+
+/// DAMAGE version implemented by the crate.
+const DAMAGE_MAJOR_VERSION: u32 = 1;
+const DAMAGE_MINOR_VERSION: u32 = 1;
+
+impl Connection {
+    /// Negotiate DAMAGE version, returns major and minor version supported by X server.
+    /// Has to be called before other DAMAGE requests.
+    pub fn damage_query_version(&mut self) -> io::Result<(u32, u32)> {
+        let major_opcode = self.extension_opcode(DAMAGE_EXTENSION_NAME)?;
+        let sequence_number = self.send_request(DamageQueryVersion {
+            major_opcode,
+            major_version: DAMAGE_MAJOR_VERSION,
+            minor_version: DAMAGE_MINOR_VERSION,
+        })?;
+        let reply: DamageQueryVersionReply = self.wait_for_reply(sequence_number)?;
+
+        Ok((reply.major_version, reply.minor_version))
+    }
+
+    /// Start tracking damage of drawable, returns damage id.
+    /// Damage is reported by events decoded with [damage_notify](Connection::damage_notify).
+    pub fn damage_create(&mut self, drawable: u32, level: DamageReportLevel) -> io::Result<u32> {
+        let major_opcode = self.extension_opcode(DAMAGE_EXTENSION_NAME)?;
+        let damage = self.generate_id()?;
+        self.send_request(DamageCreate {
+            major_opcode,
+            damage,
+            drawable,
+            level,
+        })?;
+        Ok(damage)
+    }
+
+    /// Stop tracking damage created by [damage_create](Connection::damage_create).
+    pub fn damage_destroy(&mut self, damage: u32) -> io::Result<()> {
+        let major_opcode = self.extension_opcode(DAMAGE_EXTENSION_NAME)?;
+        self.send_request(DamageDestroy {
+            major_opcode,
+            damage,
+        })?;
+        Ok(())
+    }
+
+    /// Remove `repair` region from damage, 0 (None) removes all of it.
+    /// Removed damage is stored in `parts` region unless it is 0 (None).
+    pub fn damage_subtract(&mut self, damage: u32, repair: u32, parts: u32) -> io::Result<()> {
+        let major_opcode = self.extension_opcode(DAMAGE_EXTENSION_NAME)?;
+        self.send_request(DamageSubtract {
+            major_opcode,
+            damage,
+            repair,
+            parts,
+        })?;
+        Ok(())
+    }
+
+    /// Decode event if it is DamageNotify, `None` for other events
+    /// or if DAMAGE is not supported by X server.
+    pub fn damage_notify(&mut self, event: &Event) -> io::Result<Option<DamageNotifyEvent>> {
+        match self.query_extension(DAMAGE_EXTENSION_NAME)? {
+            Some(extension) => DamageNotifyEvent::from_event(event, extension.first_event),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::protocol::{DamageReportLevel, Rectangle};
+    use crate::test_utils::mock_connection;
+
+    #[test]
+    fn test_damage_notify() {
+        let mut input = b"\x01\x00\x01\x00\x00\x00\x00\x00\x01\x8f\x5b\x00".to_vec();
+        input.extend(vec![0; 20]);
+        input.extend(b"\x01\x00\x02\x00\x00\x00\x00\x00\x01\x00\x00\x00\x01\x00\x00\x00");
+        input.extend(vec![0; 16]);
+        input.extend(b"\x5b\x03\x05\x00\x01\x02\x00\x00\x00\x00\x00\x04\x10\x27\x00\x00");
+        input.extend(b"\x00\x00\x00\x00\x20\x00\x10\x00\x00\x00\x00\x00\x80\x07\x38\x04");
+
+        let (mut connection, written) = mock_connection(input);
+        assert_eq!(connection.damage_query_version().unwrap(), (1, 1));
+
+        let damage = connection
+            .damage_create(0x201, DamageReportLevel::NonEmpty)
+            .unwrap();
+        let event = connection.wait_event().unwrap();
+        let damage_notify = connection.damage_notify(&event).unwrap().unwrap();
+        assert_eq!(damage_notify.damage, damage);
+        assert_eq!(damage_notify.drawable, 0x201);
+        assert_eq!(damage_notify.area, Rectangle::from((0, 0, 32, 16)));
+
+        connection.damage_subtract(damage, 0, 0).unwrap();
+        connection.flush().unwrap();
+
+        // QueryExtension and QueryVersion are followed by Create and Subtract
+        let written = written.borrow();
+        assert_eq!(written.len(), 16 + 12 + 16 + 16);
+        assert_eq!(&written[28..36], b"\x8f\x01\x04\x00\x00\x00\x00\x04");
+        assert_eq!(&written[44..52], b"\x8f\x03\x04\x00\x00\x00\x00\x04");
+    }
+}
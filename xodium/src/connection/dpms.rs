@@ -0,0 +1,92 @@
+use super::Connection;
+use crate::protocol::{
+    DPMSCapable, DPMSCapableReply, DPMSDisable, DPMSEnable, DPMSForceLevel, DPMSGetVersion,
+    DPMSGetVersionReply, DPMSInfo, DPMSInfoReply, DPMSLevel, DPMS_EXTENSION_NAME,
+};
+use std::io;
+
+/// DPMS version implemented by the crate.
+const DPMS_MAJOR_VERSION: u16 = 1;
+const DPMS_MINOR_VERSION: u16 = 1;
+
+impl Connection {
+    /// Query DPMS version, returns major and minor version supported by X server.
+    pub fn dpms_get_version(&mut self) -> io::Result<(u16, u16)> {
+        let major_opcode = self.extension_opcode(DPMS_EXTENSION_NAME)?;
+        let sequence_number = self.send_request(DPMSGetVersion {
+            major_opcode,
+            major_version: DPMS_MAJOR_VERSION,
+            minor_version: DPMS_MINOR_VERSION,
+        })?;
+        let reply: DPMSGetVersionReply = self.wait_for_reply(sequence_number)?;
+
+        Ok((reply.major_version, reply.minor_version))
+    }
+
+    /// Whether monitor supports power saving.
+    pub fn dpms_capable(&mut self) -> io::Result<bool> {
+        let major_opcode = self.extension_opcode(DPMS_EXTENSION_NAME)?;
+        let sequence_number = self.send_request(DPMSCapable { major_opcode })?;
+        let reply: DPMSCapableReply = self.wait_for_reply(sequence_number)?;
+        Ok(reply.capable)
+    }
+
+    /// Current power level and whether power saving is enabled.
+    pub fn dpms_info(&mut self) -> io::Result<DPMSInfoReply> {
+        let major_opcode = self.extension_opcode(DPMS_EXTENSION_NAME)?;
+        let sequence_number = self.send_request(DPMSInfo { major_opcode })?;
+        self.wait_for_reply(sequence_number)
+    }
+
+    /// Enable power saving once monitor is idle for configured timeouts.
+    pub fn dpms_enable(&mut self) -> io::Result<()> {
+        let major_opcode = self.extension_opcode(DPMS_EXTENSION_NAME)?;
+        self.send_request(DPMSEnable { major_opcode })?;
+        Ok(())
+    }
+
+    /// Disable power saving, monitor is turned on.
+    pub fn dpms_disable(&mut self) -> io::Result<()> {
+        let major_opcode = self.extension_opcode(DPMS_EXTENSION_NAME)?;
+        self.send_request(DPMSDisable { major_opcode })?;
+        Ok(())
+    }
+
+    /// Switch monitor to power level right away, e.g. [Off](DPMSLevel::Off)
+    /// to blank the screen on demand. X server responds with Match error
+    /// unless power saving is [enabled](Connection::dpms_enable).
+    /// Monitor is turned back on by user input.
+    pub fn dpms_force_level(&mut self, level: DPMSLevel) -> io::Result<()> {
+        let major_opcode = self.extension_opcode(DPMS_EXTENSION_NAME)?;
+        self.send_request(DPMSForceLevel {
+            major_opcode,
+            level,
+        })?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::protocol::DPMSLevel;
+    use crate::test_utils::mock_connection;
+
+    #[test]
+    fn test_dpms_force_level() {
+        let mut reply = b"\x01\x00\x01\x00\x00\x00\x00\x00\x01\x8c\x00\x00".to_vec();
+        reply.extend(vec![0; 20]);
+
+        let (mut connection, written) = mock_connection(reply);
+        connection.dpms_enable().unwrap();
+        connection.dpms_force_level(DPMSLevel::Off).unwrap();
+        connection.flush().unwrap();
+
+        // QueryExtension is sent only once
+        let written = written.borrow();
+        assert_eq!(written.len(), 12 + 4 + 8);
+        assert_eq!(
+            &written[12..],
+            b"\x8c\x04\x01\x00\x8c\x06\x02\x00\x03\x00\x00\x00"
+        );
+    }
+}
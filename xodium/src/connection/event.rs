@@ -0,0 +1,289 @@
+use super::Connection;
+use crate::protocol::{Event, SendEvent, Serialize};
+use std::io;
+
+/// Iterator over events, see [Connection::events].
+pub struct Events<'a> {
+    connection: &'a mut Connection,
+    finished: bool,
+}
+
+impl Iterator for Events<'_> {
+    type Item = io::Result<Event>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let result = self.connection.wait_event();
+        if let Err(err) = &result {
+            if err.kind() == io::ErrorKind::UnexpectedEof {
+                // Nothing more can be received, but end in the middle of event is an error
+                self.finished = true;
+                if !self.connection.framed.has_buffered_input() {
+                    return None;
+                }
+            }
+        }
+        Some(result)
+    }
+}
+
+impl Connection {
+    /// Iterate over events, blocking until each one is received like
+    /// [wait_event](Connection::wait_event). Errors are yielded as items,
+    /// iteration ends once X server closes connection.
+    ///
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// let mut connection = xodium::connect_default()?;
+    /// for event in connection.events() {
+    ///     println!("{:?}", event?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn events(&mut self) -> Events<'_> {
+        Events {
+            connection: self,
+            finished: false,
+        }
+    }
+
+    /// Send event to window as if it was generated by X server,
+    /// e.g. [ClientMessageEvent](crate::ClientMessageEvent) to window manager.
+    /// Event is delivered to clients selected any of `event_mask` events on destination,
+    /// or to destination owner if mask is 0.
+    pub fn send_event<E: Serialize>(
+        &mut self,
+        propagate: bool,
+        destination: u32,
+        event_mask: u32,
+        event: E,
+    ) -> io::Result<()> {
+        self.send_request(SendEvent {
+            propagate,
+            destination,
+            event_mask,
+            event,
+        })?;
+        Ok(())
+    }
+
+    /// Same as [wait_event](Connection::wait_event), but consecutive MotionNotify events
+    /// already received are collapsed to the most recent one, e.g. during fast drag.
+    /// Other events are neither dropped nor reordered.
+    pub fn wait_event_compress_motion(&mut self) -> io::Result<Event> {
+        let mut event = self.wait_event()?;
+
+        while let Event::MotionNotify(_) = event {
+            match self.next_received_event()? {
+                Some(next @ Event::MotionNotify(_)) => event = next,
+                Some(other) => {
+                    self.events.push_front(other);
+                    break;
+                }
+                None => break,
+            }
+        }
+
+        Ok(event)
+    }
+
+    /// Format event as a single line for debugging, with atoms of PropertyNotify
+    /// and ClientMessage events resolved to names by [get_atom_name](Connection::get_atom_name).
+    /// Atoms that fail to resolve are printed as numbers, other events as their `Debug`.
+    pub fn describe_event(&mut self, event: &Event) -> String {
+        match event {
+            Event::PropertyNotify(notify) => format!(
+                "PropertyNotify window={:#x} atom={} state={:?} time={}",
+                notify.window,
+                self.describe_atom(notify.atom),
+                notify.state,
+                notify.time
+            ),
+            Event::ClientMessage(message) => format!(
+                "ClientMessage window={:#x} type={} format={} data={:?}",
+                message.window,
+                self.describe_atom(message.message_type),
+                message.format,
+                message.data
+            ),
+            other => format!("{:?}", other),
+        }
+    }
+
+    fn describe_atom(&mut self, atom: u32) -> String {
+        if atom == 0 {
+            return "None".to_string();
+        }
+
+        self.get_atom_name(atom)
+            .unwrap_or_else(|_| atom.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::protocol::{ClientMessageEvent, Event, PropertyNotifyEvent, PropertyState};
+    use crate::test_utils::mock_connection;
+
+    fn input_event(code: u8, x: i16) -> Vec<u8> {
+        let mut raw = vec![code, 0, 0, 0];
+        raw.resize(20, 0);
+        raw.extend(&x.to_ne_bytes());
+        raw.resize(32, 0);
+        raw
+    }
+
+    fn root_x(event: Event) -> (&'static str, i16) {
+        match event {
+            Event::MotionNotify(motion) => ("motion", motion.root_x),
+            Event::ButtonPress(press) => ("press", press.root_x),
+            other => panic!("Unexpected event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_wait_event_compress_motion() {
+        let mut input = vec![];
+        for (code, x) in &[(6, 1), (6, 2), (4, 2), (6, 3), (6, 4), (6, 5)] {
+            input.extend(input_event(*code, *x));
+        }
+
+        let (mut connection, _) = mock_connection(input);
+
+        assert_eq!(
+            root_x(connection.wait_event_compress_motion().unwrap()),
+            ("motion", 2)
+        );
+        assert_eq!(
+            root_x(connection.wait_event_compress_motion().unwrap()),
+            ("press", 2)
+        );
+        assert_eq!(
+            root_x(connection.wait_event_compress_motion().unwrap()),
+            ("motion", 5)
+        );
+        assert!(connection.poll_event().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_events() {
+        let mut input = input_event(4, 1);
+        input.extend(input_event(6, 2));
+
+        let (mut connection, _) = mock_connection(input);
+        let events: Vec<_> = connection
+            .events()
+            .map(|event| root_x(event.unwrap()))
+            .collect();
+
+        assert_eq!(events, [("press", 1), ("motion", 2)]);
+    }
+
+    #[test]
+    fn test_events_truncated() {
+        let mut input = input_event(4, 1);
+        input.truncate(20);
+
+        let (mut connection, _) = mock_connection(input);
+        let mut events = connection.events();
+
+        assert!(events.next().unwrap().is_err());
+        assert!(events.next().is_none());
+    }
+
+    #[test]
+    fn test_describe_property_notify() {
+        let (mut connection, written) = mock_connection(vec![]);
+        connection.atoms.insert("_NET_WM_NAME", 0x130);
+
+        let event = Event::PropertyNotify(PropertyNotifyEvent {
+            window: 0x0400_0001,
+            atom: 0x130,
+            time: 42,
+            state: PropertyState::NewValue,
+        });
+        assert_eq!(
+            connection.describe_event(&event),
+            "PropertyNotify window=0x4000001 atom=_NET_WM_NAME state=NewValue time=42"
+        );
+
+        // Cached atom needs no GetAtomName request
+        connection.flush().unwrap();
+        assert!(written.borrow().is_empty());
+    }
+    #[test]
+    fn test_describe_client_message_uncached() {
+        let mut reply = b"\x01\x00\x01\x00\x03\x00\x00\x00\x0c\x00".to_vec();
+        reply.extend(vec![0; 22]);
+        reply.extend(b"WM_PROTOCOLS");
+        // Second atom is unknown to X server
+        reply.extend(b"\x00\x05\x02\x00\x31\x01\x00\x00\x00\x00\x11\x00");
+        reply.extend(vec![0; 20]);
+
+        let (mut connection, written) = mock_connection(reply);
+        let event = Event::ClientMessage(ClientMessageEvent {
+            format: 32,
+            window: 0x0400_0001,
+            message_type: 0x130,
+            data: [0; 20],
+        });
+        assert_eq!(
+            connection.describe_event(&event),
+            format!(
+                "ClientMessage window=0x4000001 type=WM_PROTOCOLS format=32 data={:?}",
+                [0u8; 20]
+            )
+        );
+        // GetAtomName of the message type
+        assert_eq!(&written.borrow()[..8], b"\x11\x00\x02\x00\x30\x01\x00\x00");
+
+        let event = Event::PropertyNotify(PropertyNotifyEvent {
+            window: 0x0400_0001,
+            atom: 0x131,
+            time: 42,
+            state: PropertyState::Deleted,
+        });
+        assert_eq!(
+            connection.describe_event(&event),
+            "PropertyNotify window=0x4000001 atom=305 state=Deleted time=42"
+        );
+    }
+
+    #[test]
+    fn test_describe_atom_io_error() {
+        // No reply to GetAtomName, connection is closed
+        let (mut connection, _) = mock_connection(vec![]);
+        let event = Event::PropertyNotify(PropertyNotifyEvent {
+            window: 0x0400_0001,
+            atom: 0x131,
+            time: 42,
+            state: PropertyState::NewValue,
+        });
+        assert_eq!(
+            connection.describe_event(&event),
+            "PropertyNotify window=0x4000001 atom=305 state=NewValue time=42"
+        );
+    }
+
+    #[test]
+    fn test_describe_atom_none() {
+        let (mut connection, written) = mock_connection(vec![]);
+        let event = Event::PropertyNotify(PropertyNotifyEvent {
+            window: 0x0400_0001,
+            atom: 0,
+            time: 42,
+            state: PropertyState::NewValue,
+        });
+        assert_eq!(
+            connection.describe_event(&event),
+            "PropertyNotify window=0x4000001 atom=None state=NewValue time=42"
+        );
+
+        connection.flush().unwrap();
+        assert!(written.borrow().is_empty());
+    }
+}
@@ -0,0 +1,281 @@
+//! Helpers for common ICCCM and EWMH (Extended Window Manager Hints)
+//! properties and messages.
+
+use super::Connection;
+use crate::protocol::{ClientMessageEvent, Event, EventMask};
+use std::io;
+
+/// Predefined WINDOW atom, type of window properties.
+const WINDOW: u32 = 33;
+
+/// Source indication of `_NET_ACTIVE_WINDOW` message sent by application.
+const SOURCE_APPLICATION: u32 = 1;
+
+impl Connection {
+    /// Set `WM_PROTOCOLS` window manager may use to talk to window,
+    /// e.g. `&["WM_DELETE_WINDOW"]` to get [is_wm_delete](Event::is_wm_delete)
+    /// message instead of being killed when user closes window.
+    pub fn set_wm_protocols(&mut self, window: u32, protocols: &[&str]) -> io::Result<()> {
+        let property = self.intern_atom("WM_PROTOCOLS", false)?;
+        let protocols = self.intern_atoms(protocols, false)?;
+        self.set_atom_property(window, property, &protocols)
+    }
+
+    /// Set `_NET_WM_NAME` window title.
+    pub fn set_wm_name(&mut self, window: u32, name: &str) -> io::Result<()> {
+        let property = self.intern_atom("_NET_WM_NAME", false)?;
+        self.set_text_property(window, property, name)
+    }
+
+    /// Set `_NET_WM_WINDOW_TYPE` to types in order of preference,
+    /// e.g. `&["_NET_WM_WINDOW_TYPE_DOCK"]`.
+    pub fn set_wm_window_type(&mut self, window: u32, types: &[&str]) -> io::Result<()> {
+        let property = self.intern_atom("_NET_WM_WINDOW_TYPE", false)?;
+        let types = self.intern_atoms(types, false)?;
+        self.set_atom_property(window, property, &types)
+    }
+
+    /// Set `_NET_WM_STATE` of window which is not mapped yet,
+    /// e.g. `&["_NET_WM_STATE_FULLSCREEN"]`.
+    /// Window manager owns the property of mapped windows.
+    pub fn set_wm_state(&mut self, window: u32, states: &[&str]) -> io::Result<()> {
+        let property = self.intern_atom("_NET_WM_STATE", false)?;
+        let states = self.intern_atoms(states, false)?;
+        self.set_atom_property(window, property, &states)
+    }
+
+    /// Ask window manager to set `_NET_FRAME_EXTENTS` of window before it is mapped.
+    pub fn request_frame_extents(&mut self, window: u32) -> io::Result<()> {
+        self.send_root_message(window, "_NET_REQUEST_FRAME_EXTENTS", [0; 5])
+    }
+
+    /// Ask window manager to activate window.
+    pub fn request_active_window(&mut self, window: u32) -> io::Result<()> {
+        self.send_root_message(
+            window,
+            "_NET_ACTIVE_WINDOW",
+            [SOURCE_APPLICATION, 0, 0, 0, 0],
+        )
+    }
+
+    /// Window activated by window manager, `None` if there is no active window
+    /// or window manager does not support `_NET_ACTIVE_WINDOW`.
+    pub fn get_active_window(&mut self) -> io::Result<Option<u32>> {
+        let property = self.intern_atom("_NET_ACTIVE_WINDOW", false)?;
+        let root = self.default_screen().root;
+        let reply = self.get_property(false, root, property, WINDOW, 0, 1)?;
+
+        match reply.value.get(..4) {
+            Some(bytes) if reply.format == 32 => {
+                let window = u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+                Ok(Some(window).filter(|&window| window != 0))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Send client message about window to window manager listening on root window.
+    fn send_root_message(
+        &mut self,
+        window: u32,
+        message_type: &str,
+        data: [u32; 5],
+    ) -> io::Result<()> {
+        let message_type = self.intern_atom(message_type, false)?;
+        let root = self.default_screen().root;
+        self.send_event(
+            false,
+            root,
+            (EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY).bits(),
+            ClientMessageEvent::new_32(window, message_type, data),
+        )
+    }
+}
+
+impl Event {
+    /// Whether event is `WM_DELETE_WINDOW` message of window manager asking
+    /// to close window, which opted in with [set_wm_protocols](Connection::set_wm_protocols).
+    /// Always false if the atoms were not interned by connection.
+    pub fn is_wm_delete(&self, connection: &Connection) -> bool {
+        let message = match self {
+            Event::ClientMessage(message) if message.format == 32 => message,
+            _ => return false,
+        };
+
+        match (
+            connection.known_atom("WM_PROTOCOLS"),
+            connection.known_atom("WM_DELETE_WINDOW"),
+        ) {
+            (Some(wm_protocols), Some(wm_delete_window)) => {
+                message.message_type == wm_protocols
+                    && message.data[..4] == wm_delete_window.to_ne_bytes()
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::protocol::{ClientMessageEvent, Event, Serialize};
+    use crate::test_utils::{intern_atom_reply, mock_connection};
+
+    #[test]
+    fn test_set_wm_protocols() {
+        let mut input = intern_atom_reply(1, 0x130);
+        input.extend(intern_atom_reply(2, 0x131));
+        // Synthesized delete message, sent by window manager with SendEvent
+        let mut message = vec![];
+        ClientMessageEvent::new_32(0x201, 0x130, [0x131, 0, 0, 0, 0])
+            .serialize(&mut message)
+            .unwrap();
+        message[0] |= 0x80;
+        input.extend(message);
+
+        let (mut connection, written) = mock_connection(input);
+        connection
+            .set_wm_protocols(0x201, &["WM_DELETE_WINDOW"])
+            .unwrap();
+        connection.flush().unwrap();
+
+        let written = written.borrow();
+        let change_property = &written[written.len() - 28..];
+        assert_eq!(
+            change_property,
+            b"\x12\x00\x07\x00\x01\x02\x00\x00\x30\x01\x00\x00\x04\x00\x00\x00\
+              \x20\x00\x00\x00\x01\x00\x00\x00\x31\x01\x00\x00"
+        );
+
+        let event = connection.wait_event().unwrap();
+        assert!(event.is_wm_delete(&connection));
+
+        // Other protocol of the same message type
+        let take_focus = ClientMessageEvent::new_32(0x201, 0x130, [0x132, 0, 0, 0, 0]);
+        assert!(!Event::ClientMessage(take_focus).is_wm_delete(&connection));
+    }
+
+    #[test]
+    fn test_is_wm_delete_without_atoms() {
+        let (connection, _) = mock_connection(vec![]);
+        let message = ClientMessageEvent::new_32(0x201, 0x130, [0x131, 0, 0, 0, 0]);
+        assert!(!Event::ClientMessage(message).is_wm_delete(&connection));
+    }
+
+    #[test]
+    fn test_set_wm_name() {
+        let mut input = intern_atom_reply(1, 0x130);
+        input.extend(intern_atom_reply(2, 0x12a));
+
+        let (mut connection, written) = mock_connection(input);
+        connection.set_wm_name(0x201, "xodium").unwrap();
+        connection.flush().unwrap();
+
+        // Property, format 8, UTF8_STRING type and padded name
+        let written = written.borrow();
+        let change_property = &written[written.len() - 32..];
+        assert_eq!(&change_property[8..12], b"\x30\x01\x00\x00");
+        assert_eq!(
+            &change_property[12..20],
+            b"\x2a\x01\x00\x00\x08\x00\x00\x00"
+        );
+        assert_eq!(&change_property[20..], b"\x06\x00\x00\x00xodium\0\0");
+    }
+
+    #[test]
+    fn test_set_wm_window_type() {
+        let mut input = intern_atom_reply(1, 0x130);
+        input.extend(intern_atom_reply(2, 0x131));
+
+        let (mut connection, written) = mock_connection(input);
+        connection
+            .set_wm_window_type(0x201, &["_NET_WM_WINDOW_TYPE_DOCK"])
+            .unwrap();
+        connection.flush().unwrap();
+
+        // Property, format 32, ATOM type and single atom
+        let written = written.borrow();
+        let change_property = &written[written.len() - 28..];
+        assert_eq!(
+            change_property,
+            b"\x12\x00\x07\x00\x01\x02\x00\x00\x30\x01\x00\x00\x04\x00\x00\x00\
+              \x20\x00\x00\x00\x01\x00\x00\x00\x31\x01\x00\x00"
+        );
+    }
+
+    #[test]
+    fn test_set_wm_state() {
+        let mut input = intern_atom_reply(1, 0x130);
+        input.extend(intern_atom_reply(2, 0x131));
+        input.extend(intern_atom_reply(3, 0x132));
+
+        let (mut connection, written) = mock_connection(input);
+        connection
+            .set_wm_state(0x201, &["_NET_WM_STATE_ABOVE", "_NET_WM_STATE_STICKY"])
+            .unwrap();
+        connection.flush().unwrap();
+
+        let written = written.borrow();
+        let change_property = &written[written.len() - 32..];
+        assert_eq!(&change_property[..4], b"\x12\x00\x08\x00");
+        assert_eq!(&change_property[8..12], b"\x30\x01\x00\x00");
+        assert_eq!(
+            &change_property[12..20],
+            b"\x04\x00\x00\x00\x20\x00\x00\x00"
+        );
+        assert_eq!(
+            &change_property[20..],
+            b"\x02\x00\x00\x00\x31\x01\x00\x00\x32\x01\x00\x00"
+        );
+    }
+
+    #[test]
+    fn test_request_frame_extents() {
+        let (mut connection, written) = mock_connection(intern_atom_reply(1, 0x130));
+        connection.request_frame_extents(0x201).unwrap();
+        connection.flush().unwrap();
+
+        // SendEvent to root with substructure mask, followed by client message
+        let written = written.borrow();
+        let send_event = &written[written.len() - 44..];
+        assert_eq!(
+            &send_event[..12],
+            b"\x19\x00\x0b\x00\x00\x01\x00\x00\x00\x00\x18\x00"
+        );
+        assert_eq!(
+            &send_event[12..24],
+            b"\x21\x20\x00\x00\x01\x02\x00\x00\x30\x01\x00\x00"
+        );
+        assert_eq!(&send_event[24..], &[0; 20][..]);
+    }
+
+    #[test]
+    fn test_request_active_window() {
+        let (mut connection, written) = mock_connection(intern_atom_reply(1, 0x130));
+        connection.request_active_window(0x201).unwrap();
+        connection.flush().unwrap();
+
+        let written = written.borrow();
+        let message = &written[written.len() - 32..];
+        assert_eq!(
+            &message[..12],
+            b"\x21\x20\x00\x00\x01\x02\x00\x00\x30\x01\x00\x00"
+        );
+        assert_eq!(&message[12..16], b"\x01\x00\x00\x00");
+    }
+
+    #[test]
+    fn test_get_active_window() {
+        let mut input = intern_atom_reply(1, 0x130);
+        input.extend(b"\x01\x20\x02\x00\x01\x00\x00\x00\x21\x00\x00\x00\x00\x00\x00\x00");
+        input.extend(b"\x01\x00\x00\x00");
+        input.extend(vec![0; 12]);
+        input.extend(b"\x03\x04\x00\x00");
+        // Property does not exist
+        input.extend(b"\x01\x00\x03\x00\x00\x00\x00\x00");
+        input.extend(vec![0; 24]);
+
+        let (mut connection, _) = mock_connection(input);
+        assert_eq!(connection.get_active_window().unwrap(), Some(0x403));
+        assert_eq!(connection.get_active_window().unwrap(), None);
+    }
+}
@@ -0,0 +1,75 @@
+use super::Connection;
+use crate::protocol::{
+    Extension, ListExtensions, ListExtensionsReply, QueryExtension, QueryExtensionReply,
+};
+use std::io;
+
+impl Connection {
+    /// Check whether extension is supported by X server, `None` if it is not.
+    /// Results are cached, so every extension is queried only once.
+    pub fn query_extension(&mut self, name: &str) -> io::Result<Option<Extension>> {
+        if let Some(extension) = self.extensions.get(name) {
+            return Ok(*extension);
+        }
+
+        let sequence_number = self.send_request(QueryExtension {
+            name: name.to_string(),
+        })?;
+        let reply: QueryExtensionReply = self.wait_for_reply(sequence_number)?;
+        self.extensions.insert(name.to_string(), reply.extension);
+
+        Ok(reply.extension)
+    }
+
+    /// Names of extensions supported by X server.
+    pub fn list_extensions(&mut self) -> io::Result<Vec<String>> {
+        let sequence_number = self.send_request(ListExtensions)?;
+        let reply: ListExtensionsReply = self.wait_for_reply(sequence_number)?;
+        Ok(reply.names)
+    }
+
+    /// Major opcode of extension, error if extension is not supported.
+    pub(crate) fn extension_opcode(&mut self, name: &str) -> io::Result<u8> {
+        match self.query_extension(name)? {
+            Some(extension) => Ok(extension.major_opcode),
+            None => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("{} extension is not supported by X server", name),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::protocol::Extension;
+    use crate::test_utils::mock_connection;
+
+    #[test]
+    fn test_query_extension_cached() {
+        let mut reply = b"\x01\x00\x01\x00\x00\x00\x00\x00\x01\x84\x00\x00".to_vec();
+        reply.extend(vec![0; 20]);
+
+        let (mut connection, written) = mock_connection(reply);
+        let expected = Some(Extension {
+            major_opcode: 132,
+            first_event: 0,
+            first_error: 0,
+        });
+
+        assert_eq!(connection.query_extension("XTEST").unwrap(), expected);
+        assert_eq!(connection.query_extension("XTEST").unwrap(), expected);
+        assert_eq!(written.borrow().len(), 16);
+    }
+
+    #[test]
+    fn test_extension_not_supported() {
+        let mut reply = b"\x01\x00\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00".to_vec();
+        reply.extend(vec![0; 20]);
+
+        let (mut connection, _) = mock_connection(reply);
+
+        assert!(connection.extension_opcode("XTEST").is_err());
+        assert!(connection.extension_opcode("XTEST").is_err());
+    }
+}
@@ -0,0 +1,187 @@
+use super::Connection;
+use crate::protocol::{
+    CloseFont, GetFontPath, GetFontPathReply, ImageText8, ListFonts, ListFontsReply, OpenFont,
+    QueryFont, QueryFontReply, SetFontPath,
+};
+use std::convert::TryFrom;
+use std::io;
+
+/// Longest string single ImageText8 request can draw.
+const MAX_IMAGE_TEXT8_LEN: usize = 255;
+
+impl Connection {
+    /// Open font by name, e.g. `fixed`, returns its id.
+    pub fn open_font(&mut self, name: &str) -> io::Result<u32> {
+        let fid = self.generate_id()?;
+        self.send_request(OpenFont {
+            fid,
+            name: name.to_string(),
+        })?;
+        Ok(fid)
+    }
+
+    /// Free font opened by [open_font](Connection::open_font).
+    pub fn close_font(&mut self, font: u32) -> io::Result<()> {
+        self.send_request(CloseFont { font })?;
+        Ok(())
+    }
+
+    /// Query metrics of font, or font of GC if GC id is passed.
+    pub fn query_font(&mut self, font: u32) -> io::Result<QueryFontReply> {
+        let sequence_number = self.send_request(QueryFont { font })?;
+        self.wait_for_reply(sequence_number)
+    }
+
+    /// List up to `max_names` names of available fonts matching pattern,
+    /// e.g. `*-iso10646-1`, where `*` matches any characters and `?` any single one.
+    pub fn list_fonts(&mut self, pattern: &str, max_names: u16) -> io::Result<Vec<String>> {
+        let sequence_number = self.send_request(ListFonts {
+            max_names,
+            pattern: pattern.to_string(),
+        })?;
+        let reply: ListFontsReply = self.wait_for_reply(sequence_number)?;
+        Ok(reply.names)
+    }
+
+    /// Set directories and font servers fonts are looked up in,
+    /// empty path restores the default one.
+    pub fn set_font_path(&mut self, paths: &[&str]) -> io::Result<()> {
+        self.send_request(SetFontPath {
+            paths: paths.iter().map(|path| path.to_string()).collect(),
+        })?;
+        Ok(())
+    }
+
+    /// Directories and font servers fonts are looked up in.
+    pub fn get_font_path(&mut self) -> io::Result<Vec<String>> {
+        let sequence_number = self.send_request(GetFontPath)?;
+        let reply: GetFontPathReply = self.wait_for_reply(sequence_number)?;
+        Ok(reply.paths)
+    }
+}
+
+/// Opened font with its metrics cached, so measuring text needs no round trips.
+/// Text is encoded as Latin-1, characters outside of it are replaced with `?`.
+#[derive(Debug)]
+pub struct Font {
+    id: u32,
+    info: QueryFontReply,
+}
+
+impl Font {
+    /// Open font by name and query its metrics.
+    pub fn open(connection: &mut Connection, name: &str) -> io::Result<Self> {
+        let id = connection.open_font(name)?;
+        let info = connection.query_font(id)?;
+        Ok(Font { id, info })
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn info(&self) -> &QueryFontReply {
+        &self.info
+    }
+
+    /// Width of text in pixels, sum of its characters widths.
+    pub fn text_width(&self, text: &str) -> i32 {
+        Self::encode(text).map(|char| self.char_width(char)).sum()
+    }
+
+    /// Draw text using ImageText8 requests, which fill text background
+    /// with GC background. GC font has to be set to this font.
+    pub fn draw_text(
+        &self,
+        connection: &mut Connection,
+        drawable: u32,
+        gc: u32,
+        x: i16,
+        y: i16,
+        text: &str,
+    ) -> io::Result<()> {
+        let string: Vec<u8> = Self::encode(text).collect();
+        let mut x = i32::from(x);
+
+        for chunk in string.chunks(MAX_IMAGE_TEXT8_LEN) {
+            let chunk_x = i16::try_from(x).map_err(|_| {
+                io::Error::new(io::ErrorKind::InvalidInput, "Text is out of drawable")
+            })?;
+            connection.send_request(ImageText8 {
+                drawable,
+                gc,
+                x: chunk_x,
+                y,
+                string: chunk.to_vec(),
+            })?;
+            x += chunk.iter().map(|&char| self.char_width(char)).sum::<i32>();
+        }
+        Ok(())
+    }
+
+    /// Free font, its id may no longer be used.
+    pub fn close(self, connection: &mut Connection) -> io::Result<()> {
+        connection.close_font(self.id)
+    }
+
+    fn encode(text: &str) -> impl Iterator<Item = u8> + '_ {
+        text.chars()
+            .map(|char| u8::try_from(u32::from(char)).unwrap_or(b'?'))
+    }
+
+    fn char_width(&self, char: u8) -> i32 {
+        self.info
+            .char_info(char)
+            .map_or(0, |info| i32::from(info.character_width))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Font;
+    use crate::test_utils::{mock_connection, query_font_reply};
+
+    /// Reply to QueryFont, the second request after OpenFont.
+    fn reply() -> Vec<u8> {
+        let mut reply = query_font_reply();
+        reply[2] = 2;
+        reply
+    }
+
+    #[test]
+    fn test_text_width() {
+        let (mut connection, written) = mock_connection(reply());
+        let font = Font::open(&mut connection, "fixed").unwrap();
+
+        assert_eq!(font.id(), 0x04000000);
+        // OpenFont is followed by QueryFont
+        assert_eq!(&written.borrow()[..2], b"\x2d\x00");
+        assert_eq!(&written.borrow()[20..], b"\x2f\x00\x02\x00\x00\x00\x00\x04");
+
+        assert_eq!(font.text_width(""), 0);
+        assert_eq!(font.text_width(" ! "), 17);
+        // Nonexistent, out of range and non Latin-1 characters use default one
+        assert_eq!(font.text_width("\"a\u{263a}"), 15);
+    }
+
+    #[test]
+    fn test_draw_long_text() {
+        let (mut connection, written) = mock_connection(reply());
+        let font = Font::open(&mut connection, "fixed").unwrap();
+        written.borrow_mut().clear();
+
+        let text = "!".repeat(300);
+        font.draw_text(&mut connection, 0x100, 0x04000001, 10, 20, &text)
+            .unwrap();
+        connection.flush().unwrap();
+
+        // Second request starts right after 255 characters 7 pixels wide
+        let written = written.borrow();
+        assert_eq!(&written[..4], b"\x4c\xff\x44\x00");
+        assert_eq!(&written[16 + 256..][..4], b"\x4c\x2d\x10\x00");
+        assert_eq!(
+            &written[16 + 256 + 12..][..2],
+            &(10 + 255 * 7_i16).to_ne_bytes()
+        );
+    }
+}
@@ -0,0 +1,298 @@
+use super::Connection;
+use crate::protocol::{
+    EventMask, GrabButton, GrabKey, GrabMode, ModMask, UngrabButton, UngrabKey, ANY_BUTTON,
+    ANY_KEY, ANY_MODIFIER,
+};
+use std::io;
+
+/// Passive grab made by [grab_key](Connection::grab_key)
+/// or [grab_button](Connection::grab_button) while grabs are tracked.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum PassiveGrab {
+    Key {
+        key: u8,
+        grab_window: u32,
+        modifiers: ModMask,
+    },
+    Button {
+        button: u8,
+        grab_window: u32,
+        modifiers: ModMask,
+    },
+}
+
+/// Whether ungrab of `requested` key or button releases `grabbed` one,
+/// `any` is the wildcard of requested value.
+fn ungrab_matches(requested: u16, grabbed: u16, any: u16) -> bool {
+    requested == any || requested == grabbed
+}
+
+impl Connection {
+    /// Passively grab keyboard once `key` is pressed with `modifiers` in `grab_window`,
+    /// [ANY_KEY] and [ANY_MODIFIER] match any key and modifiers.
+    pub fn grab_key(
+        &mut self,
+        owner_events: bool,
+        grab_window: u32,
+        modifiers: ModMask,
+        key: u8,
+        pointer_mode: GrabMode,
+        keyboard_mode: GrabMode,
+    ) -> io::Result<()> {
+        self.send_request(GrabKey {
+            owner_events,
+            grab_window,
+            modifiers,
+            key,
+            pointer_mode,
+            keyboard_mode,
+        })?;
+        if let Some(grabs) = &mut self.grabs {
+            grabs.push(PassiveGrab::Key {
+                key,
+                grab_window,
+                modifiers,
+            });
+        }
+        Ok(())
+    }
+
+    /// Release passive key grabs of `grab_window`,
+    /// [ANY_KEY] and [ANY_MODIFIER] release grabs of any key and modifiers.
+    pub fn ungrab_key(&mut self, key: u8, grab_window: u32, modifiers: ModMask) -> io::Result<()> {
+        self.send_request(UngrabKey {
+            key,
+            grab_window,
+            modifiers,
+        })?;
+        if let Some(grabs) = &mut self.grabs {
+            grabs.retain(|grab| match *grab {
+                PassiveGrab::Key {
+                    key: grabbed_key,
+                    grab_window: grabbed_window,
+                    modifiers: grabbed_modifiers,
+                } => {
+                    grabbed_window != grab_window
+                        || !ungrab_matches(key.into(), grabbed_key.into(), ANY_KEY.into())
+                        || !ungrab_matches(
+                            modifiers.into(),
+                            grabbed_modifiers.into(),
+                            ANY_MODIFIER.into(),
+                        )
+                }
+                PassiveGrab::Button { .. } => true,
+            });
+        }
+        Ok(())
+    }
+
+    /// Passively grab pointer once `button` is pressed with `modifiers` in `grab_window`,
+    /// [ANY_BUTTON] and [ANY_MODIFIER] match any button and modifiers.
+    #[allow(clippy::too_many_arguments)]
+    pub fn grab_button(
+        &mut self,
+        owner_events: bool,
+        grab_window: u32,
+        event_mask: EventMask,
+        pointer_mode: GrabMode,
+        keyboard_mode: GrabMode,
+        confine_to: u32,
+        cursor: u32,
+        button: u8,
+        modifiers: ModMask,
+    ) -> io::Result<()> {
+        self.send_request(GrabButton {
+            owner_events,
+            grab_window,
+            event_mask,
+            pointer_mode,
+            keyboard_mode,
+            confine_to,
+            cursor,
+            button,
+            modifiers,
+        })?;
+        if let Some(grabs) = &mut self.grabs {
+            grabs.push(PassiveGrab::Button {
+                button,
+                grab_window,
+                modifiers,
+            });
+        }
+        Ok(())
+    }
+
+    /// Release passive button grabs of `grab_window`,
+    /// [ANY_BUTTON] and [ANY_MODIFIER] release grabs of any button and modifiers.
+    pub fn ungrab_button(
+        &mut self,
+        button: u8,
+        grab_window: u32,
+        modifiers: ModMask,
+    ) -> io::Result<()> {
+        self.send_request(UngrabButton {
+            button,
+            grab_window,
+            modifiers,
+        })?;
+        if let Some(grabs) = &mut self.grabs {
+            grabs.retain(|grab| match *grab {
+                PassiveGrab::Button {
+                    button: grabbed_button,
+                    grab_window: grabbed_window,
+                    modifiers: grabbed_modifiers,
+                } => {
+                    grabbed_window != grab_window
+                        || !ungrab_matches(button.into(), grabbed_button.into(), ANY_BUTTON.into())
+                        || !ungrab_matches(
+                            modifiers.into(),
+                            grabbed_modifiers.into(),
+                            ANY_MODIFIER.into(),
+                        )
+                }
+                PassiveGrab::Key { .. } => true,
+            });
+        }
+        Ok(())
+    }
+
+    /// Record passive grabs made by [grab_key](Connection::grab_key) and
+    /// [grab_button](Connection::grab_button), so
+    /// [release_all_grabs](Connection::release_all_grabs) can release them.
+    /// Disabling forgets grabs recorded so far.
+    pub fn set_track_grabs(&mut self, track: bool) {
+        match (track, &self.grabs) {
+            (true, None) => self.grabs = Some(Vec::new()),
+            (false, _) => self.grabs = None,
+            (true, Some(_)) => {}
+        }
+    }
+
+    /// Release every tracked passive grab not released yet, e.g. on shutdown,
+    /// so keys and buttons are not left grabbed by windows which outlive the client.
+    pub fn release_all_grabs(&mut self) -> io::Result<()> {
+        let grabs = match &mut self.grabs {
+            Some(grabs) => std::mem::take(grabs),
+            None => return Ok(()),
+        };
+
+        for grab in grabs {
+            match grab {
+                PassiveGrab::Key {
+                    key,
+                    grab_window,
+                    modifiers,
+                } => self.send_request(UngrabKey {
+                    key,
+                    grab_window,
+                    modifiers,
+                })?,
+                PassiveGrab::Button {
+                    button,
+                    grab_window,
+                    modifiers,
+                } => self.send_request(UngrabButton {
+                    button,
+                    grab_window,
+                    modifiers,
+                })?,
+            };
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::protocol::{EventMask, GrabMode, ModMask, ANY_KEY, ANY_MODIFIER};
+    use crate::test_utils::mock_connection;
+
+    #[test]
+    fn test_release_all_grabs() {
+        let (mut connection, written) = mock_connection(vec![]);
+        connection.set_track_grabs(true);
+
+        let mode = GrabMode::Asynchronous;
+        connection
+            .grab_key(false, 0x100, ModMask::MOD4, 24, mode, mode)
+            .unwrap();
+        connection
+            .grab_key(false, 0x100, ModMask::MOD4, 25, mode, mode)
+            .unwrap();
+        connection
+            .grab_key(false, 0x200, ModMask::CONTROL, 24, mode, mode)
+            .unwrap();
+        connection
+            .grab_button(
+                true,
+                0x100,
+                EventMask::BUTTON_PRESS,
+                mode,
+                mode,
+                0,
+                0,
+                1,
+                ModMask::MOD1,
+            )
+            .unwrap();
+        connection.ungrab_key(25, 0x100, ModMask::MOD4).unwrap();
+        connection.flush().unwrap();
+        written.borrow_mut().clear();
+
+        connection.release_all_grabs().unwrap();
+        connection.flush().unwrap();
+        assert_eq!(
+            *written.borrow(),
+            b"\x22\x18\x03\x00\x00\x01\x00\x00\x40\x00\x00\x00\
+              \x22\x18\x03\x00\x00\x02\x00\x00\x04\x00\x00\x00\
+              \x1d\x01\x03\x00\x00\x01\x00\x00\x08\x00\x00\x00"
+        );
+
+        written.borrow_mut().clear();
+        connection.release_all_grabs().unwrap();
+        connection.flush().unwrap();
+        assert!(written.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_ungrab_wildcards() {
+        let (mut connection, written) = mock_connection(vec![]);
+        connection.set_track_grabs(true);
+
+        let mode = GrabMode::Synchronous;
+        connection
+            .grab_key(false, 0x100, ModMask::MOD4, 24, mode, mode)
+            .unwrap();
+        connection
+            .grab_key(false, 0x100, ModMask::CONTROL, 25, mode, mode)
+            .unwrap();
+        connection
+            .grab_key(false, 0x200, ModMask::CONTROL, 25, mode, mode)
+            .unwrap();
+        connection
+            .grab_button(
+                false,
+                0x100,
+                EventMask::BUTTON_PRESS,
+                mode,
+                mode,
+                0,
+                0,
+                1,
+                ModMask::MOD1,
+            )
+            .unwrap();
+        connection.ungrab_key(ANY_KEY, 0x100, ANY_MODIFIER).unwrap();
+        connection.flush().unwrap();
+        written.borrow_mut().clear();
+
+        // Only grabs of other windows and buttons are left
+        connection.release_all_grabs().unwrap();
+        connection.flush().unwrap();
+        assert_eq!(
+            *written.borrow(),
+            b"\x22\x19\x03\x00\x00\x02\x00\x00\x04\x00\x00\x00\
+              \x1d\x01\x03\x00\x00\x01\x00\x00\x08\x00\x00\x00"
+        );
+    }
+}
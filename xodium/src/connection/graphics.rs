@@ -0,0 +1,355 @@
+use super::Connection;
+use crate::protocol::{
+    CoordinateMode, CopyArea, CopyGC, CopyPlane, GcValueMask, ImageText16, Point, PolyPoint,
+    PolyRectangle, PolySegment, PolyText16, PolyText8, QueryBestSize, QueryBestSizeReply,
+    Rectangle, RectangleOrdering, Segment, SetClipRectangles, SetDashes, SizeClass, TextItem16,
+    TextItem8,
+};
+use std::io;
+use std::mem;
+
+/// Size of Poly* request header in 4-byte units.
+const POLY_HEADER_LEN: usize = 3;
+
+/// Points, lines and rectangles drawn into the same drawable with the same GC,
+/// sent using as few Poly* requests as maximum request length allows.
+/// Created by [draw_batch](Connection::draw_batch), sends everything left once dropped.
+pub struct DrawBatch<'a> {
+    connection: &'a mut Connection,
+    drawable: u32,
+    gc: u32,
+    points: Vec<Point>,
+    segments: Vec<Segment>,
+    rectangles: Vec<Rectangle>,
+}
+
+impl DrawBatch<'_> {
+    pub fn point(&mut self, point: Point) -> &mut Self {
+        self.points.push(point);
+        self
+    }
+
+    pub fn line(&mut self, segment: Segment) -> &mut Self {
+        self.segments.push(segment);
+        self
+    }
+
+    /// Rectangle outline.
+    pub fn rectangle(&mut self, rectangle: Rectangle) -> &mut Self {
+        self.rectangles.push(rectangle);
+        self
+    }
+
+    /// Send everything drawn so far to X server.
+    pub fn flush(&mut self) -> io::Result<()> {
+        let max_len = usize::from(self.connection.setup.maximum_request_length);
+        // Point takes one 4-byte unit, segment and rectangle take two
+        let max_points = max_len.saturating_sub(POLY_HEADER_LEN);
+        let max_pairs = max_points / 2;
+        // X server guarantees at least 4096 units, anything this small is bogus
+        if max_pairs == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Maximum request length is too small",
+            ));
+        }
+
+        for points in mem::take(&mut self.points).chunks(max_points) {
+            self.connection.send_request(PolyPoint {
+                coordinate_mode: CoordinateMode::Origin,
+                drawable: self.drawable,
+                gc: self.gc,
+                points: points.to_vec(),
+            })?;
+        }
+
+        for segments in mem::take(&mut self.segments).chunks(max_pairs) {
+            self.connection.send_request(PolySegment {
+                drawable: self.drawable,
+                gc: self.gc,
+                segments: segments.to_vec(),
+            })?;
+        }
+
+        for rectangles in mem::take(&mut self.rectangles).chunks(max_pairs) {
+            self.connection.send_request(PolyRectangle {
+                drawable: self.drawable,
+                gc: self.gc,
+                rectangles: rectangles.to_vec(),
+            })?;
+        }
+
+        self.connection.flush()
+    }
+}
+
+impl Drop for DrawBatch<'_> {
+    fn drop(&mut self) {
+        // Errors can't be reported from drop, use flush to handle them.
+        let _ = self.flush();
+    }
+}
+
+impl Connection {
+    /// Start batch of points, lines and rectangles drawn into drawable using GC.
+    pub fn draw_batch(&mut self, drawable: u32, gc: u32) -> DrawBatch<'_> {
+        DrawBatch {
+            connection: self,
+            drawable,
+            gc,
+            points: vec![],
+            segments: vec![],
+            rectangles: vec![],
+        }
+    }
+
+    /// Copy components of `src_gc` selected by `value_mask` to `dst_gc`.
+    pub fn copy_gc(&mut self, src_gc: u32, dst_gc: u32, value_mask: GcValueMask) -> io::Result<()> {
+        self.send_request(CopyGC {
+            src_gc,
+            dst_gc,
+            value_mask,
+        })?;
+        Ok(())
+    }
+
+    /// Set dash pattern of GC lines drawn with `OnOffDash` or `DoubleDash` style,
+    /// e.g. `&[4, 2]` for dashes of 4 pixels separated by 2 pixel gaps.
+    pub fn set_dashes(&mut self, gc: u32, dash_offset: u16, dashes: &[u8]) -> io::Result<()> {
+        self.send_request(SetDashes {
+            gc,
+            dash_offset,
+            dashes: dashes.to_vec(),
+        })?;
+        Ok(())
+    }
+
+    /// Clip drawing with GC to rectangles relative to clip origin,
+    /// `ordering` promises their order to X server.
+    pub fn set_clip_rectangles(
+        &mut self,
+        gc: u32,
+        clip_x_origin: i16,
+        clip_y_origin: i16,
+        ordering: RectangleOrdering,
+        rectangles: &[Rectangle],
+    ) -> io::Result<()> {
+        self.send_request(SetClipRectangles {
+            ordering,
+            gc,
+            clip_x_origin,
+            clip_y_origin,
+            rectangles: rectangles.to_vec(),
+        })?;
+        Ok(())
+    }
+
+    /// Copy area of drawable to another drawable of the same root and depth.
+    #[allow(clippy::too_many_arguments)]
+    pub fn copy_area(
+        &mut self,
+        src_drawable: u32,
+        dst_drawable: u32,
+        gc: u32,
+        src_x: i16,
+        src_y: i16,
+        dst_x: i16,
+        dst_y: i16,
+        width: u16,
+        height: u16,
+    ) -> io::Result<()> {
+        self.send_request(CopyArea {
+            src_drawable,
+            dst_drawable,
+            gc,
+            src_x,
+            src_y,
+            dst_x,
+            dst_y,
+            width,
+            height,
+        })?;
+        Ok(())
+    }
+
+    /// Copy single bit plane of drawable to another drawable of the same root,
+    /// e.g. draw 1-bit mask with GC foreground and background.
+    #[allow(clippy::too_many_arguments)]
+    pub fn copy_plane(
+        &mut self,
+        src_drawable: u32,
+        dst_drawable: u32,
+        gc: u32,
+        src_x: i16,
+        src_y: i16,
+        dst_x: i16,
+        dst_y: i16,
+        width: u16,
+        height: u16,
+        bit_plane: u32,
+    ) -> io::Result<()> {
+        self.send_request(CopyPlane {
+            src_drawable,
+            dst_drawable,
+            gc,
+            src_x,
+            src_y,
+            dst_x,
+            dst_y,
+            width,
+            height,
+            bit_plane,
+        })?;
+        Ok(())
+    }
+
+    /// Draw text items with GC foreground, switching fonts in between
+    /// with [Font](TextItem8::Font) items. GC is left with the last font set.
+    pub fn poly_text8(
+        &mut self,
+        drawable: u32,
+        gc: u32,
+        x: i16,
+        y: i16,
+        items: &[TextItem8],
+    ) -> io::Result<()> {
+        self.send_request(PolyText8 {
+            drawable,
+            gc,
+            x,
+            y,
+            items: items.to_vec(),
+        })?;
+        Ok(())
+    }
+
+    /// Draw string of two byte characters with GC font over rectangle filled
+    /// with GC background, e.g. text of `iso10646-1` font. Up to 255 characters.
+    pub fn image_text16(
+        &mut self,
+        drawable: u32,
+        gc: u32,
+        x: i16,
+        y: i16,
+        text: &[u16],
+    ) -> io::Result<()> {
+        self.send_request(ImageText16 {
+            drawable,
+            gc,
+            x,
+            y,
+            text: text.to_vec(),
+        })?;
+        Ok(())
+    }
+
+    /// Draw text items of two byte characters with GC foreground,
+    /// like [poly_text8](Connection::poly_text8).
+    pub fn poly_text16(
+        &mut self,
+        drawable: u32,
+        gc: u32,
+        x: i16,
+        y: i16,
+        items: &[TextItem16],
+    ) -> io::Result<()> {
+        self.send_request(PolyText16 {
+            drawable,
+            gc,
+            x,
+            y,
+            items: items.to_vec(),
+        })?;
+        Ok(())
+    }
+
+    /// Size closest to `width` x `height` supported by X server for the screen of drawable,
+    /// e.g. to pick cursor size before creating one.
+    pub fn query_best_size(
+        &mut self,
+        class: SizeClass,
+        drawable: u32,
+        width: u16,
+        height: u16,
+    ) -> io::Result<(u16, u16)> {
+        let sequence_number = self.send_request(QueryBestSize {
+            class,
+            drawable,
+            width,
+            height,
+        })?;
+        let reply: QueryBestSizeReply = self.wait_for_reply(sequence_number)?;
+
+        Ok((reply.width, reply.height))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::protocol::{Point, Rectangle, Segment};
+    use crate::test_utils::mock_connection;
+    use std::io;
+
+    #[test]
+    fn test_draw_batch_split() {
+        let (mut connection, written) = mock_connection(vec![]);
+        connection.setup.maximum_request_length = 1000;
+
+        let mut batch = connection.draw_batch(0x04000001, 0x04000002);
+        for i in 0..10000 {
+            batch.point(Point::from(((i % 100) as i16, (i / 100) as i16)));
+        }
+        batch.flush().unwrap();
+
+        let written = written.borrow();
+        let mut requests = &written[..];
+        let mut lengths = vec![];
+        while !requests.is_empty() {
+            assert_eq!(requests[0], 64);
+            let length = usize::from(u16::from_ne_bytes([requests[2], requests[3]]));
+            assert!(length <= 1000);
+            lengths.push(length);
+            requests = &requests[length * 4..];
+        }
+
+        // 997 points fit into request besides its header
+        assert_eq!(lengths.len(), 11);
+        assert!(lengths[..10].iter().all(|&length| length == 1000));
+        assert_eq!(
+            lengths.iter().map(|length| length - 3).sum::<usize>(),
+            10000
+        );
+    }
+
+    #[test]
+    fn test_draw_batch_tiny_max_request_length() {
+        let (mut connection, written) = mock_connection(vec![]);
+        connection.setup.maximum_request_length = 4;
+
+        let mut batch = connection.draw_batch(0x04000001, 0x04000002);
+        batch.point(Point::from((0, 0)));
+        let err = batch.flush().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+        // Dropping the batch doesn't panic either
+        drop(batch);
+        assert!(written.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_draw_batch_sent_on_drop() {
+        let (mut connection, written) = mock_connection(vec![]);
+
+        connection
+            .draw_batch(0x04000001, 0x04000002)
+            .line(Segment::from((Point::from((0, 0)), Point::from((5, 5)))))
+            .rectangle(Rectangle::from((0, 0, 5, 5)))
+            .line(Segment::from((Point::from((5, 0)), Point::from((0, 5)))));
+
+        // Both lines are sent by single request
+        let written = written.borrow();
+        assert_eq!(written.len(), 28 + 20);
+        assert_eq!(&written[..4], b"\x42\x00\x07\x00");
+        assert_eq!(&written[28..32], b"\x43\x00\x05\x00");
+    }
+}
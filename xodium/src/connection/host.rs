@@ -0,0 +1,35 @@
+use super::Connection;
+use crate::protocol::{
+    AccessControlMode, ChangeHosts, HostChangeMode, ListHosts, ListHostsReply, SetAccessControl,
+};
+use std::io;
+
+impl Connection {
+    /// Query hosts allowed to connect and whether the access list is enabled.
+    pub fn list_hosts(&mut self) -> io::Result<ListHostsReply> {
+        let sequence_number = self.send_request(ListHosts)?;
+        self.wait_for_reply(sequence_number)
+    }
+
+    /// Add host to or remove it from the access list, only permitted
+    /// to clients running on the same host as X server.
+    pub fn change_hosts(
+        &mut self,
+        mode: HostChangeMode,
+        family: u8,
+        address: &[u8],
+    ) -> io::Result<()> {
+        self.send_request(ChangeHosts {
+            mode,
+            family,
+            address: address.to_vec(),
+        })?;
+        Ok(())
+    }
+
+    /// Enable or disable the access list, disabled list lets any host connect.
+    pub fn set_access_control(&mut self, mode: AccessControlMode) -> io::Result<()> {
+        self.send_request(SetAccessControl { mode })?;
+        Ok(())
+    }
+}
@@ -0,0 +1,146 @@
+use super::Connection;
+use crate::protocol::{
+    GetXIDList, GetXIDListReply, GetXIDRange, GetXIDRangeReply, XC_MISC_EXTENSION_NAME,
+};
+use std::io;
+
+/// Allocator of resource ids (windows, pixmaps, etc.) for the client.
+/// Ids are `resource-id-base` with any subset of `resource-id-mask` bits set,
+/// allocated in increments of the lowest mask bit.
+pub(crate) struct IdAllocator {
+    step: u64,
+    /// Next id, u64 to not overflow after the last id.
+    next: u64,
+    /// Id following the last one which may be allocated.
+    end: u64,
+}
+
+impl IdAllocator {
+    pub fn new(base: u32, mask: u32) -> Self {
+        let step = u64::from(mask & mask.wrapping_neg());
+
+        IdAllocator {
+            step,
+            next: u64::from(base),
+            end: u64::from(base | mask) + step,
+        }
+    }
+
+    pub fn allocate(&mut self) -> Option<u32> {
+        if self.step == 0 || self.next >= self.end {
+            return None;
+        }
+
+        let id = self.next as u32;
+        self.next += self.step;
+        Some(id)
+    }
+
+    /// Continue allocation from range of `count` ids starting at `start`,
+    /// e.g. returned by XC-MISC extension.
+    pub fn refill(&mut self, start: u32, count: u32) {
+        self.next = u64::from(start);
+        self.end = self.next + u64::from(count) * self.step;
+    }
+}
+
+impl Connection {
+    /// Allocate id for a new resource, e.g. window or pixmap.
+    /// Once ids are exhausted, ids freed by X server are requested
+    /// using XC-MISC extension.
+    pub fn generate_id(&mut self) -> io::Result<u32> {
+        if let Some(id) = self.ids.allocate() {
+            return Ok(id);
+        }
+
+        let exhausted = || io::Error::other("Resource ids are exhausted");
+
+        let major_opcode = match self.query_extension(XC_MISC_EXTENSION_NAME)? {
+            Some(extension) => extension.major_opcode,
+            None => return Err(exhausted()),
+        };
+        let sequence_number = self.send_request(GetXIDRange { major_opcode })?;
+        let reply: GetXIDRangeReply = self.wait_for_reply(sequence_number)?;
+        self.ids.refill(reply.start_id, reply.count);
+
+        self.ids.allocate().ok_or_else(exhausted)
+    }
+
+    /// Allocate `count` ids at once, e.g. for a pool of pixmaps,
+    /// refilling from XC-MISC extension like [generate_id](Connection::generate_id).
+    pub fn generate_ids(&mut self, count: usize) -> io::Result<Vec<u32>> {
+        (0..count).map(|_| self.generate_id()).collect()
+    }
+
+    /// Request up to `count` resource ids not used by X server, using XC-MISC extension.
+    /// Ids may be not contiguous, unlike those [generate_id](Connection::generate_id) refills from.
+    pub fn xc_misc_get_xid_list(&mut self, count: u32) -> io::Result<Vec<u32>> {
+        let major_opcode = self.extension_opcode(XC_MISC_EXTENSION_NAME)?;
+        let sequence_number = self.send_request(GetXIDList {
+            major_opcode,
+            count,
+        })?;
+        let reply: GetXIDListReply = self.wait_for_reply(sequence_number)?;
+        Ok(reply.ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IdAllocator;
+    use crate::test_utils::mock_connection;
+
+    #[test]
+    fn test_allocate_ids() {
+        let mut ids = IdAllocator::new(0x0400_0000, 0x0000_0070);
+
+        let allocated: Vec<_> = std::iter::from_fn(|| ids.allocate()).collect();
+        assert_eq!(allocated.len(), 8);
+        assert_eq!(allocated[0], 0x0400_0000);
+        assert_eq!(allocated[1], 0x0400_0010);
+        assert_eq!(allocated[7], 0x0400_0070);
+        assert_eq!(ids.allocate(), None);
+    }
+
+    #[test]
+    fn test_generate_id_refill() {
+        let mut input = b"\x01\x00\x01\x00\x00\x00\x00\x00\x01\x82\x00\x00".to_vec();
+        input.extend(vec![0; 20]);
+        input.extend(b"\x01\x00\x02\x00\x00\x00\x00\x00\x00\x01\x00\x04\x02\x00\x00\x00");
+        input.extend(vec![0; 16]);
+
+        let (mut connection, written) = mock_connection(input);
+        connection.ids = IdAllocator::new(0x0400_0000, 0x0000_0001);
+
+        assert_eq!(connection.generate_id().unwrap(), 0x0400_0000);
+        assert_eq!(connection.generate_id().unwrap(), 0x0400_0001);
+        assert!(written.borrow().is_empty());
+
+        // QueryExtension is followed by GetXIDRange
+        assert_eq!(connection.generate_id().unwrap(), 0x0400_0100);
+        assert_eq!(&written.borrow()[16..], b"\x82\x01\x01\x00");
+        assert_eq!(connection.generate_id().unwrap(), 0x0400_0101);
+    }
+
+    #[test]
+    fn test_generate_ids_refill() {
+        let mut input = b"\x01\x00\x01\x00\x00\x00\x00\x00\x01\x82\x00\x00".to_vec();
+        input.extend(vec![0; 20]);
+        input.extend(b"\x01\x00\x02\x00\x00\x00\x00\x00\x10\x00\x00\x04\x02\x00\x00\x00");
+        input.extend(vec![0; 16]);
+
+        let (mut connection, written) = mock_connection(input);
+        connection.ids = IdAllocator::new(0x0400_0000, 0x0000_001c);
+        connection.ids.refill(0x0400_0000, 4);
+
+        let mut ids = connection.generate_ids(6).unwrap();
+        assert!(ids.iter().all(|id| id & !0x0000_001c == 0x0400_0000));
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), 6);
+
+        // local range of 4 ids is refilled with GetXIDRange
+        connection.flush().unwrap();
+        assert_eq!(&written.borrow()[16..], b"\x82\x01\x01\x00");
+    }
+}
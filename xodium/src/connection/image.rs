@@ -0,0 +1,187 @@
+use super::Connection;
+use crate::protocol::{
+    ByteOrder, GetImage, GetImageReply, Image, ImageFormat, PutImage, Rectangle, VisualType,
+    MESSAGE_SIZE,
+};
+use std::convert::TryFrom;
+use std::io;
+
+impl Connection {
+    /// Contents of the drawable rectangle, only planes set in `plane_mask` are returned.
+    /// [Maximum reply size](Connection::set_max_reply_len) is raised to fit image
+    /// of the deepest pixmap format.
+    pub fn get_image(
+        &mut self,
+        format: ImageFormat,
+        drawable: u32,
+        rectangle: Rectangle,
+        plane_mask: u32,
+    ) -> io::Result<GetImageReply> {
+        let (width, height) = (rectangle.width, rectangle.height);
+        let max_reply_len = match format {
+            ImageFormat::ZPixmap => self
+                .setup
+                .pixmap_formats
+                .iter()
+                .map(|format| {
+                    image_reply_len(width, height, format.bits_per_pixel, format.scanline_pad)
+                })
+                .max()
+                .unwrap_or(0),
+            ImageFormat::XYPixmap => {
+                let plane_len =
+                    image_reply_len(width, height, 1, self.setup.bitmap_format_scanline_pad)
+                        - MESSAGE_SIZE;
+                MESSAGE_SIZE
+                    .saturating_add(plane_len.saturating_mul(plane_mask.count_ones() as usize))
+            }
+        };
+
+        let sequence_number = self.send_request(GetImage {
+            format,
+            drawable,
+            rectangle,
+            plane_mask,
+        })?;
+        self.wait_for_large_reply(sequence_number, max_reply_len)
+    }
+
+    /// Write image data of `depth` into the drawable rectangle using GC.
+    /// 1-bit pixmap is filled with [XYPixmap](ImageFormat::XYPixmap) image,
+    /// each row padded to `bitmap-format-scanline-pad` bits.
+    pub fn put_image(
+        &mut self,
+        format: ImageFormat,
+        drawable: u32,
+        gc: u32,
+        rectangle: Rectangle,
+        depth: u8,
+        data: Vec<u8>,
+    ) -> io::Result<()> {
+        self.send_request(PutImage {
+            format,
+            drawable,
+            gc,
+            rectangle,
+            left_pad: 0,
+            depth,
+            data,
+        })?;
+        Ok(())
+    }
+
+    /// Capture contents of the whole viewable window, excluding its border,
+    /// in [ZPixmap](ImageFormat::ZPixmap) format, e.g. to save it as screenshot
+    /// after [to_rgba8](Image::to_rgba8) conversion.
+    /// [Maximum reply size](Connection::set_max_reply_len) is raised to fit the window.
+    pub fn capture_window(&mut self, window: u32) -> io::Result<Image> {
+        let reply = self.get_geometry(window)?;
+        let geometry = reply.geometry;
+        let format = self
+            .setup
+            .pixmap_formats
+            .iter()
+            .find(|format| format.depth == reply.depth)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "No pixmap format of window depth",
+                )
+            })?
+            .clone();
+
+        let sequence_number = self.send_request(GetImage {
+            format: ImageFormat::ZPixmap,
+            drawable: window,
+            rectangle: Rectangle {
+                x: 0,
+                y: 0,
+                width: geometry.width,
+                height: geometry.height,
+            },
+            plane_mask: 0xffff_ffff,
+        })?;
+        let max_reply_len = image_reply_len(
+            geometry.width,
+            geometry.height,
+            format.bits_per_pixel,
+            format.scanline_pad,
+        );
+        let reply: GetImageReply = self.wait_for_large_reply(sequence_number, max_reply_len)?;
+
+        Ok(Image {
+            width: geometry.width,
+            height: geometry.height,
+            depth: reply.depth,
+            format: ImageFormat::ZPixmap,
+            byte_order: match self.setup.image_byte_order {
+                0 => ByteOrder::LsbFirst,
+                _ => ByteOrder::MsbFirst,
+            },
+            bits_per_pixel: format.bits_per_pixel,
+            scanline_pad: format.scanline_pad,
+            visual: self.visual(reply.visual).cloned(),
+            data: reply.data,
+        })
+    }
+
+    /// Visual with specified id among visuals of all screens.
+    fn visual(&self, visual_id: u32) -> Option<&VisualType> {
+        self.setup
+            .roots
+            .iter()
+            .flat_map(|screen| &screen.allowed_depths)
+            .flat_map(|depth| &depth.visuals)
+            .find(|visual| visual.visual_id == visual_id)
+    }
+}
+
+/// Size of GetImage reply with `width` x `height` image, including its header,
+/// each row of `bits_per_pixel` pixels padded to `scanline_pad` bits.
+fn image_reply_len(width: u16, height: u16, bits_per_pixel: u8, scanline_pad: u8) -> usize {
+    let scanline_pad = u64::from(scanline_pad.max(8));
+    let row_bits = u64::from(width) * u64::from(bits_per_pixel);
+    let row_len = row_bits.div_ceil(scanline_pad) * scanline_pad / 8;
+    let data_len = row_len * u64::from(height);
+
+    usize::try_from(data_len)
+        .ok()
+        .and_then(|data_len| data_len.checked_add(MESSAGE_SIZE))
+        .unwrap_or(usize::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::protocol::{ByteOrder, ImageFormat};
+    use crate::test_utils::mock_connection;
+
+    #[test]
+    fn test_capture_window() {
+        let mut input = b"\x01\x18\x01\x00\x00\x00\x00\x00\x00\x01\x00\x00".to_vec();
+        input.extend(b"\x0a\x00\x14\x00\x02\x00\x01\x00\x01\x00");
+        input.extend(vec![0; 10]);
+        input.extend(b"\x01\x18\x02\x00\x02\x00\x00\x00\x21\x00\x00\x00");
+        input.extend(vec![0; 20]);
+        input.extend(b"\x00\x00\xff\x00\x80\xff\x00\x00");
+
+        let (mut connection, written) = mock_connection(input);
+        // Limit is raised for the 40-byte GetImage reply
+        connection.set_max_reply_len(32);
+        let image = connection.capture_window(0x0400_0001).unwrap();
+
+        // GetGeometry is followed by GetImage of the whole window
+        assert_eq!(
+            &written.borrow()[8..],
+            b"\x49\x02\x05\x00\x01\x00\x00\x04\x00\x00\x00\x00\
+              \x02\x00\x01\x00\xff\xff\xff\xff"
+        );
+        assert_eq!((image.width, image.height, image.depth), (2, 1, 24));
+        assert_eq!(image.format, ImageFormat::ZPixmap);
+        assert_eq!(image.byte_order, ByteOrder::LsbFirst);
+        assert_eq!(image.bits_per_pixel, 32);
+        assert_eq!(
+            image.to_rgba8().unwrap(),
+            b"\xff\x00\x00\xff\x00\xff\x80\xff"
+        );
+    }
+}
@@ -0,0 +1,27 @@
+use super::Connection;
+use crate::protocol::{
+    ChangeKeyboardControl, GetInputFocus, GetInputFocusReply, GetKeyboardControl, KeyboardControl,
+    KeyboardControlValues,
+};
+use std::io;
+
+impl Connection {
+    /// Query window having input focus and where focus reverts to.
+    pub fn get_input_focus(&mut self) -> io::Result<GetInputFocusReply> {
+        let sequence_number = self.send_request(GetInputFocus)?;
+        self.wait_for_reply(sequence_number)
+    }
+
+    /// Change keyboard bell, leds and auto repeat settings.
+    pub fn change_keyboard_control(&mut self, values: KeyboardControlValues) -> io::Result<()> {
+        self.send_request(ChangeKeyboardControl { values })?;
+        Ok(())
+    }
+
+    /// Query keyboard settings, e.g. to restore auto repeat
+    /// with [change_keyboard_control](Connection::change_keyboard_control) on exit.
+    pub fn get_keyboard_control(&mut self) -> io::Result<KeyboardControl> {
+        let sequence_number = self.send_request(GetKeyboardControl)?;
+        self.wait_for_reply(sequence_number)
+    }
+}
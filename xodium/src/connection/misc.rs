@@ -0,0 +1,124 @@
+use super::Connection;
+use crate::protocol::{GrabServer, NoOperation, UngrabServer};
+use std::io;
+use std::ops::{Deref, DerefMut};
+use std::time::Duration;
+
+/// Server grab released when dropped, see [Connection::grab].
+/// Gives access to the connection while server is grabbed.
+pub struct ServerGrab<'a> {
+    connection: &'a mut Connection,
+}
+
+impl Deref for ServerGrab<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.connection
+    }
+}
+
+impl DerefMut for ServerGrab<'_> {
+    fn deref_mut(&mut self) -> &mut Connection {
+        self.connection
+    }
+}
+
+impl Drop for ServerGrab<'_> {
+    fn drop(&mut self) {
+        // Leaked grab freezes the whole display, so ungrab is sent right away.
+        // Errors can't be reported from drop, connection is likely broken anyway.
+        let _ = self.connection.ungrab_server();
+        let _ = self.connection.flush();
+    }
+}
+
+impl Connection {
+    /// Stop processing requests of other clients, e.g. around
+    /// reconfiguration which must look atomic to them.
+    /// Prefer [grab](Connection::grab), which can't leave server grabbed.
+    pub fn grab_server(&mut self) -> io::Result<()> {
+        self.send_request(GrabServer)?;
+        Ok(())
+    }
+
+    /// Restart processing requests of other clients.
+    pub fn ungrab_server(&mut self) -> io::Result<()> {
+        self.send_request(UngrabServer)?;
+        Ok(())
+    }
+
+    /// Grab server until returned guard is dropped.
+    pub fn grab(&mut self) -> io::Result<ServerGrab<'_>> {
+        self.grab_server()?;
+        Ok(ServerGrab { connection: self })
+    }
+
+    /// Send NoOperation request right away, so idle connection
+    /// is not dropped by NAT or firewall in between.
+    /// Request generates no reply, which makes it the cheapest keep-alive.
+    pub fn keep_alive(&mut self) -> io::Result<()> {
+        self.send_request(NoOperation::default())?;
+        self.flush()
+    }
+
+    /// Make [poll_event](Connection::poll_event) call [keep_alive](Connection::keep_alive)
+    /// once nothing was sent to X server for `interval`, `None` disables it.
+    pub fn set_keep_alive(&mut self, interval: Option<Duration>) {
+        self.keep_alive_interval = interval;
+    }
+
+    /// Send keep-alive request if connection was idle for too long.
+    pub(crate) fn keep_alive_if_idle(&mut self) -> io::Result<()> {
+        match self.keep_alive_interval {
+            Some(interval) if self.last_flush.elapsed() >= interval => self.keep_alive(),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::mock_connection;
+    use std::time::Duration;
+
+    #[test]
+    fn test_server_grab() {
+        let (mut connection, written) = mock_connection(vec![]);
+
+        {
+            let mut grab = connection.grab().unwrap();
+            grab.keep_alive().unwrap();
+            assert_eq!(*written.borrow(), b"\x24\x00\x01\x00\x7f\x00\x01\x00");
+        }
+
+        assert_eq!(
+            *written.borrow(),
+            b"\x24\x00\x01\x00\x7f\x00\x01\x00\x25\x00\x01\x00"
+        );
+    }
+
+    #[test]
+    fn test_keep_alive() {
+        let (mut connection, written) = mock_connection(vec![]);
+
+        connection.keep_alive().unwrap();
+        assert_eq!(*written.borrow(), b"\x7f\x00\x01\x00");
+    }
+
+    #[test]
+    fn test_poll_event_keep_alive() {
+        let (mut connection, written) = mock_connection(vec![]);
+
+        assert!(connection.poll_event().unwrap().is_none());
+        assert!(written.borrow().is_empty());
+
+        connection.set_keep_alive(Some(Duration::from_secs(3600)));
+        assert!(connection.poll_event().unwrap().is_none());
+        assert!(written.borrow().is_empty());
+
+        connection.set_keep_alive(Some(Duration::ZERO));
+        assert!(connection.poll_event().unwrap().is_none());
+        assert_eq!(*written.borrow(), b"\x7f\x00\x01\x00");
+    }
+}
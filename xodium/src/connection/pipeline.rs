@@ -0,0 +1,168 @@
+use super::Connection;
+use crate::protocol::{Deserialize, Message, ReplyRequest};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io;
+use std::marker::PhantomData;
+
+/// Reply of request sent in [Pipeline], resolved to `T` by [Replies::resolve].
+pub struct Cookie<T> {
+    sequence_number: u16,
+    reply: PhantomData<fn() -> T>,
+}
+
+impl<T> Cookie<T> {
+    /// Sequence number of the request.
+    pub fn sequence_number(&self) -> u16 {
+        self.sequence_number
+    }
+}
+
+impl<T> fmt::Debug for Cookie<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Cookie")
+            .field("sequence_number", &self.sequence_number)
+            .finish()
+    }
+}
+
+/// Requests sent together before reading any of their replies,
+/// so the whole batch costs a single round-trip, see [Connection::pipeline].
+pub struct Pipeline<'a> {
+    connection: &'a mut Connection,
+    pending: HashSet<u16>,
+}
+
+impl Pipeline<'_> {
+    /// Buffer request, its reply is read by [finish](Pipeline::finish).
+    pub fn send<R: ReplyRequest>(&mut self, request: R) -> io::Result<Cookie<R::Reply>> {
+        let sequence_number = self.connection.send_request(request)?;
+        self.pending.insert(sequence_number);
+
+        Ok(Cookie {
+            sequence_number,
+            reply: PhantomData,
+        })
+    }
+
+    /// Flush all requests and read until replies or errors of all of them arrive.
+    /// Events and errors of other requests received meanwhile are queued.
+    pub fn finish(mut self) -> io::Result<Replies> {
+        self.connection.flush()?;
+
+        let mut replies = HashMap::with_capacity(self.pending.len());
+        while !self.pending.is_empty() {
+            match self.connection.framed.next()? {
+                Message::Reply {
+                    sequence_number,
+                    data,
+                } if self.pending.remove(&sequence_number) => {
+                    replies.insert(sequence_number, Ok(data));
+                }
+                Message::ReplyTooLong { sequence_number }
+                    if self.pending.remove(&sequence_number) =>
+                {
+                    let error = io::Error::new(io::ErrorKind::InvalidData, "Reply is too long");
+                    replies.insert(sequence_number, Err(error));
+                }
+                // Nobody waits for this reply anymore
+                Message::Reply { .. } | Message::ReplyTooLong { .. } => {}
+                Message::Error(error) if self.pending.remove(&error.sequence_number) => {
+                    replies.insert(error.sequence_number, Err(error.into()));
+                }
+                Message::Error(error) => self.connection.errors.push_back(error),
+                Message::Event(event) => self.connection.events.push_back(event),
+            }
+        }
+
+        Ok(Replies { replies })
+    }
+}
+
+/// Replies received by [Pipeline::finish], resolved by cookies in any order.
+#[derive(Debug)]
+pub struct Replies {
+    replies: HashMap<u16, io::Result<Vec<u8>>>,
+}
+
+impl Replies {
+    /// Decode reply of the request, error of the request itself is returned
+    /// as [XError](crate::XError) wrapped in `io::Error`, like by [Connection::wait_for_reply].
+    pub fn resolve<T: Deserialize>(&mut self, cookie: Cookie<T>) -> io::Result<T> {
+        let data = self
+            .replies
+            .remove(&cookie.sequence_number)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Cookie does not belong to this pipeline",
+                )
+            })??;
+
+        T::deserialize(&data[..])?
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Incomplete reply"))
+    }
+}
+
+impl Connection {
+    /// Start sending requests whose replies are read all at once, like XCB cookies.
+    /// Independent requests then cost one round-trip instead of one each.
+    pub fn pipeline(&mut self) -> Pipeline<'_> {
+        Pipeline {
+            connection: self,
+            pending: HashSet::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::protocol::{InternAtom, XError};
+    use crate::test_utils::{intern_atom_reply, mock_connection};
+
+    fn intern_atom(name: &str) -> InternAtom {
+        InternAtom {
+            only_if_exists: false,
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_pipeline_intern_atoms() {
+        let mut input = intern_atom_reply(1, 0x130);
+        // Unrelated event received in between
+        input.extend(b"\x14\x00\x00\x00\x01\x02\x00\x00\x03\x04\x00\x00");
+        input.extend(vec![0; 20]);
+        input.extend(intern_atom_reply(2, 0x131));
+        // Third request fails
+        input.extend(b"\x00\x02\x03\x00\x00\x00\x00\x00\x00\x00\x10\x00");
+        input.extend(vec![0; 20]);
+
+        let (mut connection, written) = mock_connection(input);
+        let mut pipeline = connection.pipeline();
+        let first = pipeline.send(intern_atom("WM_PROTOCOLS")).unwrap();
+        let second = pipeline.send(intern_atom("WM_DELETE_WINDOW")).unwrap();
+        let third = pipeline.send(intern_atom("_NET_WM_NAME")).unwrap();
+        assert!(written.borrow().is_empty());
+
+        let mut replies = pipeline.finish().unwrap();
+        // Names of 12, 16 and 12 bytes after 8 bytes of header each
+        assert_eq!(written.borrow().len(), 20 + 24 + 20);
+
+        // Cookies resolve in any order
+        assert_eq!(replies.resolve(second).unwrap().atom, 0x131);
+        assert_eq!(replies.resolve(first).unwrap().atom, 0x130);
+        let err = replies.resolve(third).unwrap_err();
+        assert_eq!(
+            err.get_ref()
+                .unwrap()
+                .downcast_ref::<XError>()
+                .unwrap()
+                .code,
+            2
+        );
+
+        assert!(connection.has_buffered_event());
+        assert!(connection.poll_event().unwrap().is_some());
+    }
+}
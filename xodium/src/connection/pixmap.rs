@@ -0,0 +1,31 @@
+use super::Connection;
+use crate::protocol::{CreatePixmap, FreePixmap};
+use std::io;
+
+impl Connection {
+    /// Create pixmap with undefined contents on the screen of `drawable`,
+    /// e.g. 1-bit one for a cursor. Returns id of the pixmap.
+    pub fn create_pixmap(
+        &mut self,
+        depth: u8,
+        drawable: u32,
+        width: u16,
+        height: u16,
+    ) -> io::Result<u32> {
+        let pid = self.generate_id()?;
+        self.send_request(CreatePixmap {
+            depth,
+            pid,
+            drawable,
+            width,
+            height,
+        })?;
+        Ok(pid)
+    }
+
+    /// Free pixmap, its storage is released once nothing refers to it.
+    pub fn free_pixmap(&mut self, pixmap: u32) -> io::Result<()> {
+        self.send_request(FreePixmap { pixmap })?;
+        Ok(())
+    }
+}
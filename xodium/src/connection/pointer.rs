@@ -0,0 +1,111 @@
+use super::Connection;
+use crate::protocol::{
+    AllowEvents, AllowEventsMode, GetMotionEvents, GetMotionEventsReply, GetPointerMapping,
+    GetPointerMappingReply, MappingStatus, QueryPointer, QueryPointerReply, SetPointerMapping,
+    SetPointerMappingReply, TimeCoord, Timestamp,
+};
+use std::io;
+
+impl Connection {
+    /// Release events frozen by pointer or keyboard grab in synchronous mode.
+    /// Synchronous grab stops event processing once grab activates, so
+    /// e.g. window manager can focus clicked window and then
+    /// [ReplayPointer](AllowEventsMode::ReplayPointer) the click to it.
+    pub fn allow_events(&mut self, mode: AllowEventsMode, time: Timestamp) -> io::Result<()> {
+        self.send_request(AllowEvents { mode, time })?;
+        Ok(())
+    }
+
+    /// Query pointer position relative to root and `window`,
+    /// and state of modifiers and buttons.
+    pub fn query_pointer(&mut self, window: u32) -> io::Result<QueryPointerReply> {
+        let sequence_number = self.send_request(QueryPointer { window })?;
+        self.wait_for_reply(sequence_number)
+    }
+
+    /// Pointer position relative to the root window it is on, which is
+    /// the root of the [default screen](Connection::default_screen) unless
+    /// pointer is on another screen.
+    pub fn pointer_position(&mut self) -> io::Result<(i16, i16)> {
+        let root = self.root();
+        let reply = self.query_pointer(root)?;
+        Ok((reply.root_x, reply.root_y))
+    }
+
+    /// Pointer position relative to origin of `window`,
+    /// `None` if pointer is on another screen.
+    pub fn pointer_on_window(&mut self, window: u32) -> io::Result<Option<(i16, i16)>> {
+        let reply = self.query_pointer(window)?;
+        Ok(Some((reply.win_x, reply.win_y)).filter(|_| reply.same_screen))
+    }
+
+    /// Pointer positions relative to `window` recorded by X server between `start` and `stop`.
+    /// Only positions while pointer was in the window are returned,
+    /// none if `start` is later than `stop` or X server time.
+    pub fn get_motion_events(
+        &mut self,
+        window: u32,
+        start: Timestamp,
+        stop: Timestamp,
+    ) -> io::Result<Vec<TimeCoord>> {
+        let sequence_number = self.send_request(GetMotionEvents {
+            window,
+            start,
+            stop,
+        })?;
+        let reply: GetMotionEventsReply = self.wait_for_reply(sequence_number)?;
+        Ok(reply.events)
+    }
+
+    /// Set logical buttons of physical pointer buttons, e.g. `&[3, 2, 1]`
+    /// for left-handed mouse. Map length has to match the one
+    /// returned by [get_pointer_mapping](Connection::get_pointer_mapping).
+    pub fn set_pointer_mapping(&mut self, map: &[u8]) -> io::Result<MappingStatus> {
+        let sequence_number = self.send_request(SetPointerMapping { map: map.to_vec() })?;
+        let reply: SetPointerMappingReply = self.wait_for_reply(sequence_number)?;
+        Ok(reply.status)
+    }
+
+    /// Logical buttons of physical pointer buttons,
+    /// element i is button of physical button i + 1.
+    pub fn get_pointer_mapping(&mut self) -> io::Result<Vec<u8>> {
+        let sequence_number = self.send_request(GetPointerMapping)?;
+        let reply: GetPointerMappingReply = self.wait_for_reply(sequence_number)?;
+        Ok(reply.map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::mock_connection;
+
+    fn query_pointer_reply(sequence_number: u8, same_screen: bool, root: u32) -> Vec<u8> {
+        let mut reply = vec![1, same_screen as u8, sequence_number, 0, 0, 0, 0, 0];
+        reply.extend(&root.to_ne_bytes());
+        reply.extend(&0u32.to_ne_bytes()); // child
+        if same_screen {
+            reply.extend(b"\x64\x00\xc8\x00\x0a\x00\x14\x00");
+        } else {
+            reply.extend(b"\x32\x00\x19\x00\x00\x00\x00\x00");
+        }
+        reply.resize(32, 0);
+        reply
+    }
+
+    #[test]
+    fn test_pointer_position() {
+        let mut input = query_pointer_reply(1, true, 0x100);
+        input.extend(query_pointer_reply(2, true, 0x100));
+        input.extend(query_pointer_reply(3, false, 0x101));
+
+        let (mut connection, written) = mock_connection(input);
+        assert_eq!(connection.pointer_position().unwrap(), (100, 200));
+        assert_eq!(connection.pointer_on_window(0x201).unwrap(), Some((10, 20)));
+        assert_eq!(connection.pointer_on_window(0x201).unwrap(), None);
+
+        // QueryPointer of default root, then of the window
+        let written = written.borrow();
+        assert_eq!(&written[..8], b"\x26\x00\x02\x00\x00\x01\x00\x00");
+        assert_eq!(&written[8..16], b"\x26\x00\x02\x00\x01\x02\x00\x00");
+    }
+}
@@ -0,0 +1,114 @@
+use super::Connection;
+use crate::protocol::{
+    Event, PresentCompleteNotifyEvent, PresentPixmap, PresentQueryVersion,
+    PresentQueryVersionReply, PresentSelectInput, PRESENT_EXTENSION_NAME,
+};
+use std::io;
+
+/// Present version implemented by the crate.
+const PRESENT_MAJOR_VERSION: u32 = 1;
+const PRESENT_MINOR_VERSION: u32 = 2;
+
+impl Connection {
+    /// Negotiate Present version, returns major and minor version supported by X server.
+    pub fn present_query_version(&mut self) -> io::Result<(u32, u32)> {
+        let major_opcode = self.extension_opcode(PRESENT_EXTENSION_NAME)?;
+        let sequence_number = self.send_request(PresentQueryVersion {
+            major_opcode,
+            major_version: PRESENT_MAJOR_VERSION,
+            minor_version: PRESENT_MINOR_VERSION,
+        })?;
+        let reply: PresentQueryVersionReply = self.wait_for_reply(sequence_number)?;
+
+        Ok((reply.major_version, reply.minor_version))
+    }
+
+    /// Select Present events of window, e.g. [PRESENT_COMPLETE_NOTIFY_MASK](crate::PRESENT_COMPLETE_NOTIFY_MASK).
+    /// Returns event id of the selection.
+    pub fn present_select_input(&mut self, window: u32, event_mask: u32) -> io::Result<u32> {
+        let major_opcode = self.extension_opcode(PRESENT_EXTENSION_NAME)?;
+        let eid = self.generate_id()?;
+        self.send_request(PresentSelectInput {
+            major_opcode,
+            eid,
+            window,
+            event_mask,
+        })?;
+        Ok(eid)
+    }
+
+    /// Present whole pixmap in window once target MSC (vertical blank counter) is reached.
+    /// If `target_msc` is already reached, the first MSC for which
+    /// `msc % divisor == remainder` is used instead, unless divisor is 0.
+    /// Completion is reported by PresentCompleteNotify event with the same serial.
+    pub fn present_pixmap(
+        &mut self,
+        window: u32,
+        pixmap: u32,
+        serial: u32,
+        target_msc: u64,
+        divisor: u64,
+        remainder: u64,
+    ) -> io::Result<()> {
+        let major_opcode = self.extension_opcode(PRESENT_EXTENSION_NAME)?;
+        self.send_request(PresentPixmap {
+            major_opcode,
+            window,
+            pixmap,
+            serial,
+            target_msc,
+            divisor,
+            remainder,
+            ..PresentPixmap::default()
+        })?;
+        Ok(())
+    }
+
+    /// Decode event if it is PresentCompleteNotify, `None` for other events
+    /// or if Present is not supported by X server.
+    pub fn present_complete_notify(
+        &mut self,
+        event: &Event,
+    ) -> io::Result<Option<PresentCompleteNotifyEvent>> {
+        match self.query_extension(PRESENT_EXTENSION_NAME)? {
+            Some(extension) => {
+                PresentCompleteNotifyEvent::from_event(event, extension.major_opcode)
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::protocol::PRESENT_COMPLETE_NOTIFY_MASK;
+    use crate::test_utils::mock_connection;
+
+    #[test]
+    fn test_present_complete_notify() {
+        let mut input = b"\x01\x00\x01\x00\x00\x00\x00\x00\x01\x8c\x00\x00".to_vec();
+        input.extend(vec![0; 20]);
+        input.extend(b"\x23\x8c\x05\x00\x02\x00\x00\x00\x01\x00\x00\x00");
+        input.extend(b"\x00\x00\x00\x04\x01\x02\x00\x00\x07\x00\x00\x00");
+        input.extend(vec![0; 16]);
+
+        let (mut connection, written) = mock_connection(input);
+        let eid = connection
+            .present_select_input(0x201, PRESENT_COMPLETE_NOTIFY_MASK)
+            .unwrap();
+        connection
+            .present_pixmap(0x201, 0x04000005, 7, 0, 1, 0)
+            .unwrap();
+
+        let event = connection.wait_event().unwrap();
+        let complete = connection.present_complete_notify(&event).unwrap().unwrap();
+        assert_eq!(complete.eid, eid);
+        assert_eq!(complete.serial, 7);
+
+        // QueryExtension is followed by SelectInput and PresentPixmap
+        let written = written.borrow();
+        assert_eq!(written.len(), 16 + 16 + 72);
+        assert_eq!(&written[16..20], b"\x8c\x03\x04\x00");
+        assert_eq!(&written[32..36], b"\x8c\x01\x12\x00");
+    }
+}
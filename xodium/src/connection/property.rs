@@ -0,0 +1,479 @@
+use super::Connection;
+use crate::protocol::{
+    ChangeProperty, DeleteProperty, GetProperty, ListProperties, ListPropertiesReply, Property,
+    PropertyMode, RotateProperties, MESSAGE_SIZE,
+};
+use std::io;
+
+/// Predefined atoms of property types.
+const ATOM: u32 = 4;
+const CARDINAL: u32 = 6;
+const STRING: u32 = 31;
+
+/// Property type matching any type in GetProperty, also type of nonexistent property.
+const ANY_PROPERTY_TYPE: u32 = 0;
+
+/// Length in 4-byte units requested at once by
+/// [get_property_full](Connection::get_property_full).
+const PROPERTY_CHUNK_LENGTH: u32 = 0x4000;
+
+impl Connection {
+    /// Change window property, `data` is in native byte order
+    /// and its length is multiple of `format` unit size (8, 16 or 32 bits).
+    ///
+    /// [Append](PropertyMode::Append) and [Prepend](PropertyMode::Prepend)
+    /// require `property_type` and `format` to match the existing property,
+    /// otherwise X server responds with Match error. E.g. INCR selection transfer
+    /// is received by appending chunks to the property.
+    pub fn change_property(
+        &mut self,
+        mode: PropertyMode,
+        window: u32,
+        property: u32,
+        property_type: u32,
+        format: u8,
+        data: &[u8],
+    ) -> io::Result<()> {
+        self.send_request(ChangeProperty {
+            mode,
+            window,
+            property,
+            property_type,
+            format,
+            data: data.to_vec(),
+        })?;
+        Ok(())
+    }
+
+    /// Replace property with UTF8_STRING text, e.g. `_NET_WM_NAME` window title.
+    pub fn set_text_property(&mut self, window: u32, property: u32, value: &str) -> io::Result<()> {
+        let utf8_string = self.intern_atom("UTF8_STRING", false)?;
+        self.change_property(
+            PropertyMode::Replace,
+            window,
+            property,
+            utf8_string,
+            8,
+            value.as_bytes(),
+        )
+    }
+
+    /// Replace property with list of atoms, e.g. `_NET_WM_STATE`.
+    pub fn set_atom_property(
+        &mut self,
+        window: u32,
+        property: u32,
+        atoms: &[u32],
+    ) -> io::Result<()> {
+        self.change_atom_property(PropertyMode::Replace, window, property, atoms)
+    }
+
+    /// Replace property with list of numbers, e.g. `_NET_WM_PID`.
+    pub fn set_cardinal_property(
+        &mut self,
+        window: u32,
+        property: u32,
+        values: &[u32],
+    ) -> io::Result<()> {
+        self.change_cardinal_property(PropertyMode::Replace, window, property, values)
+    }
+
+    fn change_card32_property(
+        &mut self,
+        mode: PropertyMode,
+        window: u32,
+        property: u32,
+        property_type: u32,
+        values: &[u32],
+    ) -> io::Result<()> {
+        let data: Vec<u8> = values
+            .iter()
+            .flat_map(|value| value.to_ne_bytes())
+            .collect();
+        self.change_property(mode, window, property, property_type, 32, &data)
+    }
+
+    /// Same as [set_atom_property](Connection::set_atom_property),
+    /// but atoms are added after or before the current ones depending on `mode`.
+    pub fn change_atom_property(
+        &mut self,
+        mode: PropertyMode,
+        window: u32,
+        property: u32,
+        atoms: &[u32],
+    ) -> io::Result<()> {
+        self.change_card32_property(mode, window, property, ATOM, atoms)
+    }
+
+    /// Same as [set_cardinal_property](Connection::set_cardinal_property),
+    /// but values are added after or before the current ones depending on `mode`.
+    pub fn change_cardinal_property(
+        &mut self,
+        mode: PropertyMode,
+        window: u32,
+        property: u32,
+        values: &[u32],
+    ) -> io::Result<()> {
+        self.change_card32_property(mode, window, property, CARDINAL, values)
+    }
+
+    /// Delete window property, nothing happens if it does not exist.
+    pub fn delete_property(&mut self, window: u32, property: u32) -> io::Result<()> {
+        self.send_request(DeleteProperty { window, property })?;
+        Ok(())
+    }
+
+    /// Rotate values of window properties by `delta` positions, e.g. with 1
+    /// value of the first property moves to the second one and the last one to the first.
+    /// Nothing happens if `delta` is a multiple of number of properties.
+    pub fn rotate_properties(
+        &mut self,
+        window: u32,
+        delta: i16,
+        properties: &[u32],
+    ) -> io::Result<()> {
+        self.send_request(RotateProperties {
+            window,
+            delta,
+            properties: properties.to_vec(),
+        })?;
+        Ok(())
+    }
+
+    /// Get part of window property value starting at `long_offset`,
+    /// both offset and length are in 4-byte units.
+    /// Property is deleted after reading if `delete` is set
+    /// and its whole value was returned.
+    ///
+    /// [Maximum reply size](Connection::set_max_reply_len) is raised to fit
+    /// `long_length` of value.
+    pub fn get_property(
+        &mut self,
+        delete: bool,
+        window: u32,
+        property: u32,
+        property_type: u32,
+        long_offset: u32,
+        long_length: u32,
+    ) -> io::Result<Property> {
+        let sequence_number = self.send_request(GetProperty {
+            delete,
+            window,
+            property,
+            property_type,
+            long_offset,
+            long_length,
+        })?;
+        let max_reply_len = (long_length as usize)
+            .saturating_mul(4)
+            .saturating_add(MESSAGE_SIZE);
+        self.wait_for_large_reply(sequence_number, max_reply_len)
+    }
+
+    /// List atoms of properties defined on the window.
+    pub fn list_properties(&mut self, window: u32) -> io::Result<Vec<u32>> {
+        let sequence_number = self.send_request(ListProperties { window })?;
+        let reply: ListPropertiesReply = self.wait_for_reply(sequence_number)?;
+        Ok(reply.atoms)
+    }
+
+    /// List names of properties defined on the window, like `xprop` does.
+    pub fn list_property_names(&mut self, window: u32) -> io::Result<Vec<String>> {
+        self.list_properties(window)?
+            .into_iter()
+            .map(|atom| self.get_atom_name(atom))
+            .collect()
+    }
+
+    /// Get whole window property value regardless of its size,
+    /// returns its type, format and value.
+    /// Value is empty if property does not exist or type does not match.
+    pub fn get_property_full(
+        &mut self,
+        window: u32,
+        property: u32,
+        property_type: u32,
+    ) -> io::Result<(u32, u8, Vec<u8>)> {
+        let mut long_offset = 0;
+        let mut value = vec![];
+
+        loop {
+            let chunk = self.get_property(
+                false,
+                window,
+                property,
+                property_type,
+                long_offset,
+                PROPERTY_CHUNK_LENGTH,
+            )?;
+
+            value.extend(&chunk.value);
+
+            if chunk.bytes_after == 0 || chunk.value.is_empty() {
+                return Ok((chunk.property_type, chunk.format, value));
+            }
+
+            // Only the last chunk may be not aligned to 4 bytes
+            long_offset += (chunk.value.len() / 4) as u32;
+        }
+    }
+
+    /// Text of STRING (Latin-1) or UTF8_STRING property, e.g. `WM_NAME` or
+    /// `_NET_WM_NAME` window title. `None` if property does not exist,
+    /// error if it has other type.
+    pub fn get_text_property(&mut self, window: u32, property: u32) -> io::Result<Option<String>> {
+        let (property_type, format, value) =
+            self.get_property_full(window, property, ANY_PROPERTY_TYPE)?;
+
+        if property_type == ANY_PROPERTY_TYPE {
+            return Ok(None);
+        }
+        if format != 8 {
+            return Err(invalid_property("Text property format is not 8"));
+        }
+
+        if property_type == STRING {
+            return Ok(Some(value.iter().map(|&byte| char::from(byte)).collect()));
+        }
+        if property_type == self.intern_atom("UTF8_STRING", false)? {
+            return Ok(Some(String::from_utf8_lossy(&value).into_owned()));
+        }
+        Err(invalid_property("Property is not STRING or UTF8_STRING"))
+    }
+
+    /// Numbers of CARDINAL property, e.g. `_NET_WM_PID`.
+    /// Empty if property does not exist, error if it has other type.
+    pub fn get_cardinals(&mut self, window: u32, property: u32) -> io::Result<Vec<u32>> {
+        self.get_card32_property(window, property, CARDINAL)
+    }
+
+    /// Atoms of ATOM property, e.g. `_NET_WM_STATE`.
+    /// Empty if property does not exist, error if it has other type.
+    pub fn get_atoms(&mut self, window: u32, property: u32) -> io::Result<Vec<u32>> {
+        self.get_card32_property(window, property, ATOM)
+    }
+
+    fn get_card32_property(
+        &mut self,
+        window: u32,
+        property: u32,
+        property_type: u32,
+    ) -> io::Result<Vec<u32>> {
+        let (actual_type, format, value) =
+            self.get_property_full(window, property, property_type)?;
+
+        if actual_type == ANY_PROPERTY_TYPE {
+            return Ok(vec![]);
+        }
+        if actual_type != property_type || format != 32 {
+            return Err(invalid_property("Property type or format does not match"));
+        }
+
+        Ok(value
+            .chunks_exact(4)
+            .map(|bytes| u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .collect())
+    }
+}
+
+fn invalid_property(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::protocol::PropertyMode;
+    use crate::test_utils::mock_connection;
+
+    #[test]
+    fn test_get_property_raises_max_reply_len() {
+        let mut reply = b"\x01\x08\x01\x00\x02\x00\x00\x00\x1f\x00\x00\x00".to_vec();
+        reply.extend(b"\x00\x00\x00\x00\x08\x00\x00\x00");
+        reply.extend(vec![0; 12]);
+        reply.extend(b"abcdefgh");
+
+        let (mut connection, _) = mock_connection(reply);
+        connection.set_max_reply_len(32);
+        let property = connection
+            .get_property(false, 0x201, 0x27, 0, 0, 2)
+            .unwrap();
+        assert_eq!(property.value, b"abcdefgh");
+    }
+
+    #[test]
+    fn test_set_text_property() {
+        let mut reply = b"\x01\x00\x01\x00\x00\x00\x00\x00\x2a\x01\x00\x00".to_vec();
+        reply.extend(vec![0; 20]);
+
+        let (mut connection, written) = mock_connection(reply);
+        connection.set_text_property(0x201, 0x12b, "title").unwrap();
+        connection.flush().unwrap();
+
+        // InternAtom is followed by ChangeProperty
+        let written = written.borrow();
+        assert_eq!(written.len(), 20 + 32);
+        assert_eq!(
+            &written[20..],
+            &b"\x12\x00\x08\x00\x01\x02\x00\x00\x2b\x01\x00\x00\x2a\x01\x00\x00\
+               \x08\x00\x00\x00\x05\x00\x00\x00title\0\0\0"[..]
+        );
+    }
+
+    #[test]
+    fn test_set_atom_and_cardinal_property() {
+        let (mut connection, written) = mock_connection(vec![]);
+        connection
+            .set_atom_property(0x201, 0x12b, &[0x12c])
+            .unwrap();
+        connection
+            .set_cardinal_property(0x201, 0x12d, &[1, 2])
+            .unwrap();
+        connection.flush().unwrap();
+
+        let written = written.borrow();
+        assert_eq!(written.len(), 28 + 32);
+        assert_eq!(
+            &written[..28],
+            &b"\x12\x00\x07\x00\x01\x02\x00\x00\x2b\x01\x00\x00\x04\x00\x00\x00\
+               \x20\x00\x00\x00\x01\x00\x00\x00\x2c\x01\x00\x00"[..]
+        );
+        assert_eq!(
+            &written[28..],
+            &b"\x12\x00\x08\x00\x01\x02\x00\x00\x2d\x01\x00\x00\x06\x00\x00\x00\
+               \x20\x00\x00\x00\x02\x00\x00\x00\x01\x00\x00\x00\x02\x00\x00\x00"[..]
+        );
+    }
+
+    #[test]
+    fn test_change_atom_property_modes() {
+        let (mut connection, written) = mock_connection(vec![]);
+        connection
+            .change_atom_property(PropertyMode::Append, 0x201, 0x12b, &[0x12c])
+            .unwrap();
+        connection
+            .change_cardinal_property(PropertyMode::Prepend, 0x201, 0x12d, &[1])
+            .unwrap();
+        connection.flush().unwrap();
+
+        // Mode is the header data byte
+        let written = written.borrow();
+        assert_eq!(written.len(), 2 * 28);
+        assert_eq!(&written[..4], b"\x12\x02\x07\x00");
+        assert_eq!(&written[12..16], b"\x04\x00\x00\x00");
+        assert_eq!(&written[28..32], b"\x12\x01\x07\x00");
+        assert_eq!(&written[40..44], b"\x06\x00\x00\x00");
+    }
+
+    #[test]
+    fn test_get_property_full_chunks() {
+        let mut input = vec![];
+        // "abcd", 3 bytes left
+        input.extend(b"\x01\x08\x01\x00\x01\x00\x00\x00\x1f\x00\x00\x00\x03\x00\x00\x00");
+        input.extend(b"\x04\x00\x00\x00");
+        input.extend(vec![0; 12]);
+        input.extend(b"abcd");
+        // "efg", nothing left
+        input.extend(b"\x01\x08\x02\x00\x01\x00\x00\x00\x1f\x00\x00\x00\x00\x00\x00\x00");
+        input.extend(b"\x03\x00\x00\x00");
+        input.extend(vec![0; 12]);
+        input.extend(b"efg\0");
+
+        let (mut connection, written) = mock_connection(input);
+
+        assert_eq!(
+            connection.get_property_full(0x201, 39, 31).unwrap(),
+            (31, 8, b"abcdefg".to_vec())
+        );
+
+        // Second request continues at offset of 1 unit
+        let written = written.borrow();
+        assert_eq!(written.len(), 48);
+        assert_eq!(&written[16..20], b"\x00\x00\x00\x00");
+        assert_eq!(&written[40..44], b"\x01\x00\x00\x00");
+    }
+
+    #[test]
+    fn test_list_property_names() {
+        let mut input = b"\x01\x00\x01\x00\x02\x00\x00\x00\x02\x00".to_vec();
+        input.extend(vec![0; 22]);
+        input.extend(b"\x27\x00\x00\x00\x43\x00\x00\x00");
+        input.extend(b"\x01\x00\x02\x00\x02\x00\x00\x00\x07\x00");
+        input.extend(vec![0; 22]);
+        input.extend(b"WM_NAME\0");
+
+        let (mut connection, _) = mock_connection(input);
+        connection.atoms.insert("WM_CLASS", 67);
+
+        assert_eq!(
+            connection.list_property_names(0x201).unwrap(),
+            vec!["WM_NAME".to_string(), "WM_CLASS".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_get_text_property() {
+        let mut input = vec![];
+        // STRING, Latin-1
+        input.extend(b"\x01\x08\x01\x00\x01\x00\x00\x00\x1f\x00\x00\x00\x00\x00\x00\x00");
+        input.extend(b"\x03\x00\x00\x00");
+        input.extend(vec![0; 12]);
+        input.extend(b"f\xe9e\0");
+        // UTF8_STRING, followed by InternAtom reply
+        input.extend(b"\x01\x08\x02\x00\x01\x00\x00\x00\x2a\x01\x00\x00\x00\x00\x00\x00");
+        input.extend(b"\x04\x00\x00\x00");
+        input.extend(vec![0; 12]);
+        input.extend(b"f\xc3\xa9e");
+        input.extend(b"\x01\x00\x03\x00\x00\x00\x00\x00\x2a\x01\x00\x00");
+        input.extend(vec![0; 20]);
+        // Property does not exist
+        input.extend(b"\x01\x00\x04\x00\x00\x00\x00\x00");
+        input.extend(vec![0; 24]);
+        // CARDINAL
+        input.extend(b"\x01\x20\x05\x00\x01\x00\x00\x00\x06\x00\x00\x00\x00\x00\x00\x00");
+        input.extend(b"\x01\x00\x00\x00");
+        input.extend(vec![0; 12]);
+        input.extend(b"\x01\x00\x00\x00");
+
+        let (mut connection, _) = mock_connection(input);
+
+        let title = connection.get_text_property(0x201, 39).unwrap();
+        assert_eq!(title.as_deref(), Some("f\u{e9}e"));
+        let title = connection.get_text_property(0x201, 0x12b).unwrap();
+        assert_eq!(title.as_deref(), Some("f\u{e9}e"));
+        assert_eq!(connection.get_text_property(0x201, 39).unwrap(), None);
+        assert!(connection.get_text_property(0x201, 0x12d).is_err());
+    }
+
+    #[test]
+    fn test_get_cardinals_and_atoms() {
+        let mut input = vec![];
+        // CARDINAL
+        input.extend(b"\x01\x20\x01\x00\x02\x00\x00\x00\x06\x00\x00\x00\x00\x00\x00\x00");
+        input.extend(b"\x02\x00\x00\x00");
+        input.extend(vec![0; 12]);
+        input.extend(b"\x01\x00\x00\x00\x02\x00\x00\x00");
+        // ATOM
+        input.extend(b"\x01\x20\x02\x00\x01\x00\x00\x00\x04\x00\x00\x00\x00\x00\x00\x00");
+        input.extend(b"\x01\x00\x00\x00");
+        input.extend(vec![0; 12]);
+        input.extend(b"\x2c\x01\x00\x00");
+        // Property does not exist
+        input.extend(b"\x01\x00\x03\x00\x00\x00\x00\x00");
+        input.extend(vec![0; 24]);
+        // Property has type WINDOW, so no value is returned
+        input.extend(b"\x01\x20\x04\x00\x00\x00\x00\x00\x21\x00\x00\x00\x04\x00\x00\x00");
+        input.extend(vec![0; 16]);
+
+        let (mut connection, written) = mock_connection(input);
+
+        assert_eq!(connection.get_cardinals(0x201, 0x12d).unwrap(), [1, 2]);
+        assert_eq!(connection.get_atoms(0x201, 0x12b).unwrap(), [0x12c]);
+        assert!(connection.get_atoms(0x201, 0x12b).unwrap().is_empty());
+        assert!(connection.get_atoms(0x201, 0x130).is_err());
+
+        // Requested type is CARDINAL, then ATOM
+        let written = written.borrow();
+        assert_eq!(&written[12..16], b"\x06\x00\x00\x00");
+        assert_eq!(&written[36..40], b"\x04\x00\x00\x00");
+    }
+}
@@ -0,0 +1,117 @@
+use super::Connection;
+use crate::protocol::{
+    PictOp, Rectangle, RenderComposite, RenderFillRectangles, RenderQueryVersion,
+    RenderQueryVersionReply, Rgba16, RENDER_EXTENSION_NAME,
+};
+use std::io;
+
+/// RENDER version implemented by the crate.
+const RENDER_MAJOR_VERSION: u32 = 0;
+const RENDER_MINOR_VERSION: u32 = 11;
+
+impl Connection {
+    /// Negotiate RENDER version, returns major and minor version supported by X server.
+    /// Has to be called before other RENDER requests.
+    pub fn render_query_version(&mut self) -> io::Result<(u32, u32)> {
+        let major_opcode = self.extension_opcode(RENDER_EXTENSION_NAME)?;
+        let sequence_number = self.send_request(RenderQueryVersion {
+            major_opcode,
+            major_version: RENDER_MAJOR_VERSION,
+            minor_version: RENDER_MINOR_VERSION,
+        })?;
+        let reply: RenderQueryVersionReply = self.wait_for_reply(sequence_number)?;
+
+        Ok((reply.major_version, reply.minor_version))
+    }
+
+    /// Combine rectangle of `src` picture, multiplied by alpha of `mask`
+    /// picture unless it's 0 (None), with rectangle of `dst` picture,
+    /// e.g. [Over](PictOp::Over) to blend translucent image on top.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_composite(
+        &mut self,
+        op: PictOp,
+        src: u32,
+        mask: u32,
+        dst: u32,
+        src_x: i16,
+        src_y: i16,
+        mask_x: i16,
+        mask_y: i16,
+        dst_x: i16,
+        dst_y: i16,
+        width: u16,
+        height: u16,
+    ) -> io::Result<()> {
+        let major_opcode = self.extension_opcode(RENDER_EXTENSION_NAME)?;
+        self.send_request(RenderComposite {
+            major_opcode,
+            op,
+            src,
+            mask,
+            dst,
+            src_x,
+            src_y,
+            mask_x,
+            mask_y,
+            dst_x,
+            dst_y,
+            width,
+            height,
+        })?;
+        Ok(())
+    }
+
+    /// Combine solid color with rectangles of `dst` picture.
+    pub fn render_fill_rectangles(
+        &mut self,
+        op: PictOp,
+        dst: u32,
+        color: Rgba16,
+        rectangles: &[Rectangle],
+    ) -> io::Result<()> {
+        let major_opcode = self.extension_opcode(RENDER_EXTENSION_NAME)?;
+        self.send_request(RenderFillRectangles {
+            major_opcode,
+            op,
+            dst,
+            color,
+            rectangles: rectangles.to_vec(),
+        })?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::protocol::{PictOp, Rectangle, Rgba16};
+    use crate::test_utils::mock_connection;
+
+    #[test]
+    fn test_render_fill_rectangles() {
+        let mut reply = b"\x01\x00\x01\x00\x00\x00\x00\x00\x01\x8b\x00\x00".to_vec();
+        reply.extend(vec![0; 20]);
+
+        let (mut connection, written) = mock_connection(reply);
+        let color = Rgba16 {
+            red: 0,
+            green: 0,
+            blue: 0,
+            alpha: 0x4000,
+        };
+        connection
+            .render_fill_rectangles(
+                PictOp::Over,
+                0x0400_0002,
+                color,
+                &[Rectangle::from((0, 0, 4, 4))],
+            )
+            .unwrap();
+        connection.flush().unwrap();
+
+        // QueryExtension is followed by RenderFillRectangles
+        let written = written.borrow();
+        assert_eq!(written.len(), 16 + 28);
+        assert_eq!(&written[16..20], b"\x8b\x1a\x07\x00");
+    }
+}
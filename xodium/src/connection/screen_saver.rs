@@ -0,0 +1,39 @@
+use super::Connection;
+use crate::protocol::{
+    ForceScreenSaver, GetScreenSaver, GetScreenSaverReply, ScreenSaverMode, ScreenSaverPreference,
+    SetScreenSaver,
+};
+use std::io;
+
+impl Connection {
+    /// Configure screen saver, timeout and interval are in seconds,
+    /// 0 disables them and -1 restores the defaults.
+    pub fn set_screen_saver(
+        &mut self,
+        timeout: i16,
+        interval: i16,
+        prefer_blanking: ScreenSaverPreference,
+        allow_exposures: ScreenSaverPreference,
+    ) -> io::Result<()> {
+        self.send_request(SetScreenSaver {
+            timeout,
+            interval,
+            prefer_blanking,
+            allow_exposures,
+        })?;
+        Ok(())
+    }
+
+    /// Query screen saver configuration, e.g. to restore it
+    /// after disabling screen saver during a presentation.
+    pub fn get_screen_saver(&mut self) -> io::Result<GetScreenSaverReply> {
+        let sequence_number = self.send_request(GetScreenSaver)?;
+        self.wait_for_reply(sequence_number)
+    }
+
+    /// Activate screen saver, or deactivate it and restart inactivity timer.
+    pub fn force_screen_saver(&mut self, mode: ScreenSaverMode) -> io::Result<()> {
+        self.send_request(ForceScreenSaver { mode })?;
+        Ok(())
+    }
+}
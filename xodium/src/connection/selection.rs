@@ -0,0 +1,196 @@
+use super::Connection;
+use crate::protocol::{
+    ConvertSelection, Event, EventMask, PropertyState, Rectangle, Timestamp, WindowAttributes,
+    WindowClass,
+};
+use std::io;
+
+/// Property type announcing incremental transfer of large selection data.
+const INCR: &str = "INCR";
+
+impl Connection {
+    /// Convert selection, e.g. CLIPBOARD, to `target` type, e.g. UTF8_STRING,
+    /// and return the data, receiving it in chunks if owner uses INCR transfer.
+    /// Blocks until owner responds, error if there is no owner or it refused conversion.
+    ///
+    /// Temporary window is created to receive the data, events of other windows
+    /// received meanwhile are kept for [wait_event](Connection::wait_event).
+    pub fn receive_selection(&mut self, selection: u32, target: u32) -> io::Result<Vec<u8>> {
+        let root = self.default_screen().root;
+        let window = self.create_window(
+            0,
+            root,
+            Rectangle {
+                x: 0,
+                y: 0,
+                width: 1,
+                height: 1,
+            },
+            0,
+            WindowClass::InputOnly,
+            0,
+            WindowAttributes::new().event_mask(EventMask::PROPERTY_CHANGE),
+        )?;
+
+        let result = self.receive_selection_to(window, selection, target);
+        let destroyed = self.destroy_window(window);
+        let data = result?;
+        destroyed?;
+        Ok(data)
+    }
+
+    fn receive_selection_to(
+        &mut self,
+        window: u32,
+        selection: u32,
+        target: u32,
+    ) -> io::Result<Vec<u8>> {
+        // Own window is the only one having the property, so selection atom is used
+        let property = selection;
+        self.send_request(ConvertSelection {
+            requestor: window,
+            selection,
+            target,
+            property,
+            time: Timestamp::current(),
+        })?;
+
+        let notify = self.wait_for_event_map(|event| match event {
+            Event::SelectionNotify(notify) if notify.requestor == window => Ok(notify),
+            other => Err(other),
+        })?;
+        if notify.property == 0 {
+            return Err(io::Error::other("Selection conversion failed"));
+        }
+
+        let (property_type, _, value) = self.get_property_full(window, property, 0)?;
+        // Deleting the property also tells owner to send the first INCR chunk
+        self.delete_property(window, property)?;
+        if property_type != self.intern_atom(INCR, false)? {
+            return Ok(value);
+        }
+
+        let mut data = vec![];
+        loop {
+            let notify = self.wait_for_event_map(|event| match event {
+                Event::PropertyNotify(notify) if notify.window == window => Ok(notify),
+                other => Err(other),
+            })?;
+            // Notification of own deletion
+            if notify.atom != property || notify.state != PropertyState::NewValue {
+                continue;
+            }
+
+            let (_, _, chunk) = self.get_property_full(window, property, 0)?;
+            self.delete_property(window, property)?;
+            // Empty chunk ends the transfer
+            if chunk.is_empty() {
+                return Ok(data);
+            }
+            data.extend(chunk);
+        }
+    }
+
+    /// Wait for event accepted by `map`, which gives back other events.
+    /// They are queued in the order they were received.
+    fn wait_for_event_map<T>(
+        &mut self,
+        mut map: impl FnMut(Event) -> Result<T, Event>,
+    ) -> io::Result<T> {
+        let mut skipped = vec![];
+        let result = loop {
+            match self.wait_event().map(&mut map) {
+                Ok(Ok(value)) => break Ok(value),
+                Ok(Err(event)) => skipped.push(event),
+                Err(err) => break Err(err),
+            }
+        };
+
+        for event in skipped.into_iter().rev() {
+            self.events.push_front(event);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::protocol::{pad, Event, MapRequestEvent};
+    use crate::test_utils::{event, mock_connection};
+
+    fn string_property(sequence_number: u8, value: &[u8]) -> Vec<u8> {
+        let padded_len = value.len() + pad(value.len());
+        let mut reply = vec![1, 8, sequence_number, 0];
+        reply.extend(&((padded_len / 4) as u32).to_ne_bytes());
+        reply.extend(b"\x1f\x00\x00\x00\x00\x00\x00\x00");
+        reply.extend(&(value.len() as u32).to_ne_bytes());
+        reply.extend(vec![0; 12]);
+        reply.extend(value);
+        reply.resize(32 + padded_len, 0);
+        reply
+    }
+
+    #[test]
+    fn test_receive_selection_incr() {
+        const PROPERTY_DELETED: &[u8] = b"\x1c\x00\x00\x00\x00\x00\x00\x04\x01\x00\x00\x00\
+                                          \x00\x00\x00\x00\x01";
+        const PROPERTY_NEW_VALUE: &[u8] = b"\x1c\x00\x00\x00\x00\x00\x00\x04\x01\x00\x00\x00";
+
+        let mut input = vec![];
+        // Unrelated event received while waiting
+        input.extend(event(b"\x14\x00\x00\x00\x01\x02\x00\x00\x03\x04\x00\x00"));
+        input.extend(event(
+            b"\x1f\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x04\x01\x00\x00\x00\
+              \x2a\x01\x00\x00\x01\x00\x00\x00",
+        ));
+        // INCR with lower bound of data size
+        input.extend(b"\x01\x20\x03\x00\x01\x00\x00\x00\x50\x01\x00\x00\x00\x00\x00\x00");
+        input.extend(b"\x01\x00\x00\x00");
+        input.extend(vec![0; 12]);
+        input.extend(b"\x0b\x00\x00\x00");
+        input.extend(event(PROPERTY_DELETED));
+        input.extend(event(PROPERTY_NEW_VALUE));
+        input.extend(string_property(5, b"hello "));
+        input.extend(event(PROPERTY_DELETED));
+        input.extend(event(PROPERTY_NEW_VALUE));
+        input.extend(string_property(7, b"world"));
+        input.extend(event(PROPERTY_DELETED));
+        input.extend(event(PROPERTY_NEW_VALUE));
+        input.extend(string_property(9, b""));
+
+        let (mut connection, written) = mock_connection(input);
+        connection.atoms.insert("INCR", 0x150);
+
+        assert_eq!(
+            connection.receive_selection(1, 0x12a).unwrap(),
+            b"hello world".to_vec()
+        );
+        assert_eq!(
+            connection.poll_event().unwrap(),
+            Some(Event::MapRequest(MapRequestEvent {
+                parent: 0x201,
+                window: 0x403,
+            }))
+        );
+
+        // CreateWindow with PropertyChange mask, ConvertSelection, then
+        // GetProperty and DeleteProperty for INCR and each of 3 chunks
+        connection.flush().unwrap();
+        let written = written.borrow();
+        assert_eq!(&written[32..36], b"\x00\x00\x40\x00");
+        assert_eq!(
+            &written[36..60],
+            &b"\x18\x00\x06\x00\x00\x00\x00\x04\x01\x00\x00\x00\
+               \x2a\x01\x00\x00\x01\x00\x00\x00\x00\x00\x00\x00"[..]
+        );
+        assert_eq!(
+            &written[84..96],
+            b"\x13\x00\x03\x00\x00\x00\x00\x04\x01\x00\x00\x00"
+        );
+        assert_eq!(written.len(), 60 + 4 * (24 + 12) + 8);
+        assert_eq!(
+            &written[written.len() - 8..],
+            b"\x04\x00\x02\x00\x00\x00\x00\x04"
+        );
+    }
+}
@@ -0,0 +1,97 @@
+use super::Connection;
+use crate::protocol::{
+    Rectangle, RectangleOrdering, ShapeKind, ShapeMask, ShapeOperation, ShapeQueryVersion,
+    ShapeQueryVersionReply, ShapeRectangles, SHAPE_EXTENSION_NAME,
+};
+use std::io;
+
+impl Connection {
+    /// Query SHAPE version, returns major and minor version supported by X server.
+    pub fn shape_query_version(&mut self) -> io::Result<(u16, u16)> {
+        let major_opcode = self.extension_opcode(SHAPE_EXTENSION_NAME)?;
+        let sequence_number = self.send_request(ShapeQueryVersion { major_opcode })?;
+        let reply: ShapeQueryVersionReply = self.wait_for_reply(sequence_number)?;
+
+        Ok((reply.major_version, reply.minor_version))
+    }
+
+    /// Combine window shape with union of rectangles moved by offset,
+    /// e.g. [Set](ShapeOperation::Set) [Bounding](ShapeKind::Bounding) shape
+    /// for window with rounded corners.
+    pub fn shape_window_rectangles(
+        &mut self,
+        operation: ShapeOperation,
+        kind: ShapeKind,
+        window: u32,
+        x_offset: i16,
+        y_offset: i16,
+        rectangles: &[Rectangle],
+    ) -> io::Result<()> {
+        let major_opcode = self.extension_opcode(SHAPE_EXTENSION_NAME)?;
+        self.send_request(ShapeRectangles {
+            major_opcode,
+            operation,
+            kind,
+            ordering: RectangleOrdering::UnSorted,
+            dest_window: window,
+            x_offset,
+            y_offset,
+            rectangles: rectangles.to_vec(),
+        })?;
+        Ok(())
+    }
+
+    /// Combine window shape with set bits of 1-bit depth pixmap moved by offset,
+    /// 0 (None) pixmap resets shape to the default rectangle.
+    pub fn shape_window_mask(
+        &mut self,
+        operation: ShapeOperation,
+        kind: ShapeKind,
+        window: u32,
+        x_offset: i16,
+        y_offset: i16,
+        source_bitmap: u32,
+    ) -> io::Result<()> {
+        let major_opcode = self.extension_opcode(SHAPE_EXTENSION_NAME)?;
+        self.send_request(ShapeMask {
+            major_opcode,
+            operation,
+            kind,
+            dest_window: window,
+            x_offset,
+            y_offset,
+            source_bitmap,
+        })?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::protocol::{Rectangle, ShapeKind, ShapeOperation};
+    use crate::test_utils::mock_connection;
+
+    #[test]
+    fn test_shape_window_rectangles() {
+        let mut reply = b"\x01\x00\x01\x00\x00\x00\x00\x00\x01\x81\x00\x00".to_vec();
+        reply.extend(vec![0; 20]);
+
+        let (mut connection, written) = mock_connection(reply);
+        connection
+            .shape_window_rectangles(
+                ShapeOperation::Subtract,
+                ShapeKind::Bounding,
+                0x201,
+                0,
+                0,
+                &[Rectangle::from((0, 0, 2, 2))],
+            )
+            .unwrap();
+        connection.flush().unwrap();
+
+        // QueryExtension is followed by ShapeRectangles
+        let written = written.borrow();
+        assert_eq!(written.len(), 16 + 24);
+        assert_eq!(&written[16..24], b"\x81\x01\x06\x00\x03\x00\x00\x00");
+    }
+}
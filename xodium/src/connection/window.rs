@@ -0,0 +1,575 @@
+use super::{is_x_error, Connection};
+use crate::protocol::{
+    ChangeSaveSet, ChangeWindowAttributes, CirculateDirection, CirculateWindow,
+    ConfigureRequestEvent, ConfigureValues, ConfigureWindow, CreateWindow, DestroyWindow,
+    EventMask, GetGeometry, GetGeometryReply, GetWindowAttributes, GetWindowAttributesReply,
+    MapRequestEvent, MapWindow, QueryTree, QueryTreeReply, Rectangle, SaveSetMode,
+    WindowAttributes, WindowClass,
+};
+use std::collections::{HashMap, HashSet};
+use std::io;
+
+/// Window trees deeper than this are assumed to be bogus.
+const MAX_TREE_DEPTH: usize = 64;
+
+/// Window with its geometry, attributes and subwindows, see [Connection::window_tree].
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowNode {
+    pub id: u32,
+    pub geometry: GetGeometryReply,
+    pub attributes: GetWindowAttributesReply,
+    /// Children in bottom-to-top stacking order.
+    pub children: Vec<WindowNode>,
+}
+
+/// Replies received for a window while walking the tree.
+struct WindowInfo {
+    geometry: GetGeometryReply,
+    attributes: GetWindowAttributesReply,
+    children: Vec<u32>,
+}
+
+impl WindowInfo {
+    /// Build node of the window, windows missing in `infos` are skipped.
+    fn into_node(self, id: u32, infos: &mut HashMap<u32, WindowInfo>) -> WindowNode {
+        let children = self
+            .children
+            .iter()
+            .filter_map(|child| {
+                let info = infos.remove(child)?;
+                Some(info.into_node(*child, infos))
+            })
+            .collect();
+
+        WindowNode {
+            id,
+            geometry: self.geometry,
+            attributes: self.attributes,
+            children,
+        }
+    }
+}
+
+impl Connection {
+    /// Create unmapped window, returns its id.
+    /// Depth 0 and visual 0 (CopyFromParent) are taken from the parent.
+    ///
+    /// Attributes not allowed for InputOnly windows are rejected
+    /// before anything is sent to X server.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_window(
+        &mut self,
+        depth: u8,
+        parent: u32,
+        geometry: Rectangle,
+        border_width: u16,
+        class: WindowClass,
+        visual: u32,
+        attributes: WindowAttributes,
+    ) -> io::Result<u32> {
+        attributes.check_class(class)?;
+
+        let wid = self.generate_id()?;
+        self.send_request(CreateWindow {
+            depth,
+            wid,
+            parent,
+            geometry,
+            border_width,
+            class,
+            visual,
+            attributes,
+        })?;
+        Ok(wid)
+    }
+
+    /// Same as [create_window](Connection::create_window), but waits until X server
+    /// processes the request, so error of it, e.g. Match error for depth
+    /// not supported by the visual, is returned as [XError](crate::XError) wrapped in `io::Error`
+    /// instead of being queued for [poll_error](Connection::poll_error).
+    ///
+    /// Reliable, but costs a round-trip, like checked requests of XCB.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_window_checked(
+        &mut self,
+        depth: u8,
+        parent: u32,
+        geometry: Rectangle,
+        border_width: u16,
+        class: WindowClass,
+        visual: u32,
+        attributes: WindowAttributes,
+    ) -> io::Result<u32> {
+        let wid = self.create_window(
+            depth,
+            parent,
+            geometry,
+            border_width,
+            class,
+            visual,
+            attributes,
+        )?;
+        let sequence_number = self.sequence_number;
+        self.sync()?;
+
+        match self
+            .errors
+            .iter()
+            .position(|error| error.sequence_number == sequence_number)
+        {
+            Some(index) => Err(self.errors.remove(index).unwrap().into()),
+            None => Ok(wid),
+        }
+    }
+
+    /// Create unmapped window with depth, visual and class of the parent
+    /// and only border and background pixels set, like Xlib's `XCreateSimpleWindow`.
+    /// Returns id of the window.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_simple_window(
+        &mut self,
+        parent: u32,
+        x: i16,
+        y: i16,
+        width: u16,
+        height: u16,
+        border_width: u16,
+        border_pixel: u32,
+        background_pixel: u32,
+    ) -> io::Result<u32> {
+        self.create_window(
+            0,
+            parent,
+            Rectangle {
+                x,
+                y,
+                width,
+                height,
+            },
+            border_width,
+            WindowClass::CopyFromParent,
+            0,
+            WindowAttributes::new()
+                .background_pixel(background_pixel)
+                .border_pixel(border_pixel),
+        )
+    }
+
+    /// Destroy window along with all its subwindows.
+    pub fn destroy_window(&mut self, window: u32) -> io::Result<()> {
+        self.send_request(DestroyWindow { window })?;
+        Ok(())
+    }
+
+    /// Query geometry of window or pixmap.
+    pub fn get_geometry(&mut self, drawable: u32) -> io::Result<GetGeometryReply> {
+        let sequence_number = self.send_request(GetGeometry { drawable })?;
+        self.wait_for_reply(sequence_number)
+    }
+
+    /// Query window attributes along with its map state.
+    pub fn get_window_attributes(&mut self, window: u32) -> io::Result<GetWindowAttributesReply> {
+        let sequence_number = self.send_request(GetWindowAttributes { window })?;
+        self.wait_for_reply(sequence_number)
+    }
+
+    /// Query root, parent and children of window.
+    pub fn query_tree(&mut self, window: u32) -> io::Result<QueryTreeReply> {
+        let sequence_number = self.send_request(QueryTree { window })?;
+        self.wait_for_reply(sequence_number)
+    }
+
+    /// Walk window and all of its subwindows, like `xwininfo -tree` does.
+    /// Requests for all windows of the same depth are sent together,
+    /// so there is a single round-trip per tree level.
+    ///
+    /// Subwindows destroyed during the walk are skipped,
+    /// error if tree is deeper than 64 levels.
+    pub fn window_tree(&mut self, window: u32) -> io::Result<WindowNode> {
+        let mut infos = HashMap::new();
+        // Window listed twice would be walked again, e.g. if tree changes meanwhile
+        let mut visited: HashSet<u32> = [window].iter().copied().collect();
+        let mut level = vec![window];
+        let mut depth = 0;
+
+        while !level.is_empty() {
+            if depth == MAX_TREE_DEPTH {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Window tree is too deep",
+                ));
+            }
+
+            let mut pending = Vec::with_capacity(level.len());
+            for &window in &level {
+                let query_tree = self.send_request(QueryTree { window })?;
+                let geometry = self.send_request(GetGeometry { drawable: window })?;
+                let attributes = self.send_request(GetWindowAttributes { window })?;
+                pending.push((window, query_tree, geometry, attributes));
+            }
+
+            let mut next_level = vec![];
+            for (window, query_tree, geometry, attributes) in pending {
+                let tree = self.wait_for_reply::<QueryTreeReply>(query_tree);
+                let geometry = self.wait_for_reply(geometry);
+                let attributes = self.wait_for_reply(attributes);
+
+                let (tree, geometry, attributes) = match (tree, geometry, attributes) {
+                    (Ok(tree), Ok(geometry), Ok(attributes)) => (tree, geometry, attributes),
+                    (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => {
+                        if depth == 0 || !is_x_error(&e) {
+                            return Err(e);
+                        }
+                        continue;
+                    }
+                };
+
+                let children: Vec<u32> = tree
+                    .children
+                    .into_iter()
+                    .filter(|child| visited.insert(*child))
+                    .collect();
+                next_level.extend(&children);
+                infos.insert(
+                    window,
+                    WindowInfo {
+                        geometry,
+                        attributes,
+                        children,
+                    },
+                );
+            }
+            level = next_level;
+            depth += 1;
+        }
+
+        let info = infos
+            .remove(&window)
+            .expect("Root of the walk is always queried");
+        Ok(info.into_node(window, &mut infos))
+    }
+
+    /// Change window attributes, only the attributes set are changed.
+    pub fn change_window_attributes(
+        &mut self,
+        window: u32,
+        attributes: WindowAttributes,
+    ) -> io::Result<()> {
+        self.send_request(ChangeWindowAttributes { window, attributes })?;
+        Ok(())
+    }
+
+    /// Select events X server reports about window, replacing events
+    /// selected by this client before. Same as changing only event mask attribute.
+    pub fn select_input(&mut self, window: u32, mask: EventMask) -> io::Result<()> {
+        self.change_window_attributes(window, WindowAttributes::new().event_mask(mask))
+    }
+
+    /// Add or remove window of another client from the save-set,
+    /// e.g. reparenting window manager inserts client windows
+    /// so they are not destroyed along with its frames if it exits.
+    pub fn change_save_set(&mut self, mode: SaveSetMode, window: u32) -> io::Result<()> {
+        self.send_request(ChangeSaveSet { mode, window })?;
+        Ok(())
+    }
+
+    /// Map window, see [allow_map](Connection::allow_map) for windows
+    /// of other clients whose map was redirected.
+    pub fn map_window(&mut self, window: u32) -> io::Result<()> {
+        self.send_request(MapWindow { window })?;
+        Ok(())
+    }
+
+    /// Map window as requested by client, once window manager selecting
+    /// substructure redirect on its parent got MapRequest, e.g. after reparenting it.
+    pub fn allow_map(&mut self, event: &MapRequestEvent) -> io::Result<()> {
+        self.map_window(event.window)
+    }
+
+    /// Apply configuration requested by client, once window manager selecting
+    /// substructure redirect on its parent got ConfigureRequest.
+    /// Use [configure_window](Connection::configure_window) with adjusted
+    /// [values](ConfigureRequestEvent::values) to e.g. keep window inside its frame.
+    pub fn allow_configure(&mut self, event: &ConfigureRequestEvent) -> io::Result<()> {
+        self.configure_window(event.window, event.values())
+    }
+
+    /// Change window position, size, border width or stacking order.
+    pub fn configure_window(&mut self, window: u32, values: ConfigureValues) -> io::Result<()> {
+        self.send_request(ConfigureWindow { window, values })?;
+        Ok(())
+    }
+
+    /// Raise the lowest or lower the highest mapped child of the window.
+    pub fn circulate_window(
+        &mut self,
+        direction: CirculateDirection,
+        window: u32,
+    ) -> io::Result<()> {
+        self.send_request(CirculateWindow { direction, window })?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::io;
+    use crate::protocol::{
+        ConfigWindowMask, ConfigureRequestEvent, EventMask, MapRequestEvent, MapState, Rectangle,
+        StackMode, WindowAttributes, WindowClass, XError,
+    };
+    use crate::test_utils::mock_connection;
+
+    #[test]
+    fn test_create_window_checked() {
+        // Match error of CreateWindow, then reply to GetInputFocus
+        let mut input = b"\x00\x08\x01\x00\x00\x00\x00\x04\x00\x00\x01".to_vec();
+        input.resize(32, 0);
+        input.extend(b"\x01\x01\x02\x00\x00\x00\x00\x00\x01\x00\x00\x00");
+        input.resize(64, 0);
+
+        let geometry = Rectangle {
+            x: 0,
+            y: 0,
+            width: 10,
+            height: 10,
+        };
+        let (mut connection, written) = mock_connection(input);
+
+        // Colormap of InputOnly window is rejected before sending anything
+        let err = connection
+            .create_window_checked(
+                0,
+                0x100,
+                geometry,
+                0,
+                WindowClass::InputOnly,
+                0,
+                WindowAttributes::new().colormap(0x20),
+            )
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(written.borrow().is_empty());
+
+        // Depth not supported by the parent visual is only detected by X server
+        let err = connection
+            .create_window_checked(
+                7,
+                0x100,
+                geometry,
+                0,
+                WindowClass::InputOutput,
+                0,
+                WindowAttributes::new(),
+            )
+            .unwrap_err();
+        let error = err.get_ref().unwrap().downcast_ref::<XError>().unwrap();
+        assert_eq!((error.code, error.major_opcode), (8, 1));
+        assert!(connection.poll_error().is_none());
+
+        // CreateWindow followed by GetInputFocus
+        assert_eq!(&written.borrow()[32..], b"\x2b\x00\x01\x00");
+    }
+
+    #[test]
+    fn test_select_input() {
+        let (mut connection, written) = mock_connection(vec![]);
+        connection
+            .select_input(
+                0x201,
+                EventMask::EXPOSURE | EventMask::KEY_PRESS | EventMask::STRUCTURE_NOTIFY,
+            )
+            .unwrap();
+        connection.flush().unwrap();
+
+        // ChangeWindowAttributes with event-mask as the only value
+        assert_eq!(
+            &written.borrow()[..],
+            b"\x02\x00\x04\x00\x01\x02\x00\x00\x00\x08\x00\x00\x01\x80\x02\x00"
+        );
+    }
+
+    #[test]
+    fn test_create_window_ids() {
+        let (mut connection, written) = mock_connection(vec![]);
+        let geometry = Rectangle::from((0, 0, 100, 100));
+
+        let first = connection
+            .create_window(
+                0,
+                0x100,
+                geometry,
+                0,
+                WindowClass::InputOutput,
+                0,
+                WindowAttributes::new(),
+            )
+            .unwrap();
+        let second = connection
+            .create_window(
+                0,
+                0x100,
+                geometry,
+                0,
+                WindowClass::InputOnly,
+                0,
+                WindowAttributes::new(),
+            )
+            .unwrap();
+
+        assert_eq!(first, 0x0400_0000);
+        assert_eq!(second, 0x0400_0001);
+        connection.flush().unwrap();
+        assert_eq!(written.borrow().len(), 64);
+    }
+
+    #[test]
+    fn test_create_input_only_window_rejected() {
+        let (mut connection, written) = mock_connection(vec![]);
+
+        let result = connection.create_window(
+            0,
+            0x100,
+            Rectangle::from((0, 0, 100, 100)),
+            0,
+            WindowClass::InputOnly,
+            0,
+            WindowAttributes::new().background_pixel(0),
+        );
+
+        assert!(result.is_err());
+        connection.flush().unwrap();
+        assert!(written.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_create_simple_window() {
+        let (mut connection, written) = mock_connection(vec![]);
+
+        let window = connection
+            .create_simple_window(0x100, 10, 20, 300, 200, 1, 0x000000, 0xffffff)
+            .unwrap();
+        connection.flush().unwrap();
+
+        // As sent by XCreateSimpleWindow(display, root, 10, 20, 300, 200, 1, 0, 0xffffff)
+        assert_eq!(window, 0x0400_0000);
+        assert_eq!(
+            &written.borrow()[..],
+            &b"\x01\x00\x0a\x00\x00\x00\x00\x04\x00\x01\x00\x00\x0a\x00\x14\x00\
+               \x2c\x01\xc8\x00\x01\x00\x00\x00\x00\x00\x00\x00\x0a\x00\x00\x00\
+               \xff\xff\xff\x00\x00\x00\x00\x00"[..]
+        );
+    }
+
+    #[test]
+    fn test_allow_redirected_requests() {
+        let (mut connection, written) = mock_connection(vec![]);
+
+        connection
+            .allow_configure(&ConfigureRequestEvent {
+                stack_mode: StackMode::Above,
+                parent: 0x100,
+                window: 0x403,
+                sibling: 0,
+                x: 0,
+                y: 0,
+                width: 640,
+                height: 480,
+                border_width: 0,
+                value_mask: ConfigWindowMask::WIDTH | ConfigWindowMask::HEIGHT,
+            })
+            .unwrap();
+        connection
+            .allow_map(&MapRequestEvent {
+                parent: 0x100,
+                window: 0x403,
+            })
+            .unwrap();
+        connection.flush().unwrap();
+
+        // Only the requested width and height are configured
+        assert_eq!(
+            &written.borrow()[..],
+            &b"\x0c\x00\x05\x00\x03\x04\x00\x00\x0c\x00\x00\x00\x80\x02\x00\x00\
+               \xe0\x01\x00\x00\x08\x00\x02\x00\x03\x04\x00\x00"[..]
+        );
+    }
+
+    fn query_tree_reply(sequence_number: u8, parent: u32, children: &[u32]) -> Vec<u8> {
+        let mut reply = vec![1, 0, sequence_number, 0];
+        reply.extend(&(children.len() as u32).to_ne_bytes());
+        reply.extend(&0x100u32.to_ne_bytes());
+        reply.extend(&parent.to_ne_bytes());
+        reply.extend(&(children.len() as u16).to_ne_bytes());
+        reply.resize(32, 0);
+        for child in children {
+            reply.extend(&child.to_ne_bytes());
+        }
+        reply
+    }
+
+    fn geometry_reply(sequence_number: u8, x: i16, width: u16) -> Vec<u8> {
+        let mut reply = vec![1, 24, sequence_number, 0, 0, 0, 0, 0];
+        reply.extend(&0x100u32.to_ne_bytes());
+        reply.extend(&x.to_ne_bytes());
+        reply.extend(&[0, 0]);
+        reply.extend(&width.to_ne_bytes());
+        reply.extend(&width.to_ne_bytes());
+        reply.resize(32, 0);
+        reply
+    }
+
+    fn attributes_reply(sequence_number: u8, map_state: u8) -> Vec<u8> {
+        let mut reply = vec![1, 0, sequence_number, 0, 3, 0, 0, 0];
+        reply.extend(b"\x21\x00\x00\x00\x01\x00\x01\x01");
+        reply.extend(vec![0; 8]);
+        reply.extend(&[0, 0, map_state, 0]);
+        reply.resize(44, 0);
+        reply
+    }
+
+    #[test]
+    fn test_window_tree() {
+        let mut input = vec![];
+        // Root with two children, the first one has a child of its own
+        input.extend(query_tree_reply(1, 0, &[0x201, 0x202]));
+        input.extend(geometry_reply(2, 0, 1920));
+        input.extend(attributes_reply(3, 2));
+        input.extend(query_tree_reply(4, 0x100, &[0x301]));
+        input.extend(geometry_reply(5, 10, 100));
+        input.extend(attributes_reply(6, 2));
+        // Window 0x202 is destroyed before its attributes are queried
+        input.extend(query_tree_reply(7, 0x100, &[]));
+        input.extend(geometry_reply(8, 20, 100));
+        input.extend(b"\x00\x03\x09\x00\x02\x02\x00\x00\x00\x00\x03\x00");
+        input.resize(input.len() + 20, 0);
+        // Bogus cycle back to the root is not walked
+        input.extend(query_tree_reply(10, 0x201, &[0x100]));
+        input.extend(geometry_reply(11, 1, 10));
+        input.extend(attributes_reply(12, 0));
+
+        let (mut connection, written) = mock_connection(input);
+        let tree = connection.window_tree(0x100).unwrap();
+
+        assert_eq!(tree.id, 0x100);
+        assert_eq!(tree.geometry.geometry.width, 1920);
+        assert_eq!(tree.attributes.map_state, MapState::Viewable);
+        assert_eq!(tree.children.len(), 1);
+
+        let child = &tree.children[0];
+        assert_eq!(child.id, 0x201);
+        assert_eq!(child.geometry.geometry.x, 10);
+        assert_eq!(child.children.len(), 1);
+        assert_eq!(child.children[0].id, 0x301);
+        assert_eq!(child.children[0].attributes.map_state, MapState::Unmapped);
+        assert!(child.children[0].children.is_empty());
+
+        // Both windows of the second level are queried before reading replies
+        let written = written.borrow();
+        assert_eq!(written.len(), 12 * 8);
+        let opcodes: Vec<_> = written.chunks(8).map(|request| request[0]).collect();
+        assert_eq!(opcodes, [15, 14, 3].repeat(4));
+        assert_eq!(&written[28..32], b"\x01\x02\x00\x00");
+        assert_eq!(&written[52..56], b"\x02\x02\x00\x00");
+    }
+}
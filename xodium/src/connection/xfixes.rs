@@ -0,0 +1,101 @@
+use super::Connection;
+use crate::protocol::{
+    CreateRegion, DestroyRegion, Rectangle, SetWindowShapeRegion, ShapeKind, XFixesQueryVersion,
+    XFixesQueryVersionReply, XFIXES_EXTENSION_NAME,
+};
+use std::io;
+
+/// XFIXES version implemented by the crate.
+const XFIXES_MAJOR_VERSION: u32 = 5;
+const XFIXES_MINOR_VERSION: u32 = 0;
+
+impl Connection {
+    /// Negotiate XFIXES version, returns major and minor version supported by X server.
+    /// Has to be called before other XFIXES requests.
+    pub fn xfixes_query_version(&mut self) -> io::Result<(u32, u32)> {
+        let major_opcode = self.extension_opcode(XFIXES_EXTENSION_NAME)?;
+        let sequence_number = self.send_request(XFixesQueryVersion {
+            major_opcode,
+            major_version: XFIXES_MAJOR_VERSION,
+            minor_version: XFIXES_MINOR_VERSION,
+        })?;
+        let reply: XFixesQueryVersionReply = self.wait_for_reply(sequence_number)?;
+
+        Ok((reply.major_version, reply.minor_version))
+    }
+
+    /// Create region which is union of rectangles, returns its id.
+    pub fn create_region(&mut self, rectangles: &[Rectangle]) -> io::Result<u32> {
+        let major_opcode = self.extension_opcode(XFIXES_EXTENSION_NAME)?;
+        let region = self.generate_id()?;
+        self.send_request(CreateRegion {
+            major_opcode,
+            region,
+            rectangles: rectangles.to_vec(),
+        })?;
+        Ok(region)
+    }
+
+    /// Free region created by [create_region](Connection::create_region).
+    pub fn destroy_region(&mut self, region: u32) -> io::Result<()> {
+        let major_opcode = self.extension_opcode(XFIXES_EXTENSION_NAME)?;
+        self.send_request(DestroyRegion {
+            major_opcode,
+            region,
+        })?;
+        Ok(())
+    }
+
+    /// Set window shape to region moved by offset, 0 (None) region resets it.
+    /// Empty region of [Input](ShapeKind::Input) kind makes window click-through.
+    pub fn set_window_shape_region(
+        &mut self,
+        window: u32,
+        kind: ShapeKind,
+        x_offset: i16,
+        y_offset: i16,
+        region: u32,
+    ) -> io::Result<()> {
+        let major_opcode = self.extension_opcode(XFIXES_EXTENSION_NAME)?;
+        self.send_request(SetWindowShapeRegion {
+            major_opcode,
+            window,
+            kind,
+            x_offset,
+            y_offset,
+            region,
+        })?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::protocol::ShapeKind;
+    use crate::test_utils::mock_connection;
+
+    #[test]
+    fn test_click_through_window() {
+        let mut input = b"\x01\x00\x01\x00\x00\x00\x00\x00\x01\x8a\x00\x00".to_vec();
+        input.extend(vec![0; 20]);
+        input.extend(b"\x01\x00\x02\x00\x00\x00\x00\x00\x05\x00\x00\x00\x00\x00\x00\x00");
+        input.extend(vec![0; 16]);
+
+        let (mut connection, written) = mock_connection(input);
+        assert_eq!(connection.xfixes_query_version().unwrap(), (5, 0));
+
+        let region = connection.create_region(&[]).unwrap();
+        connection
+            .set_window_shape_region(0x201, ShapeKind::Input, 0, 0, region)
+            .unwrap();
+        connection.destroy_region(region).unwrap();
+        connection.flush().unwrap();
+
+        // QueryExtension and QueryVersion are followed by requests using region
+        let written = written.borrow();
+        assert_eq!(written.len(), 16 + 12 + 8 + 20 + 8);
+        assert_eq!(&written[28..36], b"\x8a\x05\x02\x00\x00\x00\x00\x04");
+        assert_eq!(&written[36..44], b"\x8a\x15\x05\x00\x01\x02\x00\x00");
+        assert_eq!(&written[56..64], b"\x8a\x0a\x02\x00\x00\x00\x00\x04");
+    }
+}
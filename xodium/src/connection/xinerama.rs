@@ -0,0 +1,44 @@
+use super::Connection;
+use crate::protocol::{
+    ScreenInfo, XineramaIsActive, XineramaIsActiveReply, XineramaQueryScreens,
+    XineramaQueryScreensReply, XineramaQueryVersion, XineramaQueryVersionReply,
+    XINERAMA_EXTENSION_NAME,
+};
+use std::io;
+
+/// XINERAMA version implemented by the crate.
+const XINERAMA_MAJOR_VERSION: u8 = 1;
+const XINERAMA_MINOR_VERSION: u8 = 1;
+
+impl Connection {
+    /// Query XINERAMA version, returns major and minor version supported by X server.
+    pub fn xinerama_query_version(&mut self) -> io::Result<(u16, u16)> {
+        let major_opcode = self.extension_opcode(XINERAMA_EXTENSION_NAME)?;
+        let sequence_number = self.send_request(XineramaQueryVersion {
+            major_opcode,
+            major_version: XINERAMA_MAJOR_VERSION,
+            minor_version: XINERAMA_MINOR_VERSION,
+        })?;
+        let reply: XineramaQueryVersionReply = self.wait_for_reply(sequence_number)?;
+
+        Ok((reply.major_version, reply.minor_version))
+    }
+
+    /// Whether screen is combined from several monitors.
+    pub fn xinerama_is_active(&mut self) -> io::Result<bool> {
+        let major_opcode = self.extension_opcode(XINERAMA_EXTENSION_NAME)?;
+        let sequence_number = self.send_request(XineramaIsActive { major_opcode })?;
+        let reply: XineramaIsActiveReply = self.wait_for_reply(sequence_number)?;
+        Ok(reply.active)
+    }
+
+    /// Areas of monitors the screen is combined from, none if XINERAMA is not active.
+    /// Simpler than RANDR for clients which only need monitor rectangles,
+    /// e.g. to center window on a monitor.
+    pub fn xinerama_screens(&mut self) -> io::Result<Vec<ScreenInfo>> {
+        let major_opcode = self.extension_opcode(XINERAMA_EXTENSION_NAME)?;
+        let sequence_number = self.send_request(XineramaQueryScreens { major_opcode })?;
+        let reply: XineramaQueryScreensReply = self.wait_for_reply(sequence_number)?;
+        Ok(reply.screens)
+    }
+}
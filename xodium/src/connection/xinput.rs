@@ -0,0 +1,81 @@
+use super::Connection;
+use crate::protocol::{
+    Event, XIDeviceEvent, XIQueryVersion, XIQueryVersionReply, XISelectEvents,
+    XINPUT_EXTENSION_NAME,
+};
+use std::io;
+
+/// XI version implemented by the crate, 2.2 adds touch events.
+const XINPUT_MAJOR_VERSION: u16 = 2;
+const XINPUT_MINOR_VERSION: u16 = 2;
+
+impl Connection {
+    /// Negotiate XI2 version, returns major and minor version supported by X server.
+    /// Has to be called before selecting XI2 events.
+    pub fn xinput_query_version(&mut self) -> io::Result<(u16, u16)> {
+        let major_opcode = self.extension_opcode(XINPUT_EXTENSION_NAME)?;
+        let sequence_number = self.send_request(XIQueryVersion {
+            major_opcode,
+            major_version: XINPUT_MAJOR_VERSION,
+            minor_version: XINPUT_MINOR_VERSION,
+        })?;
+        let reply: XIQueryVersionReply = self.wait_for_reply(sequence_number)?;
+
+        Ok((reply.major_version, reply.minor_version))
+    }
+
+    /// Select XI2 events of window per device, e.g.
+    /// `(XI_ALL_MASTER_DEVICES, 1 << XI_MOTION | 1 << XI_BUTTON_PRESS)`.
+    pub fn xinput_select_events(&mut self, window: u32, masks: &[(u16, u32)]) -> io::Result<()> {
+        let major_opcode = self.extension_opcode(XINPUT_EXTENSION_NAME)?;
+        self.send_request(XISelectEvents {
+            major_opcode,
+            window,
+            masks: masks.to_vec(),
+        })?;
+        Ok(())
+    }
+
+    /// Decode event if it is XI2 device event, `None` for other events
+    /// or if XInput is not supported by X server.
+    pub fn xinput_device_event(&mut self, event: &Event) -> io::Result<Option<XIDeviceEvent>> {
+        match self.query_extension(XINPUT_EXTENSION_NAME)? {
+            Some(extension) => XIDeviceEvent::from_event(event, extension.major_opcode),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::protocol::{XI_ALL_MASTER_DEVICES, XI_BUTTON_PRESS, XI_MOTION};
+    use crate::test_utils::mock_connection;
+
+    #[test]
+    fn test_xinput_query_version() {
+        let mut input = b"\x01\x00\x01\x00\x00\x00\x00\x00\x01\x83\x42\x80".to_vec();
+        input.extend(vec![0; 20]);
+        input.extend(b"\x01\x00\x02\x00\x00\x00\x00\x00\x02\x00\x02\x00");
+        input.extend(vec![0; 20]);
+
+        let (mut connection, written) = mock_connection(input);
+        assert_eq!(connection.xinput_query_version().unwrap(), (2, 2));
+        connection
+            .xinput_select_events(
+                0x201,
+                &[(XI_ALL_MASTER_DEVICES, 1 << XI_MOTION | 1 << XI_BUTTON_PRESS)],
+            )
+            .unwrap();
+        connection.flush().unwrap();
+
+        // QueryExtension is followed by XIQueryVersion and XISelectEvents
+        let written = written.borrow();
+        assert_eq!(written.len(), 24 + 8 + 20);
+        assert_eq!(
+            &written[24..],
+            b"\x83\x2f\x02\x00\x02\x00\x02\x00\
+                                     \x83\x2e\x05\x00\x01\x02\x00\x00\x01\x00\x00\x00\
+                                     \x01\x00\x01\x00\x50\x00\x00\x00"
+        );
+    }
+}
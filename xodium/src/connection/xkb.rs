@@ -0,0 +1,68 @@
+use super::Connection;
+use crate::protocol::{
+    XkbGetMap, XkbGetMapReply, XkbUseExtension, XkbUseExtensionReply, XKB_EXTENSION_NAME,
+    XKB_USE_CORE_KBD,
+};
+use std::io;
+
+/// XKB version implemented by the crate.
+const XKB_MAJOR_VERSION: u16 = 1;
+const XKB_MINOR_VERSION: u16 = 0;
+
+impl Connection {
+    /// Negotiate XKB version, returns major and minor version supported by X server.
+    /// Has to be called before other XKB requests.
+    pub fn xkb_use_extension(&mut self) -> io::Result<(u16, u16)> {
+        let major_opcode = self.extension_opcode(XKB_EXTENSION_NAME)?;
+        let sequence_number = self.send_request(XkbUseExtension {
+            major_opcode,
+            wanted_major: XKB_MAJOR_VERSION,
+            wanted_minor: XKB_MINOR_VERSION,
+        })?;
+        let reply: XkbUseExtensionReply = self.wait_for_reply(sequence_number)?;
+
+        if !reply.supported {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "XKB version is not supported by X server",
+            ));
+        }
+        Ok((reply.server_major, reply.server_minor))
+    }
+
+    /// Query key types and key symbols of the core keyboard,
+    /// e.g. to translate key events to keysyms with
+    /// [keysym_for_state](XkbGetMapReply::keysym_for_state).
+    pub fn xkb_get_map(&mut self) -> io::Result<XkbGetMapReply> {
+        let major_opcode = self.extension_opcode(XKB_EXTENSION_NAME)?;
+        let sequence_number = self.send_request(XkbGetMap {
+            major_opcode,
+            device_spec: XKB_USE_CORE_KBD,
+        })?;
+        self.wait_for_reply(sequence_number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::mock_connection;
+    use std::io;
+
+    #[test]
+    fn test_xkb_use_extension() {
+        let mut input = b"\x01\x00\x01\x00\x00\x00\x00\x00\x01\x87\x55\x89".to_vec();
+        input.extend(vec![0; 20]);
+        input.extend(b"\x01\x01\x02\x00\x00\x00\x00\x00\x01\x00\x00\x00");
+        input.extend(vec![0; 20]);
+        input.extend(b"\x01\x00\x03\x00\x00\x00\x00\x00\x01\x00\x00\x00");
+        input.extend(vec![0; 20]);
+
+        let (mut connection, written) = mock_connection(input);
+        assert_eq!(connection.xkb_use_extension().unwrap(), (1, 0));
+        // QueryExtension is followed by UseExtension
+        assert_eq!(&written.borrow()[20..], b"\x87\x00\x02\x00\x01\x00\x00\x00");
+
+        let err = connection.xkb_use_extension().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+    }
+}
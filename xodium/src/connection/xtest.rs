@@ -0,0 +1,105 @@
+use super::Connection;
+use crate::protocol::{
+    FakeInputType, XTestCompareCursor, XTestCompareCursorReply, XTestFakeInput, XTestGetVersion,
+    XTestGetVersionReply, XTestGrabControl, XTEST_EXTENSION_NAME,
+};
+use std::io;
+
+/// XTEST version implemented by the crate.
+const XTEST_MAJOR_VERSION: u8 = 2;
+const XTEST_MINOR_VERSION: u16 = 2;
+
+impl Connection {
+    /// Negotiate XTEST version, returns major and minor version supported by X server.
+    pub fn xtest_get_version(&mut self) -> io::Result<(u8, u16)> {
+        let major_opcode = self.extension_opcode(XTEST_EXTENSION_NAME)?;
+        let sequence_number = self.send_request(XTestGetVersion {
+            major_opcode,
+            major_version: XTEST_MAJOR_VERSION,
+            minor_version: XTEST_MINOR_VERSION,
+        })?;
+        let reply: XTestGetVersionReply = self.wait_for_reply(sequence_number)?;
+
+        Ok((reply.major_version, reply.minor_version))
+    }
+
+    /// Synthesize key press or release, like `XTestFakeKeyEvent`.
+    pub fn xtest_fake_key(&mut self, keycode: u8, press: bool) -> io::Result<()> {
+        let event_type = if press {
+            FakeInputType::KeyPress
+        } else {
+            FakeInputType::KeyRelease
+        };
+        self.xtest_fake_input(event_type, keycode)
+    }
+
+    /// Synthesize pointer button press or release, like `XTestFakeButtonEvent`.
+    pub fn xtest_fake_button(&mut self, button: u8, press: bool) -> io::Result<()> {
+        let event_type = if press {
+            FakeInputType::ButtonPress
+        } else {
+            FakeInputType::ButtonRelease
+        };
+        self.xtest_fake_input(event_type, button)
+    }
+
+    /// Whether `window` has `cursor`, or 1 (CurrentCursor) to check
+    /// cursor currently displayed in it.
+    pub fn xtest_compare_cursor(&mut self, window: u32, cursor: u32) -> io::Result<bool> {
+        let major_opcode = self.extension_opcode(XTEST_EXTENSION_NAME)?;
+        let sequence_number = self.send_request(XTestCompareCursor {
+            major_opcode,
+            window,
+            cursor,
+        })?;
+        let reply: XTestCompareCursorReply = self.wait_for_reply(sequence_number)?;
+        Ok(reply.same)
+    }
+
+    /// Keep processing requests of this client while another one grabs the server,
+    /// e.g. so test harness can drive application which grabbed it.
+    pub fn xtest_grab_control(&mut self, impervious: bool) -> io::Result<()> {
+        let major_opcode = self.extension_opcode(XTEST_EXTENSION_NAME)?;
+        self.send_request(XTestGrabControl {
+            major_opcode,
+            impervious,
+        })?;
+        Ok(())
+    }
+
+    fn xtest_fake_input(&mut self, event_type: FakeInputType, detail: u8) -> io::Result<()> {
+        let major_opcode = self.extension_opcode(XTEST_EXTENSION_NAME)?;
+        self.send_request(XTestFakeInput {
+            major_opcode,
+            event_type,
+            detail,
+            time: 0,
+            root: 0,
+            root_x: 0,
+            root_y: 0,
+        })?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::mock_connection;
+
+    #[test]
+    fn test_fake_key_uses_extension_opcode() {
+        let mut reply = b"\x01\x00\x01\x00\x00\x00\x00\x00\x01\x84\x00\x00".to_vec();
+        reply.extend(vec![0; 20]);
+
+        let (mut connection, written) = mock_connection(reply);
+        connection.xtest_fake_key(38, true).unwrap();
+        connection.xtest_fake_key(38, false).unwrap();
+        connection.flush().unwrap();
+
+        // QueryExtension is followed by press and release
+        let written = written.borrow();
+        assert_eq!(written.len(), 16 + 36 * 2);
+        assert_eq!(&written[16..22], b"\x84\x02\x09\x00\x02\x26");
+        assert_eq!(&written[52..58], b"\x84\x02\x09\x00\x03\x26");
+    }
+}
@@ -1,5 +1,24 @@
 use std::env::{self, VarError};
+use std::error::Error;
 use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Directory local X servers create UNIX sockets in.
+pub(crate) const DEFAULT_UNIX_X_SERVER_SOCKET_DIR: &str = "/tmp/.X11-unix";
+
+/// X servers listening on TCP use port 6000 + display number.
+const X_TCP_PORT: u16 = 6000;
+
+/// Hostname explicitly selecting local UNIX socket, e.g. `unix:0`.
+const UNIX_HOSTNAME: &str = "unix";
+
+/// Address X server of display is listening at, see [resolve](Display::resolve).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Endpoint {
+    Unix(PathBuf),
+    /// Hostname and port.
+    Tcp(String, u16),
+}
 
 /// A `DISPLAY` environment variable type.
 ///
@@ -43,29 +62,28 @@ use std::fmt;
 /// DISPLAY=host:0.1
 /// ```
 ///
+/// On macOS XQuartz uses full path to UNIX socket instead of hostname:
+/// ```quote
+/// DISPLAY=/private/tmp/com.apple.launchd.XXXX/org.xquartz:0
+/// ```
+///
 /// *TODO:* Check validity of DISPLAY=localhost/unix:0
+///
+/// Equality is structural, so `:0` and `:0.0` differ,
+/// compare [canonical](Display::canonical) forms to check whether they address the same screen.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
 pub struct Display {
     pub hostname: Option<String>,
     pub display: u16,
     pub screen: Option<u16>,
 }
 
-impl Default for Display {
-    fn default() -> Self {
-        Display {
-            hostname: None,
-            display: 0,
-            screen: None,
-        }
-    }
-}
-
 impl fmt::Display for Display {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
             "{}:{}",
-            self.hostname.as_ref().map(String::as_str).unwrap_or(""),
+            self.hostname.as_deref().unwrap_or(""),
             self.display
         )?;
 
@@ -87,10 +105,15 @@ impl Display {
     }
 
     /// Try to parse DISPLAY string
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Result<Self, DisplayError> {
-        let hostname_end = s
-            .find(|c| c == ':')
-            .ok_or(DisplayError::InvalidDisplayFormat)?;
+        // Socket path may contain colons itself, display number follows the last one
+        let hostname_end = if s.starts_with('/') {
+            s.rfind(':')
+        } else {
+            s.find(':')
+        }
+        .ok_or(DisplayError::InvalidDisplayFormat)?;
         let hostname = match &s[0..hostname_end] {
             "" => None,
             other => Some(other.into()),
@@ -99,8 +122,8 @@ impl Display {
             .get(hostname_end + 1..)
             .ok_or(DisplayError::InvalidDisplayFormat)?;
 
-        let screen_start = s.find(|c| c == '.');
-        let display_end = screen_start.unwrap_or_else(|| s.len());
+        let screen_start = s.find('.');
+        let display_end = screen_start.unwrap_or(s.len());
         let display = match &s[0..display_end] {
             "" => return Err(DisplayError::InvalidDisplayFormat),
             other => other
@@ -134,6 +157,87 @@ impl Display {
 
         Display::from_str(&raw_display_value)
     }
+
+    /// Same display with screen set to 0 if it was not specified,
+    /// e.g. to key connection pool by displays.
+    pub fn canonical(&self) -> Display {
+        Display {
+            screen: Some(self.screen.unwrap_or(0)),
+            ..self.clone()
+        }
+    }
+
+    /// Whether hostname is actually an absolute path of UNIX socket
+    /// (without display number), like launchd sockets used by XQuartz.
+    pub fn is_unix_socket_path(&self) -> bool {
+        self.hostname
+            .as_ref()
+            .map(|hostname| hostname.starts_with('/'))
+            .unwrap_or(false)
+    }
+
+    /// Address to connect to for the display, e.g. to pass it to own connection logic:
+    /// UNIX socket for local display, i.e. without hostname or with `unix` hostname,
+    /// TCP port `6000 + display` of the host otherwise, including `localhost`
+    /// used by SSH forwarding. Error if the port is out of range.
+    ///
+    /// [connect_to_display](crate::connect_to_display) additionally falls back
+    /// to abstract socket and TCP on localhost if UNIX socket of local display fails.
+    pub fn resolve(&self) -> Result<Endpoint, DisplayError> {
+        self.resolve_in(Path::new(DEFAULT_UNIX_X_SERVER_SOCKET_DIR))
+    }
+
+    /// Same as [resolve](Display::resolve),
+    /// but socket of local display is looked up in `dir`.
+    pub fn resolve_in(&self, dir: &Path) -> Result<Endpoint, DisplayError> {
+        match self.unix_socket_path_in(dir) {
+            Some(path) => Ok(Endpoint::Unix(path)),
+            None => Ok(Endpoint::Tcp(
+                self.hostname.clone().unwrap_or_default(),
+                self.tcp_port()?,
+            )),
+        }
+    }
+
+    /// TCP port X server of display listens on, unless it listens only on UNIX socket.
+    pub(crate) fn tcp_port(&self) -> Result<u16, DisplayError> {
+        X_TCP_PORT
+            .checked_add(self.display)
+            .ok_or(DisplayError::InvalidDisplayFormat)
+    }
+
+    /// Path to UNIX socket of X server, `None` for remote displays.
+    pub fn unix_socket_path(&self) -> Option<PathBuf> {
+        self.unix_socket_path_in(Path::new(DEFAULT_UNIX_X_SERVER_SOCKET_DIR))
+    }
+
+    /// Same as [unix_socket_path](Display::unix_socket_path),
+    /// but socket of local display is looked up in `dir`.
+    pub fn unix_socket_path_in(&self, dir: &Path) -> Option<PathBuf> {
+        match self.hostname.as_deref() {
+            None | Some(UNIX_HOSTNAME) => Some(dir.join(format!("X{}", self.display))),
+            Some(path) if self.is_unix_socket_path() => {
+                Some(format!("{}:{}", path, self.display).into())
+            }
+            Some(_) => None,
+        }
+    }
+}
+
+/// Serialized as `DISPLAY` string, e.g. `"hostname:0.1"`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Display {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Display {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw_display = String::deserialize(deserializer)?;
+        Display::from_str(&raw_display).map_err(serde::de::Error::custom)
+    }
 }
 
 // TODO: Fmt
@@ -152,17 +256,20 @@ impl fmt::Display for DisplayError {
     }
 }
 
+impl Error for DisplayError {}
+
 #[cfg(test)]
 mod tests {
-    use super::{Display, DisplayError};
+    use super::{Display, DisplayError, Endpoint};
     use std::env;
+    use std::path::{Path, PathBuf};
 
     #[test]
     fn test_display_from_str() {
         const EXAMPLES: &[&str] = &[":10", ":10.20", "hostname:10", "hostname:10.20"];
 
         for example in EXAMPLES {
-            assert_eq!(Display::from_str(*example).unwrap().to_string(), *example);
+            assert_eq!(Display::from_str(example).unwrap().to_string(), *example);
         }
 
         const BAD_EXAMPLES: &[&str] = &[
@@ -180,7 +287,7 @@ mod tests {
         ];
 
         for bad_example in BAD_EXAMPLES {
-            match Display::from_str(*bad_example) {
+            match Display::from_str(bad_example) {
                 Ok(_) => panic!(
                     "Display parsed invalid display string successfully!: {}",
                     bad_example
@@ -194,6 +301,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_canonical() {
+        let implicit_screen = Display::from_str(":0").unwrap();
+        let explicit_screen = Display::from_str(":0.0").unwrap();
+
+        assert_ne!(implicit_screen, explicit_screen);
+        assert_eq!(implicit_screen.canonical(), explicit_screen.canonical());
+        assert_eq!(implicit_screen.canonical().to_string(), ":0.0");
+        assert_ne!(
+            Display::from_str(":0.1").unwrap().canonical(),
+            explicit_screen.canonical()
+        );
+    }
+
     #[test]
     fn test_from_env() {
         const DISPLAY: &str = "hostname:10.20";
@@ -212,8 +333,93 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_launchd_display() {
+        const DISPLAY: &str = "/private/tmp/com.apple.launchd.AbCd1234/org.xquartz:0";
+        let display = Display::from_str(DISPLAY).unwrap();
+
+        assert!(display.is_unix_socket_path());
+        assert_eq!(display.display, 0);
+        assert_eq!(display.screen, None);
+        assert_eq!(display.unix_socket_path(), Some(PathBuf::from(DISPLAY)));
+        assert_eq!(display.to_string(), DISPLAY);
+
+        let display = Display::from_str("/tmp/launchd:dir/org.xquartz:1.2").unwrap();
+        assert_eq!(
+            display.unix_socket_path(),
+            Some(PathBuf::from("/tmp/launchd:dir/org.xquartz:1"))
+        );
+        assert_eq!(display.screen, Some(2));
+    }
+
+    #[test]
+    fn test_resolve() {
+        assert_eq!(
+            Display::from_str(":0").unwrap().resolve().unwrap(),
+            Endpoint::Unix(PathBuf::from("/tmp/.X11-unix/X0"))
+        );
+        assert_eq!(
+            Display::from_str("unix:2.1").unwrap().resolve().unwrap(),
+            Endpoint::Unix(PathBuf::from("/tmp/.X11-unix/X2"))
+        );
+        assert_eq!(
+            Display::from_str("host:1").unwrap().resolve().unwrap(),
+            Endpoint::Tcp("host".to_owned(), 6001)
+        );
+        assert_eq!(
+            Display::from_str("localhost:10")
+                .unwrap()
+                .resolve()
+                .unwrap(),
+            Endpoint::Tcp("localhost".to_owned(), 6010)
+        );
+        assert_eq!(
+            Display::from_str(":3")
+                .unwrap()
+                .resolve_in(Path::new("/run/user/1000/.X11-unix"))
+                .unwrap(),
+            Endpoint::Unix(PathBuf::from("/run/user/1000/.X11-unix/X3"))
+        );
+        assert!(matches!(
+            Display::from_str("host:60000").unwrap().resolve(),
+            Err(DisplayError::InvalidDisplayFormat)
+        ));
+    }
+
+    #[test]
+    fn test_unix_socket_path() {
+        let display = Display::from_str(":1").unwrap();
+        assert!(!display.is_unix_socket_path());
+        assert_eq!(
+            display.unix_socket_path(),
+            Some(PathBuf::from("/tmp/.X11-unix/X1"))
+        );
+
+        assert_eq!(
+            display.unix_socket_path_in(Path::new("/run/user/1000/.X11-unix")),
+            Some(PathBuf::from("/run/user/1000/.X11-unix/X1"))
+        );
+
+        let display = Display::from_str("hostname:1").unwrap();
+        assert!(!display.is_unix_socket_path());
+        assert_eq!(display.unix_socket_path(), None);
+    }
+
     #[test]
     fn test_default_display() {
         assert_eq!(Display::default().to_string(), ":0");
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        for example in &[":0", "hostname:10.20", "/private/tmp/org.xquartz:0"] {
+            let display = Display::from_str(example).unwrap();
+            let json = serde_json::to_string(&display).unwrap();
+            assert_eq!(json, format!("\"{}\"", example));
+            assert_eq!(serde_json::from_str::<Display>(&json).unwrap(), display);
+        }
+
+        assert!(serde_json::from_str::<Display>("\"hostname\"").is_err());
+    }
 }
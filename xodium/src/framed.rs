@@ -1,12 +1,11 @@
 use crate::utils::StreamMarker;
-use std::io::{self, Read};
+use std::io::{self, Read, Write};
 
 /// Take supported structure and produce Vec<u8>
-pub(crate) trait Encoder {
-    type Item;
+pub(crate) trait Encoder<Item> {
     type Error: From<io::Error>;
 
-    fn encode(&mut self, item: Self::Item, dst: &mut Vec<u8>) -> Result<(), Self::Error>;
+    fn encode(&mut self, item: Item, dst: &mut Vec<u8>) -> Result<(), Self::Error>;
 }
 
 /// Take Vec<u8> and try to parse it into structures.
@@ -21,23 +20,111 @@ pub(crate) trait Decoder {
 /// Allows sending and receiving data as structs instead of raw Vec<u8>
 /// Coding and encoding works using Codec,
 /// a type implementing [Encoder] and [Decoder]
-pub(crate) struct Framed<C: Encoder + Decoder> {
+pub(crate) struct Framed<C> {
     stream: Box<dyn StreamMarker>,
     codec: C,
     read_buffer: Vec<u8>,
+    write_buffer: Vec<u8>,
 }
 
-impl<C: Encoder + Decoder> Framed<C> {
+impl<C> Framed<C> {
     pub fn new(stream: Box<dyn StreamMarker>, codec: C) -> Framed<C> {
         Framed {
             stream,
             codec,
             read_buffer: Default::default(),
+            write_buffer: Default::default(),
+        }
+    }
+
+    /// Replace codec keeping buffered data,
+    /// e.g. to switch protocol stage after handshake.
+    pub fn map_codec<D, F: FnOnce(C) -> D>(self, f: F) -> Framed<D> {
+        Framed {
+            stream: self.stream,
+            codec: f(self.codec),
+            read_buffer: self.read_buffer,
+            write_buffer: self.write_buffer,
         }
     }
 
+    pub fn codec(&self) -> &C {
+        &self.codec
+    }
+
+    /// Codec used for encoding and decoding, e.g. to change its state.
+    pub fn codec_mut(&mut self) -> &mut C {
+        &mut self.codec
+    }
+
+    /// Encode item into the write buffer using specified codec.
+    /// Nothing is sent until [flush](Framed::flush) is called.
+    pub fn send<I>(&mut self, item: I) -> Result<(), <C as Encoder<I>>::Error>
+    where
+        C: Encoder<I>,
+    {
+        self.codec.encode(item, &mut self.write_buffer)
+    }
+
+    /// Write everything buffered by [send](Framed::send) to the raw stream.
+    ///
+    /// If the stream fails in the middle, e.g. non-blocking one returns `WouldBlock`,
+    /// only the unwritten remainder is kept buffered, so calling it again
+    /// continues where it stopped without sending anything twice.
+    pub fn flush(&mut self) -> io::Result<()> {
+        let mut written = 0;
+        let result = loop {
+            if written == self.write_buffer.len() {
+                break Ok(());
+            }
+
+            match self.stream.write(&self.write_buffer[written..]) {
+                Ok(0) => {
+                    break Err(io::Error::new(
+                        io::ErrorKind::WriteZero,
+                        "Failed to write buffered data",
+                    ))
+                }
+                Ok(size) => written += size,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => break Err(err),
+            }
+        };
+
+        self.write_buffer.drain(..written);
+        result?;
+        self.stream.flush()
+    }
+
+    /// Whether received data which is not decoded yet is buffered,
+    /// e.g. to tell clean end of stream from one in the middle of an item.
+    pub fn has_buffered_input(&self) -> bool {
+        !self.read_buffer.is_empty()
+    }
+
+    /// Received data which is not decoded yet.
+    pub fn buffered_input(&self) -> &[u8] {
+        &self.read_buffer
+    }
+
+    /// Number of bytes buffered by [send](Framed::send) and not flushed yet.
+    pub fn buffered_output_len(&self) -> usize {
+        self.write_buffer.len()
+    }
+
+    /// Try to decode next item from already received data, without reading the stream.
+    pub fn next_buffered(&mut self) -> Result<Option<<C as Decoder>::Item>, <C as Decoder>::Error>
+    where
+        C: Decoder,
+    {
+        self.codec.decode(&mut self.read_buffer)
+    }
+
     /// Try to receive next item from raw stream using specified codec.
-    pub fn next(&mut self) -> Result<<C as Decoder>::Item, <C as Decoder>::Error> {
+    pub fn next(&mut self) -> Result<<C as Decoder>::Item, <C as Decoder>::Error>
+    where
+        C: Decoder,
+    {
         loop {
             match self.codec.decode(&mut self.read_buffer) {
                 Ok(Some(v)) => return Ok(v),
@@ -64,15 +151,16 @@ impl<C: Encoder + Decoder> Framed<C> {
 #[cfg(test)]
 mod tests {
     use super::{Decoder, Encoder, Framed};
-    use std::io::{self, Cursor};
+    use std::cell::{Cell, RefCell};
+    use std::io::{self, Cursor, Write};
+    use std::rc::Rc;
 
     struct LinesCodec;
 
-    impl Encoder for LinesCodec {
-        type Item = Vec<u8>;
+    impl Encoder<Vec<u8>> for LinesCodec {
         type Error = io::Error;
 
-        fn encode(&mut self, item: Self::Item, dst: &mut Vec<u8>) -> Result<(), Self::Error> {
+        fn encode(&mut self, item: Vec<u8>, dst: &mut Vec<u8>) -> Result<(), Self::Error> {
             dst.extend(item);
             Ok(())
         }
@@ -106,4 +194,106 @@ mod tests {
         assert_eq!(framed.next().unwrap(), b"line2");
         assert!(framed.next().is_err());
     }
+
+    #[test]
+    fn test_framed_next_buffered() {
+        let stream = Cursor::new("line1\nline2\nline3".as_bytes().to_vec());
+        let mut framed = Framed::new(Box::new(stream), LinesCodec);
+        assert!(framed.next_buffered().unwrap().is_none());
+        assert_eq!(framed.next().unwrap(), b"line1");
+        assert_eq!(framed.next_buffered().unwrap().unwrap(), b"line2");
+        assert!(framed.next_buffered().unwrap().is_none());
+    }
+
+    #[derive(Clone, Default)]
+    struct SharedWriter(Rc<RefCell<Vec<u8>>>);
+
+    impl io::Read for SharedWriter {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            Ok(0)
+        }
+    }
+
+    impl Write for SharedWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_framed_send_flush() {
+        let writer = SharedWriter::default();
+        let mut framed = Framed::new(Box::new(writer.clone()), LinesCodec);
+        framed.send(b"line1\n".to_vec()).unwrap();
+        framed.send(b"line2\n".to_vec()).unwrap();
+        assert!(writer.0.borrow().is_empty());
+
+        framed.flush().unwrap();
+        assert_eq!(&writer.0.borrow()[..], b"line1\nline2\n");
+    }
+
+    /// Non-blocking stream accepting at most `chunk` bytes per write,
+    /// and then nothing until `ready` is set again.
+    struct ChunkedWriter {
+        written: Rc<RefCell<Vec<u8>>>,
+        chunk: usize,
+        ready: Rc<Cell<bool>>,
+    }
+
+    impl io::Read for ChunkedWriter {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            Ok(0)
+        }
+    }
+
+    impl Write for ChunkedWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if !self.ready.replace(false) {
+                return Err(io::ErrorKind::WouldBlock.into());
+            }
+
+            let size = buf.len().min(self.chunk);
+            self.written.borrow_mut().extend(&buf[..size]);
+            Ok(size)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_framed_flush_partial_write() {
+        let written = Rc::new(RefCell::new(vec![]));
+        let ready = Rc::new(Cell::new(true));
+        let writer = ChunkedWriter {
+            written: written.clone(),
+            chunk: 4,
+            ready: ready.clone(),
+        };
+        let mut framed = Framed::new(Box::new(writer), LinesCodec);
+        framed.send(b"line1\nline2\n".to_vec()).unwrap();
+
+        let err = framed.flush().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+        assert_eq!(&written.borrow()[..], b"line");
+        assert_eq!(framed.buffered_output_len(), 8);
+
+        // Item sent meanwhile is queued after the remainder
+        framed.send(b"line3\n".to_vec()).unwrap();
+        let mut retries = 0;
+        while let Err(err) = framed.flush() {
+            assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+            ready.set(true);
+            retries += 1;
+        }
+
+        assert_eq!(retries, 4);
+        assert_eq!(&written.borrow()[..], b"line1\nline2\nline3\n");
+        assert_eq!(framed.buffered_output_len(), 0);
+    }
 }
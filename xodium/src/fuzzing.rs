@@ -0,0 +1,26 @@
+//! Entry points of fuzz targets in `fuzz/`, exposing parsers of data received
+//! from X server or read from Xauthority file. Results are discarded,
+//! parsers are only expected to not panic or allocate past the input size.
+
+use crate::framed::Decoder;
+use crate::protocol::{ByteOrder, MessageCodec, SetupCodec};
+
+/// Parse Xauthority file contents.
+pub fn parse_xauthority(data: &[u8]) {
+    let _ = crate::xauthority::from_reader(data);
+}
+
+/// Decode setup response in both byte orders.
+pub fn decode_setup_response(data: &[u8]) {
+    for &order in &[ByteOrder::LsbFirst, ByteOrder::MsbFirst] {
+        let mut src = data.to_vec();
+        let _ = SetupCodec::new(order).decode(&mut src);
+    }
+}
+
+/// Decode errors, replies and events sent after setup until data runs out.
+pub fn decode_messages(data: &[u8]) {
+    let mut codec = MessageCodec::default();
+    let mut src = data.to_vec();
+    while let Ok(Some(_)) = codec.decode(&mut src) {}
+}
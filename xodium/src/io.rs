@@ -0,0 +1,168 @@
+//! Subset of `std::io` protocol encoding and decoding is built upon.
+//!
+//! With `std` feature it is `std::io` itself, without it protocol layer
+//! uses minimal replacement below, so it may be used with `alloc` only.
+
+#[cfg(feature = "std")]
+pub use std::io::{Error, ErrorKind, Read, Result, Write};
+
+#[cfg(not(feature = "std"))]
+pub use self::no_std::{Error, ErrorKind, Read, Result, Write};
+
+/// Writer over fixed size buffer, for encoding requests without allocations.
+/// Writing past the end of buffer fails with [WriteZero](ErrorKind::WriteZero).
+pub struct SliceWriter<'a> {
+    buf: &'a mut [u8],
+    position: usize,
+}
+
+impl<'a> SliceWriter<'a> {
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        SliceWriter { buf, position: 0 }
+    }
+
+    /// Number of bytes written so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl Write for SliceWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let len = buf.len().min(self.buf.len() - self.position);
+        self.buf[self.position..self.position + len].copy_from_slice(&buf[..len]);
+        self.position += len;
+        Ok(len)
+    }
+
+    #[cfg(feature = "std")]
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "std"))]
+mod no_std {
+    use alloc::string::{String, ToString};
+    use alloc::vec::Vec;
+    use core::fmt;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum ErrorKind {
+        InvalidData,
+        InvalidInput,
+        Unsupported,
+        UnexpectedEof,
+        WriteZero,
+        Other,
+    }
+
+    /// Error of protocol encoding or decoding.
+    #[derive(Debug)]
+    pub struct Error {
+        kind: ErrorKind,
+        message: String,
+    }
+
+    impl Error {
+        pub fn new<M: fmt::Display>(kind: ErrorKind, message: M) -> Self {
+            Error {
+                kind,
+                message: message.to_string(),
+            }
+        }
+
+        pub fn other<M: fmt::Display>(message: M) -> Self {
+            Error::new(ErrorKind::Other, message)
+        }
+
+        pub fn kind(&self) -> ErrorKind {
+            self.kind
+        }
+    }
+
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str(&self.message)
+        }
+    }
+
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    pub trait Read {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize>;
+
+        fn read_exact(&mut self, mut buf: &mut [u8]) -> Result<()> {
+            while !buf.is_empty() {
+                match self.read(buf)? {
+                    0 => return Err(Error::new(ErrorKind::UnexpectedEof, "Unexpected end")),
+                    n => buf = &mut buf[n..],
+                }
+            }
+            Ok(())
+        }
+    }
+
+    pub trait Write {
+        fn write(&mut self, buf: &[u8]) -> Result<usize>;
+
+        fn write_all(&mut self, mut buf: &[u8]) -> Result<()> {
+            while !buf.is_empty() {
+                match self.write(buf)? {
+                    0 => return Err(Error::new(ErrorKind::WriteZero, "Buffer is full")),
+                    n => buf = &buf[n..],
+                }
+            }
+            Ok(())
+        }
+    }
+
+    impl Read for &[u8] {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            let len = buf.len().min(self.len());
+            let (head, tail) = self.split_at(len);
+            buf[..len].copy_from_slice(head);
+            *self = tail;
+            Ok(len)
+        }
+    }
+
+    impl<R: Read + ?Sized> Read for &mut R {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            (**self).read(buf)
+        }
+    }
+
+    impl Write for Vec<u8> {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            self.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+    }
+
+    impl<W: Write + ?Sized> Write for &mut W {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            (**self).write(buf)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ErrorKind, SliceWriter, Write};
+    use crate::protocol::{GetAtomName, Serialize};
+
+    #[test]
+    fn test_slice_writer() {
+        let mut buf = [0; 12];
+        let mut writer = SliceWriter::new(&mut buf);
+
+        GetAtomName { atom: 39 }.serialize(&mut writer).unwrap();
+        assert_eq!(writer.position(), 8);
+
+        let error = writer.write_all(b"\x01\x02\x03\x04\x05").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::WriteZero);
+        assert_eq!(writer.position(), 12);
+        assert_eq!(&buf, b"\x11\x00\x02\x00\x27\x00\x00\x00\x01\x02\x03\x04");
+    }
+}
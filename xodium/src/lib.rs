@@ -1,20 +1,75 @@
 #![forbid(unsafe_code)]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
 //! Xodium is a pure rust X11 client.
 //!
 //! Opening connection is as easy as:
 //! ```no_run
+//! # #[cfg(feature = "std")] {
 //! // Connect to X server using DISPLAY environment variable
 //! let connection = xodium::connect_default();
+//! # }
 //! ```
+//!
+//! Without default `std` feature crate is `no_std` and only provides
+//! protocol types and their encoding, which requires `alloc`.
+
+#[cfg_attr(not(feature = "std"), macro_use)]
+extern crate alloc;
 
+#[cfg(feature = "std")]
 mod connection;
+#[cfg(feature = "std")]
 mod display;
+#[cfg(feature = "std")]
 mod framed;
+#[cfg(feature = "fuzzing")]
+#[doc(hidden)]
+pub mod fuzzing;
+pub mod io;
+#[cfg(feature = "std")]
 mod protocol;
+#[cfg(not(feature = "std"))]
+pub mod protocol;
+#[cfg(test)]
+mod test_utils;
 mod utils;
+#[cfg(feature = "std")]
 mod xauthority;
 
-pub use connection::{connect_default, connect_to_display, Connection, ConnectionError};
-pub use display::Display;
+#[cfg(feature = "std")]
+pub use connection::{
+    connect, connect_default, connect_to_display, connect_to_display_with_auth,
+    connect_to_display_with_socket_dir, connect_to_display_with_timeout, discover_local_displays,
+    discover_local_displays_in, Connection, ConnectionError, Cookie, DrawBatch, Events, Font,
+    Pipeline, Replies, ServerGrab, WindowNode,
+};
+#[cfg(feature = "std")]
+pub use display::{Display, DisplayError, Endpoint};
+pub use protocol::{decode_zpixmap, pad, Deserialize, ReplyRequest, Serialize};
+pub use protocol::{
+    AccessControlMode, AllowEventsMode, Arc, AutoRepeatMode, BackingStore, BitGravity, ByteOrder,
+    CharInfo, CirculateDirection, ClientMessageEvent, ColorPlanes, ConfigWindowMask,
+    ConfigureRequestEvent, ConfigureValues, CoordinateMode, DPMSInfoReply, DPMSLevel,
+    DamageNotifyEvent, DamageReportLevel, Depth, DestroyNotifyEvent, DrawDirection, Event,
+    EventMask, Extension, FakeInputType, FontProp, Format, GcValueMask, GenericEvent,
+    GetGeometryReply, GetImageReply, GetInputFocusReply, GetScreenSaverReply,
+    GetWindowAttributesReply, GrabMode, Host, HostChangeMode, Image, ImageFormat, InputEvent,
+    KeyButMask, KeyboardControl, KeyboardControlValues, LedMode, ListHostsReply, MapNotifyEvent,
+    MapRequestEvent, MapState, MappingStatus, ModMask, PictOp, Point, PresentCompleteKind,
+    PresentCompleteMode, PresentCompleteNotifyEvent, Property, PropertyMode, PropertyNotifyEvent,
+    PropertyState, QueryFontReply, QueryPointerReply, QueryTreeReply, Rectangle, RectangleOrdering,
+    RevertTo, Rgb, Rgba16, SaveSetMode, Screen, ScreenInfo, ScreenSaverMode, ScreenSaverPreference,
+    Segment, SelectionClearEvent, SelectionNotifyEvent, SelectionRequestEvent, Setup, ShapeKind,
+    ShapeOperation, SizeClass, StackMode, TextItem16, TextItem8, TimeCoord, Timestamp,
+    UnmapNotifyEvent, VisualType, WinGravity, WindowAttributes, WindowClass, XError, XIDeviceEvent,
+    XkbGetMapReply, XkbKeySymMap, XkbKeyType, XkbKeyTypeMapEntry, ANY_BUTTON, ANY_KEY,
+    ANY_MODIFIER, PRESENT_COMPLETE_NOTIFY_MASK, PRESENT_CONFIGURE_NOTIFY_MASK,
+    PRESENT_IDLE_NOTIFY_MASK, XI_ALL_DEVICES, XI_ALL_MASTER_DEVICES, XI_BUTTON_PRESS,
+    XI_BUTTON_RELEASE, XI_KEY_PRESS, XI_KEY_RELEASE, XI_MOTION, XI_TOUCH_BEGIN, XI_TOUCH_END,
+    XI_TOUCH_UPDATE,
+};
+#[cfg(feature = "std")]
+pub use protocol::{Direction, Trace};
+#[cfg(feature = "std")]
 pub use utils::StreamMarker;
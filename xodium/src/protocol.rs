@@ -1,20 +1,184 @@
+mod atom;
+mod color;
+mod cursor;
+mod damage;
+mod dpms;
+mod error;
+mod event;
+mod extension;
+mod flags;
+mod font;
+mod geometry;
+mod graphics;
+mod host;
+mod image;
+mod input;
+mod keyboard;
+#[cfg(feature = "std")]
+mod message_codec;
+mod misc;
+mod pixmap;
+mod pointer;
+mod present;
+mod property;
+mod render;
+mod screen_saver;
+mod selection;
+mod setup;
+#[cfg(feature = "std")]
 mod setup_codec;
 mod setup_request;
+mod shape;
+mod str_list;
+mod timestamp;
+mod window;
+mod xc_misc;
+mod xfixes;
+mod xinerama;
+mod xinput;
+mod xkb;
+mod xtest;
 
+pub use self::atom::{GetAtomName, GetAtomNameReply, InternAtom, InternAtomReply};
+pub use self::color::{
+    AllocColorCells, AllocColorCellsReply, AllocColorPlanes, ColorPlanes, FreeColors, QueryColors,
+    QueryColorsReply, Rgb,
+};
+pub use self::cursor::{CreateCursor, RecolorCursor};
+pub use self::damage::{
+    DamageCreate, DamageDestroy, DamageNotifyEvent, DamageQueryVersion, DamageQueryVersionReply,
+    DamageReportLevel, DamageSubtract, DAMAGE_EXTENSION_NAME,
+};
+pub use self::dpms::{
+    DPMSCapable, DPMSCapableReply, DPMSDisable, DPMSEnable, DPMSForceLevel, DPMSGetVersion,
+    DPMSGetVersionReply, DPMSInfo, DPMSInfoReply, DPMSLevel, DPMS_EXTENSION_NAME,
+};
+pub use self::error::XError;
+pub use self::event::{
+    ClientMessageEvent, ConfigureRequestEvent, DestroyNotifyEvent, Event, EventMask, GenericEvent,
+    InputEvent, MapNotifyEvent, MapRequestEvent, PropertyNotifyEvent, PropertyState,
+    SelectionClearEvent, SelectionNotifyEvent, SelectionRequestEvent, SendEvent, UnmapNotifyEvent,
+};
+pub use self::extension::{
+    Extension, ListExtensions, ListExtensionsReply, QueryExtension, QueryExtensionReply,
+};
+pub use self::font::{
+    CharInfo, CloseFont, DrawDirection, FontProp, GetFontPath, GetFontPathReply, ListFonts,
+    ListFontsReply, OpenFont, QueryFont, QueryFontReply, SetFontPath,
+};
+pub use self::geometry::{Arc, Point, Rectangle, Segment};
+pub use self::graphics::{
+    CoordinateMode, CopyArea, CopyGC, CopyPlane, GcValueMask, ImageText16, ImageText8, PolyPoint,
+    PolyRectangle, PolySegment, PolyText16, PolyText8, QueryBestSize, QueryBestSizeReply,
+    SetClipRectangles, SetDashes, SizeClass, TextItem16, TextItem8,
+};
+pub use self::host::{
+    AccessControlMode, ChangeHosts, Host, HostChangeMode, ListHosts, ListHostsReply,
+    SetAccessControl,
+};
+pub use self::image::{decode_zpixmap, GetImage, GetImageReply, Image, ImageFormat, PutImage};
+pub use self::input::{
+    AllowEvents, AllowEventsMode, GetInputFocus, GetInputFocusReply, GetMotionEvents,
+    GetMotionEventsReply, GrabButton, GrabKey, GrabMode, KeyButMask, ModMask, QueryPointer,
+    QueryPointerReply, RevertTo, TimeCoord, UngrabButton, UngrabKey, ANY_BUTTON, ANY_KEY,
+    ANY_MODIFIER,
+};
+pub use self::keyboard::{
+    AutoRepeatMode, ChangeKeyboardControl, GetKeyboardControl, KeyboardControl,
+    KeyboardControlValues, LedMode,
+};
+#[cfg(feature = "std")]
+pub use self::message_codec::{Direction, Trace};
+#[cfg(feature = "std")]
+pub(crate) use self::message_codec::{Message, MessageCodec, MESSAGE_SIZE};
+pub use self::misc::{GrabServer, NoOperation, UngrabServer};
+pub use self::pixmap::{CreatePixmap, FreePixmap};
+pub use self::pointer::{
+    GetPointerMapping, GetPointerMappingReply, MappingStatus, SetPointerMapping,
+    SetPointerMappingReply,
+};
+pub use self::present::{
+    PresentCompleteKind, PresentCompleteMode, PresentCompleteNotifyEvent, PresentPixmap,
+    PresentQueryVersion, PresentQueryVersionReply, PresentSelectInput,
+    PRESENT_COMPLETE_NOTIFY_MASK, PRESENT_CONFIGURE_NOTIFY_MASK, PRESENT_EXTENSION_NAME,
+    PRESENT_IDLE_NOTIFY_MASK,
+};
+pub use self::property::{
+    ChangeProperty, DeleteProperty, GetProperty, ListProperties, ListPropertiesReply, Property,
+    PropertyMode, RotateProperties,
+};
+pub use self::render::{
+    PictOp, RenderComposite, RenderFillRectangles, RenderQueryVersion, RenderQueryVersionReply,
+    Rgba16, RENDER_EXTENSION_NAME,
+};
+pub use self::screen_saver::{
+    ForceScreenSaver, GetScreenSaver, GetScreenSaverReply, ScreenSaverMode, ScreenSaverPreference,
+    SetScreenSaver,
+};
+pub use self::selection::ConvertSelection;
+pub use self::setup::{
+    Depth, Format, Screen, Setup, SetupResponse, VisualType, SETUP_RESPONSE_HEADER_SIZE,
+};
+#[cfg(feature = "std")]
 pub(crate) use self::setup_codec::SetupCodec;
+pub use self::setup_request::SetupRequest;
+pub use self::shape::{
+    RectangleOrdering, ShapeKind, ShapeMask, ShapeOperation, ShapeQueryVersion,
+    ShapeQueryVersionReply, ShapeRectangles, SHAPE_EXTENSION_NAME,
+};
+pub use self::timestamp::Timestamp;
+pub use self::window::{
+    BackingStore, BitGravity, ChangeSaveSet, ChangeWindowAttributes, CirculateDirection,
+    CirculateWindow, ConfigWindowMask, ConfigureValues, ConfigureWindow, CreateWindow,
+    DestroyWindow, GetGeometry, GetGeometryReply, GetWindowAttributes, GetWindowAttributesReply,
+    MapState, MapWindow, QueryTree, QueryTreeReply, SaveSetMode, StackMode, WinGravity,
+    WindowAttributes, WindowClass,
+};
+pub use self::xc_misc::{
+    GetXIDList, GetXIDListReply, GetXIDRange, GetXIDRangeReply, XC_MISC_EXTENSION_NAME,
+};
+pub use self::xfixes::{
+    CreateRegion, DestroyRegion, SetWindowShapeRegion, XFixesQueryVersion, XFixesQueryVersionReply,
+    XFIXES_EXTENSION_NAME,
+};
+pub use self::xinerama::{
+    ScreenInfo, XineramaIsActive, XineramaIsActiveReply, XineramaQueryScreens,
+    XineramaQueryScreensReply, XineramaQueryVersion, XineramaQueryVersionReply,
+    XINERAMA_EXTENSION_NAME,
+};
+pub use self::xinput::{
+    XIDeviceEvent, XIQueryVersion, XIQueryVersionReply, XISelectEvents, XINPUT_EXTENSION_NAME,
+    XI_ALL_DEVICES, XI_ALL_MASTER_DEVICES, XI_BUTTON_PRESS, XI_BUTTON_RELEASE, XI_KEY_PRESS,
+    XI_KEY_RELEASE, XI_MOTION, XI_TOUCH_BEGIN, XI_TOUCH_END, XI_TOUCH_UPDATE,
+};
+pub use self::xkb::{
+    XkbGetMap, XkbGetMapReply, XkbKeySymMap, XkbKeyType, XkbKeyTypeMapEntry, XkbUseExtension,
+    XkbUseExtensionReply, XKB_EXTENSION_NAME, XKB_USE_CORE_KBD,
+};
+pub use self::xtest::{
+    FakeInputType, XTestCompareCursor, XTestCompareCursorReply, XTestFakeInput, XTestGetVersion,
+    XTestGetVersionReply, XTestGrabControl, XTEST_EXTENSION_NAME,
+};
 
-use std::io::{self, Read, Write};
+use crate::io::{self, Read, Write};
+use crate::utils::{ReadBytesExt, WriteBytesExt};
+use core::convert::TryFrom;
 
-#[cfg(target_endian = "big")]
-/// Protocol byte order.
-/// X Window protocol allows us to specify connection endianness,
-/// and we use native endianness for compilation target platform.
-pub const BYTE_ORDER: u8 = b'B';
-#[cfg(target_endian = "little")]
-/// Protocol byte order.
-/// X Window protocol allows us to specify connection endianness,
-/// and we use native endianness for compilation target platform.
-pub const BYTE_ORDER: u8 = b'l';
+/// Order of multi-byte values on the connection, announced by client in setup request.
+/// X server swaps bytes for clients using the opposite order to its own,
+/// so the client always uses native order of the compilation target platform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteOrder {
+    MsbFirst = 0x42,
+    LsbFirst = 0x6c,
+}
+
+impl ByteOrder {
+    #[cfg(target_endian = "big")]
+    pub const NATIVE: ByteOrder = ByteOrder::MsbFirst;
+    #[cfg(target_endian = "little")]
+    pub const NATIVE: ByteOrder = ByteOrder::LsbFirst;
+}
 
 /// X Window System protocol major version
 pub(crate) const PROTOCOL_MAJOR_VERSION: u16 = 11;
@@ -37,9 +201,9 @@ pub(crate) type INT32 = i32;
 pub(crate) type INT16 = i16;
 #[allow(dead_code)]
 pub(crate) type INT64 = i64;
-#[allow(dead_code)]
+#[allow(dead_code, clippy::upper_case_acronyms)]
 pub(crate) type BYTE = u8;
-#[allow(dead_code)]
+#[allow(dead_code, clippy::upper_case_acronyms)]
 pub(crate) type BOOL = bool;
 
 /// General crate serialization trait.
@@ -47,11 +211,143 @@ pub trait Serialize {
     fn serialize<W: Write>(&self, writer: W) -> io::Result<()>;
 }
 
+/// Core protocol request. Its [Serialize] implementation writes the header
+/// with request length computed from the body, which is padded to 4 bytes.
+pub trait Request {
+    const OPCODE: u8;
+
+    /// Write request body following the header, returns header data byte,
+    /// which is unused (0) for most of requests.
+    fn serialize_body<W: Write>(&self, writer: W) -> io::Result<u8>;
+}
+
+// 1     CARD8                           opcode
+// 1                                     data
+// 2     CARD16                          request length
+// n                                     body
+// p                                     unused, p=pad(n)
+impl<R: Request> Serialize for R {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let mut body = vec![];
+        let data = self.serialize_body(&mut body)?;
+        let body_pad = pad(body.len());
+
+        writer.write_u8(R::OPCODE)?;
+        writer.write_u8(data)?;
+        writer.write_u16_ne(request_length((4 + body.len() + body_pad) / 4)?)?;
+        writer.write_all(&body)?;
+        writer.write_all(&[0; 3][..body_pad])?;
+        Ok(())
+    }
+}
+
 /// General crate deserialization trait.
 pub trait Deserialize: Sized {
     fn deserialize<R: Read>(reader: R) -> io::Result<Option<Self>>;
 }
 
+/// Request which generates exactly one reply of [Reply](ReplyRequest::Reply) type.
+pub trait ReplyRequest: Serialize {
+    type Reply: Deserialize;
+}
+
 pub fn pad(e: usize) -> usize {
     (4 - (e % 4)) % 4
 }
+
+/// Skip n bytes of reader, e.g. unused fields and padding.
+pub(crate) fn skip<R: Read>(mut reader: R, n: usize) -> io::Result<()> {
+    for _ in 0..n {
+        reader.read_u8()?;
+    }
+    Ok(())
+}
+
+/// Ensure request length in 4-byte units fits into request header.
+pub(crate) fn request_length(units: usize) -> io::Result<u16> {
+    u16::try_from(units)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Request is too long"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ChangeProperty, FreeColors, ImageText8, InternAtom, NoOperation, PropertyMode, Request,
+        Serialize, SetFontPath,
+    };
+    use alloc::vec::Vec;
+
+    /// Opcode and request length (in 4-byte units) from the header of serialized request,
+    /// followed by the total number of bytes written.
+    fn header_length<R: Request>(request: R) -> (u8, u16, usize) {
+        let mut write_buf = vec![];
+        request.serialize(&mut write_buf).unwrap();
+        let length = u16::from_ne_bytes([write_buf[2], write_buf[3]]);
+        (write_buf[0], length, write_buf.len())
+    }
+
+    #[test]
+    fn test_request_length() {
+        let intern_atom = InternAtom {
+            only_if_exists: true,
+            name: "WM_NAME".into(),
+        };
+        // 2+(n+p)/4
+        assert_eq!(header_length(intern_atom), (16, 4, 16));
+
+        let no_operation = NoOperation { data: vec![1; 5] };
+        // 1+(n+p)/4
+        assert_eq!(header_length(no_operation), (127, 3, 12));
+
+        let change_property = ChangeProperty {
+            mode: PropertyMode::Append,
+            window: 0x100,
+            property: 39,
+            property_type: 31,
+            format: 16,
+            data: vec![0; 6],
+        };
+        // 6+(n+p)/4
+        assert_eq!(header_length(change_property), (18, 8, 32));
+
+        let free_colors = FreeColors {
+            cmap: 0x20,
+            plane_mask: 0,
+            pixels: vec![1, 2, 3],
+        };
+        // 3+n
+        assert_eq!(header_length(free_colors), (88, 6, 24));
+
+        let image_text = ImageText8 {
+            drawable: 0x100,
+            gc: 0x04000001,
+            x: 0,
+            y: 0,
+            string: b"hello".to_vec(),
+        };
+        // 4+(n+p)/4
+        assert_eq!(header_length(image_text), (76, 6, 24));
+
+        let set_font_path = SetFontPath {
+            paths: vec!["built-ins".into(), "/usr/share/fonts".into()],
+        };
+        // 2+(n+p)/4, n is total length of STRs
+        assert_eq!(header_length(set_font_path), (51, 9, 36));
+    }
+
+    #[test]
+    fn test_request_body_error() {
+        let mut write_buf = Vec::new();
+        let too_long = ImageText8 {
+            drawable: 0x100,
+            gc: 0x04000001,
+            x: 0,
+            y: 0,
+            string: vec![b'a'; 256],
+        };
+
+        assert!(too_long.serialize(&mut write_buf).is_err());
+        // Header is not written for invalid body
+        assert!(write_buf.is_empty());
+    }
+}
@@ -0,0 +1,173 @@
+use super::{pad, skip, Deserialize, ReplyRequest, Request};
+use crate::io::{self, Read, Write};
+use crate::utils::{ReadBytesExt, WriteBytesExt};
+use alloc::string::String;
+use core::convert::TryFrom;
+
+/// Get atom for the name, optionally creating it.
+pub struct InternAtom {
+    pub only_if_exists: bool,
+    pub name: String,
+}
+
+// 1     16                              opcode
+// 1     BOOL                            only-if-exists
+// 2     2+(n+p)/4                       request length
+// 2     n                               length of name
+// 2                                     unused
+// n     STRING8                         name
+// p                                     unused, p=pad(n)
+impl Request for InternAtom {
+    const OPCODE: u8 = 16;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        let name_len = u16::try_from(self.name.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Atom name is too long"))?;
+
+        writer.write_u16_ne(name_len)?;
+        writer.write_u16_ne(0)?; // pad
+        writer.write_all(self.name.as_bytes())?;
+        for _ in 0..pad(self.name.len()) {
+            writer.write_u8(0)?;
+        }
+
+        Ok(self.only_if_exists.into())
+    }
+}
+
+/// Atom for interned name, 0 (None) if it does not exist.
+#[derive(Debug, PartialEq)]
+pub struct InternAtomReply {
+    pub atom: u32,
+}
+
+// 1     1                               Reply
+// 1                                     unused
+// 2     CARD16                          sequence number
+// 4     0                               reply length
+// 4     ATOM                            atom
+// 20                                    unused
+impl Deserialize for InternAtomReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        skip(&mut reader, 8)?; // reply header
+        let atom = reader.read_u32_ne()?;
+        skip(&mut reader, 20)?;
+
+        Ok(Some(InternAtomReply { atom }))
+    }
+}
+
+impl ReplyRequest for InternAtom {
+    type Reply = InternAtomReply;
+}
+
+/// Get name of the atom.
+pub struct GetAtomName {
+    pub atom: u32,
+}
+
+// 1     17                              opcode
+// 1                                     unused
+// 2     2                               request length
+// 4     ATOM                            atom
+impl Request for GetAtomName {
+    const OPCODE: u8 = 17;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.atom)?;
+        Ok(0)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct GetAtomNameReply {
+    pub name: String,
+}
+
+// 1     1                               Reply
+// 1                                     unused
+// 2     CARD16                          sequence number
+// 4     (n+p)/4                         reply length
+// 2     n                               length of name
+// 22                                    unused
+// n     STRING8                         name
+// p                                     unused, p=pad(n)
+impl Deserialize for GetAtomNameReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        skip(&mut reader, 8)?; // reply header
+        let name_len = reader.read_u16_ne()?;
+        skip(&mut reader, 22)?;
+
+        let name = reader.read_utf8_lossy_string8(name_len.into())?;
+
+        Ok(Some(GetAtomNameReply { name }))
+    }
+}
+
+impl ReplyRequest for GetAtomName {
+    type Reply = GetAtomNameReply;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GetAtomName, GetAtomNameReply, InternAtom, InternAtomReply};
+    use crate::io;
+    use crate::protocol::{Deserialize, Serialize};
+
+    #[test]
+    fn test_serialize_intern_atom() {
+        const EXPECTED: &[u8] = b"\x10\x01\x05\x00\x0b\x00\x00\x00UTF8_STRING\0";
+        let mut write_buf = vec![];
+
+        InternAtom {
+            only_if_exists: true,
+            name: "UTF8_STRING".into(),
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+
+        let too_long = InternAtom {
+            only_if_exists: false,
+            name: "a".repeat(0x10000),
+        };
+        let err = too_long.serialize(&mut write_buf).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_deserialize_intern_atom_reply() {
+        let mut reply = b"\x01\x00\x01\x00\x00\x00\x00\x00\x2a\x01\x00\x00".to_vec();
+        reply.extend(vec![0; 20]);
+
+        assert_eq!(
+            InternAtomReply::deserialize(&reply[..]).unwrap().unwrap(),
+            InternAtomReply { atom: 0x12a }
+        );
+    }
+
+    #[test]
+    fn test_serialize_get_atom_name() {
+        const EXPECTED: &[u8] = b"\x11\x00\x02\x00\x27\x00\x00\x00";
+        let mut write_buf = vec![];
+
+        GetAtomName { atom: 39 }.serialize(&mut write_buf).unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_deserialize_get_atom_name_reply() {
+        let mut reply = b"\x01\x00\x01\x00\x02\x00\x00\x00\x07\x00".to_vec();
+        reply.extend(vec![0; 22]);
+        reply.extend(b"WM_NAME\0");
+
+        assert_eq!(
+            GetAtomNameReply::deserialize(&reply[..]).unwrap().unwrap(),
+            GetAtomNameReply {
+                name: "WM_NAME".into()
+            }
+        );
+    }
+}
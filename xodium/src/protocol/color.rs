@@ -0,0 +1,379 @@
+use super::{skip, Deserialize, ReplyRequest, Request};
+use crate::io::{self, Read, Write};
+use crate::utils::{ReadBytesExt, WriteBytesExt};
+use alloc::vec::Vec;
+
+/// Color value with 16 bit channels.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Rgb {
+    pub red: u16,
+    pub green: u16,
+    pub blue: u16,
+}
+
+/// Allocate read/write colormap cells, combining `colors` pixels with `planes` plane masks.
+pub struct AllocColorCells {
+    pub contiguous: bool,
+    pub cmap: u32,
+    pub colors: u16,
+    pub planes: u16,
+}
+
+// 1     86                              opcode
+// 1     BOOL                            contiguous
+// 2     3                               request length
+// 4     COLORMAP                        cmap
+// 2     CARD16                          colors
+// 2     CARD16                          planes
+impl Request for AllocColorCells {
+    const OPCODE: u8 = 86;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.cmap)?;
+        writer.write_u16_ne(self.colors)?;
+        writer.write_u16_ne(self.planes)?;
+        Ok(self.contiguous.into())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct AllocColorCellsReply {
+    pub pixels: Vec<u32>,
+    pub masks: Vec<u32>,
+}
+
+// 1     1                               Reply
+// 1                                     unused
+// 2     CARD16                          sequence number
+// 4     n+m                             reply length
+// 2     n                               number of CARD32s in pixels
+// 2     m                               number of CARD32s in masks
+// 20                                    unused
+// 4n    LISTofCARD32                    pixels
+// 4m    LISTofCARD32                    masks
+impl Deserialize for AllocColorCellsReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        skip(&mut reader, 8)?; // reply header
+        let pixels_len = reader.read_u16_ne()?;
+        let masks_len = reader.read_u16_ne()?;
+        skip(&mut reader, 20)?;
+
+        let pixels = read_card32_list(&mut reader, pixels_len)?;
+        let masks = read_card32_list(&mut reader, masks_len)?;
+
+        Ok(Some(AllocColorCellsReply { pixels, masks }))
+    }
+}
+
+impl ReplyRequest for AllocColorCells {
+    type Reply = AllocColorCellsReply;
+}
+
+/// Allocate read/write colormap cells with separate red, green and blue planes.
+pub struct AllocColorPlanes {
+    pub contiguous: bool,
+    pub cmap: u32,
+    pub colors: u16,
+    pub reds: u16,
+    pub greens: u16,
+    pub blues: u16,
+}
+
+// 1     87                              opcode
+// 1     BOOL                            contiguous
+// 2     4                               request length
+// 4     COLORMAP                        cmap
+// 2     CARD16                          colors
+// 2     CARD16                          reds
+// 2     CARD16                          greens
+// 2     CARD16                          blues
+impl Request for AllocColorPlanes {
+    const OPCODE: u8 = 87;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.cmap)?;
+        writer.write_u16_ne(self.colors)?;
+        writer.write_u16_ne(self.reds)?;
+        writer.write_u16_ne(self.greens)?;
+        writer.write_u16_ne(self.blues)?;
+        Ok(self.contiguous.into())
+    }
+}
+
+/// Cells allocated by AllocColorPlanes.
+/// Every pixel combined with any subset of bits of the three masks is allocated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorPlanes {
+    pub pixels: Vec<u32>,
+    pub red_mask: u32,
+    pub green_mask: u32,
+    pub blue_mask: u32,
+}
+
+// 1     1                               Reply
+// 1                                     unused
+// 2     CARD16                          sequence number
+// 4     n                               reply length
+// 2     n                               number of CARD32s in pixels
+// 2                                     unused
+// 4     CARD32                          red-mask
+// 4     CARD32                          green-mask
+// 4     CARD32                          blue-mask
+// 8                                     unused
+// 4n    LISTofCARD32                    pixels
+impl Deserialize for ColorPlanes {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        skip(&mut reader, 8)?; // reply header
+        let pixels_len = reader.read_u16_ne()?;
+        skip(&mut reader, 2)?;
+        let red_mask = reader.read_u32_ne()?;
+        let green_mask = reader.read_u32_ne()?;
+        let blue_mask = reader.read_u32_ne()?;
+        skip(&mut reader, 8)?;
+
+        let pixels = read_card32_list(&mut reader, pixels_len)?;
+
+        Ok(Some(ColorPlanes {
+            pixels,
+            red_mask,
+            green_mask,
+            blue_mask,
+        }))
+    }
+}
+
+fn read_card32_list<R: Read>(mut reader: R, len: u16) -> io::Result<Vec<u32>> {
+    (0..len).map(|_| reader.read_u32_ne()).collect()
+}
+
+/// Free colormap cells allocated by the client.
+pub struct FreeColors {
+    pub cmap: u32,
+    pub plane_mask: u32,
+    pub pixels: Vec<u32>,
+}
+
+// 1     88                              opcode
+// 1                                     unused
+// 2     3+n                             request length
+// 4     COLORMAP                        cmap
+// 4     CARD32                          plane-mask
+// 4n    LISTofCARD32                    pixels
+impl Request for FreeColors {
+    const OPCODE: u8 = 88;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.cmap)?;
+        writer.write_u32_ne(self.plane_mask)?;
+
+        for pixel in &self.pixels {
+            writer.write_u32_ne(*pixel)?;
+        }
+
+        Ok(0)
+    }
+}
+
+/// Query color values stored in colormap cells.
+pub struct QueryColors {
+    pub cmap: u32,
+    pub pixels: Vec<u32>,
+}
+
+// 1     91                              opcode
+// 1                                     unused
+// 2     2+n                             request length
+// 4     COLORMAP                        cmap
+// 4n    LISTofCARD32                    pixels
+impl Request for QueryColors {
+    const OPCODE: u8 = 91;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.cmap)?;
+
+        for pixel in &self.pixels {
+            writer.write_u32_ne(*pixel)?;
+        }
+
+        Ok(0)
+    }
+}
+
+/// Colors of requested pixels, in the same order.
+#[derive(Debug, PartialEq)]
+pub struct QueryColorsReply {
+    pub colors: Vec<Rgb>,
+}
+
+// 1     1                               Reply
+// 1                                     unused
+// 2     CARD16                          sequence number
+// 4     2n                              reply length
+// 2     n                               number of RGBs in colors
+// 22                                    unused
+// 8n    LISTofRGB                       colors
+//
+// RGB
+// 2     CARD16                          red
+// 2     CARD16                          green
+// 2     CARD16                          blue
+// 2                                     unused
+impl Deserialize for QueryColorsReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        skip(&mut reader, 8)?; // reply header
+        let colors_len = reader.read_u16_ne()?;
+        skip(&mut reader, 22)?;
+
+        let mut colors = Vec::with_capacity(colors_len.into());
+        for _ in 0..colors_len {
+            let red = reader.read_u16_ne()?;
+            let green = reader.read_u16_ne()?;
+            let blue = reader.read_u16_ne()?;
+            skip(&mut reader, 2)?;
+            colors.push(Rgb { red, green, blue });
+        }
+
+        Ok(Some(QueryColorsReply { colors }))
+    }
+}
+
+impl ReplyRequest for QueryColors {
+    type Reply = QueryColorsReply;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        AllocColorCells, AllocColorCellsReply, AllocColorPlanes, ColorPlanes, FreeColors,
+        QueryColors, QueryColorsReply, Rgb,
+    };
+    use crate::protocol::{Deserialize, Serialize};
+
+    #[test]
+    fn test_serialize_alloc_color_cells() {
+        const EXPECTED: &[u8] = b"\x56\x01\x03\x00\x20\x00\x00\x00\x02\x00\x01\x00";
+        let mut write_buf = vec![];
+
+        AllocColorCells {
+            contiguous: true,
+            cmap: 0x20,
+            colors: 2,
+            planes: 1,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_deserialize_alloc_color_cells_reply() {
+        let mut reply = b"\x01\x00\x01\x00\x03\x00\x00\x00\x02\x00\x01\x00".to_vec();
+        reply.extend(vec![0; 20]);
+        reply.extend(b"\x10\x00\x00\x00\x12\x00\x00\x00\x01\x00\x00\x00");
+
+        assert_eq!(
+            AllocColorCellsReply::deserialize(&reply[..])
+                .unwrap()
+                .unwrap(),
+            AllocColorCellsReply {
+                pixels: vec![0x10, 0x12],
+                masks: vec![0x01],
+            }
+        );
+    }
+
+    #[test]
+    fn test_serialize_alloc_color_planes() {
+        const EXPECTED: &[u8] = b"\x57\x00\x04\x00\x20\x00\x00\x00\x01\x00\x02\x00\x02\x00\x01\x00";
+        let mut write_buf = vec![];
+
+        AllocColorPlanes {
+            contiguous: false,
+            cmap: 0x20,
+            colors: 1,
+            reds: 2,
+            greens: 2,
+            blues: 1,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_deserialize_color_planes() {
+        let mut reply = b"\x01\x00\x01\x00\x01\x00\x00\x00\x01\x00\x00\x00".to_vec();
+        reply.extend(b"\x06\x00\x00\x00\x18\x00\x00\x00\x20\x00\x00\x00");
+        reply.extend(vec![0; 8]);
+        reply.extend(b"\x01\x00\x00\x00");
+
+        assert_eq!(
+            ColorPlanes::deserialize(&reply[..]).unwrap().unwrap(),
+            ColorPlanes {
+                pixels: vec![1],
+                red_mask: 0x06,
+                green_mask: 0x18,
+                blue_mask: 0x20,
+            }
+        );
+    }
+
+    #[test]
+    fn test_serialize_query_colors() {
+        const EXPECTED: &[u8] = b"\x5b\x00\x04\x00\x20\x00\x00\x00\x00\x00\x00\x00\xff\xff\xff\x00";
+        let mut write_buf = vec![];
+
+        QueryColors {
+            cmap: 0x20,
+            pixels: vec![0, 0x00ff_ffff],
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_serialize_free_colors() {
+        const EXPECTED: &[u8] = b"\x58\x00\x04\x00\x20\x00\x00\x00\x00\x00\x00\x00\x05\x00\x00\x00";
+        let mut write_buf = vec![];
+
+        FreeColors {
+            cmap: 0x20,
+            plane_mask: 0,
+            pixels: vec![5],
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_deserialize_query_colors_reply() {
+        let mut reply = b"\x01\x00\x01\x00\x04\x00\x00\x00\x02\x00".to_vec();
+        reply.extend(vec![0; 22]);
+        reply.extend(b"\x00\x00\x00\x00\x00\x00\x00\x00");
+        reply.extend(b"\xff\xff\x00\x80\x34\x12\x00\x00");
+
+        assert_eq!(
+            QueryColorsReply::deserialize(&reply[..]).unwrap().unwrap(),
+            QueryColorsReply {
+                colors: vec![
+                    Rgb {
+                        red: 0,
+                        green: 0,
+                        blue: 0
+                    },
+                    Rgb {
+                        red: 0xffff,
+                        green: 0x8000,
+                        blue: 0x1234
+                    },
+                ]
+            }
+        );
+    }
+}
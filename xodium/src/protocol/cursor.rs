@@ -0,0 +1,162 @@
+use super::{Request, Rgb};
+use crate::io::{self, Write};
+use crate::utils::WriteBytesExt;
+
+/// Create cursor from 1-bit `source` pixmap, bits set to 1 are drawn with
+/// foreground color and bits set to 0 with background color.
+/// Only pixels set in 1-bit `mask` pixmap of the same size are shown,
+/// all of them if it's 0 (None).
+pub struct CreateCursor {
+    pub cid: u32,
+    pub source: u32,
+    pub mask: u32,
+    pub fore_rgb: Rgb,
+    pub back_rgb: Rgb,
+    /// Hotspot, relative to the origin of `source`, has to be inside it.
+    pub x: u16,
+    pub y: u16,
+}
+
+// 1     93                              opcode
+// 1                                     unused
+// 2     8                               request length
+// 4     CURSOR                          cid
+// 4     PIXMAP                          source
+// 4     PIXMAP                          mask
+//       0     None
+// 2     CARD16                          fore-red
+// 2     CARD16                          fore-green
+// 2     CARD16                          fore-blue
+// 2     CARD16                          back-red
+// 2     CARD16                          back-green
+// 2     CARD16                          back-blue
+// 2     CARD16                          x
+// 2     CARD16                          y
+impl Request for CreateCursor {
+    const OPCODE: u8 = 93;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.cid)?;
+        writer.write_u32_ne(self.source)?;
+        writer.write_u32_ne(self.mask)?;
+        writer.write_u16_ne(self.fore_rgb.red)?;
+        writer.write_u16_ne(self.fore_rgb.green)?;
+        writer.write_u16_ne(self.fore_rgb.blue)?;
+        writer.write_u16_ne(self.back_rgb.red)?;
+        writer.write_u16_ne(self.back_rgb.green)?;
+        writer.write_u16_ne(self.back_rgb.blue)?;
+        writer.write_u16_ne(self.x)?;
+        writer.write_u16_ne(self.y)?;
+        Ok(0)
+    }
+}
+
+/// Change cursor colors, cursor is redrawn if it's displayed.
+pub struct RecolorCursor {
+    pub cursor: u32,
+    pub fore_red: u16,
+    pub fore_green: u16,
+    pub fore_blue: u16,
+    pub back_red: u16,
+    pub back_green: u16,
+    pub back_blue: u16,
+}
+
+// 1     96                              opcode
+// 1                                     unused
+// 2     5                               request length
+// 4     CURSOR                          cursor
+// 2     CARD16                          fore-red
+// 2     CARD16                          fore-green
+// 2     CARD16                          fore-blue
+// 2     CARD16                          back-red
+// 2     CARD16                          back-green
+// 2     CARD16                          back-blue
+impl Request for RecolorCursor {
+    const OPCODE: u8 = 96;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.cursor)?;
+        writer.write_u16_ne(self.fore_red)?;
+        writer.write_u16_ne(self.fore_green)?;
+        writer.write_u16_ne(self.fore_blue)?;
+        writer.write_u16_ne(self.back_red)?;
+        writer.write_u16_ne(self.back_green)?;
+        writer.write_u16_ne(self.back_blue)?;
+        Ok(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CreateCursor, RecolorCursor};
+    use crate::protocol::{Rgb, Serialize};
+
+    #[test]
+    fn test_serialize_create_cursor() {
+        const EXPECTED: &[u8] = b"\x5d\x00\x08\x00\x03\x00\x00\x04\x01\x00\x00\x04\x02\x00\x00\x04\
+                                  \x00\x00\x00\x00\x00\x00\xff\xff\xff\xff\xff\xff\x07\x00\x03\x00";
+        let mut write_buf = vec![];
+
+        CreateCursor {
+            cid: 0x04000003,
+            source: 0x04000001,
+            mask: 0x04000002,
+            fore_rgb: Rgb::default(),
+            back_rgb: Rgb {
+                red: 0xffff,
+                green: 0xffff,
+                blue: 0xffff,
+            },
+            x: 7,
+            y: 3,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+        assert_eq!(write_buf.len(), 8 * 4);
+    }
+
+    #[test]
+    fn test_serialize_create_cursor_without_mask() {
+        let mut write_buf = vec![];
+
+        CreateCursor {
+            cid: 0x04000003,
+            source: 0x04000001,
+            mask: 0,
+            fore_rgb: Rgb::default(),
+            back_rgb: Rgb::default(),
+            x: 0,
+            y: 0,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf.len(), 8 * 4);
+        assert_eq!(&write_buf[12..16], b"\x00\x00\x00\x00");
+    }
+
+    #[test]
+    fn test_serialize_recolor_cursor() {
+        const EXPECTED: &[u8] = b"\x60\x00\x05\x00\x01\x00\x00\x04\xff\xff\x00\x00\x00\x00\
+                                  \xff\xff\xff\xff\xff\xff";
+        let mut write_buf = vec![];
+
+        RecolorCursor {
+            cursor: 0x04000001,
+            fore_red: 0xffff,
+            fore_green: 0,
+            fore_blue: 0,
+            back_red: 0xffff,
+            back_green: 0xffff,
+            back_blue: 0xffff,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+        assert_eq!(write_buf.len(), 5 * 4);
+    }
+}
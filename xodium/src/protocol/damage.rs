@@ -0,0 +1,354 @@
+//! DAMAGE extension, tracking of modified drawable areas used by compositors.
+
+use super::event::SEND_EVENT_MASK;
+use super::{skip, Deserialize, Event, Rectangle, ReplyRequest, Serialize};
+use crate::io::{self, Read, Write};
+use crate::utils::{ReadBytesExt, WriteBytesExt};
+
+pub const DAMAGE_EXTENSION_NAME: &str = "DAMAGE";
+
+const DAMAGE_QUERY_VERSION_OPCODE: u8 = 0;
+const DAMAGE_CREATE_OPCODE: u8 = 1;
+const DAMAGE_DESTROY_OPCODE: u8 = 2;
+const DAMAGE_SUBTRACT_OPCODE: u8 = 3;
+
+/// DamageNotify event code relative to the extension first event.
+const DAMAGE_NOTIFY_EVENT: u8 = 0;
+/// Set in level of DamageNotify if more events for the same damage follow.
+const DAMAGE_NOTIFY_MORE: u8 = 0x80;
+
+/// How damage is reported by DamageNotify events.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DamageReportLevel {
+    /// Every damaged rectangle is reported.
+    RawRectangles = 0,
+    /// Only rectangles which extend damage are reported.
+    DeltaRectangles = 1,
+    /// Only changes of damage bounding box are reported.
+    BoundingBox = 2,
+    /// Only transition from empty damage is reported.
+    NonEmpty = 3,
+}
+
+impl DamageReportLevel {
+    pub(crate) fn try_from(raw: u8) -> Option<Self> {
+        match raw {
+            0 => Some(DamageReportLevel::RawRectangles),
+            1 => Some(DamageReportLevel::DeltaRectangles),
+            2 => Some(DamageReportLevel::BoundingBox),
+            3 => Some(DamageReportLevel::NonEmpty),
+            _ => None,
+        }
+    }
+}
+
+/// Negotiate DAMAGE version, client sends highest version it supports.
+/// X server rejects other DAMAGE requests until version is negotiated.
+pub struct DamageQueryVersion {
+    pub major_opcode: u8,
+    pub major_version: u32,
+    pub minor_version: u32,
+}
+
+// 1     CARD8                           major opcode
+// 1     0                               damage opcode
+// 2     3                               request length
+// 4     CARD32                          major version
+// 4     CARD32                          minor version
+impl Serialize for DamageQueryVersion {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(DAMAGE_QUERY_VERSION_OPCODE)?;
+        writer.write_u16_ne(3)?;
+        writer.write_u32_ne(self.major_version)?;
+        writer.write_u32_ne(self.minor_version)?;
+        Ok(())
+    }
+}
+
+/// DAMAGE version supported by both X server and client.
+#[derive(Debug, PartialEq)]
+pub struct DamageQueryVersionReply {
+    pub major_version: u32,
+    pub minor_version: u32,
+}
+
+// 1     1                               Reply
+// 1                                     unused
+// 2     CARD16                          sequence number
+// 4     0                               reply length
+// 4     CARD32                          major version
+// 4     CARD32                          minor version
+// 16                                    unused
+impl Deserialize for DamageQueryVersionReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        skip(&mut reader, 8)?; // reply header
+        let major_version = reader.read_u32_ne()?;
+        let minor_version = reader.read_u32_ne()?;
+        skip(&mut reader, 16)?;
+
+        Ok(Some(DamageQueryVersionReply {
+            major_version,
+            minor_version,
+        }))
+    }
+}
+
+impl ReplyRequest for DamageQueryVersion {
+    type Reply = DamageQueryVersionReply;
+}
+
+/// Start tracking damage of drawable.
+pub struct DamageCreate {
+    pub major_opcode: u8,
+    pub damage: u32,
+    pub drawable: u32,
+    pub level: DamageReportLevel,
+}
+
+// 1     CARD8                           major opcode
+// 1     1                               damage opcode
+// 2     4                               request length
+// 4     DAMAGE                          damage
+// 4     DRAWABLE                        drawable
+// 1     DAMAGEREPORTLEVEL               level
+// 3                                     unused
+impl Serialize for DamageCreate {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(DAMAGE_CREATE_OPCODE)?;
+        writer.write_u16_ne(4)?;
+        writer.write_u32_ne(self.damage)?;
+        writer.write_u32_ne(self.drawable)?;
+        writer.write_u8(self.level as u8)?;
+        writer.write_all(&[0; 3])?; // pad
+        Ok(())
+    }
+}
+
+/// Stop tracking damage and free damage object.
+pub struct DamageDestroy {
+    pub major_opcode: u8,
+    pub damage: u32,
+}
+
+// 1     CARD8                           major opcode
+// 1     2                               damage opcode
+// 2     2                               request length
+// 4     DAMAGE                          damage
+impl Serialize for DamageDestroy {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(DAMAGE_DESTROY_OPCODE)?;
+        writer.write_u16_ne(2)?;
+        writer.write_u32_ne(self.damage)?;
+        Ok(())
+    }
+}
+
+/// Remove repaired area from damage.
+pub struct DamageSubtract {
+    pub major_opcode: u8,
+    pub damage: u32,
+    /// Region to remove from damage, 0 (None) removes all of it.
+    pub repair: u32,
+    /// Region set to the removed damage, 0 (None) if not needed.
+    pub parts: u32,
+}
+
+// 1     CARD8                           major opcode
+// 1     3                               damage opcode
+// 2     4                               request length
+// 4     DAMAGE                          damage
+// 4     REGION or None                  repair
+// 4     REGION or None                  parts
+impl Serialize for DamageSubtract {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(DAMAGE_SUBTRACT_OPCODE)?;
+        writer.write_u16_ne(4)?;
+        writer.write_u32_ne(self.damage)?;
+        writer.write_u32_ne(self.repair)?;
+        writer.write_u32_ne(self.parts)?;
+        Ok(())
+    }
+}
+
+/// Drawable was damaged.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DamageNotifyEvent {
+    pub level: DamageReportLevel,
+    /// More events for the same damage follow.
+    pub more: bool,
+    pub drawable: u32,
+    pub damage: u32,
+    pub timestamp: u32,
+    /// Damaged area relative to drawable.
+    pub area: Rectangle,
+    /// Drawable geometry.
+    pub geometry: Rectangle,
+}
+
+impl DamageNotifyEvent {
+    /// Decode event if it is DamageNotify, which is received as [Unknown](Event::Unknown).
+    /// `first_event` of DAMAGE extension is returned by QueryExtension.
+    pub fn from_event(event: &Event, first_event: u8) -> io::Result<Option<Self>> {
+        let code = first_event.wrapping_add(DAMAGE_NOTIFY_EVENT);
+
+        match event {
+            Event::Unknown(raw) if raw.first().map(|c| c & !SEND_EVENT_MASK) == Some(code) => {
+                DamageNotifyEvent::deserialize(&raw[..])
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+// 1     CARD8                           code
+// 1     DAMAGEREPORTLEVEL               level, 0x80 bit is set if more events follow
+// 2     CARD16                          sequence number
+// 4     DRAWABLE                        drawable
+// 4     DAMAGE                          damage
+// 4     TIMESTAMP                       timestamp
+// 8     RECTANGLE                       area
+// 8     RECTANGLE                       drawable geometry
+impl Deserialize for DamageNotifyEvent {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        reader.read_u8()?; // code
+        let raw_level = reader.read_u8()?;
+        let level = DamageReportLevel::try_from(raw_level & !DAMAGE_NOTIFY_MORE)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Invalid damage level"))?;
+        skip(&mut reader, 2)?; // sequence number
+        let drawable = reader.read_u32_ne()?;
+        let damage = reader.read_u32_ne()?;
+        let timestamp = reader.read_u32_ne()?;
+        let area = Rectangle::deserialize(&mut reader)?;
+        let geometry = Rectangle::deserialize(&mut reader)?;
+
+        match (area, geometry) {
+            (Some(area), Some(geometry)) => Ok(Some(DamageNotifyEvent {
+                level,
+                more: raw_level & DAMAGE_NOTIFY_MORE != 0,
+                drawable,
+                damage,
+                timestamp,
+                area,
+                geometry,
+            })),
+            _ => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        DamageCreate, DamageNotifyEvent, DamageQueryVersion, DamageQueryVersionReply,
+        DamageReportLevel, DamageSubtract,
+    };
+    use crate::protocol::{Deserialize, Event, Rectangle, Serialize};
+
+    #[test]
+    fn test_serialize_query_version() {
+        const EXPECTED: &[u8] = b"\x8f\x00\x03\x00\x01\x00\x00\x00\x01\x00\x00\x00";
+        let mut write_buf = vec![];
+
+        DamageQueryVersion {
+            major_opcode: 143,
+            major_version: 1,
+            minor_version: 1,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_deserialize_query_version_reply() {
+        let mut reply =
+            b"\x01\x00\x01\x00\x00\x00\x00\x00\x01\x00\x00\x00\x01\x00\x00\x00".to_vec();
+        reply.extend(vec![0; 16]);
+
+        assert_eq!(
+            DamageQueryVersionReply::deserialize(&reply[..])
+                .unwrap()
+                .unwrap(),
+            DamageQueryVersionReply {
+                major_version: 1,
+                minor_version: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_serialize_create() {
+        const EXPECTED: &[u8] = b"\x8f\x01\x04\x00\x01\x00\x00\x04\x01\x02\x00\x00\
+                                  \x03\x00\x00\x00";
+        let mut write_buf = vec![];
+
+        DamageCreate {
+            major_opcode: 143,
+            damage: 0x04000001,
+            drawable: 0x201,
+            level: DamageReportLevel::NonEmpty,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_serialize_subtract() {
+        const EXPECTED: &[u8] = b"\x8f\x03\x04\x00\x01\x00\x00\x04\x00\x00\x00\x00\
+                                  \x00\x00\x00\x00";
+        let mut write_buf = vec![];
+
+        DamageSubtract {
+            major_opcode: 143,
+            damage: 0x04000001,
+            repair: 0,
+            parts: 0,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_deserialize_damage_notify() {
+        const EVENT: &[u8] = b"\x5b\x80\x05\x00\x01\x02\x00\x00\x01\x00\x00\x04\
+                               \x10\x27\x00\x00\x0a\x00\x14\x00\x20\x00\x10\x00\
+                               \x00\x00\x00\x00\x80\x07\x38\x04";
+
+        assert_eq!(
+            DamageNotifyEvent::deserialize(EVENT).unwrap().unwrap(),
+            DamageNotifyEvent {
+                level: DamageReportLevel::RawRectangles,
+                more: true,
+                drawable: 0x201,
+                damage: 0x04000001,
+                timestamp: 10000,
+                area: Rectangle::from((10, 20, 32, 16)),
+                geometry: Rectangle::from((0, 0, 1920, 1080)),
+            }
+        );
+    }
+
+    #[test]
+    fn test_damage_notify_from_event() {
+        let mut raw = b"\x5b\x03\x05\x00\x01\x02\x00\x00\x01\x00\x00\x04".to_vec();
+        raw.resize(32, 0);
+        let event = Event::Unknown(raw);
+
+        let damage_notify = DamageNotifyEvent::from_event(&event, 91).unwrap().unwrap();
+        assert_eq!(damage_notify.level, DamageReportLevel::NonEmpty);
+        assert!(!damage_notify.more);
+        assert_eq!(damage_notify.damage, 0x04000001);
+
+        // Event of other extension
+        assert!(DamageNotifyEvent::from_event(&event, 90).unwrap().is_none());
+    }
+}
@@ -0,0 +1,350 @@
+//! DPMS extension, power saving of the monitor independent of screen saver.
+
+use super::{skip, Deserialize, ReplyRequest, Serialize};
+use crate::io::{self, Read, Write};
+use crate::utils::{ReadBytesExt, WriteBytesExt};
+
+pub const DPMS_EXTENSION_NAME: &str = "DPMS";
+
+const DPMS_GET_VERSION_OPCODE: u8 = 0;
+const DPMS_CAPABLE_OPCODE: u8 = 1;
+const DPMS_ENABLE_OPCODE: u8 = 4;
+const DPMS_DISABLE_OPCODE: u8 = 5;
+const DPMS_FORCE_LEVEL_OPCODE: u8 = 6;
+const DPMS_INFO_OPCODE: u8 = 7;
+
+/// Monitor power level, from the most to the least power used.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DPMSLevel {
+    On = 0,
+    Standby = 1,
+    Suspend = 2,
+    Off = 3,
+}
+
+impl DPMSLevel {
+    pub(crate) fn try_from(raw_level: u16) -> Option<DPMSLevel> {
+        match raw_level {
+            0 => Some(DPMSLevel::On),
+            1 => Some(DPMSLevel::Standby),
+            2 => Some(DPMSLevel::Suspend),
+            3 => Some(DPMSLevel::Off),
+            _ => None,
+        }
+    }
+}
+
+/// Query DPMS version, client sends highest version it supports.
+pub struct DPMSGetVersion {
+    pub major_opcode: u8,
+    pub major_version: u16,
+    pub minor_version: u16,
+}
+
+// 1     CARD8                           major opcode
+// 1     0                               DPMS opcode
+// 2     2                               request length
+// 2     CARD16                          client major version
+// 2     CARD16                          client minor version
+impl Serialize for DPMSGetVersion {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(DPMS_GET_VERSION_OPCODE)?;
+        writer.write_u16_ne(2)?;
+        writer.write_u16_ne(self.major_version)?;
+        writer.write_u16_ne(self.minor_version)?;
+        Ok(())
+    }
+}
+
+/// DPMS version supported by X server.
+#[derive(Debug, PartialEq)]
+pub struct DPMSGetVersionReply {
+    pub major_version: u16,
+    pub minor_version: u16,
+}
+
+// 1     1                               Reply
+// 1                                     unused
+// 2     CARD16                          sequence number
+// 4     0                               reply length
+// 2     CARD16                          server major version
+// 2     CARD16                          server minor version
+// 20                                    unused
+impl Deserialize for DPMSGetVersionReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        skip(&mut reader, 8)?; // reply header
+        let major_version = reader.read_u16_ne()?;
+        let minor_version = reader.read_u16_ne()?;
+        skip(&mut reader, 20)?;
+
+        Ok(Some(DPMSGetVersionReply {
+            major_version,
+            minor_version,
+        }))
+    }
+}
+
+impl ReplyRequest for DPMSGetVersion {
+    type Reply = DPMSGetVersionReply;
+}
+
+/// Query whether monitor supports power saving.
+pub struct DPMSCapable {
+    pub major_opcode: u8,
+}
+
+// 1     CARD8                           major opcode
+// 1     1                               DPMS opcode
+// 2     1                               request length
+impl Serialize for DPMSCapable {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(DPMS_CAPABLE_OPCODE)?;
+        writer.write_u16_ne(1)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct DPMSCapableReply {
+    pub capable: bool,
+}
+
+// 1     1                               Reply
+// 1                                     unused
+// 2     CARD16                          sequence number
+// 4     0                               reply length
+// 1     BOOL                            capable
+// 23                                    unused
+impl Deserialize for DPMSCapableReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        skip(&mut reader, 8)?; // reply header
+        let capable = reader.read_u8()? != 0;
+        skip(&mut reader, 23)?;
+
+        Ok(Some(DPMSCapableReply { capable }))
+    }
+}
+
+impl ReplyRequest for DPMSCapable {
+    type Reply = DPMSCapableReply;
+}
+
+/// Enable power saving once monitor is idle for configured timeouts.
+pub struct DPMSEnable {
+    pub major_opcode: u8,
+}
+
+// 1     CARD8                           major opcode
+// 1     4                               DPMS opcode
+// 2     1                               request length
+impl Serialize for DPMSEnable {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(DPMS_ENABLE_OPCODE)?;
+        writer.write_u16_ne(1)?;
+        Ok(())
+    }
+}
+
+/// Disable power saving, monitor is turned on.
+pub struct DPMSDisable {
+    pub major_opcode: u8,
+}
+
+// 1     CARD8                           major opcode
+// 1     5                               DPMS opcode
+// 2     1                               request length
+impl Serialize for DPMSDisable {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(DPMS_DISABLE_OPCODE)?;
+        writer.write_u16_ne(1)?;
+        Ok(())
+    }
+}
+
+/// Switch monitor to power level right away, power saving has to be enabled.
+pub struct DPMSForceLevel {
+    pub major_opcode: u8,
+    pub level: DPMSLevel,
+}
+
+// 1     CARD8                           major opcode
+// 1     6                               DPMS opcode
+// 2     2                               request length
+// 2     CARD16                          power level
+//       0     DPMSModeOn
+//       1     DPMSModeStandby
+//       2     DPMSModeSuspend
+//       3     DPMSModeOff
+// 2                                     unused
+impl Serialize for DPMSForceLevel {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(DPMS_FORCE_LEVEL_OPCODE)?;
+        writer.write_u16_ne(2)?;
+        writer.write_u16_ne(self.level as u16)?;
+        writer.write_u16_ne(0)?;
+        Ok(())
+    }
+}
+
+/// Query current power level and whether power saving is enabled.
+pub struct DPMSInfo {
+    pub major_opcode: u8,
+}
+
+// 1     CARD8                           major opcode
+// 1     7                               DPMS opcode
+// 2     1                               request length
+impl Serialize for DPMSInfo {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(DPMS_INFO_OPCODE)?;
+        writer.write_u16_ne(1)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct DPMSInfoReply {
+    pub power_level: DPMSLevel,
+    /// Whether power saving is enabled.
+    pub state: bool,
+}
+
+// 1     1                               Reply
+// 1                                     unused
+// 2     CARD16                          sequence number
+// 4     0                               reply length
+// 2     CARD16                          power level
+//       0     DPMSModeOn
+//       1     DPMSModeStandby
+//       2     DPMSModeSuspend
+//       3     DPMSModeOff
+// 1     BOOL                            state
+// 21                                    unused
+impl Deserialize for DPMSInfoReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        skip(&mut reader, 8)?; // reply header
+        let power_level = DPMSLevel::try_from(reader.read_u16_ne()?).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "Invalid DPMS power level")
+        })?;
+        let state = reader.read_u8()? != 0;
+        skip(&mut reader, 21)?;
+
+        Ok(Some(DPMSInfoReply { power_level, state }))
+    }
+}
+
+impl ReplyRequest for DPMSInfo {
+    type Reply = DPMSInfoReply;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        DPMSCapableReply, DPMSDisable, DPMSEnable, DPMSForceLevel, DPMSGetVersion,
+        DPMSGetVersionReply, DPMSInfoReply, DPMSLevel,
+    };
+    use crate::protocol::{Deserialize, Serialize};
+
+    #[test]
+    fn test_serialize_dpms_get_version() {
+        const EXPECTED: &[u8] = b"\x8c\x00\x02\x00\x01\x00\x01\x00";
+        let mut write_buf = vec![];
+
+        DPMSGetVersion {
+            major_opcode: 140,
+            major_version: 1,
+            minor_version: 1,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_deserialize_dpms_get_version_reply() {
+        let mut reply = b"\x01\x00\x01\x00\x00\x00\x00\x00\x01\x00\x01\x00".to_vec();
+        reply.extend(vec![0; 20]);
+
+        assert_eq!(
+            DPMSGetVersionReply::deserialize(&reply[..])
+                .unwrap()
+                .unwrap(),
+            DPMSGetVersionReply {
+                major_version: 1,
+                minor_version: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_deserialize_dpms_capable_reply() {
+        let mut reply = b"\x01\x00\x01\x00\x00\x00\x00\x00\x01".to_vec();
+        reply.extend(vec![0; 23]);
+
+        assert_eq!(
+            DPMSCapableReply::deserialize(&reply[..]).unwrap().unwrap(),
+            DPMSCapableReply { capable: true }
+        );
+    }
+
+    #[test]
+    fn test_serialize_dpms_enable_disable() {
+        const EXPECTED: &[u8] = b"\x8c\x04\x01\x00\x8c\x05\x01\x00";
+        let mut write_buf = vec![];
+
+        DPMSEnable { major_opcode: 140 }
+            .serialize(&mut write_buf)
+            .unwrap();
+        DPMSDisable { major_opcode: 140 }
+            .serialize(&mut write_buf)
+            .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_serialize_dpms_force_level() {
+        let levels = [
+            (DPMSLevel::On, b"\x8c\x06\x02\x00\x00\x00\x00\x00"),
+            (DPMSLevel::Standby, b"\x8c\x06\x02\x00\x01\x00\x00\x00"),
+            (DPMSLevel::Suspend, b"\x8c\x06\x02\x00\x02\x00\x00\x00"),
+            (DPMSLevel::Off, b"\x8c\x06\x02\x00\x03\x00\x00\x00"),
+        ];
+
+        for &(level, expected) in &levels {
+            let mut write_buf = vec![];
+            DPMSForceLevel {
+                major_opcode: 140,
+                level,
+            }
+            .serialize(&mut write_buf)
+            .unwrap();
+
+            assert_eq!(&write_buf, expected);
+        }
+    }
+
+    #[test]
+    fn test_deserialize_dpms_info_reply() {
+        let mut reply = b"\x01\x00\x01\x00\x00\x00\x00\x00\x03\x00\x01".to_vec();
+        reply.extend(vec![0; 21]);
+
+        assert_eq!(
+            DPMSInfoReply::deserialize(&reply[..]).unwrap().unwrap(),
+            DPMSInfoReply {
+                power_level: DPMSLevel::Off,
+                state: true,
+            }
+        );
+
+        reply[8] = 4;
+        assert!(DPMSInfoReply::deserialize(&reply[..]).is_err());
+    }
+}
@@ -0,0 +1,116 @@
+use super::{skip, Deserialize};
+use crate::io::{self, Read};
+use crate::utils::ReadBytesExt;
+use core::error::Error;
+use core::fmt;
+
+/// Error generated by X server in response to a request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct XError {
+    pub code: u8,
+    pub sequence_number: u16,
+    /// Bad resource id or value, depending on error code.
+    pub bad_value: u32,
+    pub minor_opcode: u16,
+    pub major_opcode: u8,
+}
+
+impl XError {
+    /// Protocol name of the error code.
+    pub fn name(&self) -> &'static str {
+        match self.code {
+            1 => "Request",
+            2 => "Value",
+            3 => "Window",
+            4 => "Pixmap",
+            5 => "Atom",
+            6 => "Cursor",
+            7 => "Font",
+            8 => "Match",
+            9 => "Drawable",
+            10 => "Access",
+            11 => "Alloc",
+            12 => "Colormap",
+            13 => "GContext",
+            14 => "IDChoice",
+            15 => "Name",
+            16 => "Length",
+            17 => "Implementation",
+            _ => "Unknown",
+        }
+    }
+}
+
+impl fmt::Display for XError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "X error {} ({}) for request {}.{}, sequence {}, value {:#x}",
+            self.name(),
+            self.code,
+            self.major_opcode,
+            self.minor_opcode,
+            self.sequence_number,
+            self.bad_value
+        )
+    }
+}
+
+impl Error for XError {}
+
+impl From<XError> for io::Error {
+    fn from(e: XError) -> io::Error {
+        io::Error::other(e)
+    }
+}
+
+// 1     0                               Error
+// 1     CARD8                           code
+// 2     CARD16                          sequence number
+// 4     CARD32                          bad resource id or value
+// 2     CARD16                          minor opcode
+// 1     CARD8                           major opcode
+// 21                                    unused
+impl Deserialize for XError {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        reader.read_u8()?; // Error
+        let code = reader.read_u8()?;
+        let sequence_number = reader.read_u16_ne()?;
+        let bad_value = reader.read_u32_ne()?;
+        let minor_opcode = reader.read_u16_ne()?;
+        let major_opcode = reader.read_u8()?;
+        skip(&mut reader, 21)?;
+
+        Ok(Some(XError {
+            code,
+            sequence_number,
+            bad_value,
+            minor_opcode,
+            major_opcode,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::XError;
+    use crate::protocol::Deserialize;
+
+    #[test]
+    fn test_deserialize_error() {
+        const ERROR: &[u8] = b"\x00\x03\x05\x00\x01\x02\x00\x00\x00\x00\x0c\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0";
+
+        let error = XError::deserialize(ERROR).unwrap().unwrap();
+        assert_eq!(
+            error,
+            XError {
+                code: 3,
+                sequence_number: 5,
+                bad_value: 0x201,
+                minor_opcode: 0,
+                major_opcode: 12,
+            }
+        );
+        assert_eq!(error.name(), "Window");
+    }
+}
@@ -0,0 +1,887 @@
+use super::flags::define_flags;
+use super::{
+    ConfigWindowMask, ConfigureValues, Deserialize, KeyButMask, Request, Serialize, StackMode,
+};
+use crate::io::{self, Read, Write};
+use crate::utils::{ReadBytesExt, WriteBytesExt};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+const KEY_PRESS: u8 = 2;
+const KEY_RELEASE: u8 = 3;
+const BUTTON_PRESS: u8 = 4;
+const BUTTON_RELEASE: u8 = 5;
+const MOTION_NOTIFY: u8 = 6;
+const DESTROY_NOTIFY: u8 = 17;
+const UNMAP_NOTIFY: u8 = 18;
+const MAP_NOTIFY: u8 = 19;
+const MAP_REQUEST: u8 = 20;
+const CONFIGURE_REQUEST: u8 = 23;
+const PROPERTY_NOTIFY: u8 = 28;
+const SELECTION_CLEAR: u8 = 29;
+const SELECTION_REQUEST: u8 = 30;
+const SELECTION_NOTIFY: u8 = 31;
+const CLIENT_MESSAGE: u8 = 33;
+/// Event of extension, which may be longer than 32 bytes.
+pub(crate) const GENERIC_EVENT: u8 = 35;
+
+/// Most significant bit of event code is set for events sent by SendEvent.
+pub(crate) const SEND_EVENT_MASK: u8 = 0x80;
+
+define_flags! {
+    /// Events window is selected for, combined with `|`,
+    /// e.g. `EventMask::EXPOSURE | EventMask::KEY_PRESS`.
+    pub struct EventMask: u32 {
+        const NO_EVENT = 0;
+        const KEY_PRESS = 0x0000_0001;
+        const KEY_RELEASE = 0x0000_0002;
+        const BUTTON_PRESS = 0x0000_0004;
+        const BUTTON_RELEASE = 0x0000_0008;
+        const ENTER_WINDOW = 0x0000_0010;
+        const LEAVE_WINDOW = 0x0000_0020;
+        const POINTER_MOTION = 0x0000_0040;
+        const POINTER_MOTION_HINT = 0x0000_0080;
+        const BUTTON1_MOTION = 0x0000_0100;
+        const BUTTON2_MOTION = 0x0000_0200;
+        const BUTTON3_MOTION = 0x0000_0400;
+        const BUTTON4_MOTION = 0x0000_0800;
+        const BUTTON5_MOTION = 0x0000_1000;
+        /// Motion with any button pressed.
+        const BUTTON_MOTION = 0x0000_2000;
+        const KEYMAP_STATE = 0x0000_4000;
+        const EXPOSURE = 0x0000_8000;
+        const VISIBILITY_CHANGE = 0x0001_0000;
+        /// Changes of the window itself, e.g. ConfigureNotify and DestroyNotify.
+        const STRUCTURE_NOTIFY = 0x0002_0000;
+        const RESIZE_REDIRECT = 0x0004_0000;
+        /// Changes of children of the window.
+        const SUBSTRUCTURE_NOTIFY = 0x0008_0000;
+        /// Redirect map and configure requests of children, only one client may select it.
+        const SUBSTRUCTURE_REDIRECT = 0x0010_0000;
+        const FOCUS_CHANGE = 0x0020_0000;
+        const PROPERTY_CHANGE = 0x0040_0000;
+        const COLORMAP_CHANGE = 0x0080_0000;
+        const OWNER_GRAB_BUTTON = 0x0100_0000;
+    }
+}
+
+/// Event sent by X server.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    KeyPress(InputEvent),
+    KeyRelease(InputEvent),
+    ButtonPress(InputEvent),
+    ButtonRelease(InputEvent),
+    MotionNotify(InputEvent),
+    DestroyNotify(DestroyNotifyEvent),
+    UnmapNotify(UnmapNotifyEvent),
+    MapNotify(MapNotifyEvent),
+    MapRequest(MapRequestEvent),
+    ConfigureRequest(ConfigureRequestEvent),
+    PropertyNotify(PropertyNotifyEvent),
+    SelectionClear(SelectionClearEvent),
+    SelectionRequest(SelectionRequestEvent),
+    SelectionNotify(SelectionNotifyEvent),
+    ClientMessage(ClientMessageEvent),
+    Generic(GenericEvent),
+    /// Event not supported yet, contains raw 32 bytes.
+    Unknown(Vec<u8>),
+}
+
+/// Event of extension using X Generic Event extension, e.g. Present.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenericEvent {
+    /// Major opcode of extension.
+    pub extension: u8,
+    pub event_type: u16,
+    /// Complete event, including 32 bytes header.
+    pub data: Vec<u8>,
+}
+
+/// Key, button or pointer motion event, all of them share the layout.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputEvent {
+    /// Keycode for key events, button for button events,
+    /// 1 (Hint) for motion events if only hint was selected, 0 (Normal) otherwise.
+    pub detail: u8,
+    pub time: u32,
+    pub root: u32,
+    pub event: u32,
+    /// Child of event window containing pointer, 0 (None) if there is none.
+    pub child: u32,
+    pub root_x: i16,
+    pub root_y: i16,
+    pub event_x: i16,
+    pub event_y: i16,
+    /// Modifiers and buttons state right before the event.
+    pub state: KeyButMask,
+    pub same_screen: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DestroyNotifyEvent {
+    pub event: u32,
+    pub window: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnmapNotifyEvent {
+    pub event: u32,
+    pub window: u32,
+    pub from_configure: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapNotifyEvent {
+    pub event: u32,
+    pub window: u32,
+    pub override_redirect: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapRequestEvent {
+    pub parent: u32,
+    pub window: u32,
+}
+
+/// Client attempt to configure window redirected to window manager.
+/// Only fields with corresponding `value_mask` bit set were requested.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigureRequestEvent {
+    pub stack_mode: StackMode,
+    pub parent: u32,
+    pub window: u32,
+    pub sibling: u32,
+    pub x: i16,
+    pub y: i16,
+    pub width: u16,
+    pub height: u16,
+    pub border_width: u16,
+    pub value_mask: ConfigWindowMask,
+}
+
+impl ConfigureRequestEvent {
+    /// Values requested by client, for ConfigureWindow applying them on its behalf.
+    pub fn values(&self) -> ConfigureValues {
+        let mut values = ConfigureValues::new();
+        let requested = |bit: ConfigWindowMask| self.value_mask.contains(bit);
+
+        if requested(ConfigWindowMask::X) {
+            values = values.x(self.x);
+        }
+        if requested(ConfigWindowMask::Y) {
+            values = values.y(self.y);
+        }
+        if requested(ConfigWindowMask::WIDTH) {
+            values = values.width(self.width);
+        }
+        if requested(ConfigWindowMask::HEIGHT) {
+            values = values.height(self.height);
+        }
+        if requested(ConfigWindowMask::BORDER_WIDTH) {
+            values = values.border_width(self.border_width);
+        }
+        if requested(ConfigWindowMask::SIBLING) {
+            values = values.sibling(self.sibling);
+        }
+        if requested(ConfigWindowMask::STACK_MODE) {
+            values = values.stack_mode(self.stack_mode);
+        }
+
+        values
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PropertyState {
+    NewValue = 0,
+    Deleted = 1,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyNotifyEvent {
+    pub window: u32,
+    pub atom: u32,
+    pub time: u32,
+    pub state: PropertyState,
+}
+
+/// Selection owned by the window was taken by another client.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectionClearEvent {
+    pub time: u32,
+    pub owner: u32,
+    pub selection: u32,
+}
+
+/// Request to selection owner to convert selection to `target` type
+/// and store it in `property` of `requestor` window.
+/// Owner replies with [SelectionNotifyEvent] sent with SendEvent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectionRequestEvent {
+    /// Timestamp of the ConvertSelection request, 0 (CurrentTime) if unspecified.
+    pub time: u32,
+    pub owner: u32,
+    pub requestor: u32,
+    pub selection: u32,
+    pub target: u32,
+    /// 0 (None) for clients using obsolete protocol, `target` should be used instead.
+    pub property: u32,
+}
+
+/// Result of selection conversion, `property` is 0 (None) if it failed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectionNotifyEvent {
+    pub time: u32,
+    pub requestor: u32,
+    pub selection: u32,
+    pub target: u32,
+    pub property: u32,
+}
+
+// 1     31                              code
+// 1                                     unused
+// 2     CARD16                          sequence number
+// 4     TIMESTAMP                       time
+//       0     CurrentTime
+// 4     WINDOW                          requestor
+// 4     ATOM                            selection
+// 4     ATOM                            target
+// 4     ATOM                            property
+//       0     None
+// 8                                     unused
+impl Serialize for SelectionNotifyEvent {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(SELECTION_NOTIFY)?;
+        writer.write_u8(0)?;
+        writer.write_u16_ne(0)?; // sequence number is set by X server
+        writer.write_u32_ne(self.time)?;
+        writer.write_u32_ne(self.requestor)?;
+        writer.write_u32_ne(self.selection)?;
+        writer.write_u32_ne(self.target)?;
+        writer.write_u32_ne(self.property)?;
+        writer.write_all(&[0; 8])?;
+        Ok(())
+    }
+}
+
+/// Message sent by client with SendEvent, e.g. EWMH request to window manager.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientMessageEvent {
+    /// Unit size of data in bits: 8, 16 or 32.
+    pub format: u8,
+    pub window: u32,
+    pub message_type: u32,
+    /// Data in native byte order.
+    pub data: [u8; 20],
+}
+
+impl ClientMessageEvent {
+    /// Message with data of format 32, used by most of EWMH messages.
+    pub fn new_32(window: u32, message_type: u32, data: [u32; 5]) -> Self {
+        let mut bytes = [0; 20];
+        for (chunk, value) in bytes.chunks_exact_mut(4).zip(&data) {
+            chunk.copy_from_slice(&value.to_ne_bytes());
+        }
+
+        ClientMessageEvent {
+            format: 32,
+            window,
+            message_type,
+            data: bytes,
+        }
+    }
+}
+
+// 1     33                              code
+// 1     CARD8                           format
+// 2     CARD16                          sequence number
+// 4     WINDOW                          window
+// 4     ATOM                            type
+// 20                                    data
+impl Serialize for ClientMessageEvent {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(CLIENT_MESSAGE)?;
+        writer.write_u8(self.format)?;
+        writer.write_u16_ne(0)?; // sequence number is set by X server
+        writer.write_u32_ne(self.window)?;
+        writer.write_u32_ne(self.message_type)?;
+        writer.write_all(&self.data)?;
+        Ok(())
+    }
+}
+
+/// Send event to window as if it was generated by X server.
+pub struct SendEvent<E> {
+    /// Propagate event to ancestors if no client selected it on destination.
+    pub propagate: bool,
+    /// Window, or 0 (PointerWindow) or 1 (InputFocus).
+    pub destination: u32,
+    /// Event is sent to clients selected any of these events,
+    /// 0 sends it to the destination window owner.
+    pub event_mask: u32,
+    pub event: E,
+}
+
+// 1     25                              opcode
+// 1     BOOL                            propagate
+// 2     11                              request length
+// 4     WINDOW                          destination
+//       0     PointerWindow
+//       1     InputFocus
+// 4     SETofEVENT                      event-mask
+// 32                                    event
+impl<E: Serialize> Request for SendEvent<E> {
+    const OPCODE: u8 = 25;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        let mut event = Vec::with_capacity(32);
+        self.event.serialize(&mut event)?;
+        if event.len() != 32 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Event must be 32 bytes long",
+            ));
+        }
+
+        writer.write_u32_ne(self.destination)?;
+        writer.write_u32_ne(self.event_mask)?;
+        writer.write_all(&event)?;
+        Ok(self.propagate.into())
+    }
+}
+
+fn read_bool<R: Read>(mut reader: R) -> io::Result<bool> {
+    Ok(reader.read_u8()? != 0)
+}
+
+// 1                                     detail
+// 4     TIMESTAMP                       time
+// 4     WINDOW                          root
+// 4     WINDOW                          event
+// 4     WINDOW                          child
+//       0     None
+// 2     INT16                           root-x
+// 2     INT16                           root-y
+// 2     INT16                           event-x
+// 2     INT16                           event-y
+// 2     SETofKEYBUTMASK                 state
+// 1     BOOL                            same-screen
+// 1                                     unused
+fn read_input_event<R: Read>(detail: u8, mut reader: R) -> io::Result<InputEvent> {
+    Ok(InputEvent {
+        detail,
+        time: reader.read_u32_ne()?,
+        root: reader.read_u32_ne()?,
+        event: reader.read_u32_ne()?,
+        child: reader.read_u32_ne()?,
+        root_x: reader.read_i16_ne()?,
+        root_y: reader.read_i16_ne()?,
+        event_x: reader.read_i16_ne()?,
+        event_y: reader.read_i16_ne()?,
+        state: KeyButMask::from(reader.read_u16_ne()?),
+        same_screen: read_bool(&mut reader)?,
+    })
+}
+
+fn invalid_value(what: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Invalid {} in event", what),
+    )
+}
+
+// Every event is 32 bytes long and starts with:
+// 1     CARD8                           code
+// 1                                     event specific byte
+// 2     CARD16                          sequence number
+impl Deserialize for Event {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        let mut raw = [0; 32];
+        reader.read_exact(&mut raw)?;
+
+        let code = raw[0] & !SEND_EVENT_MASK;
+        let detail = raw[1];
+
+        // 1     35                              code
+        // 1     CARD8                           extension
+        // 2     CARD16                          sequence number
+        // 4     CARD32                          length
+        // 2     CARD16                          event type
+        // 22                                    event specific data
+        // 4n                                    event specific data
+        if code == GENERIC_EVENT {
+            let length = u32::from_ne_bytes([raw[4], raw[5], raw[6], raw[7]]);
+            let extra_len = usize::try_from(length)
+                .ok()
+                .and_then(|length| length.checked_mul(4))
+                .ok_or_else(|| invalid_value("generic event length"))?;
+
+            let mut data = raw.to_vec();
+            data.resize(raw.len() + extra_len, 0);
+            reader.read_exact(&mut data[raw.len()..])?;
+
+            return Ok(Some(Event::Generic(GenericEvent {
+                extension: detail,
+                event_type: u16::from_ne_bytes([raw[8], raw[9]]),
+                data,
+            })));
+        }
+
+        let mut reader = &raw[4..];
+
+        let event = match code {
+            KEY_PRESS => Event::KeyPress(read_input_event(detail, &mut reader)?),
+            KEY_RELEASE => Event::KeyRelease(read_input_event(detail, &mut reader)?),
+            BUTTON_PRESS => Event::ButtonPress(read_input_event(detail, &mut reader)?),
+            BUTTON_RELEASE => Event::ButtonRelease(read_input_event(detail, &mut reader)?),
+            MOTION_NOTIFY => Event::MotionNotify(read_input_event(detail, &mut reader)?),
+            // 4     WINDOW                          event
+            // 4     WINDOW                          window
+            // 20                                    unused
+            DESTROY_NOTIFY => Event::DestroyNotify(DestroyNotifyEvent {
+                event: reader.read_u32_ne()?,
+                window: reader.read_u32_ne()?,
+            }),
+            // 4     WINDOW                          event
+            // 4     WINDOW                          window
+            // 1     BOOL                            from-configure
+            // 19                                    unused
+            UNMAP_NOTIFY => Event::UnmapNotify(UnmapNotifyEvent {
+                event: reader.read_u32_ne()?,
+                window: reader.read_u32_ne()?,
+                from_configure: read_bool(&mut reader)?,
+            }),
+            // 4     WINDOW                          event
+            // 4     WINDOW                          window
+            // 1     BOOL                            override-redirect
+            // 19                                    unused
+            MAP_NOTIFY => Event::MapNotify(MapNotifyEvent {
+                event: reader.read_u32_ne()?,
+                window: reader.read_u32_ne()?,
+                override_redirect: read_bool(&mut reader)?,
+            }),
+            // 4     WINDOW                          parent
+            // 4     WINDOW                          window
+            // 20                                    unused
+            MAP_REQUEST => Event::MapRequest(MapRequestEvent {
+                parent: reader.read_u32_ne()?,
+                window: reader.read_u32_ne()?,
+            }),
+            // 1                                     stack-mode (detail)
+            // 4     WINDOW                          parent
+            // 4     WINDOW                          window
+            // 4     WINDOW                          sibling
+            // 2     INT16                           x
+            // 2     INT16                           y
+            // 2     CARD16                          width
+            // 2     CARD16                          height
+            // 2     CARD16                          border-width
+            // 2     BITMASK                         value-mask
+            // 4                                     unused
+            CONFIGURE_REQUEST => Event::ConfigureRequest(ConfigureRequestEvent {
+                stack_mode: StackMode::try_from(detail)
+                    .ok_or_else(|| invalid_value("stack mode"))?,
+                parent: reader.read_u32_ne()?,
+                window: reader.read_u32_ne()?,
+                sibling: reader.read_u32_ne()?,
+                x: reader.read_i16_ne()?,
+                y: reader.read_i16_ne()?,
+                width: reader.read_u16_ne()?,
+                height: reader.read_u16_ne()?,
+                border_width: reader.read_u16_ne()?,
+                value_mask: ConfigWindowMask(reader.read_u16_ne()?),
+            }),
+            // 4     WINDOW                          window
+            // 4     ATOM                            atom
+            // 4     TIMESTAMP                       time
+            // 1                                     state
+            //       0     NewValue
+            //       1     Deleted
+            // 15                                    unused
+            PROPERTY_NOTIFY => {
+                let window = reader.read_u32_ne()?;
+                let atom = reader.read_u32_ne()?;
+                let time = reader.read_u32_ne()?;
+                let state = match reader.read_u8()? {
+                    0 => PropertyState::NewValue,
+                    1 => PropertyState::Deleted,
+                    _ => return Err(invalid_value("property state")),
+                };
+
+                Event::PropertyNotify(PropertyNotifyEvent {
+                    window,
+                    atom,
+                    time,
+                    state,
+                })
+            }
+            // 4     TIMESTAMP                       time
+            // 4     WINDOW                          owner
+            // 4     ATOM                            selection
+            // 16                                    unused
+            SELECTION_CLEAR => Event::SelectionClear(SelectionClearEvent {
+                time: reader.read_u32_ne()?,
+                owner: reader.read_u32_ne()?,
+                selection: reader.read_u32_ne()?,
+            }),
+            // 4     TIMESTAMP                       time
+            //       0     CurrentTime
+            // 4     WINDOW                          owner
+            // 4     WINDOW                          requestor
+            // 4     ATOM                            selection
+            // 4     ATOM                            target
+            // 4     ATOM                            property
+            //       0     None
+            // 4                                     unused
+            SELECTION_REQUEST => Event::SelectionRequest(SelectionRequestEvent {
+                time: reader.read_u32_ne()?,
+                owner: reader.read_u32_ne()?,
+                requestor: reader.read_u32_ne()?,
+                selection: reader.read_u32_ne()?,
+                target: reader.read_u32_ne()?,
+                property: reader.read_u32_ne()?,
+            }),
+            // 4     TIMESTAMP                       time
+            //       0     CurrentTime
+            // 4     WINDOW                          requestor
+            // 4     ATOM                            selection
+            // 4     ATOM                            target
+            // 4     ATOM                            property
+            //       0     None
+            // 8                                     unused
+            SELECTION_NOTIFY => Event::SelectionNotify(SelectionNotifyEvent {
+                time: reader.read_u32_ne()?,
+                requestor: reader.read_u32_ne()?,
+                selection: reader.read_u32_ne()?,
+                target: reader.read_u32_ne()?,
+                property: reader.read_u32_ne()?,
+            }),
+            // 4     WINDOW                          window
+            // 4     ATOM                            type
+            // 20                                    data
+            CLIENT_MESSAGE => {
+                let window = reader.read_u32_ne()?;
+                let message_type = reader.read_u32_ne()?;
+                let mut data = [0; 20];
+                reader.read_exact(&mut data)?;
+
+                Event::ClientMessage(ClientMessageEvent {
+                    format: detail,
+                    window,
+                    message_type,
+                    data,
+                })
+            }
+            _ => Event::Unknown(raw.to_vec()),
+        };
+
+        Ok(Some(event))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ClientMessageEvent, ConfigureRequestEvent, DestroyNotifyEvent, Event, GenericEvent,
+        InputEvent, MapNotifyEvent, MapRequestEvent, PropertyNotifyEvent, PropertyState,
+        SelectionClearEvent, SelectionNotifyEvent, SelectionRequestEvent, SendEvent,
+        UnmapNotifyEvent,
+    };
+    use crate::protocol::{
+        ConfigWindowMask, ConfigureValues, Deserialize, KeyButMask, Serialize, StackMode,
+    };
+    use crate::test_utils::event;
+
+    #[test]
+    fn test_deserialize_key_press() {
+        // Ctrl+C
+        let raw = event(
+            b"\x02\x36\x05\x00\x10\x27\x00\x00\x00\x01\x00\x00\x01\x02\x00\x00\
+              \x00\x00\x00\x00\x64\x00\xc8\x00\x0a\x00\x14\x00\x04\x00\x01",
+        );
+
+        let key_press = match Event::deserialize(&raw[..]).unwrap().unwrap() {
+            Event::KeyPress(key_press) => key_press,
+            other => panic!("Unexpected event: {:?}", other),
+        };
+
+        assert_eq!(
+            key_press,
+            InputEvent {
+                detail: 54,
+                time: 10000,
+                root: 0x100,
+                event: 0x201,
+                child: 0,
+                root_x: 100,
+                root_y: 200,
+                event_x: 10,
+                event_y: 20,
+                state: KeyButMask(0x0004),
+                same_screen: true,
+            }
+        );
+        assert!(key_press.state.control());
+        assert!(!key_press.state.shift());
+    }
+
+    #[test]
+    fn test_deserialize_motion_notify_drag() {
+        let raw = event(
+            b"\x06\x00\x05\x00\x10\x27\x00\x00\x00\x01\x00\x00\x01\x02\x00\x00\
+              \x00\x00\x00\x00\x64\x00\xc8\x00\x0a\x00\x14\x00\x00\x01\x01",
+        );
+
+        match Event::deserialize(&raw[..]).unwrap().unwrap() {
+            Event::MotionNotify(motion) => assert!(motion.state.button1()),
+            other => panic!("Unexpected event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_deserialize_destroy_notify() {
+        let raw = event(b"\x11\x00\x05\x00\x01\x02\x00\x00\x03\x04\x00\x00");
+
+        assert_eq!(
+            Event::deserialize(&raw[..]).unwrap().unwrap(),
+            Event::DestroyNotify(DestroyNotifyEvent {
+                event: 0x201,
+                window: 0x403,
+            })
+        );
+    }
+
+    #[test]
+    fn test_deserialize_unmap_notify() {
+        let raw = event(b"\x12\x00\x05\x00\x01\x02\x00\x00\x03\x04\x00\x00\x01");
+
+        assert_eq!(
+            Event::deserialize(&raw[..]).unwrap().unwrap(),
+            Event::UnmapNotify(UnmapNotifyEvent {
+                event: 0x201,
+                window: 0x403,
+                from_configure: true,
+            })
+        );
+    }
+
+    #[test]
+    fn test_deserialize_map_notify() {
+        let raw = event(b"\x13\x00\x05\x00\x01\x02\x00\x00\x03\x04\x00\x00\x00");
+
+        assert_eq!(
+            Event::deserialize(&raw[..]).unwrap().unwrap(),
+            Event::MapNotify(MapNotifyEvent {
+                event: 0x201,
+                window: 0x403,
+                override_redirect: false,
+            })
+        );
+    }
+
+    #[test]
+    fn test_deserialize_map_request() {
+        let raw = event(b"\x14\x00\x05\x00\x01\x02\x00\x00\x03\x04\x00\x00");
+
+        assert_eq!(
+            Event::deserialize(&raw[..]).unwrap().unwrap(),
+            Event::MapRequest(MapRequestEvent {
+                parent: 0x201,
+                window: 0x403,
+            })
+        );
+    }
+
+    #[test]
+    fn test_deserialize_configure_request() {
+        // Only x, width and stack mode are requested
+        let raw = event(
+            b"\x17\x01\x05\x00\x01\x02\x00\x00\x03\x04\x00\x00\x00\x00\x00\x00\
+              \xf6\xff\x00\x00\x20\x03\x00\x00\x00\x00\x45\x00",
+        );
+
+        let request = match Event::deserialize(&raw[..]).unwrap().unwrap() {
+            Event::ConfigureRequest(request) => request,
+            other => panic!("Unexpected event: {:?}", other),
+        };
+
+        assert_eq!(
+            request,
+            ConfigureRequestEvent {
+                stack_mode: StackMode::Below,
+                parent: 0x201,
+                window: 0x403,
+                sibling: 0,
+                x: -10,
+                y: 0,
+                width: 800,
+                height: 0,
+                border_width: 0,
+                value_mask: ConfigWindowMask::X
+                    | ConfigWindowMask::WIDTH
+                    | ConfigWindowMask::STACK_MODE,
+            }
+        );
+        assert_eq!(
+            request.values(),
+            ConfigureValues::new()
+                .x(-10)
+                .width(800)
+                .stack_mode(StackMode::Below)
+        );
+        assert_eq!(request.values().value_mask(), request.value_mask);
+    }
+
+    #[test]
+    fn test_deserialize_property_notify() {
+        let raw = event(b"\x1c\x00\x05\x00\x01\x02\x00\x00\x27\x00\x00\x00\x10\x27\x00\x00\x01");
+
+        assert_eq!(
+            Event::deserialize(&raw[..]).unwrap().unwrap(),
+            Event::PropertyNotify(PropertyNotifyEvent {
+                window: 0x201,
+                atom: 39,
+                time: 10000,
+                state: PropertyState::Deleted,
+            })
+        );
+    }
+
+    #[test]
+    fn test_deserialize_selection_clear() {
+        let raw = event(b"\x1d\x00\x05\x00\x10\x27\x00\x00\x01\x02\x00\x00\x01\x00\x00\x00");
+
+        assert_eq!(
+            Event::deserialize(&raw[..]).unwrap().unwrap(),
+            Event::SelectionClear(SelectionClearEvent {
+                time: 10000,
+                owner: 0x201,
+                selection: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_deserialize_selection_request() {
+        let raw = event(
+            b"\x1e\x00\x05\x00\x00\x00\x00\x00\x01\x02\x00\x00\x03\x04\x00\x00\
+              \x01\x00\x00\x00\x2a\x01\x00\x00\x2b\x01\x00\x00",
+        );
+
+        assert_eq!(
+            Event::deserialize(&raw[..]).unwrap().unwrap(),
+            Event::SelectionRequest(SelectionRequestEvent {
+                time: 0,
+                owner: 0x201,
+                requestor: 0x403,
+                selection: 1,
+                target: 0x12a,
+                property: 0x12b,
+            })
+        );
+    }
+
+    #[test]
+    fn test_selection_notify_round_trip() {
+        let notify = SelectionNotifyEvent {
+            time: 10000,
+            requestor: 0x403,
+            selection: 1,
+            target: 0x12a,
+            property: 0x12b,
+        };
+        let mut raw = vec![];
+        notify.serialize(&mut raw).unwrap();
+
+        assert_eq!(
+            raw,
+            event(
+                b"\x1f\x00\x00\x00\x10\x27\x00\x00\x03\x04\x00\x00\x01\x00\x00\x00\
+                  \x2a\x01\x00\x00\x2b\x01\x00\x00"
+            )
+        );
+        assert_eq!(
+            Event::deserialize(&raw[..]).unwrap().unwrap(),
+            Event::SelectionNotify(notify)
+        );
+    }
+
+    #[test]
+    fn test_client_message_round_trip() {
+        let message = ClientMessageEvent::new_32(0x201, 0x12a, [1, 2, 0, 0, 0]);
+        let mut raw = vec![];
+        message.serialize(&mut raw).unwrap();
+
+        assert_eq!(
+            raw,
+            event(
+                b"\x21\x20\x00\x00\x01\x02\x00\x00\x2a\x01\x00\x00\
+                  \x01\x00\x00\x00\x02\x00\x00\x00"
+            )
+        );
+        assert_eq!(
+            Event::deserialize(&raw[..]).unwrap().unwrap(),
+            Event::ClientMessage(message)
+        );
+    }
+
+    #[test]
+    fn test_serialize_send_event() {
+        let mut expected = b"\x19\x00\x0b\x00\x00\x01\x00\x00\x00\x00\x18\x00".to_vec();
+        expected.extend(event(b"\x21\x20\x00\x00\x01\x02\x00\x00\x2a\x01\x00\x00"));
+        let mut write_buf = vec![];
+
+        SendEvent {
+            propagate: false,
+            destination: 0x100,
+            event_mask: 0x0018_0000,
+            event: ClientMessageEvent::new_32(0x201, 0x12a, [0; 5]),
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, expected);
+    }
+
+    #[test]
+    fn test_deserialize_sent_event() {
+        let raw = event(b"\x94\x00\x05\x00\x01\x02\x00\x00\x03\x04\x00\x00");
+
+        assert_eq!(
+            Event::deserialize(&raw[..]).unwrap().unwrap(),
+            Event::MapRequest(MapRequestEvent {
+                parent: 0x201,
+                window: 0x403,
+            })
+        );
+    }
+
+    #[test]
+    fn test_deserialize_unknown() {
+        let raw = event(b"\x0c\x00\x05\x00");
+
+        assert_eq!(
+            Event::deserialize(&raw[..]).unwrap().unwrap(),
+            Event::Unknown(raw)
+        );
+    }
+
+    #[test]
+    fn test_deserialize_generic() {
+        let mut raw = event(b"\x23\x8c\x05\x00\x02\x00\x00\x00\x01\x00");
+        raw.extend(b"\x01\x02\x03\x04\x05\x06\x07\x08");
+
+        assert_eq!(
+            Event::deserialize(&raw[..]).unwrap().unwrap(),
+            Event::Generic(GenericEvent {
+                extension: 0x8c,
+                event_type: 1,
+                data: raw.clone(),
+            })
+        );
+
+        // Event is shorter than its length
+        assert!(Event::deserialize(&raw[..36]).is_err());
+    }
+}
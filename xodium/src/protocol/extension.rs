@@ -0,0 +1,211 @@
+use super::str_list::read_str_list;
+use super::{pad, skip, Deserialize, ReplyRequest, Request};
+use crate::io::{self, Read, Write};
+use crate::utils::{ReadBytesExt, WriteBytesExt};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// Check whether extension is supported by X server.
+pub struct QueryExtension {
+    pub name: String,
+}
+
+// 1     98                              opcode
+// 1                                     unused
+// 2     2+(n+p)/4                       request length
+// 2     n                               length of name
+// 2                                     unused
+// n     STRING8                         name
+// p                                     unused, p=pad(n)
+impl Request for QueryExtension {
+    const OPCODE: u8 = 98;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        let name_len = u16::try_from(self.name.len()).map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidInput, "Extension name is too long")
+        })?;
+
+        writer.write_u16_ne(name_len)?;
+        writer.write_u16_ne(0)?; // pad
+        writer.write_all(self.name.as_bytes())?;
+        for _ in 0..pad(self.name.len()) {
+            writer.write_u8(0)?;
+        }
+
+        Ok(0)
+    }
+}
+
+/// Extension supported by X server.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Extension {
+    /// Major opcode of extension requests.
+    pub major_opcode: u8,
+    /// Code of the first extension event, 0 if it has none.
+    pub first_event: u8,
+    /// Code of the first extension error, 0 if it has none.
+    pub first_error: u8,
+}
+
+/// Extension info, `None` if extension is not supported.
+#[derive(Debug, PartialEq)]
+pub struct QueryExtensionReply {
+    pub extension: Option<Extension>,
+}
+
+// 1     1                               Reply
+// 1                                     unused
+// 2     CARD16                          sequence number
+// 4     0                               reply length
+// 1     BOOL                            present
+// 1     CARD8                           major-opcode
+// 1     CARD8                           first-event
+// 1     CARD8                           first-error
+// 20                                    unused
+impl Deserialize for QueryExtensionReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        skip(&mut reader, 8)?; // reply header
+        let present = reader.read_u8()? != 0;
+        let extension = Extension {
+            major_opcode: reader.read_u8()?,
+            first_event: reader.read_u8()?,
+            first_error: reader.read_u8()?,
+        };
+        skip(&mut reader, 20)?;
+
+        Ok(Some(QueryExtensionReply {
+            extension: if present { Some(extension) } else { None },
+        }))
+    }
+}
+
+impl ReplyRequest for QueryExtension {
+    type Reply = QueryExtensionReply;
+}
+
+/// List names of extensions supported by X server.
+pub struct ListExtensions;
+
+// 1     99                              opcode
+// 1                                     unused
+// 2     1                               request length
+impl Request for ListExtensions {
+    const OPCODE: u8 = 99;
+
+    fn serialize_body<W: Write>(&self, _writer: W) -> io::Result<u8> {
+        Ok(0)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ListExtensionsReply {
+    pub names: Vec<String>,
+}
+
+// 1     1                               Reply
+// 1     CARD8                           number of STRs in names
+// 2     CARD16                          sequence number
+// 4     (n+p)/4                         reply length
+// 24                                    unused
+// n     LISTofSTR                       names
+// p                                     unused, p=pad(n)
+impl Deserialize for ListExtensionsReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        reader.read_u8()?; // Reply
+        let names_count = reader.read_u8()?;
+        skip(&mut reader, 30)?;
+        let names = read_str_list(&mut reader, names_count.into())?;
+
+        Ok(Some(ListExtensionsReply { names }))
+    }
+}
+
+impl ReplyRequest for ListExtensions {
+    type Reply = ListExtensionsReply;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Extension, ListExtensions, ListExtensionsReply, QueryExtension, QueryExtensionReply,
+    };
+    use crate::io;
+    use crate::protocol::{Deserialize, Serialize};
+
+    #[test]
+    fn test_serialize_query_extension() {
+        const EXPECTED: &[u8] = b"\x62\x00\x04\x00\x05\x00\x00\x00XTEST\0\0\0";
+        let mut write_buf = vec![];
+
+        QueryExtension {
+            name: "XTEST".into(),
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+
+        let too_long = QueryExtension {
+            name: "a".repeat(0x10000),
+        };
+        let err = too_long.serialize(&mut write_buf).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_deserialize_query_extension_reply() {
+        let mut reply = b"\x01\x00\x01\x00\x00\x00\x00\x00\x01\x84\x00\x00".to_vec();
+        reply.extend(vec![0; 20]);
+
+        assert_eq!(
+            QueryExtensionReply::deserialize(&reply[..])
+                .unwrap()
+                .unwrap(),
+            QueryExtensionReply {
+                extension: Some(Extension {
+                    major_opcode: 132,
+                    first_event: 0,
+                    first_error: 0,
+                })
+            }
+        );
+    }
+
+    #[test]
+    fn test_deserialize_query_extension_reply_absent() {
+        let mut reply = b"\x01\x00\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00".to_vec();
+        reply.extend(vec![0; 20]);
+
+        assert_eq!(
+            QueryExtensionReply::deserialize(&reply[..])
+                .unwrap()
+                .unwrap(),
+            QueryExtensionReply { extension: None }
+        );
+    }
+
+    #[test]
+    fn test_list_extensions() {
+        let mut write_buf = vec![];
+        ListExtensions.serialize(&mut write_buf).unwrap();
+        assert_eq!(write_buf, b"\x63\x00\x01\x00");
+
+        let mut reply = b"\x01\x03\x01\x00\x05\x00\x00\x00".to_vec();
+        reply.extend(vec![0; 24]);
+        reply.extend(b"\x05SHAPE\x05XTEST\x0fXInputExtension\x00\x00\x00");
+
+        assert_eq!(
+            ListExtensionsReply::deserialize(&reply[..])
+                .unwrap()
+                .unwrap(),
+            ListExtensionsReply {
+                names: vec![
+                    "SHAPE".to_string(),
+                    "XTEST".to_string(),
+                    "XInputExtension".to_string()
+                ],
+            }
+        );
+    }
+}
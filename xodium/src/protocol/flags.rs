@@ -0,0 +1,183 @@
+//! Bit flag types of protocol masks, e.g. SETofEVENT.
+
+/// Define newtype over integer with named flags as associated constants,
+/// combined with `|`, `&` and `^`. Unknown bits are kept as they are,
+/// since X server may send bits of newer protocol versions.
+///
+/// ```text
+/// define_flags! {
+///     /// Documentation of type.
+///     pub struct Mask: u16 {
+///         const FIRST = 0x0001;
+///         const SECOND = 0x0002;
+///     }
+/// }
+/// ```
+macro_rules! define_flags {
+    (
+        $(#[$attr:meta])*
+        pub struct $name:ident: $repr:ty {
+            $(
+                $(#[$flag_attr:meta])*
+                const $flag:ident = $value:expr;
+            )*
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+        pub struct $name(pub $repr);
+
+        impl $name {
+            $(
+                $(#[$flag_attr])*
+                pub const $flag: $name = $name($value);
+            )*
+
+            /// No flags set.
+            pub const fn empty() -> $name {
+                $name(0)
+            }
+
+            /// Raw value, as sent over the wire.
+            pub const fn bits(self) -> $repr {
+                self.0
+            }
+
+            pub const fn is_empty(self) -> bool {
+                self.0 == 0
+            }
+
+            /// Whether all flags of `other` are set.
+            pub const fn contains(self, other: $name) -> bool {
+                self.0 & other.0 == other.0
+            }
+
+            /// Whether any flag of `other` is set.
+            pub const fn intersects(self, other: $name) -> bool {
+                self.0 & other.0 != 0
+            }
+        }
+
+        impl core::ops::BitOr for $name {
+            type Output = $name;
+
+            fn bitor(self, other: $name) -> $name {
+                $name(self.0 | other.0)
+            }
+        }
+
+        impl core::ops::BitOrAssign for $name {
+            fn bitor_assign(&mut self, other: $name) {
+                self.0 |= other.0;
+            }
+        }
+
+        impl core::ops::BitAnd for $name {
+            type Output = $name;
+
+            fn bitand(self, other: $name) -> $name {
+                $name(self.0 & other.0)
+            }
+        }
+
+        impl core::ops::BitAndAssign for $name {
+            fn bitand_assign(&mut self, other: $name) {
+                self.0 &= other.0;
+            }
+        }
+
+        impl core::ops::BitXor for $name {
+            type Output = $name;
+
+            fn bitxor(self, other: $name) -> $name {
+                $name(self.0 ^ other.0)
+            }
+        }
+
+        impl core::ops::BitXorAssign for $name {
+            fn bitxor_assign(&mut self, other: $name) {
+                self.0 ^= other.0;
+            }
+        }
+
+        impl From<$repr> for $name {
+            fn from(bits: $repr) -> Self {
+                $name(bits)
+            }
+        }
+
+        impl From<$name> for $repr {
+            fn from(flags: $name) -> Self {
+                flags.0
+            }
+        }
+
+        /// Names of set flags joined by `|`, followed by unknown bits, e.g. `Mask(FIRST | 0x80)`.
+        impl core::fmt::Debug for $name {
+            fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                let mut remaining = self.0;
+                let mut first = true;
+                write!(f, "{}(", stringify!($name))?;
+                $(
+                    let flag: $repr = $value;
+                    if flag != 0 && remaining & flag == flag {
+                        if !first {
+                            f.write_str(" | ")?;
+                        }
+                        f.write_str(stringify!($flag))?;
+                        remaining &= !flag;
+                        first = false;
+                    }
+                )*
+                if remaining != 0 || first {
+                    if !first {
+                        f.write_str(" | ")?;
+                    }
+                    write!(f, "{:#x}", remaining)?;
+                }
+                f.write_str(")")
+            }
+        }
+    };
+}
+
+pub(crate) use define_flags;
+
+#[cfg(test)]
+mod tests {
+    use crate::protocol::{EventMask, ModMask};
+    use alloc::format;
+
+    #[test]
+    fn test_flags_compose() {
+        let mask = EventMask::KEY_PRESS | EventMask::EXPOSURE;
+        assert_eq!(mask.bits(), 0x0000_8001);
+        assert!(mask.contains(EventMask::KEY_PRESS));
+        assert!(mask.contains(EventMask::KEY_PRESS | EventMask::EXPOSURE));
+        assert!(!mask.contains(EventMask::KEY_PRESS | EventMask::KEY_RELEASE));
+        assert!(mask.intersects(EventMask::KEY_PRESS | EventMask::KEY_RELEASE));
+        assert!(!mask.intersects(EventMask::KEY_RELEASE));
+        assert!(mask.contains(EventMask::NO_EVENT));
+
+        assert_eq!(mask & EventMask::EXPOSURE, EventMask::EXPOSURE);
+        assert_eq!(mask ^ EventMask::KEY_PRESS, EventMask::EXPOSURE);
+        assert!((mask & EventMask::KEY_RELEASE).is_empty());
+
+        let mut modifiers = ModMask::empty();
+        modifiers |= ModMask::CONTROL;
+        modifiers ^= ModMask::MOD1;
+        assert_eq!(modifiers, ModMask::from(0x000c));
+        modifiers &= ModMask::MOD1;
+        assert_eq!(u16::from(modifiers), 0x0008);
+    }
+
+    #[test]
+    fn test_flags_debug() {
+        assert_eq!(
+            format!("{:?}", ModMask::CONTROL | ModMask::MOD4),
+            "ModMask(CONTROL | MOD4)"
+        );
+        assert_eq!(format!("{:?}", ModMask(0x0101)), "ModMask(SHIFT | 0x100)");
+        assert_eq!(format!("{:?}", EventMask::NO_EVENT), "EventMask(0x0)");
+    }
+}
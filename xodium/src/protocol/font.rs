@@ -0,0 +1,509 @@
+use super::str_list::{read_str_list, write_str_list};
+use super::{skip, Deserialize, ReplyRequest, Request};
+use crate::io::{self, Read, Write};
+use crate::utils::{ReadBytesExt, WriteBytesExt};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// Load font with name, e.g. `fixed` or XLFD pattern, under id allocated by client.
+pub struct OpenFont {
+    pub fid: u32,
+    pub name: String,
+}
+
+// 1     45                              opcode
+// 1                                     unused
+// 2     3+(n+p)/4                       request length
+// 4     FONT                            fid
+// 2     n                               length of name
+// 2                                     unused
+// n     STRING8                         name
+// p                                     unused, p=pad(n)
+impl Request for OpenFont {
+    const OPCODE: u8 = 45;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        let name_len = u16::try_from(self.name.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Font name is too long"))?;
+
+        writer.write_u32_ne(self.fid)?;
+        writer.write_u16_ne(name_len)?;
+        writer.write_u16_ne(0)?; // pad
+        writer.write_all(self.name.as_bytes())?;
+        Ok(0)
+    }
+}
+
+/// Free font opened by OpenFont.
+pub struct CloseFont {
+    pub font: u32,
+}
+
+// 1     46                              opcode
+// 1                                     unused
+// 2     2                               request length
+// 4     FONT                            font
+impl Request for CloseFont {
+    const OPCODE: u8 = 46;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.font)?;
+        Ok(0)
+    }
+}
+
+/// Query metrics of font or font of GC.
+pub struct QueryFont {
+    pub font: u32,
+}
+
+// 1     47                              opcode
+// 1                                     unused
+// 2     2                               request length
+// 4     FONTABLE                        font
+impl Request for QueryFont {
+    const OPCODE: u8 = 47;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.font)?;
+        Ok(0)
+    }
+}
+
+/// Metrics of single character.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CharInfo {
+    pub left_side_bearing: i16,
+    pub right_side_bearing: i16,
+    /// Horizontal advance of the origin to the next character.
+    pub character_width: i16,
+    pub ascent: i16,
+    pub descent: i16,
+    pub attributes: u16,
+}
+
+// 2     INT16                           left-side-bearing
+// 2     INT16                           right-side-bearing
+// 2     INT16                           character-width
+// 2     INT16                           ascent
+// 2     INT16                           descent
+// 2     CARD16                          attributes
+impl Deserialize for CharInfo {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        Ok(Some(CharInfo {
+            left_side_bearing: reader.read_i16_ne()?,
+            right_side_bearing: reader.read_i16_ne()?,
+            character_width: reader.read_i16_ne()?,
+            ascent: reader.read_i16_ne()?,
+            descent: reader.read_i16_ne()?,
+            attributes: reader.read_u16_ne()?,
+        }))
+    }
+}
+
+/// Font property, e.g. its family name.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FontProp {
+    pub name: u32,
+    /// Value, interpreted according to the property.
+    pub value: u32,
+}
+
+/// Predominant direction of text drawn with the font.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DrawDirection {
+    LeftToRight = 0,
+    RightToLeft = 1,
+}
+
+/// Font metrics.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryFontReply {
+    pub min_bounds: CharInfo,
+    pub max_bounds: CharInfo,
+    /// First character of single byte font, or first byte2 of two byte font.
+    pub min_char_or_byte2: u16,
+    pub max_char_or_byte2: u16,
+    /// Character used in place of characters font has no metrics for.
+    pub default_char: u16,
+    pub draw_direction: DrawDirection,
+    /// Both are 0 for single byte fonts.
+    pub min_byte1: u8,
+    pub max_byte1: u8,
+    pub all_chars_exist: bool,
+    pub font_ascent: i16,
+    pub font_descent: i16,
+    pub properties: Vec<FontProp>,
+    /// Metrics of characters from min to max, empty if all of them equal `max_bounds`.
+    pub char_infos: Vec<CharInfo>,
+}
+
+impl QueryFontReply {
+    /// Metrics of character of single byte font, `default_char` metrics
+    /// for characters font has no metrics for, `None` if there are none either.
+    pub fn char_info(&self, char: u8) -> Option<CharInfo> {
+        self.char_info_in_range(u16::from(char))
+            .or_else(|| self.char_info_in_range(self.default_char))
+    }
+
+    fn char_info_in_range(&self, char: u16) -> Option<CharInfo> {
+        if char < self.min_char_or_byte2 || char > self.max_char_or_byte2 {
+            return None;
+        }
+
+        if self.char_infos.is_empty() {
+            return Some(self.max_bounds);
+        }
+
+        self.char_infos
+            .get(usize::from(char - self.min_char_or_byte2))
+            .copied()
+            // Nonexistent characters have all metrics set to 0
+            .filter(|info| *info != CharInfo::default())
+    }
+}
+
+// 1     1                               Reply
+// 1                                     unused
+// 2     CARD16                          sequence number
+// 4     7+2n+3m                         reply length
+// 12    CHARINFO                        min-bounds
+// 4                                     unused
+// 12    CHARINFO                        max-bounds
+// 4                                     unused
+// 2     CARD16                          min-char-or-byte2
+// 2     CARD16                          max-char-or-byte2
+// 2     CARD16                          default-char
+// 2     n                               number of FONTPROPs in properties
+// 1                                     draw-direction
+//       0     LeftToRight
+//       1     RightToLeft
+// 1     CARD8                           min-byte1
+// 1     CARD8                           max-byte1
+// 1     BOOL                            all-chars-exist
+// 2     INT16                           font-ascent
+// 2     INT16                           font-descent
+// 4     m                               number of CHARINFOs in char-infos
+// 8n    LISTofFONTPROP                  properties
+// 12m   LISTofCHARINFO                  char-infos
+impl Deserialize for QueryFontReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        skip(&mut reader, 8)?; // reply header
+        let min_bounds = CharInfo::deserialize(&mut reader)?;
+        skip(&mut reader, 4)?;
+        let max_bounds = CharInfo::deserialize(&mut reader)?;
+        skip(&mut reader, 4)?;
+        let min_char_or_byte2 = reader.read_u16_ne()?;
+        let max_char_or_byte2 = reader.read_u16_ne()?;
+        let default_char = reader.read_u16_ne()?;
+        let properties_len = reader.read_u16_ne()?;
+        let draw_direction = match reader.read_u8()? {
+            0 => DrawDirection::LeftToRight,
+            1 => DrawDirection::RightToLeft,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Invalid draw direction",
+                ))
+            }
+        };
+        let min_byte1 = reader.read_u8()?;
+        let max_byte1 = reader.read_u8()?;
+        let all_chars_exist = reader.read_u8()? != 0;
+        let font_ascent = reader.read_i16_ne()?;
+        let font_descent = reader.read_i16_ne()?;
+        let char_infos_len = reader.read_u32_ne()?;
+
+        let properties = (0..properties_len)
+            .map(|_| {
+                Ok(FontProp {
+                    name: reader.read_u32_ne()?,
+                    value: reader.read_u32_ne()?,
+                })
+            })
+            .collect::<io::Result<_>>()?;
+
+        let mut char_infos = vec![];
+        for _ in 0..char_infos_len {
+            char_infos.extend(CharInfo::deserialize(&mut reader)?);
+        }
+
+        match (min_bounds, max_bounds) {
+            (Some(min_bounds), Some(max_bounds)) => Ok(Some(QueryFontReply {
+                min_bounds,
+                max_bounds,
+                min_char_or_byte2,
+                max_char_or_byte2,
+                default_char,
+                draw_direction,
+                min_byte1,
+                max_byte1,
+                all_chars_exist,
+                font_ascent,
+                font_descent,
+                properties,
+                char_infos,
+            })),
+            _ => Ok(None),
+        }
+    }
+}
+
+impl ReplyRequest for QueryFont {
+    type Reply = QueryFontReply;
+}
+
+/// List names of available fonts matching pattern,
+/// e.g. `*-iso10646-1`, where `*` matches any characters and `?` any single one.
+pub struct ListFonts {
+    pub max_names: u16,
+    pub pattern: String,
+}
+
+// 1     49                              opcode
+// 1                                     unused
+// 2     2+(n+p)/4                       request length
+// 2     CARD16                          max-names
+// 2     n                               length of pattern
+// n     STRING8                         pattern
+// p                                     unused, p=pad(n)
+impl Request for ListFonts {
+    const OPCODE: u8 = 49;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        let pattern_len = u16::try_from(self.pattern.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Font pattern is too long"))?;
+
+        writer.write_u16_ne(self.max_names)?;
+        writer.write_u16_ne(pattern_len)?;
+        writer.write_all(self.pattern.as_bytes())?;
+        Ok(0)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ListFontsReply {
+    pub names: Vec<String>,
+}
+
+// 1     1                               Reply
+// 1                                     unused
+// 2     CARD16                          sequence number
+// 4     (n+p)/4                         reply length
+// 2     CARD16                          number of STRs in names
+// 22                                    unused
+// n     LISTofSTR                       names
+// p                                     unused, p=pad(n)
+impl Deserialize for ListFontsReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        skip(&mut reader, 8)?; // reply header
+        let names_count = reader.read_u16_ne()?;
+        skip(&mut reader, 22)?;
+        let names = read_str_list(&mut reader, names_count.into())?;
+
+        Ok(Some(ListFontsReply { names }))
+    }
+}
+
+impl ReplyRequest for ListFonts {
+    type Reply = ListFontsReply;
+}
+
+/// Set directories and font servers fonts are looked up in,
+/// empty path restores the default one.
+pub struct SetFontPath {
+    pub paths: Vec<String>,
+}
+
+// 1     51                              opcode
+// 1                                     unused
+// 2     2+(n+p)/4                       request length
+// 2     CARD16                          number of STRs in path
+// 2                                     unused
+// n     LISTofSTR                       path
+// p                                     unused, p=pad(n)
+impl Request for SetFontPath {
+    const OPCODE: u8 = 51;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        let paths_count = u16::try_from(self.paths.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Too many font paths"))?;
+
+        writer.write_u16_ne(paths_count)?;
+        writer.write_u16_ne(0)?; // pad
+        write_str_list(&mut writer, &self.paths)?;
+        Ok(0)
+    }
+}
+
+/// Get directories and font servers fonts are looked up in.
+pub struct GetFontPath;
+
+// 1     52                              opcode
+// 1                                     unused
+// 2     1                               request length
+impl Request for GetFontPath {
+    const OPCODE: u8 = 52;
+
+    fn serialize_body<W: Write>(&self, _writer: W) -> io::Result<u8> {
+        Ok(0)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct GetFontPathReply {
+    pub paths: Vec<String>,
+}
+
+// 1     1                               Reply
+// 1                                     unused
+// 2     CARD16                          sequence number
+// 4     (n+p)/4                         reply length
+// 2     CARD16                          number of STRs in path
+// 22                                    unused
+// n     LISTofSTR                       path
+// p                                     unused, p=pad(n)
+impl Deserialize for GetFontPathReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        skip(&mut reader, 8)?; // reply header
+        let paths_count = reader.read_u16_ne()?;
+        skip(&mut reader, 22)?;
+        let paths = read_str_list(&mut reader, paths_count.into())?;
+
+        Ok(Some(GetFontPathReply { paths }))
+    }
+}
+
+impl ReplyRequest for GetFontPath {
+    type Reply = GetFontPathReply;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CharInfo, DrawDirection, GetFontPath, GetFontPathReply, ListFonts, ListFontsReply,
+        OpenFont, QueryFontReply, SetFontPath,
+    };
+    use crate::io;
+    use crate::protocol::{Deserialize, Serialize};
+    use crate::test_utils::query_font_reply;
+
+    #[test]
+    fn test_serialize_open_font() {
+        const EXPECTED: &[u8] =
+            b"\x2d\x00\x05\x00\x01\x00\x00\x04\x05\x00\x00\x00fixed\x00\x00\x00";
+        let mut write_buf = vec![];
+
+        OpenFont {
+            fid: 0x04000001,
+            name: "fixed".into(),
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+
+        let too_long = OpenFont {
+            fid: 0x04000001,
+            name: "a".repeat(0x10000),
+        };
+        let err = too_long.serialize(&mut write_buf).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_deserialize_query_font_reply() {
+        let reply = QueryFontReply::deserialize(&query_font_reply()[..])
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(reply.max_bounds.character_width, 7);
+        assert_eq!(reply.draw_direction, DrawDirection::LeftToRight);
+        assert_eq!((reply.font_ascent, reply.font_descent), (10, 2));
+        assert_eq!(reply.properties.len(), 1);
+        assert_eq!(reply.properties[0].value, 12);
+        assert_eq!(reply.char_infos.len(), 3);
+
+        assert_eq!(reply.char_info(b'!').unwrap().character_width, 7);
+        // Nonexistent and out of range characters use default one
+        assert_eq!(reply.char_info(b'"').unwrap().character_width, 5);
+        assert_eq!(reply.char_info(b'z').unwrap().character_width, 5);
+        assert_eq!(reply.char_infos[2], CharInfo::default());
+    }
+
+    #[test]
+    fn test_serialize_list_fonts() {
+        const EXPECTED: &[u8] = b"\x31\x00\x05\x00\x0a\x00\x0b\x00*-iso8859-1\x00";
+        let mut write_buf = vec![];
+
+        ListFonts {
+            max_names: 10,
+            pattern: "*-iso8859-1".into(),
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_deserialize_list_fonts_reply() {
+        let mut reply = b"\x01\x00\x01\x00\x03\x00\x00\x00\x02\x00".to_vec();
+        reply.extend(vec![0; 22]);
+        reply.extend(b"\x05fixed\x046x13\x00");
+
+        assert_eq!(
+            ListFontsReply::deserialize(&reply[..]).unwrap().unwrap(),
+            ListFontsReply {
+                names: vec!["fixed".to_string(), "6x13".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn test_serialize_get_font_path() {
+        let mut write_buf = vec![];
+        GetFontPath.serialize(&mut write_buf).unwrap();
+        assert_eq!(write_buf, b"\x34\x00\x01\x00");
+    }
+
+    #[test]
+    fn test_set_font_path_round_trip() {
+        const EXPECTED: &[u8] = b"\x33\x00\x09\x00\x02\x00\x00\x00\
+                                  \x09built-ins\x10/usr/share/fonts\x00";
+        let paths = vec!["built-ins".to_string(), "/usr/share/fonts".to_string()];
+        let mut write_buf = vec![];
+
+        SetFontPath {
+            paths: paths.clone(),
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+        assert_eq!(write_buf, EXPECTED);
+
+        // Reply carries the same LISTofSTR as request
+        let mut reply = b"\x01\x00\x01\x00\x07\x00\x00\x00\x02\x00".to_vec();
+        reply.extend(vec![0; 22]);
+        reply.extend(&write_buf[8..]);
+
+        assert_eq!(
+            GetFontPathReply::deserialize(&reply[..]).unwrap().unwrap(),
+            GetFontPathReply { paths }
+        );
+    }
+
+    #[test]
+    fn test_deserialize_get_font_path_reply() {
+        let mut reply = b"\x01\x00\x01\x00\x06\x00\x00\x00\x02\x00".to_vec();
+        reply.extend(vec![0; 22]);
+        reply.extend(b"\x04misc\x0fcatalogue:/etc/\x00\x00\x00");
+
+        assert_eq!(
+            GetFontPathReply::deserialize(&reply[..]).unwrap().unwrap(),
+            GetFontPathReply {
+                paths: vec!["misc".to_string(), "catalogue:/etc/".to_string()],
+            }
+        );
+    }
+}
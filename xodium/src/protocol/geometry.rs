@@ -0,0 +1,191 @@
+use super::{Deserialize, Serialize};
+use crate::io::{self, Read, Write};
+use crate::utils::{ReadBytesExt, WriteBytesExt};
+
+/// Point used by drawing requests.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Point {
+    pub x: i16,
+    pub y: i16,
+}
+
+impl From<(i16, i16)> for Point {
+    fn from((x, y): (i16, i16)) -> Self {
+        Point { x, y }
+    }
+}
+
+// 2     INT16                           x
+// 2     INT16                           y
+impl Serialize for Point {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_i16_ne(self.x)?;
+        writer.write_i16_ne(self.y)?;
+        Ok(())
+    }
+}
+
+/// Rectangle with top left corner at `x`, `y`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Rectangle {
+    pub x: i16,
+    pub y: i16,
+    pub width: u16,
+    pub height: u16,
+}
+
+impl From<(i16, i16, u16, u16)> for Rectangle {
+    fn from((x, y, width, height): (i16, i16, u16, u16)) -> Self {
+        Rectangle {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+}
+
+// 2     INT16                           x
+// 2     INT16                           y
+// 2     CARD16                          width
+// 2     CARD16                          height
+impl Serialize for Rectangle {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_i16_ne(self.x)?;
+        writer.write_i16_ne(self.y)?;
+        writer.write_u16_ne(self.width)?;
+        writer.write_u16_ne(self.height)?;
+        Ok(())
+    }
+}
+
+impl Deserialize for Rectangle {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        Ok(Some(Rectangle {
+            x: reader.read_i16_ne()?,
+            y: reader.read_i16_ne()?,
+            width: reader.read_u16_ne()?,
+            height: reader.read_u16_ne()?,
+        }))
+    }
+}
+
+/// Arc of ellipse inscribed into rectangle.
+/// Angles are specified in degrees scaled by 64,
+/// `angle1` is relative to three o'clock and `angle2` relative to `angle1`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Arc {
+    pub x: i16,
+    pub y: i16,
+    pub width: u16,
+    pub height: u16,
+    pub angle1: i16,
+    pub angle2: i16,
+}
+
+// 2     INT16                           x
+// 2     INT16                           y
+// 2     CARD16                          width
+// 2     CARD16                          height
+// 2     INT16                           angle1
+// 2     INT16                           angle2
+impl Serialize for Arc {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_i16_ne(self.x)?;
+        writer.write_i16_ne(self.y)?;
+        writer.write_u16_ne(self.width)?;
+        writer.write_u16_ne(self.height)?;
+        writer.write_i16_ne(self.angle1)?;
+        writer.write_i16_ne(self.angle2)?;
+        Ok(())
+    }
+}
+
+/// Line segment between two points.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Segment {
+    pub x1: i16,
+    pub y1: i16,
+    pub x2: i16,
+    pub y2: i16,
+}
+
+impl From<(Point, Point)> for Segment {
+    fn from((start, end): (Point, Point)) -> Self {
+        Segment {
+            x1: start.x,
+            y1: start.y,
+            x2: end.x,
+            y2: end.y,
+        }
+    }
+}
+
+// 2     INT16                           x1
+// 2     INT16                           y1
+// 2     INT16                           x2
+// 2     INT16                           y2
+impl Serialize for Segment {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_i16_ne(self.x1)?;
+        writer.write_i16_ne(self.y1)?;
+        writer.write_i16_ne(self.x2)?;
+        writer.write_i16_ne(self.y2)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Arc, Point, Rectangle, Segment};
+    use crate::protocol::Serialize;
+
+    fn serialize<T: Serialize>(item: T) -> Vec<u8> {
+        let mut write_buf = vec![];
+        item.serialize(&mut write_buf).unwrap();
+        write_buf
+    }
+
+    #[test]
+    fn test_serialize_point() {
+        assert_eq!(
+            serialize(Point::from((-1, 2))),
+            b"\xff\xff\x02\x00".to_vec()
+        );
+    }
+
+    #[test]
+    fn test_serialize_rectangle() {
+        assert_eq!(
+            serialize(Rectangle::from((-1, 2, 300, 4))),
+            b"\xff\xff\x02\x00\x2c\x01\x04\x00".to_vec()
+        );
+    }
+
+    #[test]
+    fn test_serialize_arc() {
+        let arc = Arc {
+            x: 1,
+            y: 2,
+            width: 3,
+            height: 4,
+            angle1: 0,
+            angle2: 360 * 64,
+        };
+
+        assert_eq!(
+            serialize(arc),
+            b"\x01\x00\x02\x00\x03\x00\x04\x00\x00\x00\x00\x5a".to_vec()
+        );
+    }
+
+    #[test]
+    fn test_serialize_segment() {
+        let segment = Segment::from((Point::from((1, 2)), Point::from((-3, 4))));
+
+        assert_eq!(
+            serialize(segment),
+            b"\x01\x00\x02\x00\xfd\xff\x04\x00".to_vec()
+        );
+    }
+}
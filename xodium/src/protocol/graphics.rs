@@ -0,0 +1,993 @@
+use super::flags::define_flags;
+use super::{
+    skip, Deserialize, Point, Rectangle, RectangleOrdering, ReplyRequest, Request, Segment,
+    Serialize,
+};
+use crate::io::{self, Read, Write};
+use crate::utils::{ReadBytesExt, WriteBytesExt};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+define_flags! {
+    /// Components of GC set by CreateGC and ChangeGC or copied by CopyGC.
+    pub struct GcValueMask: u32 {
+        const FUNCTION = 0x0000_0001;
+        const PLANE_MASK = 0x0000_0002;
+        const FOREGROUND = 0x0000_0004;
+        const BACKGROUND = 0x0000_0008;
+        const LINE_WIDTH = 0x0000_0010;
+        const LINE_STYLE = 0x0000_0020;
+        const CAP_STYLE = 0x0000_0040;
+        const JOIN_STYLE = 0x0000_0080;
+        const FILL_STYLE = 0x0000_0100;
+        const FILL_RULE = 0x0000_0200;
+        const TILE = 0x0000_0400;
+        const STIPPLE = 0x0000_0800;
+        const TILE_STIPPLE_X_ORIGIN = 0x0000_1000;
+        const TILE_STIPPLE_Y_ORIGIN = 0x0000_2000;
+        const FONT = 0x0000_4000;
+        const SUBWINDOW_MODE = 0x0000_8000;
+        const GRAPHICS_EXPOSURES = 0x0001_0000;
+        const CLIP_X_ORIGIN = 0x0002_0000;
+        const CLIP_Y_ORIGIN = 0x0004_0000;
+        const CLIP_MASK = 0x0008_0000;
+        const DASH_OFFSET = 0x0010_0000;
+        const DASHES = 0x0020_0000;
+        const ARC_MODE = 0x0040_0000;
+    }
+}
+
+/// Copy components selected by `value_mask` from one GC to another
+/// of the same root and depth.
+pub struct CopyGC {
+    pub src_gc: u32,
+    pub dst_gc: u32,
+    pub value_mask: GcValueMask,
+}
+
+// 1     57                              opcode
+// 1                                     unused
+// 2     4                               request length
+// 4     GCONTEXT                        src-gc
+// 4     GCONTEXT                        dst-gc
+// 4     BITMASK                         value-mask
+impl Request for CopyGC {
+    const OPCODE: u8 = 57;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.src_gc)?;
+        writer.write_u32_ne(self.dst_gc)?;
+        writer.write_u32_ne(self.value_mask.bits())?;
+        Ok(0)
+    }
+}
+
+/// Set GC dash pattern of lines, lengths of dashes and gaps between them alternate.
+pub struct SetDashes {
+    pub gc: u32,
+    /// Pixel of the pattern the line starts with.
+    pub dash_offset: u16,
+    /// Non-zero lengths in pixels, odd number of them is repeated twice.
+    pub dashes: Vec<u8>,
+}
+
+// 1     58                              opcode
+// 1                                     unused
+// 2     3+(n+p)/4                       request length
+// 4     GCONTEXT                        gc
+// 2     CARD16                          dash-offset
+// 2     n                               length of dashes
+// n     LISTofCARD8                     dashes
+// p                                     unused, p=pad(n)
+impl Request for SetDashes {
+    const OPCODE: u8 = 58;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        if self.dashes.is_empty() || self.dashes.contains(&0) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Dashes have to be non-empty and non-zero",
+            ));
+        }
+        let dashes_len = u16::try_from(self.dashes.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Too many dashes"))?;
+
+        writer.write_u32_ne(self.gc)?;
+        writer.write_u16_ne(self.dash_offset)?;
+        writer.write_u16_ne(dashes_len)?;
+        writer.write_all(&self.dashes)?;
+        Ok(0)
+    }
+}
+
+/// Restrict drawing with GC to rectangles, relative to clip origin.
+/// Empty list disables all output.
+pub struct SetClipRectangles {
+    pub ordering: RectangleOrdering,
+    pub gc: u32,
+    pub clip_x_origin: i16,
+    pub clip_y_origin: i16,
+    pub rectangles: Vec<Rectangle>,
+}
+
+// 1     59                              opcode
+// 1                                     ordering
+//       0     UnSorted
+//       1     YSorted
+//       2     YXSorted
+//       3     YXBanded
+// 2     3+2n                            request length
+// 4     GCONTEXT                        gc
+// 2     INT16                           clip-x-origin
+// 2     INT16                           clip-y-origin
+// 8n    LISTofRECTANGLE                 rectangles
+impl Request for SetClipRectangles {
+    const OPCODE: u8 = 59;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.gc)?;
+        writer.write_i16_ne(self.clip_x_origin)?;
+        writer.write_i16_ne(self.clip_y_origin)?;
+        for rectangle in &self.rectangles {
+            rectangle.serialize(&mut writer)?;
+        }
+        Ok(self.ordering as u8)
+    }
+}
+
+/// Copy area of drawable to another drawable of the same root and depth.
+pub struct CopyArea {
+    pub src_drawable: u32,
+    pub dst_drawable: u32,
+    pub gc: u32,
+    pub src_x: i16,
+    pub src_y: i16,
+    pub dst_x: i16,
+    pub dst_y: i16,
+    pub width: u16,
+    pub height: u16,
+}
+
+// 1     62                              opcode
+// 1                                     unused
+// 2     7                               request length
+// 4     DRAWABLE                        src-drawable
+// 4     DRAWABLE                        dst-drawable
+// 4     GCONTEXT                        gc
+// 2     INT16                           src-x
+// 2     INT16                           src-y
+// 2     INT16                           dst-x
+// 2     INT16                           dst-y
+// 2     CARD16                          width
+// 2     CARD16                          height
+impl Request for CopyArea {
+    const OPCODE: u8 = 62;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.src_drawable)?;
+        writer.write_u32_ne(self.dst_drawable)?;
+        writer.write_u32_ne(self.gc)?;
+        writer.write_i16_ne(self.src_x)?;
+        writer.write_i16_ne(self.src_y)?;
+        writer.write_i16_ne(self.dst_x)?;
+        writer.write_i16_ne(self.dst_y)?;
+        writer.write_u16_ne(self.width)?;
+        writer.write_u16_ne(self.height)?;
+        Ok(0)
+    }
+}
+
+/// Copy single bit plane of drawable to another drawable of the same root,
+/// set bits are drawn with GC foreground and unset ones with background.
+pub struct CopyPlane {
+    pub src_drawable: u32,
+    pub dst_drawable: u32,
+    pub gc: u32,
+    pub src_x: i16,
+    pub src_y: i16,
+    pub dst_x: i16,
+    pub dst_y: i16,
+    pub width: u16,
+    pub height: u16,
+    /// Plane to copy, exactly one bit has to be set.
+    pub bit_plane: u32,
+}
+
+// 1     63                              opcode
+// 1                                     unused
+// 2     8                               request length
+// 4     DRAWABLE                        src-drawable
+// 4     DRAWABLE                        dst-drawable
+// 4     GCONTEXT                        gc
+// 2     INT16                           src-x
+// 2     INT16                           src-y
+// 2     INT16                           dst-x
+// 2     INT16                           dst-y
+// 2     CARD16                          width
+// 2     CARD16                          height
+// 4     CARD32                          bit-plane
+impl Request for CopyPlane {
+    const OPCODE: u8 = 63;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.src_drawable)?;
+        writer.write_u32_ne(self.dst_drawable)?;
+        writer.write_u32_ne(self.gc)?;
+        writer.write_i16_ne(self.src_x)?;
+        writer.write_i16_ne(self.src_y)?;
+        writer.write_i16_ne(self.dst_x)?;
+        writer.write_i16_ne(self.dst_y)?;
+        writer.write_u16_ne(self.width)?;
+        writer.write_u16_ne(self.height)?;
+        writer.write_u32_ne(self.bit_plane)?;
+        Ok(0)
+    }
+}
+
+/// How coordinates of points are interpreted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CoordinateMode {
+    /// Relative to drawable origin.
+    Origin = 0,
+    /// Relative to the previous point, the first one relative to drawable origin.
+    Previous = 1,
+}
+
+/// Draw points using GC foreground.
+pub struct PolyPoint {
+    pub coordinate_mode: CoordinateMode,
+    pub drawable: u32,
+    pub gc: u32,
+    pub points: Vec<Point>,
+}
+
+// 1     64                              opcode
+// 1                                     coordinate-mode
+//       0     Origin
+//       1     Previous
+// 2     3+n                             request length
+// 4     DRAWABLE                        drawable
+// 4     GCONTEXT                        gc
+// 4n    LISTofPOINT                     points
+impl Request for PolyPoint {
+    const OPCODE: u8 = 64;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.drawable)?;
+        writer.write_u32_ne(self.gc)?;
+        for point in &self.points {
+            point.serialize(&mut writer)?;
+        }
+        Ok(self.coordinate_mode as u8)
+    }
+}
+
+/// Draw unconnected lines.
+pub struct PolySegment {
+    pub drawable: u32,
+    pub gc: u32,
+    pub segments: Vec<Segment>,
+}
+
+// 1     66                              opcode
+// 1                                     unused
+// 2     3+2n                            request length
+// 4     DRAWABLE                        drawable
+// 4     GCONTEXT                        gc
+// 8n    LISTofSEGMENT                   segments
+impl Request for PolySegment {
+    const OPCODE: u8 = 66;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.drawable)?;
+        writer.write_u32_ne(self.gc)?;
+        for segment in &self.segments {
+            segment.serialize(&mut writer)?;
+        }
+        Ok(0)
+    }
+}
+
+/// Draw outlines of rectangles.
+pub struct PolyRectangle {
+    pub drawable: u32,
+    pub gc: u32,
+    pub rectangles: Vec<Rectangle>,
+}
+
+// 1     67                              opcode
+// 1                                     unused
+// 2     3+2n                            request length
+// 4     DRAWABLE                        drawable
+// 4     GCONTEXT                        gc
+// 8n    LISTofRECTANGLE                 rectangles
+impl Request for PolyRectangle {
+    const OPCODE: u8 = 67;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.drawable)?;
+        writer.write_u32_ne(self.gc)?;
+        for rectangle in &self.rectangles {
+            rectangle.serialize(&mut writer)?;
+        }
+        Ok(0)
+    }
+}
+
+/// Draw string of single byte characters with GC font over rectangle
+/// filled with GC background, `x` and `y` are origin of the first character.
+pub struct ImageText8 {
+    pub drawable: u32,
+    pub gc: u32,
+    pub x: i16,
+    pub y: i16,
+    /// Up to 255 bytes.
+    pub string: Vec<u8>,
+}
+
+// 1     76                              opcode
+// 1     n                               length of string
+// 2     4+(n+p)/4                       request length
+// 4     DRAWABLE                        drawable
+// 4     GCONTEXT                        gc
+// 2     INT16                           x
+// 2     INT16                           y
+// n     STRING8                         string
+// p                                     unused, p=pad(n)
+impl Request for ImageText8 {
+    const OPCODE: u8 = 76;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        let string_len = self.string.len();
+        if string_len > usize::from(u8::MAX) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "String is longer than 255 bytes",
+            ));
+        }
+
+        writer.write_u32_ne(self.drawable)?;
+        writer.write_u32_ne(self.gc)?;
+        writer.write_i16_ne(self.x)?;
+        writer.write_i16_ne(self.y)?;
+        writer.write_all(&self.string)?;
+        Ok(string_len as u8)
+    }
+}
+
+/// Item of PolyText8 request, either string drawn with GC font or font switch
+/// for the following items.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextItem8 {
+    /// String of up to 254 bytes, drawn after moving by `delta` pixels.
+    Text { delta: i8, string: Vec<u8> },
+    /// Font used for the rest of items, it is also stored into GC.
+    Font(u32),
+}
+
+/// Marker of font switch in place of text item string length.
+const FONT_SHIFT: u8 = 255;
+
+// 1     m                               length of string (cannot be 255)
+// 1     INT8                            delta
+// m     STRING8                         string
+// or
+// 1     255                             font-shift indicator
+// 1                                     font byte 3 (most-significant)
+// 1                                     font byte 2
+// 1                                     font byte 1
+// 1                                     font byte 0 (least-significant)
+impl Serialize for TextItem8 {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        match self {
+            TextItem8::Text { delta, string } => {
+                if string.len() >= usize::from(FONT_SHIFT) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "Text item is longer than 254 bytes",
+                    ));
+                }
+
+                writer.write_u8(string.len() as u8)?;
+                writer.write_i8(*delta)?;
+                writer.write_all(string)?;
+            }
+            TextItem8::Font(font) => {
+                writer.write_u8(FONT_SHIFT)?;
+                writer.write_all(&font.to_be_bytes())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Draw text items with GC foreground only, unlike [ImageText8]
+/// fonts may be switched in the middle. `x` and `y` are origin of the first character.
+pub struct PolyText8 {
+    pub drawable: u32,
+    pub gc: u32,
+    pub x: i16,
+    pub y: i16,
+    pub items: Vec<TextItem8>,
+}
+
+// 1     74                              opcode
+// 1                                     unused
+// 2     4+(n+p)/4                       request length
+// 4     DRAWABLE                        drawable
+// 4     GCONTEXT                        gc
+// 2     INT16                           x
+// 2     INT16                           y
+// n     LISTofTEXTITEM8                 items
+// p                                     unused, p=pad(n)
+impl Request for PolyText8 {
+    const OPCODE: u8 = 74;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.drawable)?;
+        writer.write_u32_ne(self.gc)?;
+        writer.write_i16_ne(self.x)?;
+        writer.write_i16_ne(self.y)?;
+        for item in &self.items {
+            item.serialize(&mut writer)?;
+        }
+        Ok(0)
+    }
+}
+
+/// Draw string of two byte characters, e.g. of fonts indexed by Unicode
+/// (`iso10646-1`), like [ImageText8].
+pub struct ImageText16 {
+    pub drawable: u32,
+    pub gc: u32,
+    pub x: i16,
+    pub y: i16,
+    /// Up to 255 characters, each with its first byte (byte1) in the high bits.
+    pub text: Vec<u16>,
+}
+
+// 1     77                              opcode
+// 1     n                               number of CHAR2Bs in string
+// 2     4+(2n+p)/4                      request length
+// 4     DRAWABLE                        drawable
+// 4     GCONTEXT                        gc
+// 2     INT16                           x
+// 2     INT16                           y
+// 2n    STRING16                        string
+// p                                     unused, p=pad(2n)
+impl Request for ImageText16 {
+    const OPCODE: u8 = 77;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        let text_len = self.text.len();
+        if text_len > usize::from(u8::MAX) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "String is longer than 255 characters",
+            ));
+        }
+
+        writer.write_u32_ne(self.drawable)?;
+        writer.write_u32_ne(self.gc)?;
+        writer.write_i16_ne(self.x)?;
+        writer.write_i16_ne(self.y)?;
+        write_string16(&mut writer, &self.text)?;
+        Ok(text_len as u8)
+    }
+}
+
+/// CHAR2B is byte1 followed by byte2 regardless of connection byte order.
+fn write_string16<W: Write>(mut writer: W, text: &[u16]) -> io::Result<()> {
+    for &char in text {
+        writer.write_u16_be(char)?;
+    }
+    Ok(())
+}
+
+/// Item of PolyText16 request, like [TextItem8] with two byte characters.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextItem16 {
+    /// String of up to 254 characters, drawn after moving by `delta` pixels.
+    Text { delta: i8, text: Vec<u16> },
+    /// Font used for the rest of items, it is also stored into GC.
+    Font(u32),
+}
+
+// 1     m                               number of CHAR2Bs in string (cannot be 255)
+// 1     INT8                            delta
+// 2m    STRING16                        string
+// or
+// 1     255                             font-shift indicator
+// 1                                     font byte 3 (most-significant)
+// 1                                     font byte 2
+// 1                                     font byte 1
+// 1                                     font byte 0 (least-significant)
+impl Serialize for TextItem16 {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        match self {
+            TextItem16::Text { delta, text } => {
+                if text.len() >= usize::from(FONT_SHIFT) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "Text item is longer than 254 characters",
+                    ));
+                }
+
+                writer.write_u8(text.len() as u8)?;
+                writer.write_i8(*delta)?;
+                write_string16(&mut writer, text)?;
+            }
+            TextItem16::Font(font) => {
+                writer.write_u8(FONT_SHIFT)?;
+                writer.write_all(&font.to_be_bytes())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Draw text items of two byte characters with GC foreground, like [PolyText8].
+pub struct PolyText16 {
+    pub drawable: u32,
+    pub gc: u32,
+    pub x: i16,
+    pub y: i16,
+    pub items: Vec<TextItem16>,
+}
+
+// 1     75                              opcode
+// 1                                     unused
+// 2     4+(n+p)/4                       request length
+// 4     DRAWABLE                        drawable
+// 4     GCONTEXT                        gc
+// 2     INT16                           x
+// 2     INT16                           y
+// n     LISTofTEXTITEM16                items
+// p                                     unused, p=pad(n)
+impl Request for PolyText16 {
+    const OPCODE: u8 = 75;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.drawable)?;
+        writer.write_u32_ne(self.gc)?;
+        writer.write_i16_ne(self.x)?;
+        writer.write_i16_ne(self.y)?;
+        for item in &self.items {
+            item.serialize(&mut writer)?;
+        }
+        Ok(0)
+    }
+}
+
+/// What size is queried for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SizeClass {
+    /// Largest cursor size which can be fully displayed.
+    Cursor = 0,
+    /// Size which can be tiled fastest.
+    Tile = 1,
+    /// Size which can be stippled fastest.
+    Stipple = 2,
+}
+
+/// Query size closest to the requested one supported by X server.
+pub struct QueryBestSize {
+    pub class: SizeClass,
+    /// Any drawable on the screen size is queried for.
+    pub drawable: u32,
+    pub width: u16,
+    pub height: u16,
+}
+
+// 1     97                              opcode
+// 1                                     class
+//       0     Cursor
+//       1     Tile
+//       2     Stipple
+// 2     3                               request length
+// 4     DRAWABLE                        drawable
+// 2     CARD16                          width
+// 2     CARD16                          height
+impl Request for QueryBestSize {
+    const OPCODE: u8 = 97;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.drawable)?;
+        writer.write_u16_ne(self.width)?;
+        writer.write_u16_ne(self.height)?;
+        Ok(self.class as u8)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct QueryBestSizeReply {
+    pub width: u16,
+    pub height: u16,
+}
+
+// 1     1                               Reply
+// 1                                     unused
+// 2     CARD16                          sequence number
+// 4     0                               reply length
+// 2     CARD16                          width
+// 2     CARD16                          height
+// 20                                    unused
+impl Deserialize for QueryBestSizeReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        skip(&mut reader, 8)?; // reply header
+        let width = reader.read_u16_ne()?;
+        let height = reader.read_u16_ne()?;
+        skip(&mut reader, 20)?;
+
+        Ok(Some(QueryBestSizeReply { width, height }))
+    }
+}
+
+impl ReplyRequest for QueryBestSize {
+    type Reply = QueryBestSizeReply;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CoordinateMode, CopyArea, CopyGC, CopyPlane, GcValueMask, ImageText16, ImageText8,
+        PolyPoint, PolyRectangle, PolySegment, PolyText16, PolyText8, QueryBestSize,
+        QueryBestSizeReply, SetClipRectangles, SetDashes, SizeClass, TextItem16, TextItem8,
+    };
+    use crate::io;
+    use crate::protocol::{Deserialize, Point, Rectangle, RectangleOrdering, Segment, Serialize};
+
+    #[test]
+    fn test_serialize_copy_gc() {
+        const EXPECTED: &[u8] = b"\x39\x00\x04\x00\x01\x00\x00\x04\x02\x00\x00\x04\x0c\x40\x00\x00";
+        let mut write_buf = vec![];
+
+        CopyGC {
+            src_gc: 0x04000001,
+            dst_gc: 0x04000002,
+            value_mask: GcValueMask::FOREGROUND | GcValueMask::BACKGROUND | GcValueMask::FONT,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_serialize_set_dashes() {
+        const EXPECTED: &[u8] = b"\x3a\x00\x04\x00\x01\x00\x00\x04\x02\x00\x03\x00\x04\x02\x01\x00";
+        let mut write_buf = vec![];
+
+        SetDashes {
+            gc: 0x04000001,
+            dash_offset: 2,
+            dashes: vec![4, 2, 1],
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+        assert_eq!(write_buf.len(), 4 * 4);
+    }
+
+    #[test]
+    fn test_serialize_set_dashes_zero() {
+        let err = SetDashes {
+            gc: 0x04000001,
+            dash_offset: 0,
+            dashes: vec![4, 0],
+        }
+        .serialize(vec![])
+        .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_serialize_set_clip_rectangles() {
+        const EXPECTED: &[u8] = b"\x3b\x03\x07\x00\x01\x00\x00\x04\x0a\x00\xfb\xff\
+                                  \x00\x00\x00\x00\x10\x00\x08\x00\x00\x00\x08\x00\x20\x00\x04\x00";
+        let mut write_buf = vec![];
+
+        SetClipRectangles {
+            ordering: RectangleOrdering::YXBanded,
+            gc: 0x04000001,
+            clip_x_origin: 10,
+            clip_y_origin: -5,
+            rectangles: vec![
+                Rectangle::from((0, 0, 16, 8)),
+                Rectangle::from((0, 8, 32, 4)),
+            ],
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_serialize_copy_area() {
+        const EXPECTED: &[u8] = b"\x3e\x00\x07\x00\x01\x00\x00\x04\x01\x02\x00\x00\
+                                  \x02\x00\x00\x04\x00\x00\x00\x00\x0a\x00\x14\x00\
+                                  \x20\x00\x10\x00";
+        let mut write_buf = vec![];
+
+        CopyArea {
+            src_drawable: 0x04000001,
+            dst_drawable: 0x201,
+            gc: 0x04000002,
+            src_x: 0,
+            src_y: 0,
+            dst_x: 10,
+            dst_y: 20,
+            width: 32,
+            height: 16,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_serialize_copy_plane() {
+        const EXPECTED: &[u8] = b"\x3f\x00\x08\x00\x01\x00\x00\x04\x01\x02\x00\x00\
+                                  \x02\x00\x00\x04\x00\x00\x00\x00\x0a\x00\x14\x00\
+                                  \x20\x00\x10\x00\x01\x00\x00\x00";
+        let mut write_buf = vec![];
+
+        CopyPlane {
+            src_drawable: 0x04000001,
+            dst_drawable: 0x201,
+            gc: 0x04000002,
+            src_x: 0,
+            src_y: 0,
+            dst_x: 10,
+            dst_y: 20,
+            width: 32,
+            height: 16,
+            bit_plane: 1,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        // Same as CopyArea with bit plane appended
+        assert_eq!(write_buf, EXPECTED);
+        assert_eq!(write_buf.len(), 8 * 4);
+    }
+
+    #[test]
+    fn test_serialize_query_best_size() {
+        let classes = [
+            (SizeClass::Cursor, 0),
+            (SizeClass::Tile, 1),
+            (SizeClass::Stipple, 2),
+        ];
+
+        for (class, raw_class) in &classes {
+            let expected = [
+                b"\x61".as_ref(),
+                &[*raw_class],
+                b"\x03\x00\x00\x01\x00\x00\x20\x00\x18\x00",
+            ]
+            .concat();
+            let mut write_buf = vec![];
+
+            QueryBestSize {
+                class: *class,
+                drawable: 0x100,
+                width: 32,
+                height: 24,
+            }
+            .serialize(&mut write_buf)
+            .unwrap();
+
+            assert_eq!(write_buf, expected);
+        }
+    }
+
+    #[test]
+    fn test_deserialize_query_best_size_reply() {
+        let mut reply = b"\x01\x00\x01\x00\x00\x00\x00\x00\x40\x00\x40\x00".to_vec();
+        reply.extend(vec![0; 20]);
+
+        assert_eq!(
+            QueryBestSizeReply::deserialize(&reply[..])
+                .unwrap()
+                .unwrap(),
+            QueryBestSizeReply {
+                width: 64,
+                height: 64,
+            }
+        );
+    }
+
+    #[test]
+    fn test_serialize_poly_point() {
+        const EXPECTED: &[u8] = b"\x40\x01\x05\x00\x01\x00\x00\x04\x02\x00\x00\x04\
+                                  \x0a\x00\x0a\x00\x01\x00\xff\xff";
+        let mut write_buf = vec![];
+
+        PolyPoint {
+            coordinate_mode: CoordinateMode::Previous,
+            drawable: 0x04000001,
+            gc: 0x04000002,
+            points: vec![Point::from((10, 10)), Point::from((1, -1))],
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_serialize_poly_segment_and_rectangle() {
+        let mut write_buf = vec![];
+
+        PolySegment {
+            drawable: 0x04000001,
+            gc: 0x04000002,
+            segments: vec![Segment::from((Point::from((0, 0)), Point::from((5, 5))))],
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+        PolyRectangle {
+            drawable: 0x04000001,
+            gc: 0x04000002,
+            rectangles: vec![Rectangle::from((0, 0, 5, 5))],
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(&write_buf[..4], b"\x42\x00\x05\x00");
+        assert_eq!(&write_buf[12..20], b"\x00\x00\x00\x00\x05\x00\x05\x00");
+        assert_eq!(&write_buf[20..24], b"\x43\x00\x05\x00");
+        assert_eq!(write_buf.len(), 40);
+    }
+
+    #[test]
+    fn test_serialize_image_text8() {
+        const EXPECTED: &[u8] = b"\x4c\x05\x06\x00\x01\x00\x00\x04\x02\x00\x00\x04\
+                                  \x0a\x00\x14\x00hello\x00\x00\x00";
+        let mut write_buf = vec![];
+
+        ImageText8 {
+            drawable: 0x04000001,
+            gc: 0x04000002,
+            x: 10,
+            y: 20,
+            string: b"hello".to_vec(),
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+
+        let too_long = ImageText8 {
+            drawable: 0x04000001,
+            gc: 0x04000002,
+            x: 0,
+            y: 0,
+            string: vec![b'a'; 256],
+        };
+        assert!(too_long.serialize(&mut write_buf).is_err());
+    }
+
+    #[test]
+    fn test_serialize_poly_text8() {
+        const EXPECTED: &[u8] = b"\x4a\x00\x06\x00\x01\x00\x00\x04\x02\x00\x00\x04\
+                                  \x0a\x00\x14\x00\x05\xfehello\x00";
+        let mut write_buf = vec![];
+
+        PolyText8 {
+            drawable: 0x04000001,
+            gc: 0x04000002,
+            x: 10,
+            y: 20,
+            items: vec![TextItem8::Text {
+                delta: -2,
+                string: b"hello".to_vec(),
+            }],
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_serialize_image_text16() {
+        // CHAR2B are big endian whatever the byte order, then padding
+        const EXPECTED: &[u8] = b"\x4d\x03\x06\x00\x01\x00\x00\x04\x02\x00\x00\x04\
+                                  \x0a\x00\x14\x00\x4e\x2d\x65\x87\x00\x41\x00\x00";
+        let mut write_buf = vec![];
+
+        ImageText16 {
+            drawable: 0x04000001,
+            gc: 0x04000002,
+            x: 10,
+            y: 20,
+            text: vec![0x4e2d, 0x6587, 0x0041],
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+
+        let too_long = ImageText16 {
+            drawable: 0x04000001,
+            gc: 0x04000002,
+            x: 0,
+            y: 0,
+            text: vec![0x0041; 256],
+        };
+        assert!(too_long.serialize(&mut write_buf).is_err());
+    }
+
+    #[test]
+    fn test_serialize_poly_text16() {
+        const EXPECTED: &[u8] = b"\x4b\x00\x07\x00\x01\x00\x00\x04\x02\x00\x00\x04\
+                                  \x0a\x00\x14\x00\x02\xfe\x4e\x2d\x65\x87\xff\x04\
+                                  \x00\x00\x03\x00";
+        let mut write_buf = vec![];
+
+        PolyText16 {
+            drawable: 0x04000001,
+            gc: 0x04000002,
+            x: 10,
+            y: 20,
+            items: vec![
+                TextItem16::Text {
+                    delta: -2,
+                    text: vec![0x4e2d, 0x6587],
+                },
+                TextItem16::Font(0x04000003),
+            ],
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+
+        let too_long = TextItem16::Text {
+            delta: 0,
+            text: vec![0x0041; 255],
+        };
+        assert!(too_long.serialize(&mut write_buf).is_err());
+    }
+
+    #[test]
+    fn test_serialize_poly_text8_font_shift() {
+        const EXPECTED: &[u8] = b"\x4a\x00\x07\x00\x01\x00\x00\x04\x02\x00\x00\x04\
+                                  \x00\x00\x00\x00\x02\x00ab\xff\x04\x00\x00\x03\
+                                  \x01\x01c";
+        let mut write_buf = vec![];
+
+        PolyText8 {
+            drawable: 0x04000001,
+            gc: 0x04000002,
+            x: 0,
+            y: 0,
+            items: vec![
+                TextItem8::Text {
+                    delta: 0,
+                    string: b"ab".to_vec(),
+                },
+                TextItem8::Font(0x04000003),
+                TextItem8::Text {
+                    delta: 1,
+                    string: b"c".to_vec(),
+                },
+            ],
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+
+        let too_long = TextItem8::Text {
+            delta: 0,
+            string: vec![b'a'; 255],
+        };
+        assert!(too_long.serialize(&mut write_buf).is_err());
+    }
+}
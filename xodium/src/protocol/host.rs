@@ -0,0 +1,224 @@
+//! Access control, list of hosts allowed to connect to X server.
+
+use super::{skip, Deserialize, ReplyRequest, Request};
+use crate::io::{self, Read, Write};
+use crate::utils::{ReadBytesExt, WriteBytesExt};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// Whether X server checks connecting hosts against the access list.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AccessControlMode {
+    Disabled = 0,
+    Enabled = 1,
+}
+
+impl AccessControlMode {
+    pub(crate) fn try_from(raw_mode: u8) -> Option<AccessControlMode> {
+        match raw_mode {
+            0 => Some(AccessControlMode::Disabled),
+            1 => Some(AccessControlMode::Enabled),
+            _ => None,
+        }
+    }
+}
+
+/// Whether host is added to or removed from the access list.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HostChangeMode {
+    Insert = 0,
+    Delete = 1,
+}
+
+/// Host address of the given family: 0 Internet, 1 DECnet, 2 Chaos,
+/// 5 ServerInterpreted or 6 InternetV6.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Host {
+    pub family: u8,
+    /// Address bytes, e.g. 4 bytes of IPv4 address for Internet family.
+    pub address: Vec<u8>,
+}
+
+// 1     CARD8                           family
+// 1                                     unused
+// 2     n                               length of address
+// n     LISTofBYTE                      address
+// p                                     unused, p=pad(n)
+impl Deserialize for Host {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        let family = reader.read_u8()?;
+        reader.read_u8()?;
+        let address_len = reader.read_u16_ne()?;
+        let address = reader.read_string8(usize::from(address_len))?;
+
+        Ok(Some(Host { family, address }))
+    }
+}
+
+/// Add host to or remove it from the access list.
+pub struct ChangeHosts {
+    pub mode: HostChangeMode,
+    pub family: u8,
+    pub address: Vec<u8>,
+}
+
+// 1     109                             opcode
+// 1                                     mode
+//       0     Insert
+//       1     Delete
+// 2     2+(n+p)/4                       request length
+// 1                                     family
+//       0     Internet
+//       1     DECnet
+//       2     Chaos
+//       5     ServerInterpreted
+//       6     InternetV6
+// 1                                     unused
+// 2     n                               length of address
+// n     LISTofCARD8                     address
+// p                                     unused, p=pad(n)
+impl Request for ChangeHosts {
+    const OPCODE: u8 = 109;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        let address_len = u16::try_from(self.address.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Address is too long"))?;
+
+        writer.write_u8(self.family)?;
+        writer.write_u8(0)?;
+        writer.write_u16_ne(address_len)?;
+        writer.write_all(&self.address)?;
+        Ok(self.mode as u8)
+    }
+}
+
+/// Query the access list and whether it is used.
+pub struct ListHosts;
+
+// 1     110                             opcode
+// 1                                     unused
+// 2     1                               request length
+impl Request for ListHosts {
+    const OPCODE: u8 = 110;
+
+    fn serialize_body<W: Write>(&self, _writer: W) -> io::Result<u8> {
+        Ok(0)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ListHostsReply {
+    pub mode: AccessControlMode,
+    pub hosts: Vec<Host>,
+}
+
+// 1     1                               Reply
+// 1                                     mode
+//       0     Disabled
+//       1     Enabled
+// 2     CARD16                          sequence number
+// 4     n/4                             reply length
+// 2     CARD16                          number of HOSTs in hosts
+// 22                                    unused
+// n     LISTofHOST                      hosts (n always a multiple of 4)
+impl Deserialize for ListHostsReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        reader.read_u8()?; // Reply
+        let mode = AccessControlMode::try_from(reader.read_u8()?).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "Invalid access control mode")
+        })?;
+        skip(&mut reader, 6)?;
+        let hosts_len = reader.read_u16_ne()?;
+        skip(&mut reader, 22)?;
+
+        let mut hosts = vec![];
+        for _ in 0..hosts_len {
+            hosts.extend(Host::deserialize(&mut reader)?);
+        }
+
+        Ok(Some(ListHostsReply { mode, hosts }))
+    }
+}
+
+impl ReplyRequest for ListHosts {
+    type Reply = ListHostsReply;
+}
+
+/// Enable or disable checking connecting hosts against the access list.
+pub struct SetAccessControl {
+    pub mode: AccessControlMode,
+}
+
+// 1     111                             opcode
+// 1                                     mode
+//       0     Disable
+//       1     Enable
+// 2     1                               request length
+impl Request for SetAccessControl {
+    const OPCODE: u8 = 111;
+
+    fn serialize_body<W: Write>(&self, _writer: W) -> io::Result<u8> {
+        Ok(self.mode as u8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        AccessControlMode, ChangeHosts, Host, HostChangeMode, ListHosts, ListHostsReply,
+        SetAccessControl,
+    };
+    use crate::protocol::{Deserialize, Serialize};
+
+    #[test]
+    fn test_serialize_change_hosts() {
+        const EXPECTED: &[u8] = b"\x6d\x00\x03\x00\x00\x00\x04\x00\xc0\xa8\x00\x01";
+        let mut write_buf = vec![];
+
+        ChangeHosts {
+            mode: HostChangeMode::Insert,
+            family: 0,
+            address: vec![192, 168, 0, 1],
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_serialize_list_hosts() {
+        let mut write_buf = vec![];
+        ListHosts.serialize(&mut write_buf).unwrap();
+        assert_eq!(write_buf, b"\x6e\x00\x01\x00");
+    }
+
+    #[test]
+    fn test_serialize_set_access_control() {
+        let mut write_buf = vec![];
+        SetAccessControl {
+            mode: AccessControlMode::Enabled,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+        assert_eq!(write_buf, b"\x6f\x01\x01\x00");
+    }
+
+    #[test]
+    fn test_deserialize_list_hosts_reply() {
+        let mut reply = b"\x01\x01\x01\x00\x02\x00\x00\x00\x01\x00".to_vec();
+        reply.extend(vec![0; 22]);
+        reply.extend(b"\x00\x00\x04\x00\x7f\x00\x00\x01");
+
+        assert_eq!(
+            ListHostsReply::deserialize(&reply[..]).unwrap().unwrap(),
+            ListHostsReply {
+                mode: AccessControlMode::Enabled,
+                hosts: vec![Host {
+                    family: 0,
+                    address: vec![127, 0, 0, 1],
+                }],
+            }
+        );
+    }
+}
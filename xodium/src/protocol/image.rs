@@ -0,0 +1,513 @@
+use super::{
+    skip, ByteOrder, Deserialize, Rectangle, ReplyRequest, Request, Serialize, Setup, VisualType,
+};
+use crate::io::{self, Read, Write};
+use crate::utils::{ReadBytesExt, WriteBytesExt};
+use alloc::vec::Vec;
+
+/// Layout of image data.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageFormat {
+    /// Separate bitmap for each bit plane, the most significant plane first.
+    XYPixmap = 1,
+    /// Pixels stored one after another, `bits-per-pixel` of pixmap format each.
+    ZPixmap = 2,
+}
+
+/// Query contents of the drawable rectangle, only planes set in `plane_mask` are returned.
+/// Window has to be viewable and the rectangle has to be fully inside the screen.
+pub struct GetImage {
+    pub format: ImageFormat,
+    pub drawable: u32,
+    pub rectangle: Rectangle,
+    pub plane_mask: u32,
+}
+
+// 1     73                              opcode
+// 1                                     format
+//       1     XYPixmap
+//       2     ZPixmap
+// 2     5                               request length
+// 4     DRAWABLE                        drawable
+// 2     INT16                           x
+// 2     INT16                           y
+// 2     CARD16                          width
+// 2     CARD16                          height
+// 4     CARD32                          plane-mask
+impl Request for GetImage {
+    const OPCODE: u8 = 73;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.drawable)?;
+        self.rectangle.serialize(&mut writer)?;
+        writer.write_u32_ne(self.plane_mask)?;
+        Ok(self.format as u8)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct GetImageReply {
+    pub depth: u8,
+    /// Visual of window, 0 (None) for pixmap.
+    pub visual: u32,
+    pub data: Vec<u8>,
+}
+
+// 1     1                               Reply
+// 1     CARD8                           depth
+// 2     CARD16                          sequence number
+// 4     (n+p)/4                         reply length
+// 4     VISUALID                        visual
+//       0     None
+// 20                                    unused
+// n     LISTofBYTE                      data
+// p                                     unused, p=pad(n)
+impl Deserialize for GetImageReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        reader.read_u8()?; // Reply
+        let depth = reader.read_u8()?;
+        skip(&mut reader, 2)?;
+        let data_len = (reader.read_u32_ne()? as usize)
+            .checked_mul(4)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Image is too long"))?;
+        let visual = reader.read_u32_ne()?;
+        skip(&mut reader, 20)?;
+
+        // Padding of data is not distinguishable from data
+        let data = reader.read_string8(data_len)?;
+
+        Ok(Some(GetImageReply {
+            depth,
+            visual,
+            data,
+        }))
+    }
+}
+
+impl ReplyRequest for GetImage {
+    type Reply = GetImageReply;
+}
+
+/// Write image data into the drawable rectangle using GC function and plane mask.
+/// Data layout has to follow setup pixmap format of `depth`, e.g. 1-bit rows
+/// padded to `bitmap-format-scanline-pad` for a bitmap.
+pub struct PutImage {
+    pub format: ImageFormat,
+    pub drawable: u32,
+    pub gc: u32,
+    pub rectangle: Rectangle,
+    /// Number of bits to skip at the start of each row of XYPixmap image.
+    pub left_pad: u8,
+    pub depth: u8,
+    pub data: Vec<u8>,
+}
+
+// 1     72                              opcode
+// 1                                     format
+//       0     Bitmap
+//       1     XYPixmap
+//       2     ZPixmap
+// 2     6+(n+p)/4                       request length
+// 4     DRAWABLE                        drawable
+// 4     GCONTEXT                        gc
+// 2     CARD16                          width
+// 2     CARD16                          height
+// 2     INT16                           dst-x
+// 2     INT16                           dst-y
+// 1     CARD8                           left-pad
+// 1     CARD8                           depth
+// 2                                     unused
+// n     LISTofBYTE                      data
+// p                                     unused, p=pad(n)
+impl Request for PutImage {
+    const OPCODE: u8 = 72;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.drawable)?;
+        writer.write_u32_ne(self.gc)?;
+        writer.write_u16_ne(self.rectangle.width)?;
+        writer.write_u16_ne(self.rectangle.height)?;
+        writer.write_i16_ne(self.rectangle.x)?;
+        writer.write_i16_ne(self.rectangle.y)?;
+        writer.write_u8(self.left_pad)?;
+        writer.write_u8(self.depth)?;
+        writer.write_u16_ne(0)?;
+        writer.write_all(&self.data)?;
+        Ok(self.format as u8)
+    }
+}
+
+/// Image contents along with everything needed to interpret them,
+/// e.g. returned by `Connection::capture_window`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub width: u16,
+    pub height: u16,
+    pub depth: u8,
+    pub format: ImageFormat,
+    /// Byte order of pixels, `image-byte-order` of setup.
+    pub byte_order: ByteOrder,
+    /// Bits per pixel of [ZPixmap](ImageFormat::ZPixmap) image.
+    pub bits_per_pixel: u8,
+    /// Each scanline is padded to multiple of this number of bits.
+    pub scanline_pad: u8,
+    /// Visual of the captured window, `None` if not known.
+    pub visual: Option<VisualType>,
+    pub data: Vec<u8>,
+}
+
+impl Image {
+    /// Convert pixels to RGBA with 8 bits per channel and alpha set to 255,
+    /// row by row without padding. Pixel values are taken apart using masks
+    /// of the visual, so `None` is returned unless image is in
+    /// [ZPixmap](ImageFormat::ZPixmap) format of known visual, with 8, 16, 24
+    /// or 32 bits per pixel, and data covers the whole image.
+    pub fn to_rgba8(&self) -> Option<Vec<u8>> {
+        if self.format != ImageFormat::ZPixmap {
+            return None;
+        }
+
+        let pixels = zpixmap_to_rgba8(
+            self.visual.as_ref()?,
+            self.byte_order,
+            self.bits_per_pixel,
+            self.scanline_pad,
+            self.width,
+            self.height,
+            &self.data,
+        )?;
+        Some(pixels.concat())
+    }
+}
+
+/// Convert [ZPixmap](ImageFormat::ZPixmap) image data of visual, e.g. from
+/// `GetImageReply`, to RGBA pixels with alpha set to 255, row by row.
+/// Bits per pixel and scanline pad are taken from setup pixmap format
+/// of the depth visual belongs to, byte order from `image-byte-order`.
+/// `None` if visual is not in setup, or under conditions of [Image::to_rgba8].
+pub fn decode_zpixmap(
+    setup: &Setup,
+    visual: &VisualType,
+    width: u16,
+    height: u16,
+    data: &[u8],
+) -> Option<Vec<[u8; 4]>> {
+    let depth = setup
+        .roots
+        .iter()
+        .flat_map(|screen| &screen.allowed_depths)
+        .find(|depth| depth.visuals.contains(visual))?
+        .depth;
+    let format = setup
+        .pixmap_formats
+        .iter()
+        .find(|format| format.depth == depth)?;
+    let byte_order = match setup.image_byte_order {
+        0 => ByteOrder::LsbFirst,
+        _ => ByteOrder::MsbFirst,
+    };
+
+    zpixmap_to_rgba8(
+        visual,
+        byte_order,
+        format.bits_per_pixel,
+        format.scanline_pad,
+        width,
+        height,
+        data,
+    )
+}
+
+/// Take pixels with 8, 16, 24 or 32 bits apart using masks of visual,
+/// `None` for other sizes or if data does not cover the whole image.
+/// Pixels take whole bytes, so bit order of bitmaps does not matter.
+fn zpixmap_to_rgba8(
+    visual: &VisualType,
+    byte_order: ByteOrder,
+    bits_per_pixel: u8,
+    scanline_pad: u8,
+    width: u16,
+    height: u16,
+    data: &[u8],
+) -> Option<Vec<[u8; 4]>> {
+    let bytes_per_pixel = match bits_per_pixel {
+        8 | 16 | 24 | 32 => usize::from(bits_per_pixel / 8),
+        _ => return None,
+    };
+
+    let width = usize::from(width);
+    let height = usize::from(height);
+    let scanline_pad = usize::from(scanline_pad).max(8);
+    let scanline_bits = width * usize::from(bits_per_pixel);
+    let stride = scanline_bits.div_ceil(scanline_pad) * scanline_pad / 8;
+    if stride == 0 || data.len() < stride * height {
+        return None;
+    }
+
+    let mut pixels = Vec::with_capacity(width * height);
+    for scanline in data.chunks(stride).take(height) {
+        for bytes in scanline.chunks_exact(bytes_per_pixel).take(width) {
+            let pixel = match byte_order {
+                ByteOrder::LsbFirst => bytes
+                    .iter()
+                    .rev()
+                    .fold(0, |pixel, &byte| pixel << 8 | u32::from(byte)),
+                ByteOrder::MsbFirst => bytes
+                    .iter()
+                    .fold(0, |pixel, &byte| pixel << 8 | u32::from(byte)),
+            };
+
+            pixels.push([
+                channel(pixel, visual.red_mask),
+                channel(pixel, visual.green_mask),
+                channel(pixel, visual.blue_mask),
+                0xff,
+            ]);
+        }
+    }
+    Some(pixels)
+}
+
+/// Value of pixel channel selected by mask, scaled to 8 bits.
+fn channel(pixel: u32, mask: u32) -> u8 {
+    if mask == 0 {
+        return 0;
+    }
+
+    let shift = mask.trailing_zeros();
+    let max = u64::from(mask >> shift);
+    let value = u64::from((pixel & mask) >> shift);
+    (value * 255 / max) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_zpixmap, GetImage, GetImageReply, Image, ImageFormat, PutImage};
+    use crate::protocol::{
+        ByteOrder, Depth, Deserialize, Format, Rectangle, Serialize, Setup, SetupResponse,
+        VisualType,
+    };
+    use crate::test_utils::setup_response;
+
+    fn visual(red_mask: u32, green_mask: u32, blue_mask: u32) -> VisualType {
+        VisualType {
+            visual_id: 0x21,
+            class: 4,
+            bits_per_rgb_value: 8,
+            colormap_entries: 256,
+            red_mask,
+            green_mask,
+            blue_mask,
+        }
+    }
+
+    /// Setup with 24-bit visual 0x21 of depth 24 and RGB 565 visual 0x22 of depth 16,
+    /// with `bits_per_pixel` of depth 24.
+    fn setup(image_byte_order: u8, bits_per_pixel: u8) -> Setup {
+        let mut setup = match SetupResponse::deserialize(&setup_response(1)[..]) {
+            Ok(Some(SetupResponse::Success(setup))) => setup,
+            other => panic!("Unexpected setup response: {:?}", other),
+        };
+
+        setup.image_byte_order = image_byte_order;
+        setup.pixmap_formats = vec![
+            Format {
+                depth: 24,
+                bits_per_pixel,
+                scanline_pad: 32,
+            },
+            Format {
+                depth: 16,
+                bits_per_pixel: 16,
+                scanline_pad: 32,
+            },
+        ];
+        setup.roots[0].allowed_depths.push(Depth {
+            depth: 16,
+            visuals: vec![VisualType {
+                visual_id: 0x22,
+                ..visual(0xf800, 0x07e0, 0x001f)
+            }],
+        });
+        setup
+    }
+
+    #[test]
+    fn test_serialize_get_image() {
+        const EXPECTED: &[u8] = b"\x49\x02\x05\x00\x00\x01\x00\x00\x00\x00\x00\x00\
+                                  \x80\x07\x38\x04\xff\xff\xff\xff";
+        let mut write_buf = vec![];
+
+        GetImage {
+            format: ImageFormat::ZPixmap,
+            drawable: 0x100,
+            rectangle: Rectangle::from((0, 0, 1920, 1080)),
+            plane_mask: 0xffff_ffff,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_serialize_put_image() {
+        const EXPECTED: &[u8] = b"\x48\x01\x07\x00\x01\x00\x00\x04\x02\x00\x00\x04\
+                                  \x08\x00\x03\x00\xff\xff\x02\x00\x00\x01\x00\x00\
+                                  \x18\x3c\x18\x00";
+        let mut write_buf = vec![];
+
+        PutImage {
+            format: ImageFormat::XYPixmap,
+            drawable: 0x04000001,
+            gc: 0x04000002,
+            rectangle: Rectangle::from((-1, 2, 8, 3)),
+            left_pad: 0,
+            depth: 1,
+            data: vec![0x18, 0x3c, 0x18],
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_deserialize_get_image_reply() {
+        let mut reply = b"\x01\x18\x01\x00\x02\x00\x00\x00\x21\x00\x00\x00".to_vec();
+        reply.extend(vec![0; 20]);
+        reply.extend(b"\x00\x00\xff\x00\xff\x00\x00\x00");
+
+        assert_eq!(
+            GetImageReply::deserialize(&reply[..]).unwrap().unwrap(),
+            GetImageReply {
+                depth: 24,
+                visual: 0x21,
+                data: b"\x00\x00\xff\x00\xff\x00\x00\x00".to_vec(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_image_to_rgba8() {
+        // 2x2 image, 32 bits per pixel
+        let image = Image {
+            width: 2,
+            height: 2,
+            depth: 24,
+            format: ImageFormat::ZPixmap,
+            byte_order: ByteOrder::LsbFirst,
+            bits_per_pixel: 32,
+            scanline_pad: 32,
+            visual: Some(visual(0x00ff_0000, 0x0000_ff00, 0x0000_00ff)),
+            data: b"\x00\x00\xff\x00\x00\xff\x00\x00\xff\x00\x00\x00\x40\x80\xc0\x00".to_vec(),
+        };
+
+        assert_eq!(
+            image.to_rgba8().unwrap(),
+            b"\xff\x00\x00\xff\x00\xff\x00\xff\x00\x00\xff\xff\xc0\x80\x40\xff"
+        );
+    }
+
+    #[test]
+    fn test_image_to_rgba8_scanline_pad() {
+        // 1x2 image, 16 bits per pixel (RGB 565) in MSB first order,
+        // each scanline padded to 32 bits
+        let image = Image {
+            width: 1,
+            height: 2,
+            depth: 16,
+            format: ImageFormat::ZPixmap,
+            byte_order: ByteOrder::MsbFirst,
+            bits_per_pixel: 16,
+            scanline_pad: 32,
+            visual: Some(visual(0xf800, 0x07e0, 0x001f)),
+            data: b"\xf8\x00\x00\x00\x07\xff\x00\x00".to_vec(),
+        };
+
+        assert_eq!(
+            image.to_rgba8().unwrap(),
+            b"\xff\x00\x00\xff\x00\xff\xff\xff"
+        );
+    }
+
+    #[test]
+    fn test_image_to_rgba8_unsupported() {
+        let image = Image {
+            width: 1,
+            height: 1,
+            depth: 24,
+            format: ImageFormat::ZPixmap,
+            byte_order: ByteOrder::LsbFirst,
+            bits_per_pixel: 32,
+            scanline_pad: 32,
+            visual: None,
+            data: vec![0; 4],
+        };
+        assert_eq!(image.to_rgba8(), None);
+
+        let image = Image {
+            visual: Some(visual(0x00ff_0000, 0x0000_ff00, 0x0000_00ff)),
+            data: vec![0; 3],
+            ..image
+        };
+        assert_eq!(image.to_rgba8(), None);
+    }
+
+    #[test]
+    fn test_decode_zpixmap_24_bits() {
+        let visual = visual(0x00ff_0000, 0x0000_ff00, 0x0000_00ff);
+        // 2x2 image, each scanline of 6 bytes padded to 8
+        let lsb_first = b"\x00\x00\xff\x00\xff\x00\x00\x00\xc0\x80\x40\xff\xff\xff\x00\x00";
+        let msb_first = b"\xff\x00\x00\x00\xff\x00\x00\x00\x40\x80\xc0\xff\xff\xff\x00\x00";
+        let expected = vec![
+            [0xff, 0, 0, 0xff],
+            [0, 0xff, 0, 0xff],
+            [0x40, 0x80, 0xc0, 0xff],
+            [0xff, 0xff, 0xff, 0xff],
+        ];
+
+        assert_eq!(
+            decode_zpixmap(&setup(0, 24), &visual, 2, 2, lsb_first),
+            Some(expected.clone())
+        );
+        assert_eq!(
+            decode_zpixmap(&setup(1, 24), &visual, 2, 2, msb_first),
+            Some(expected)
+        );
+        assert_eq!(
+            decode_zpixmap(&setup(0, 24), &visual, 2, 2, &lsb_first[..12]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_decode_zpixmap_16_bits() {
+        let visual = VisualType {
+            visual_id: 0x22,
+            ..visual(0xf800, 0x07e0, 0x001f)
+        };
+        // 3x1 image of red, green and blue, scanline padded to 8 bytes
+        let lsb_first = b"\x00\xf8\xe0\x07\x1f\x00\x00\x00";
+        let msb_first = b"\xf8\x00\x07\xe0\x00\x1f\x00\x00";
+        let expected = vec![[0xff, 0, 0, 0xff], [0, 0xff, 0, 0xff], [0, 0, 0xff, 0xff]];
+
+        assert_eq!(
+            decode_zpixmap(&setup(0, 32), &visual, 3, 1, lsb_first),
+            Some(expected.clone())
+        );
+        assert_eq!(
+            decode_zpixmap(&setup(1, 32), &visual, 3, 1, msb_first),
+            Some(expected)
+        );
+    }
+
+    #[test]
+    fn test_decode_zpixmap_unknown_visual() {
+        let visual = VisualType {
+            visual_id: 0x23,
+            ..visual(0x00ff_0000, 0x0000_ff00, 0x0000_00ff)
+        };
+        assert_eq!(decode_zpixmap(&setup(0, 32), &visual, 1, 1, &[0; 4]), None);
+    }
+}
@@ -0,0 +1,825 @@
+use super::flags::define_flags;
+use super::{skip, Deserialize, EventMask, ReplyRequest, Request, Serialize, Timestamp};
+use crate::io::{self, Read, Write};
+use crate::utils::{ReadBytesExt, WriteBytesExt};
+use alloc::vec::Vec;
+
+define_flags! {
+    /// State of modifier keys and pointer buttons, e.g. `state` of key and button events.
+    pub struct KeyButMask: u16 {
+        const SHIFT = 0x0001;
+        const LOCK = 0x0002;
+        const CONTROL = 0x0004;
+        const MOD1 = 0x0008;
+        const MOD2 = 0x0010;
+        const MOD3 = 0x0020;
+        const MOD4 = 0x0040;
+        const MOD5 = 0x0080;
+        const BUTTON1 = 0x0100;
+        const BUTTON2 = 0x0200;
+        const BUTTON3 = 0x0400;
+        const BUTTON4 = 0x0800;
+        const BUTTON5 = 0x1000;
+    }
+}
+
+impl KeyButMask {
+    pub fn shift(self) -> bool {
+        self.contains(Self::SHIFT)
+    }
+
+    /// Caps Lock.
+    pub fn lock(self) -> bool {
+        self.contains(Self::LOCK)
+    }
+
+    pub fn control(self) -> bool {
+        self.contains(Self::CONTROL)
+    }
+
+    /// Usually Alt.
+    pub fn mod1(self) -> bool {
+        self.contains(Self::MOD1)
+    }
+
+    /// Usually Num Lock.
+    pub fn mod2(self) -> bool {
+        self.contains(Self::MOD2)
+    }
+
+    pub fn mod3(self) -> bool {
+        self.contains(Self::MOD3)
+    }
+
+    /// Usually Super.
+    pub fn mod4(self) -> bool {
+        self.contains(Self::MOD4)
+    }
+
+    pub fn mod5(self) -> bool {
+        self.contains(Self::MOD5)
+    }
+
+    pub fn button1(self) -> bool {
+        self.contains(Self::BUTTON1)
+    }
+
+    pub fn button2(self) -> bool {
+        self.contains(Self::BUTTON2)
+    }
+
+    pub fn button3(self) -> bool {
+        self.contains(Self::BUTTON3)
+    }
+
+    pub fn button4(self) -> bool {
+        self.contains(Self::BUTTON4)
+    }
+
+    pub fn button5(self) -> bool {
+        self.contains(Self::BUTTON5)
+    }
+}
+
+define_flags! {
+    /// Modifiers of passive grabs, e.g. `ModMask::CONTROL | ModMask::MOD1` for Ctrl+Alt.
+    pub struct ModMask: u16 {
+        const SHIFT = 0x0001;
+        /// Caps Lock.
+        const LOCK = 0x0002;
+        const CONTROL = 0x0004;
+        /// Usually Alt.
+        const MOD1 = 0x0008;
+        /// Usually Num Lock.
+        const MOD2 = 0x0010;
+        const MOD3 = 0x0020;
+        /// Usually Super.
+        const MOD4 = 0x0040;
+        const MOD5 = 0x0080;
+        /// Any combination of modifiers, including none.
+        const ANY = 0x8000;
+    }
+}
+
+/// Key of key grab and ungrab matching any key.
+pub const ANY_KEY: u8 = 0;
+/// Button of button grab and ungrab matching any button.
+pub const ANY_BUTTON: u8 = 0;
+/// Modifiers of grab and ungrab matching any combination of modifiers, including none.
+pub const ANY_MODIFIER: ModMask = ModMask::ANY;
+
+/// How pointer or keyboard events are processed once grab activates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GrabMode {
+    /// Events are frozen until AllowEvents is sent.
+    Synchronous = 0,
+    Asynchronous = 1,
+}
+
+/// Events allowed in SETofPOINTEREVENT.
+const POINTER_EVENTS: EventMask = EventMask(
+    EventMask::BUTTON_PRESS.bits()
+        | EventMask::BUTTON_RELEASE.bits()
+        | EventMask::ENTER_WINDOW.bits()
+        | EventMask::LEAVE_WINDOW.bits()
+        | EventMask::POINTER_MOTION.bits()
+        | EventMask::POINTER_MOTION_HINT.bits()
+        | EventMask::BUTTON1_MOTION.bits()
+        | EventMask::BUTTON2_MOTION.bits()
+        | EventMask::BUTTON3_MOTION.bits()
+        | EventMask::BUTTON4_MOTION.bits()
+        | EventMask::BUTTON5_MOTION.bits()
+        | EventMask::BUTTON_MOTION.bits()
+        | EventMask::KEYMAP_STATE.bits(),
+);
+
+/// Passively grab pointer once `button` is pressed with `modifiers` in the window.
+pub struct GrabButton {
+    pub owner_events: bool,
+    pub grab_window: u32,
+    /// Pointer events reported to the client while grab is active,
+    /// only pointer related [EventMask] flags are allowed.
+    pub event_mask: EventMask,
+    pub pointer_mode: GrabMode,
+    pub keyboard_mode: GrabMode,
+    /// Window pointer is confined to, 0 (None) to not confine.
+    pub confine_to: u32,
+    /// Cursor displayed during grab, 0 (None) to keep the current one.
+    pub cursor: u32,
+    pub button: u8,
+    pub modifiers: ModMask,
+}
+
+// 1     28                              opcode
+// 1     BOOL                            owner-events
+// 2     6                               request length
+// 4     WINDOW                          grab-window
+// 2     SETofPOINTEREVENT               event-mask
+// 1                                     pointer-mode
+//       0     Synchronous
+//       1     Asynchronous
+// 1                                     keyboard-mode
+//       0     Synchronous
+//       1     Asynchronous
+// 4     WINDOW                          confine-to
+//       0     None
+// 4     CURSOR                          cursor
+//       0     None
+// 1     BUTTON                          button
+//       0     AnyButton
+// 1                                     unused
+// 2     SETofKEYMASK                    modifiers
+//       #x8000                          AnyModifier
+impl Request for GrabButton {
+    const OPCODE: u8 = 28;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        if !POINTER_EVENTS.contains(self.event_mask) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Event mask has non-pointer events",
+            ));
+        }
+
+        writer.write_u32_ne(self.grab_window)?;
+        writer.write_u16_ne(self.event_mask.bits() as u16)?;
+        writer.write_u8(self.pointer_mode as u8)?;
+        writer.write_u8(self.keyboard_mode as u8)?;
+        writer.write_u32_ne(self.confine_to)?;
+        writer.write_u32_ne(self.cursor)?;
+        writer.write_u8(self.button)?;
+        writer.write_u8(0)?;
+        writer.write_u16_ne(self.modifiers.bits())?;
+        Ok(self.owner_events as u8)
+    }
+}
+
+/// Release passive button grab of the window, wildcards release all matching grabs.
+pub struct UngrabButton {
+    pub button: u8,
+    pub grab_window: u32,
+    pub modifiers: ModMask,
+}
+
+// 1     29                              opcode
+// 1     BUTTON                          button
+//       0     AnyButton
+// 2     3                               request length
+// 4     WINDOW                          grab-window
+// 2     SETofKEYMASK                    modifiers
+//       #x8000                          AnyModifier
+// 2                                     unused
+impl Request for UngrabButton {
+    const OPCODE: u8 = 29;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.grab_window)?;
+        writer.write_u16_ne(self.modifiers.bits())?;
+        writer.write_u16_ne(0)?;
+        Ok(self.button)
+    }
+}
+
+/// Passively grab keyboard once `key` is pressed with `modifiers` in the window,
+/// e.g. for global shortcuts.
+pub struct GrabKey {
+    pub owner_events: bool,
+    pub grab_window: u32,
+    pub modifiers: ModMask,
+    pub key: u8,
+    pub pointer_mode: GrabMode,
+    pub keyboard_mode: GrabMode,
+}
+
+// 1     33                              opcode
+// 1     BOOL                            owner-events
+// 2     4                               request length
+// 4     WINDOW                          grab-window
+// 2     SETofKEYMASK                    modifiers
+//       #x8000                          AnyModifier
+// 1     KEYCODE                         key
+//       0     AnyKey
+// 1                                     pointer-mode
+//       0     Synchronous
+//       1     Asynchronous
+// 1                                     keyboard-mode
+//       0     Synchronous
+//       1     Asynchronous
+// 3                                     unused
+impl Request for GrabKey {
+    const OPCODE: u8 = 33;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.grab_window)?;
+        writer.write_u16_ne(self.modifiers.bits())?;
+        writer.write_u8(self.key)?;
+        writer.write_u8(self.pointer_mode as u8)?;
+        writer.write_u8(self.keyboard_mode as u8)?;
+        writer.write_all(&[0; 3])?;
+        Ok(self.owner_events as u8)
+    }
+}
+
+/// Release passive key grab of the window, wildcards release all matching grabs.
+pub struct UngrabKey {
+    pub key: u8,
+    pub grab_window: u32,
+    pub modifiers: ModMask,
+}
+
+// 1     34                              opcode
+// 1     KEYCODE                         key
+//       0     AnyKey
+// 2     3                               request length
+// 4     WINDOW                          grab-window
+// 2     SETofKEYMASK                    modifiers
+//       #x8000                          AnyModifier
+// 2                                     unused
+impl Request for UngrabKey {
+    const OPCODE: u8 = 34;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.grab_window)?;
+        writer.write_u16_ne(self.modifiers.bits())?;
+        writer.write_u16_ne(0)?;
+        Ok(self.key)
+    }
+}
+
+/// How events frozen by synchronous grab are released.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AllowEventsMode {
+    /// Thaw pointer, further pointer events are processed normally.
+    AsyncPointer = 0,
+    /// Thaw pointer until the next pointer event, which freezes it again.
+    SyncPointer = 1,
+    /// Release pointer grab activated by passive grab and reprocess the event
+    /// which activated it, e.g. to deliver click to window after focusing it.
+    ReplayPointer = 2,
+    AsyncKeyboard = 3,
+    SyncKeyboard = 4,
+    ReplayKeyboard = 5,
+    /// Thaw both pointer and keyboard if both are frozen by the client.
+    AsyncBoth = 6,
+    SyncBoth = 7,
+}
+
+/// Release events queued by X server while pointer or keyboard is frozen
+/// by grab in synchronous mode.
+pub struct AllowEvents {
+    pub mode: AllowEventsMode,
+    /// Request is ignored if time is earlier than the last grab time.
+    pub time: Timestamp,
+}
+
+// 1     35                              opcode
+// 1                                     mode
+//       0     AsyncPointer
+//       1     SyncPointer
+//       2     ReplayPointer
+//       3     AsyncKeyboard
+//       4     SyncKeyboard
+//       5     ReplayKeyboard
+//       6     AsyncBoth
+//       7     SyncBoth
+// 2     2                               request length
+// 4     TIMESTAMP                       time
+//       0     CurrentTime
+impl Request for AllowEvents {
+    const OPCODE: u8 = 35;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        self.time.serialize(&mut writer)?;
+        Ok(self.mode as u8)
+    }
+}
+
+/// Query pointer position and modifiers state.
+pub struct QueryPointer {
+    pub window: u32,
+}
+
+// 1     38                              opcode
+// 1                                     unused
+// 2     2                               request length
+// 4     WINDOW                          window
+impl Request for QueryPointer {
+    const OPCODE: u8 = 38;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.window)?;
+        Ok(0)
+    }
+}
+
+/// Pointer position relative to root and queried window.
+#[derive(Debug, PartialEq)]
+pub struct QueryPointerReply {
+    /// Pointer is not on the screen of queried window if not set,
+    /// then `child` is 0 and window coordinates are 0.
+    pub same_screen: bool,
+    pub root: u32,
+    /// Child of queried window containing pointer, 0 (None) if there is none.
+    pub child: u32,
+    pub root_x: i16,
+    pub root_y: i16,
+    pub win_x: i16,
+    pub win_y: i16,
+    pub mask: KeyButMask,
+}
+
+// 1     1                               Reply
+// 1     BOOL                            same-screen
+// 2     CARD16                          sequence number
+// 4     0                               reply length
+// 4     WINDOW                          root
+// 4     WINDOW                          child
+//       0     None
+// 2     INT16                           root-x
+// 2     INT16                           root-y
+// 2     INT16                           win-x
+// 2     INT16                           win-y
+// 2     SETofKEYBUTMASK                 mask
+// 6                                     unused
+impl Deserialize for QueryPointerReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        reader.read_u8()?; // Reply
+        let same_screen = reader.read_u8()? != 0;
+        skip(&mut reader, 6)?;
+        let root = reader.read_u32_ne()?;
+        let child = reader.read_u32_ne()?;
+        let root_x = reader.read_i16_ne()?;
+        let root_y = reader.read_i16_ne()?;
+        let win_x = reader.read_i16_ne()?;
+        let win_y = reader.read_i16_ne()?;
+        let mask = KeyButMask::from(reader.read_u16_ne()?);
+        skip(&mut reader, 6)?;
+
+        Ok(Some(QueryPointerReply {
+            same_screen,
+            root,
+            child,
+            root_x,
+            root_y,
+            win_x,
+            win_y,
+            mask,
+        }))
+    }
+}
+
+impl ReplyRequest for QueryPointer {
+    type Reply = QueryPointerReply;
+}
+
+/// Query pointer motion history of the window between two times,
+/// X server may keep no history at all (`motion-buffer-size` of setup is 0).
+pub struct GetMotionEvents {
+    pub window: u32,
+    pub start: Timestamp,
+    pub stop: Timestamp,
+}
+
+// 1     39                              opcode
+// 1                                     unused
+// 2     4                               request length
+// 4     WINDOW                          window
+// 4     TIMESTAMP                       start
+//       0     CurrentTime
+// 4     TIMESTAMP                       stop
+//       0     CurrentTime
+impl Request for GetMotionEvents {
+    const OPCODE: u8 = 39;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.window)?;
+        self.start.serialize(&mut writer)?;
+        self.stop.serialize(&mut writer)?;
+        Ok(0)
+    }
+}
+
+/// Pointer position relative to the window at the time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeCoord {
+    pub time: u32,
+    pub x: i16,
+    pub y: i16,
+}
+
+// 4     TIMESTAMP                       time
+// 2     INT16                           x
+// 2     INT16                           y
+impl Deserialize for TimeCoord {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        let time = reader.read_u32_ne()?;
+        let x = reader.read_i16_ne()?;
+        let y = reader.read_i16_ne()?;
+
+        Ok(Some(TimeCoord { time, x, y }))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct GetMotionEventsReply {
+    pub events: Vec<TimeCoord>,
+}
+
+// 1     1                               Reply
+// 1                                     unused
+// 2     CARD16                          sequence number
+// 4     2n                              reply length
+// 4     n                               number of TIMECOORDs in events
+// 20                                    unused
+// 8n    LISTofTIMECOORD                 events
+impl Deserialize for GetMotionEventsReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        skip(&mut reader, 8)?; // reply header
+        let events_len = reader.read_u32_ne()?;
+        skip(&mut reader, 20)?;
+
+        let mut events = Vec::new();
+        for _ in 0..events_len {
+            if let Some(event) = TimeCoord::deserialize(&mut reader)? {
+                events.push(event);
+            }
+        }
+
+        Ok(Some(GetMotionEventsReply { events }))
+    }
+}
+
+impl ReplyRequest for GetMotionEvents {
+    type Reply = GetMotionEventsReply;
+}
+
+/// Where input focus reverts to once focused window becomes not viewable.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RevertTo {
+    None = 0,
+    PointerRoot = 1,
+    Parent = 2,
+}
+
+/// Query current input focus, also used as the cheapest round trip.
+pub struct GetInputFocus;
+
+// 1     43                              opcode
+// 1                                     unused
+// 2     1                               request length
+impl Request for GetInputFocus {
+    const OPCODE: u8 = 43;
+
+    fn serialize_body<W: Write>(&self, _writer: W) -> io::Result<u8> {
+        Ok(0)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct GetInputFocusReply {
+    pub revert_to: RevertTo,
+    /// Focused window, 0 (None) or 1 (PointerRoot).
+    pub focus: u32,
+}
+
+// 1     1                               Reply
+// 1                                     revert-to
+//       0     None
+//       1     PointerRoot
+//       2     Parent
+// 2     CARD16                          sequence number
+// 4     0                               reply length
+// 4     WINDOW                          focus
+//       0     None
+//       1     PointerRoot
+// 20                                    unused
+impl Deserialize for GetInputFocusReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        reader.read_u8()?; // Reply
+        let revert_to = match reader.read_u8()? {
+            0 => RevertTo::None,
+            1 => RevertTo::PointerRoot,
+            2 => RevertTo::Parent,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Invalid revert-to in GetInputFocus reply",
+                ))
+            }
+        };
+        skip(&mut reader, 6)?;
+        let focus = reader.read_u32_ne()?;
+        skip(&mut reader, 20)?;
+
+        Ok(Some(GetInputFocusReply { revert_to, focus }))
+    }
+}
+
+impl ReplyRequest for GetInputFocus {
+    type Reply = GetInputFocusReply;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        AllowEvents, AllowEventsMode, GetInputFocus, GetInputFocusReply, GetMotionEvents,
+        GetMotionEventsReply, GrabButton, GrabKey, GrabMode, KeyButMask, ModMask, QueryPointer,
+        QueryPointerReply, RevertTo, TimeCoord, UngrabButton, UngrabKey, ANY_BUTTON, ANY_KEY,
+        ANY_MODIFIER,
+    };
+    use crate::io;
+    use crate::protocol::{Deserialize, EventMask, Serialize, Timestamp};
+
+    #[test]
+    fn test_key_but_mask() {
+        let mask = KeyButMask::from(0x0014);
+
+        assert!(mask.control());
+        assert!(mask.mod2());
+        assert!(!mask.shift());
+        assert!(!mask.lock());
+        assert!(!mask.mod1());
+        assert!(!mask.mod3());
+        assert!(!mask.mod4());
+        assert!(!mask.mod5());
+        assert!(!mask.button1());
+
+        let mask = KeyButMask::from(0x1101);
+        assert!(mask.shift());
+        assert!(mask.button1());
+        assert!(mask.button5());
+        assert!(!mask.button2());
+        assert!(!mask.button3());
+        assert!(!mask.button4());
+    }
+
+    #[test]
+    fn test_serialize_grab_button() {
+        const EXPECTED: &[u8] = b"\x1c\x01\x06\x00\x00\x01\x00\x00\x04\x00\x00\x01\
+                                  \x00\x00\x00\x00\x02\x00\x00\x04\x03\x00\x08\x00";
+        let mut write_buf = vec![];
+
+        GrabButton {
+            owner_events: true,
+            grab_window: 0x100,
+            event_mask: EventMask::BUTTON_PRESS,
+            pointer_mode: GrabMode::Synchronous,
+            keyboard_mode: GrabMode::Asynchronous,
+            confine_to: 0,
+            cursor: 0x04000002,
+            button: 3,
+            modifiers: ModMask::MOD1,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_serialize_grab_button_non_pointer_events() {
+        let mut write_buf = vec![];
+
+        let err = GrabButton {
+            owner_events: true,
+            grab_window: 0x100,
+            event_mask: EventMask::BUTTON_PRESS | EventMask::KEY_PRESS,
+            pointer_mode: GrabMode::Asynchronous,
+            keyboard_mode: GrabMode::Asynchronous,
+            confine_to: 0,
+            cursor: 0,
+            button: 1,
+            modifiers: ModMask::empty(),
+        }
+        .serialize(&mut write_buf)
+        .unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_serialize_ungrab_button() {
+        const EXPECTED: &[u8] = b"\x1d\x00\x03\x00\x00\x01\x00\x00\x00\x80\x00\x00";
+        let mut write_buf = vec![];
+
+        UngrabButton {
+            button: ANY_BUTTON,
+            grab_window: 0x100,
+            modifiers: ANY_MODIFIER,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_serialize_grab_key() {
+        const EXPECTED: &[u8] = b"\x21\x00\x04\x00\x00\x01\x00\x00\x44\x00\x18\x01\x00\x00\x00\x00";
+        let mut write_buf = vec![];
+
+        GrabKey {
+            owner_events: false,
+            grab_window: 0x100,
+            modifiers: ModMask::MOD4 | ModMask::CONTROL,
+            key: 24,
+            pointer_mode: GrabMode::Asynchronous,
+            keyboard_mode: GrabMode::Synchronous,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_serialize_ungrab_key() {
+        const EXPECTED: &[u8] = b"\x22\x18\x03\x00\x00\x01\x00\x00\x00\x80\x00\x00\
+                                  \x22\x00\x03\x00\x00\x01\x00\x00\x40\x00\x00\x00";
+        let mut write_buf = vec![];
+
+        UngrabKey {
+            key: 24,
+            grab_window: 0x100,
+            modifiers: ANY_MODIFIER,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+        UngrabKey {
+            key: ANY_KEY,
+            grab_window: 0x100,
+            modifiers: ModMask::MOD4,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_serialize_allow_events() {
+        const EXPECTED: &[u8] = b"\x23\x02\x02\x00\x00\x00\x00\x00\x23\x07\x02\x00\x10\x27\x00\x00";
+        let mut write_buf = vec![];
+
+        AllowEvents {
+            mode: AllowEventsMode::ReplayPointer,
+            time: Timestamp::current(),
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+        AllowEvents {
+            mode: AllowEventsMode::SyncBoth,
+            time: Timestamp::from_millis(10000),
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_serialize_query_pointer() {
+        const EXPECTED: &[u8] = b"\x26\x00\x02\x00\x00\x01\x00\x00";
+        let mut write_buf = vec![];
+
+        QueryPointer { window: 0x100 }
+            .serialize(&mut write_buf)
+            .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_deserialize_query_pointer_reply() {
+        let reply = b"\x01\x01\x01\x00\x00\x00\x00\x00\x00\x01\x00\x00\x01\x02\x00\x00\
+                      \x64\x00\xc8\x00\x0a\x00\x14\x00\x04\x01\x00\x00\x00\x00\x00\x00";
+
+        let reply = QueryPointerReply::deserialize(&reply[..]).unwrap().unwrap();
+
+        assert_eq!(
+            reply,
+            QueryPointerReply {
+                same_screen: true,
+                root: 0x100,
+                child: 0x201,
+                root_x: 100,
+                root_y: 200,
+                win_x: 10,
+                win_y: 20,
+                mask: KeyButMask(0x0104),
+            }
+        );
+        assert!(reply.mask.control());
+        assert!(reply.mask.button1());
+    }
+
+    #[test]
+    fn test_serialize_get_motion_events() {
+        const EXPECTED: &[u8] = b"\x27\x00\x04\x00\x01\x02\x00\x00\x10\x27\x00\x00\x00\x00\x00\x00";
+        let mut write_buf = vec![];
+
+        GetMotionEvents {
+            window: 0x201,
+            start: Timestamp::from_millis(10000),
+            stop: Timestamp::current(),
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_deserialize_get_motion_events_reply() {
+        let mut reply = b"\x01\x00\x01\x00\x06\x00\x00\x00\x03\x00\x00\x00".to_vec();
+        reply.extend(vec![0; 20]);
+        reply.extend(b"\x10\x27\x00\x00\x0a\x00\x14\x00");
+        reply.extend(b"\x20\x27\x00\x00\x0c\x00\x13\x00");
+        reply.extend(b"\x30\x27\x00\x00\xfe\xff\x12\x00");
+
+        assert_eq!(
+            GetMotionEventsReply::deserialize(&reply[..])
+                .unwrap()
+                .unwrap(),
+            GetMotionEventsReply {
+                events: vec![
+                    TimeCoord {
+                        time: 10000,
+                        x: 10,
+                        y: 20,
+                    },
+                    TimeCoord {
+                        time: 10016,
+                        x: 12,
+                        y: 19,
+                    },
+                    TimeCoord {
+                        time: 10032,
+                        x: -2,
+                        y: 18,
+                    },
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn test_get_input_focus() {
+        let mut write_buf = vec![];
+        GetInputFocus.serialize(&mut write_buf).unwrap();
+        assert_eq!(write_buf, b"\x2b\x00\x01\x00");
+
+        let mut reply = b"\x01\x02\x01\x00\x00\x00\x00\x00\x01\x02\x00\x00".to_vec();
+        reply.extend(vec![0; 20]);
+
+        assert_eq!(
+            GetInputFocusReply::deserialize(&reply[..])
+                .unwrap()
+                .unwrap(),
+            GetInputFocusReply {
+                revert_to: RevertTo::Parent,
+                focus: 0x201,
+            }
+        );
+    }
+}
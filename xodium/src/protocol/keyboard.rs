@@ -0,0 +1,266 @@
+use super::{skip, Deserialize, ReplyRequest, Request};
+use crate::io::{self, Read, Write};
+use crate::utils::{ReadBytesExt, WriteBytesExt};
+use alloc::vec::Vec;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LedMode {
+    Off = 0,
+    On = 1,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AutoRepeatMode {
+    Off = 0,
+    On = 1,
+    Default = 2,
+}
+
+/// Values changed by ChangeKeyboardControl.
+/// Only the values which were set are sent to the server.
+///
+/// `led` and `key` select which led or key `led_mode` and `auto_repeat_mode`
+/// apply to, without them modes are applied to all leds or keys.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KeyboardControlValues {
+    key_click_percent: Option<i8>,
+    bell_percent: Option<i8>,
+    bell_pitch: Option<i16>,
+    bell_duration: Option<i16>,
+    led: Option<u8>,
+    led_mode: Option<LedMode>,
+    key: Option<u8>,
+    auto_repeat_mode: Option<AutoRepeatMode>,
+}
+
+impl KeyboardControlValues {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Key click volume 0-100, -1 restores default.
+    pub fn key_click_percent(mut self, key_click_percent: i8) -> Self {
+        self.key_click_percent = Some(key_click_percent);
+        self
+    }
+
+    /// Bell volume 0-100, -1 restores default.
+    pub fn bell_percent(mut self, bell_percent: i8) -> Self {
+        self.bell_percent = Some(bell_percent);
+        self
+    }
+
+    /// Bell pitch in Hz, -1 restores default.
+    pub fn bell_pitch(mut self, bell_pitch: i16) -> Self {
+        self.bell_pitch = Some(bell_pitch);
+        self
+    }
+
+    /// Bell duration in milliseconds, -1 restores default.
+    pub fn bell_duration(mut self, bell_duration: i16) -> Self {
+        self.bell_duration = Some(bell_duration);
+        self
+    }
+
+    pub fn led(mut self, led: u8) -> Self {
+        self.led = Some(led);
+        self
+    }
+
+    pub fn led_mode(mut self, led_mode: LedMode) -> Self {
+        self.led_mode = Some(led_mode);
+        self
+    }
+
+    pub fn key(mut self, key: u8) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    pub fn auto_repeat_mode(mut self, auto_repeat_mode: AutoRepeatMode) -> Self {
+        self.auto_repeat_mode = Some(auto_repeat_mode);
+        self
+    }
+
+    /// Values in protocol order, paired with their value-mask bit.
+    fn values(&self) -> Vec<(u32, u32)> {
+        [
+            (0x0001, self.key_click_percent.map(|v| i32::from(v) as u32)),
+            (0x0002, self.bell_percent.map(|v| i32::from(v) as u32)),
+            (0x0004, self.bell_pitch.map(|v| i32::from(v) as u32)),
+            (0x0008, self.bell_duration.map(|v| i32::from(v) as u32)),
+            (0x0010, self.led.map(u32::from)),
+            (0x0020, self.led_mode.map(|v| v as u32)),
+            (0x0040, self.key.map(u32::from)),
+            (0x0080, self.auto_repeat_mode.map(|v| v as u32)),
+        ]
+        .iter()
+        .filter_map(|(bit, value)| value.map(|v| (*bit, v)))
+        .collect()
+    }
+}
+
+/// Change keyboard bell, leds and auto repeat settings.
+pub struct ChangeKeyboardControl {
+    pub values: KeyboardControlValues,
+}
+
+// 1     102                             opcode
+// 1                                     unused
+// 2     2+n                             request length
+// 4     BITMASK                         value-mask
+// 4n    LISTofVALUE                     value-list
+impl Request for ChangeKeyboardControl {
+    const OPCODE: u8 = 102;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        let values = self.values.values();
+        let value_mask = values.iter().fold(0, |mask, (bit, _)| mask | bit);
+
+        writer.write_u32_ne(value_mask)?;
+
+        for (_, value) in values {
+            writer.write_u32_ne(value)?;
+        }
+
+        Ok(0)
+    }
+}
+
+/// Query current keyboard control settings.
+pub struct GetKeyboardControl;
+
+// 1     103                             opcode
+// 1                                     unused
+// 2     1                               request length
+impl Request for GetKeyboardControl {
+    const OPCODE: u8 = 103;
+
+    fn serialize_body<W: Write>(&self, _writer: W) -> io::Result<u8> {
+        Ok(0)
+    }
+}
+
+/// Keyboard control settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyboardControl {
+    pub global_auto_repeat: bool,
+    pub led_mask: u32,
+    pub key_click_percent: u8,
+    pub bell_percent: u8,
+    pub bell_pitch: u16,
+    pub bell_duration: u16,
+    /// Bit vector of keys with auto repeat enabled,
+    /// bit `k % 8` of byte `k / 8` corresponds to keycode `k`.
+    pub auto_repeats: [u8; 32],
+}
+
+impl KeyboardControl {
+    /// Whether auto repeat is enabled for the key.
+    /// Keys repeat only if global auto repeat is enabled as well.
+    pub fn auto_repeat(&self, keycode: u8) -> bool {
+        let byte = self.auto_repeats[usize::from(keycode / 8)];
+        byte & (1 << (keycode % 8)) != 0
+    }
+}
+
+// 1     1                               Reply
+// 1                                     global-auto-repeat
+//       0     Off
+//       1     On
+// 2     CARD16                          sequence number
+// 4     5                               reply length
+// 4     CARD32                          led-mask
+// 1     CARD8                           key-click-percent
+// 1     CARD8                           bell-percent
+// 2     CARD16                          bell-pitch
+// 2     CARD16                          bell-duration
+// 2                                     unused
+// 32    LISTofCARD8                     auto-repeats
+impl Deserialize for KeyboardControl {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        reader.read_u8()?; // Reply
+        let global_auto_repeat = reader.read_u8()? != 0;
+        skip(&mut reader, 6)?;
+        let led_mask = reader.read_u32_ne()?;
+        let key_click_percent = reader.read_u8()?;
+        let bell_percent = reader.read_u8()?;
+        let bell_pitch = reader.read_u16_ne()?;
+        let bell_duration = reader.read_u16_ne()?;
+        skip(&mut reader, 2)?;
+        let mut auto_repeats = [0; 32];
+        reader.read_exact(&mut auto_repeats)?;
+
+        Ok(Some(KeyboardControl {
+            global_auto_repeat,
+            led_mask,
+            key_click_percent,
+            bell_percent,
+            bell_pitch,
+            bell_duration,
+            auto_repeats,
+        }))
+    }
+}
+
+impl ReplyRequest for GetKeyboardControl {
+    type Reply = KeyboardControl;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        AutoRepeatMode, ChangeKeyboardControl, GetKeyboardControl, KeyboardControl,
+        KeyboardControlValues,
+    };
+    use crate::protocol::{Deserialize, Serialize};
+
+    #[test]
+    fn test_serialize_get_keyboard_control() {
+        let mut write_buf = vec![];
+        GetKeyboardControl.serialize(&mut write_buf).unwrap();
+        assert_eq!(write_buf, b"\x67\x00\x01\x00");
+    }
+
+    #[test]
+    fn test_serialize_change_keyboard_control() {
+        const EXPECTED: &[u8] = b"\x66\x00\x04\x00\x82\x00\x00\x00\xff\xff\xff\xff\x00\x00\x00\x00";
+        let mut write_buf = vec![];
+
+        ChangeKeyboardControl {
+            values: KeyboardControlValues::new()
+                .auto_repeat_mode(AutoRepeatMode::Off)
+                .bell_percent(-1),
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_deserialize_keyboard_control() {
+        let mut reply =
+            b"\x01\x01\x01\x00\x05\x00\x00\x00\x02\x00\x00\x00\x00\x32\x90\x01\x64\x00\x00\x00"
+                .to_vec();
+        let mut auto_repeats = [0xff; 32];
+        // Keycodes 8 and 66 do not repeat
+        auto_repeats[1] = 0xfe;
+        auto_repeats[8] = 0xfb;
+        reply.extend(&auto_repeats);
+
+        let control = KeyboardControl::deserialize(&reply[..]).unwrap().unwrap();
+
+        assert!(control.global_auto_repeat);
+        assert_eq!(control.led_mask, 2);
+        assert_eq!(control.key_click_percent, 0);
+        assert_eq!(control.bell_percent, 50);
+        assert_eq!(control.bell_pitch, 400);
+        assert_eq!(control.bell_duration, 100);
+        assert!(!control.auto_repeat(8));
+        assert!(control.auto_repeat(9));
+        assert!(control.auto_repeat(65));
+        assert!(!control.auto_repeat(66));
+        assert!(control.auto_repeat(255));
+    }
+}
@@ -0,0 +1,318 @@
+use super::event::{GENERIC_EVENT, SEND_EVENT_MASK};
+use super::{Deserialize, Event, Serialize, XError};
+use crate::framed::{Decoder, Encoder};
+use crate::io;
+use core::convert::TryFrom;
+
+/// Size of every error and event, and minimal size of reply.
+pub(crate) const MESSAGE_SIZE: usize = 32;
+
+const ERROR: u8 = 0;
+const REPLY: u8 = 1;
+
+/// Message sent by X server after setup.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Message {
+    Error(XError),
+    /// Complete reply, including its 32 byte header.
+    Reply {
+        sequence_number: u16,
+        data: Vec<u8>,
+    },
+    /// Reply longer than [max_reply_len](MessageCodec::max_reply_len),
+    /// its data is skipped.
+    ReplyTooLong {
+        sequence_number: u16,
+    },
+    Event(Event),
+}
+
+/// Direction of traced protocol traffic.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    /// Message read from X server.
+    In,
+    /// Request written to X server.
+    Out,
+}
+
+/// Callback invoked with raw bytes of every request and message.
+pub type Trace = Box<dyn FnMut(Direction, &[u8])>;
+
+/// Default limit of reply size, including its 32 byte header.
+pub(crate) const DEFAULT_MAX_REPLY_LEN: usize = 4 * 1024 * 1024;
+
+/// Codec used for regular protocol messages once setup is done.
+pub(crate) struct MessageCodec {
+    pub trace: Option<Trace>,
+    /// Replies and generic events declaring larger length
+    /// are skipped instead of being buffered.
+    pub max_reply_len: usize,
+    /// Bytes of skipped message not received yet.
+    skip_len: usize,
+}
+
+impl Default for MessageCodec {
+    fn default() -> Self {
+        MessageCodec {
+            trace: None,
+            max_reply_len: DEFAULT_MAX_REPLY_LEN,
+            skip_len: 0,
+        }
+    }
+}
+
+impl MessageCodec {
+    /// Declared length of the first message in `src`, `None` if its header
+    /// is not complete yet. Message may be longer than `src`.
+    fn message_len(src: &[u8]) -> Option<usize> {
+        if src.len() < MESSAGE_SIZE {
+            return None;
+        }
+
+        let is_generic_event = src[0] & !SEND_EVENT_MASK == GENERIC_EVENT;
+        if src[0] != REPLY && !is_generic_event {
+            return Some(MESSAGE_SIZE);
+        }
+
+        // Length of data following 32 bytes is stored in bytes 4-7 in 4-byte units.
+        let length = u32::from_ne_bytes([src[4], src[5], src[6], src[7]]);
+        Some(
+            usize::try_from(length)
+                .ok()
+                .and_then(|length| length.checked_mul(4))
+                .and_then(|length| length.checked_add(MESSAGE_SIZE))
+                .unwrap_or(usize::MAX),
+        )
+    }
+
+    /// Whether `src` holds a complete event, possibly following replies and errors.
+    pub fn has_complete_event(&self, src: &[u8]) -> bool {
+        let mut src = &src[self.skip_len.min(src.len())..];
+        while let Some(message_len) = Self::message_len(src) {
+            if message_len > self.max_reply_len {
+                // Skipped without being decoded
+                src = &src[message_len.min(src.len())..];
+            } else if src.len() < message_len {
+                return false;
+            } else if src[0] == ERROR || src[0] == REPLY {
+                src = &src[message_len..];
+            } else {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Drop bytes of skipped message received so far,
+    /// whether it is skipped completely.
+    fn skip(&mut self, src: &mut Vec<u8>) -> bool {
+        let len = self.skip_len.min(src.len());
+        src.drain(..len);
+        self.skip_len -= len;
+        self.skip_len == 0
+    }
+}
+
+impl<T: Serialize> Encoder<T> for MessageCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: T, dst: &mut Vec<u8>) -> Result<(), Self::Error> {
+        let start = dst.len();
+        // Partially serialized request would corrupt the stream
+        if let Err(e) = item.serialize(&mut *dst) {
+            dst.truncate(start);
+            return Err(e);
+        }
+
+        if let Some(trace) = &mut self.trace {
+            trace(Direction::Out, &dst[start..]);
+        }
+
+        Ok(())
+    }
+}
+
+impl Decoder for MessageCodec {
+    type Item = Message;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut Vec<u8>) -> Result<Option<Self::Item>, Self::Error> {
+        let message_len = loop {
+            if !self.skip(src) {
+                return Ok(None);
+            }
+
+            let message_len = match Self::message_len(src) {
+                Some(message_len) => message_len,
+                None => return Ok(None),
+            };
+            if message_len <= self.max_reply_len {
+                break message_len;
+            }
+
+            // Data is dropped as it arrives, so the following messages can still be read
+            self.skip_len = message_len;
+            if src[0] == REPLY {
+                let sequence_number = u16::from_ne_bytes([src[2], src[3]]);
+                self.skip(src);
+                return Ok(Some(Message::ReplyTooLong { sequence_number }));
+            }
+        };
+        if src.len() < message_len {
+            return Ok(None);
+        }
+
+        let message: Vec<u8> = src.drain(0..message_len).collect();
+
+        if let Some(trace) = &mut self.trace {
+            trace(Direction::In, &message);
+        }
+
+        match message[0] {
+            ERROR => Ok(XError::deserialize(&message[..])?.map(Message::Error)),
+            REPLY => Ok(Some(Message::Reply {
+                sequence_number: u16::from_ne_bytes([message[2], message[3]]),
+                data: message,
+            })),
+            _ => Ok(Event::deserialize(&message[..])?.map(Message::Event)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Message, MessageCodec};
+    use crate::framed::{Decoder, Encoder};
+    use crate::io::{self, Write};
+    use crate::protocol::{Event, Serialize};
+
+    #[test]
+    fn test_encode_error_discards_partial_request() {
+        struct Invalid;
+
+        impl Serialize for Invalid {
+            fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+                writer.write_all(b"\x01\x00")?;
+                Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid"))
+            }
+        }
+
+        let mut codec = MessageCodec::default();
+        let mut dst = b"\x7f\x00\x01\x00".to_vec();
+
+        assert!(codec.encode(Invalid, &mut dst).is_err());
+        assert_eq!(dst, b"\x7f\x00\x01\x00");
+    }
+
+    #[test]
+    fn test_decode_partial_reply() {
+        let mut codec = MessageCodec::default();
+        let mut src = b"\x01\x00\x07\x00\x01\x00\x00\x00".to_vec();
+        src.extend(vec![0; 24]);
+        src.extend(b"\x0a\x0b");
+
+        assert!(codec.decode(&mut src).unwrap().is_none());
+
+        src.extend(b"\x0c\x0d\xff");
+        match codec.decode(&mut src).unwrap() {
+            Some(Message::Reply {
+                sequence_number,
+                data,
+            }) => {
+                assert_eq!(sequence_number, 7);
+                assert_eq!(data.len(), 36);
+                assert_eq!(&data[32..], b"\x0a\x0b\x0c\x0d");
+            }
+            other => panic!("Unexpected message: {:?}", other),
+        }
+        assert_eq!(src, b"\xff");
+    }
+
+    #[test]
+    fn test_decode_too_long_reply() {
+        let mut codec = MessageCodec::default();
+        let mut src = b"\x01\x00\x07\x00\xff\xff\xff\xff".to_vec();
+        src.extend(vec![0; 24]);
+
+        assert_eq!(
+            codec.decode(&mut src).unwrap(),
+            Some(Message::ReplyTooLong { sequence_number: 7 })
+        );
+        assert!(src.is_empty());
+
+        // Reply of exactly maximum size is accepted
+        codec = MessageCodec::default();
+        codec.max_reply_len = 36;
+        let mut src = b"\x01\x00\x07\x00\x01\x00\x00\x00".to_vec();
+        src.extend(vec![0; 28]);
+        assert!(matches!(
+            codec.decode(&mut src).unwrap(),
+            Some(Message::Reply { .. })
+        ));
+
+        // Longer one is skipped as it arrives, followed by an event
+        let mut src = b"\x01\x00\x08\x00\x02\x00\x00\x00".to_vec();
+        src.extend(vec![0; 28]);
+        assert_eq!(
+            codec.decode(&mut src).unwrap(),
+            Some(Message::ReplyTooLong { sequence_number: 8 })
+        );
+        assert!(codec.decode(&mut src).unwrap().is_none());
+        assert!(!codec.has_complete_event(&src));
+
+        src.extend(vec![0; 4]);
+        src.extend(vec![12; 32]);
+        assert!(codec.has_complete_event(&src));
+        assert_eq!(
+            codec.decode(&mut src).unwrap(),
+            Some(Message::Event(Event::Unknown(vec![12; 32])))
+        );
+        assert!(src.is_empty());
+    }
+
+    #[test]
+    fn test_decode_event() {
+        let mut codec = MessageCodec::default();
+        let mut src = vec![12; 33];
+
+        assert_eq!(
+            codec.decode(&mut src).unwrap(),
+            Some(Message::Event(Event::Unknown(vec![12; 32])))
+        );
+        assert_eq!(src.len(), 1);
+    }
+
+    #[test]
+    fn test_decode_generic_event() {
+        let mut codec = MessageCodec::default();
+        let mut src = b"\x23\x8c\x05\x00\x02\x00\x00\x00\x01\x00".to_vec();
+        src.resize(32, 0);
+
+        assert!(codec.decode(&mut src).unwrap().is_none());
+
+        src.extend(vec![0; 8]);
+        match codec.decode(&mut src).unwrap() {
+            Some(Message::Event(Event::Generic(event))) => {
+                assert_eq!(event.extension, 0x8c);
+                assert_eq!(event.data.len(), 40);
+            }
+            other => panic!("Unexpected message: {:?}", other),
+        }
+        assert!(src.is_empty());
+    }
+
+    #[test]
+    fn test_has_complete_event() {
+        let codec = MessageCodec::default();
+        // Reply with 4 bytes of data followed by an event
+        let mut src = b"\x01\x00\x01\x00\x01\x00\x00\x00".to_vec();
+        src.resize(36, 0);
+        src.extend(vec![12; 32]);
+
+        assert!(!codec.has_complete_event(&src[..20]));
+        assert!(!codec.has_complete_event(&src[..36]));
+        assert!(!codec.has_complete_event(&src[..67]));
+        assert!(codec.has_complete_event(&src));
+    }
+}
@@ -0,0 +1,101 @@
+use super::Request;
+use crate::io::{self, Write};
+use alloc::vec::Vec;
+
+/// Stop processing requests of all other clients until UngrabServer.
+pub struct GrabServer;
+
+// 1     36                              opcode
+// 1                                     unused
+// 2     1                               request length
+impl Request for GrabServer {
+    const OPCODE: u8 = 36;
+
+    fn serialize_body<W: Write>(&self, _writer: W) -> io::Result<u8> {
+        Ok(0)
+    }
+}
+
+/// Restart processing requests of other clients.
+pub struct UngrabServer;
+
+// 1     37                              opcode
+// 1                                     unused
+// 2     1                               request length
+impl Request for UngrabServer {
+    const OPCODE: u8 = 37;
+
+    fn serialize_body<W: Write>(&self, _writer: W) -> io::Result<u8> {
+        Ok(0)
+    }
+}
+
+/// Request doing nothing, which makes it cheapest way to keep connection alive.
+#[derive(Default)]
+pub struct NoOperation {
+    /// Data ignored by X server, padded to 4 bytes.
+    pub data: Vec<u8>,
+}
+
+// 1     127                             opcode
+// 1                                     unused
+// 2     1+n                             request length
+// 4n                                    unused
+impl Request for NoOperation {
+    const OPCODE: u8 = 127;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_all(&self.data)?;
+        Ok(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GrabServer, NoOperation, UngrabServer};
+    use crate::protocol::Serialize;
+
+    #[test]
+    fn test_serialize_grab_server() {
+        const EXPECTED: &[u8] = b"\x24\x00\x01\x00";
+        let mut write_buf = vec![];
+
+        GrabServer.serialize(&mut write_buf).unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_serialize_ungrab_server() {
+        const EXPECTED: &[u8] = b"\x25\x00\x01\x00";
+        let mut write_buf = vec![];
+
+        UngrabServer.serialize(&mut write_buf).unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_serialize_no_operation() {
+        const EXPECTED: &[u8] = b"\x7f\x00\x01\x00";
+        let mut write_buf = vec![];
+
+        NoOperation::default().serialize(&mut write_buf).unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_serialize_no_operation_data() {
+        const EXPECTED: &[u8] = b"\x7f\x00\x03\x00\x01\x02\x03\x04\x05\x00\x00\x00";
+        let mut write_buf = vec![];
+
+        NoOperation {
+            data: b"\x01\x02\x03\x04\x05".to_vec(),
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+}
@@ -0,0 +1,82 @@
+use super::Request;
+use crate::io::{self, Write};
+use crate::utils::WriteBytesExt;
+
+/// Create pixmap of the given depth on the screen of `drawable`.
+pub struct CreatePixmap {
+    pub depth: u8,
+    pub pid: u32,
+    pub drawable: u32,
+    pub width: u16,
+    pub height: u16,
+}
+
+// 1     53                              opcode
+// 1     CARD8                           depth
+// 2     4                               request length
+// 4     PIXMAP                          pid
+// 4     DRAWABLE                        drawable
+// 2     CARD16                          width
+// 2     CARD16                          height
+impl Request for CreatePixmap {
+    const OPCODE: u8 = 53;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.pid)?;
+        writer.write_u32_ne(self.drawable)?;
+        writer.write_u16_ne(self.width)?;
+        writer.write_u16_ne(self.height)?;
+        Ok(self.depth)
+    }
+}
+
+/// Remove pixmap id, storage is freed once nothing, e.g. a cursor, refers to it.
+pub struct FreePixmap {
+    pub pixmap: u32,
+}
+
+// 1     54                              opcode
+// 1                                     unused
+// 2     2                               request length
+// 4     PIXMAP                          pixmap
+impl Request for FreePixmap {
+    const OPCODE: u8 = 54;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.pixmap)?;
+        Ok(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CreatePixmap, FreePixmap};
+    use crate::protocol::Serialize;
+
+    #[test]
+    fn test_serialize_create_pixmap() {
+        const EXPECTED: &[u8] = b"\x35\x01\x04\x00\x01\x00\x00\x04\x00\x01\x00\x00\x10\x00\x20\x00";
+        let mut write_buf = vec![];
+
+        CreatePixmap {
+            depth: 1,
+            pid: 0x04000001,
+            drawable: 0x100,
+            width: 16,
+            height: 32,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_serialize_free_pixmap() {
+        let mut write_buf = vec![];
+        FreePixmap { pixmap: 0x04000001 }
+            .serialize(&mut write_buf)
+            .unwrap();
+        assert_eq!(write_buf, b"\x36\x00\x02\x00\x01\x00\x00\x04");
+    }
+}
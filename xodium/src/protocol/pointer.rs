@@ -0,0 +1,182 @@
+use super::{skip, Deserialize, ReplyRequest, Request};
+use crate::io::{self, Read, Write};
+use crate::utils::ReadBytesExt;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// Result of changing pointer or keyboard mapping.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MappingStatus {
+    Success = 0,
+    /// Some of buttons or keys to change are pressed, nothing is changed.
+    Busy = 1,
+    /// X server refused the mapping.
+    Failed = 2,
+}
+
+impl MappingStatus {
+    pub(crate) fn try_from(raw_status: u8) -> Option<MappingStatus> {
+        match raw_status {
+            0 => Some(MappingStatus::Success),
+            1 => Some(MappingStatus::Busy),
+            2 => Some(MappingStatus::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// Set logical buttons of physical pointer buttons,
+/// element i of map is button for physical button i + 1, 0 disables it.
+pub struct SetPointerMapping {
+    pub map: Vec<u8>,
+}
+
+// 1     116                             opcode
+// 1     n                               length of map
+// 2     1+(n+p)/4                       request length
+// n     LISTofCARD8                     map
+// p                                     unused, p=pad(n)
+impl Request for SetPointerMapping {
+    const OPCODE: u8 = 116;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        let map_len = u8::try_from(self.map.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Pointer map is too long"))?;
+
+        writer.write_all(&self.map)?;
+        Ok(map_len)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SetPointerMappingReply {
+    pub status: MappingStatus,
+}
+
+// 1     1                               Reply
+// 1                                     status
+//       0     Success
+//       1     Busy
+// 2     CARD16                          sequence number
+// 4     0                               reply length
+// 24                                    unused
+impl Deserialize for SetPointerMappingReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        reader.read_u8()?; // Reply
+        let status = MappingStatus::try_from(reader.read_u8()?)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Invalid mapping status"))?;
+        skip(&mut reader, 30)?;
+
+        Ok(Some(SetPointerMappingReply { status }))
+    }
+}
+
+impl ReplyRequest for SetPointerMapping {
+    type Reply = SetPointerMappingReply;
+}
+
+/// Query logical buttons of physical pointer buttons.
+pub struct GetPointerMapping;
+
+// 1     117                             opcode
+// 1                                     unused
+// 2     1                               request length
+impl Request for GetPointerMapping {
+    const OPCODE: u8 = 117;
+
+    fn serialize_body<W: Write>(&self, _writer: W) -> io::Result<u8> {
+        Ok(0)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct GetPointerMappingReply {
+    pub map: Vec<u8>,
+}
+
+// 1     1                               Reply
+// 1     n                               length of map
+// 2     CARD16                          sequence number
+// 4     (n+p)/4                         reply length
+// 24                                    unused
+// n     LISTofCARD8                     map
+// p                                     unused, p=pad(n)
+impl Deserialize for GetPointerMappingReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        reader.read_u8()?; // Reply
+        let map_len = reader.read_u8()?;
+        skip(&mut reader, 30)?;
+        let map = reader.read_string8(map_len.into())?;
+
+        Ok(Some(GetPointerMappingReply { map }))
+    }
+}
+
+impl ReplyRequest for GetPointerMapping {
+    type Reply = GetPointerMappingReply;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        GetPointerMapping, GetPointerMappingReply, MappingStatus, SetPointerMapping,
+        SetPointerMappingReply,
+    };
+    use crate::protocol::{Deserialize, Serialize};
+
+    #[test]
+    fn test_serialize_set_pointer_mapping() {
+        // Left-handed: buttons 1 and 3 swapped
+        const EXPECTED: &[u8] = b"\x74\x03\x02\x00\x03\x02\x01\x00";
+        let mut write_buf = vec![];
+
+        SetPointerMapping { map: vec![3, 2, 1] }
+            .serialize(&mut write_buf)
+            .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_deserialize_set_pointer_mapping_reply() {
+        let mut reply = b"\x01\x01\x01\x00\x00\x00\x00\x00".to_vec();
+        reply.extend(vec![0; 24]);
+
+        assert_eq!(
+            SetPointerMappingReply::deserialize(&reply[..])
+                .unwrap()
+                .unwrap(),
+            SetPointerMappingReply {
+                status: MappingStatus::Busy,
+            }
+        );
+    }
+
+    #[test]
+    fn test_serialize_get_pointer_mapping() {
+        const EXPECTED: &[u8] = b"\x75\x00\x01\x00";
+        let mut write_buf = vec![];
+
+        GetPointerMapping.serialize(&mut write_buf).unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_deserialize_get_pointer_mapping_reply() {
+        let mut reply = b"\x01\x03\x01\x00\x01\x00\x00\x00".to_vec();
+        reply.extend(vec![0; 24]);
+        reply.extend(b"\x01\x02\x03\x00");
+        // Next message must not be consumed
+        reply.extend(b"\xff");
+        let mut reader = &reply[..];
+
+        assert_eq!(
+            GetPointerMappingReply::deserialize(&mut reader)
+                .unwrap()
+                .unwrap(),
+            GetPointerMappingReply { map: vec![1, 2, 3] }
+        );
+        assert_eq!(reader, b"\xff");
+    }
+}
@@ -0,0 +1,348 @@
+//! Present extension, tear-free presentation of pixmaps synchronized to vertical blank.
+
+use super::{request_length, skip, Deserialize, Event, ReplyRequest, Serialize};
+use crate::io::{self, Read, Write};
+use crate::utils::{ReadBytesExt, WriteBytesExt};
+
+pub const PRESENT_EXTENSION_NAME: &str = "Present";
+
+const PRESENT_QUERY_VERSION_OPCODE: u8 = 0;
+const PRESENT_PIXMAP_OPCODE: u8 = 1;
+const PRESENT_SELECT_INPUT_OPCODE: u8 = 3;
+
+/// PresentCompleteNotify generic event type.
+const PRESENT_COMPLETE_NOTIFY: u16 = 1;
+
+/// Present events delivered to client, set by PresentSelectInput.
+pub const PRESENT_CONFIGURE_NOTIFY_MASK: u32 = 0x1;
+pub const PRESENT_COMPLETE_NOTIFY_MASK: u32 = 0x2;
+pub const PRESENT_IDLE_NOTIFY_MASK: u32 = 0x4;
+
+/// Negotiate Present version, client sends highest version it supports.
+pub struct PresentQueryVersion {
+    pub major_opcode: u8,
+    pub major_version: u32,
+    pub minor_version: u32,
+}
+
+// 1     CARD8                           major opcode
+// 1     0                               Present opcode
+// 2     3                               request length
+// 4     CARD32                          major version
+// 4     CARD32                          minor version
+impl Serialize for PresentQueryVersion {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(PRESENT_QUERY_VERSION_OPCODE)?;
+        writer.write_u16_ne(3)?;
+        writer.write_u32_ne(self.major_version)?;
+        writer.write_u32_ne(self.minor_version)?;
+        Ok(())
+    }
+}
+
+/// Present version supported by both X server and client.
+#[derive(Debug, PartialEq)]
+pub struct PresentQueryVersionReply {
+    pub major_version: u32,
+    pub minor_version: u32,
+}
+
+// 1     1                               Reply
+// 1                                     unused
+// 2     CARD16                          sequence number
+// 4     0                               reply length
+// 4     CARD32                          major version
+// 4     CARD32                          minor version
+// 16                                    unused
+impl Deserialize for PresentQueryVersionReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        skip(&mut reader, 8)?; // reply header
+        let major_version = reader.read_u32_ne()?;
+        let minor_version = reader.read_u32_ne()?;
+        skip(&mut reader, 16)?;
+
+        Ok(Some(PresentQueryVersionReply {
+            major_version,
+            minor_version,
+        }))
+    }
+}
+
+impl ReplyRequest for PresentQueryVersion {
+    type Reply = PresentQueryVersionReply;
+}
+
+/// Present pixmap contents in window once target MSC (vertical blank counter) is reached.
+/// If `target_msc` is already reached, the first MSC for which
+/// `msc % divisor == remainder` is used instead, unless divisor is 0.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PresentPixmap {
+    pub major_opcode: u8,
+    pub window: u32,
+    pub pixmap: u32,
+    /// Identifies the request in PresentCompleteNotify event.
+    pub serial: u32,
+    /// Region of pixmap with valid contents, 0 (None) for the whole pixmap.
+    pub valid: u32,
+    /// Region of pixmap to update, 0 (None) for the whole pixmap.
+    pub update: u32,
+    pub x_offset: i16,
+    pub y_offset: i16,
+    /// CRTC to synchronize with, 0 (None) lets X server pick one.
+    pub target_crtc: u32,
+    /// Fence to wait for before presenting, 0 (None) for no waiting.
+    pub wait_fence: u32,
+    /// Fence triggered once pixmap is idle, 0 (None) for no fence.
+    pub idle_fence: u32,
+    pub options: u32,
+    pub target_msc: u64,
+    pub divisor: u64,
+    pub remainder: u64,
+}
+
+// 1     CARD8                           major opcode
+// 1     1                               Present opcode
+// 2     18+2n                           request length
+// 4     WINDOW                          window
+// 4     PIXMAP                          pixmap
+// 4     CARD32                          serial
+// 4     REGION                          valid-area
+// 4     REGION                          update-area
+// 2     INT16                           x-off
+// 2     INT16                           y-off
+// 4     CRTC                            target-crtc
+// 4     SYNCFENCE                       wait-fence
+// 4     SYNCFENCE                       idle-fence
+// 4     CARD32                          options
+// 4                                     unused
+// 8     CARD64                          target-msc
+// 8     CARD64                          divisor
+// 8     CARD64                          remainder
+// 8n    LISTofPRESENTNOTIFY             notifies
+impl Serialize for PresentPixmap {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(PRESENT_PIXMAP_OPCODE)?;
+        writer.write_u16_ne(request_length(18)?)?;
+        writer.write_u32_ne(self.window)?;
+        writer.write_u32_ne(self.pixmap)?;
+        writer.write_u32_ne(self.serial)?;
+        writer.write_u32_ne(self.valid)?;
+        writer.write_u32_ne(self.update)?;
+        writer.write_i16_ne(self.x_offset)?;
+        writer.write_i16_ne(self.y_offset)?;
+        writer.write_u32_ne(self.target_crtc)?;
+        writer.write_u32_ne(self.wait_fence)?;
+        writer.write_u32_ne(self.idle_fence)?;
+        writer.write_u32_ne(self.options)?;
+        writer.write_u32_ne(0)?; // pad
+        writer.write_u64_ne(self.target_msc)?;
+        writer.write_u64_ne(self.divisor)?;
+        writer.write_u64_ne(self.remainder)?;
+        Ok(())
+    }
+}
+
+/// Select Present events of window, events are delivered as generic events.
+pub struct PresentSelectInput {
+    pub major_opcode: u8,
+    /// Id identifying the selection, allocated by client.
+    pub eid: u32,
+    pub window: u32,
+    pub event_mask: u32,
+}
+
+// 1     CARD8                           major opcode
+// 1     3                               Present opcode
+// 2     4                               request length
+// 4     EVENTID                         eid
+// 4     WINDOW                          window
+// 4     SETofPRESENTEVENT               event-mask
+impl Serialize for PresentSelectInput {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(PRESENT_SELECT_INPUT_OPCODE)?;
+        writer.write_u16_ne(4)?;
+        writer.write_u32_ne(self.eid)?;
+        writer.write_u32_ne(self.window)?;
+        writer.write_u32_ne(self.event_mask)?;
+        Ok(())
+    }
+}
+
+/// What PresentCompleteNotify reports completion of.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PresentCompleteKind {
+    Pixmap = 0,
+    NotifyMsc = 1,
+}
+
+/// How pixmap was presented.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PresentCompleteMode {
+    Copy = 0,
+    Flip = 1,
+    /// Pixmap was skipped in favor of a later one.
+    Skip = 2,
+    SuboptimalCopy = 3,
+}
+
+/// Presentation requested by PresentPixmap was completed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PresentCompleteNotifyEvent {
+    pub kind: PresentCompleteKind,
+    pub mode: PresentCompleteMode,
+    pub eid: u32,
+    pub window: u32,
+    pub serial: u32,
+    /// Time of presentation in microseconds.
+    pub ust: u64,
+    /// MSC pixmap was presented at.
+    pub msc: u64,
+}
+
+impl PresentCompleteNotifyEvent {
+    /// Decode event if it is PresentCompleteNotify of Present with specified major opcode.
+    pub fn from_event(event: &Event, major_opcode: u8) -> io::Result<Option<Self>> {
+        match event {
+            Event::Generic(event)
+                if event.extension == major_opcode
+                    && event.event_type == PRESENT_COMPLETE_NOTIFY =>
+            {
+                PresentCompleteNotifyEvent::deserialize(&event.data[..])
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+fn invalid_value(what: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Invalid {} in PresentCompleteNotify", what),
+    )
+}
+
+// 1     35                              XGE event type
+// 1     CARD8                           Present extension opcode
+// 2     CARD16                          sequence number
+// 4     2                               length
+// 2     1                               PresentCompleteNotify
+// 1     CARD8                           kind
+// 1     CARD8                           mode
+// 4     EVENTID                         event id
+// 4     WINDOW                          window
+// 4     CARD32                          serial
+// 8     CARD64                          ust
+// 8     CARD64                          msc
+impl Deserialize for PresentCompleteNotifyEvent {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        skip(&mut reader, 10)?; // generic event header
+        let kind = match reader.read_u8()? {
+            0 => PresentCompleteKind::Pixmap,
+            1 => PresentCompleteKind::NotifyMsc,
+            _ => return Err(invalid_value("kind")),
+        };
+        let mode = match reader.read_u8()? {
+            0 => PresentCompleteMode::Copy,
+            1 => PresentCompleteMode::Flip,
+            2 => PresentCompleteMode::Skip,
+            3 => PresentCompleteMode::SuboptimalCopy,
+            _ => return Err(invalid_value("mode")),
+        };
+
+        Ok(Some(PresentCompleteNotifyEvent {
+            kind,
+            mode,
+            eid: reader.read_u32_ne()?,
+            window: reader.read_u32_ne()?,
+            serial: reader.read_u32_ne()?,
+            ust: reader.read_u64_ne()?,
+            msc: reader.read_u64_ne()?,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        PresentCompleteKind, PresentCompleteMode, PresentCompleteNotifyEvent, PresentPixmap,
+        PresentQueryVersionReply,
+    };
+    use crate::protocol::{Deserialize, Event, GenericEvent, Serialize};
+
+    #[test]
+    fn test_deserialize_query_version_reply() {
+        let mut reply =
+            b"\x01\x00\x01\x00\x00\x00\x00\x00\x01\x00\x00\x00\x02\x00\x00\x00".to_vec();
+        reply.extend(vec![0; 16]);
+
+        assert_eq!(
+            PresentQueryVersionReply::deserialize(&reply[..])
+                .unwrap()
+                .unwrap(),
+            PresentQueryVersionReply {
+                major_version: 1,
+                minor_version: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_serialize_present_pixmap() {
+        const EXPECTED: &[u8] = b"\x8c\x01\x12\x00\x01\x02\x00\x00\x05\x00\x00\x04\
+                                  \x07\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\
+                                  \x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\
+                                  \x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\
+                                  \x64\x00\x00\x00\x00\x00\x00\x00\x02\x00\x00\x00\
+                                  \x00\x00\x00\x00\x01\x00\x00\x00\x00\x00\x00\x00";
+        let mut write_buf = vec![];
+
+        PresentPixmap {
+            major_opcode: 140,
+            window: 0x201,
+            pixmap: 0x04000005,
+            serial: 7,
+            target_msc: 100,
+            divisor: 2,
+            remainder: 1,
+            ..PresentPixmap::default()
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf.len(), 18 * 4);
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_complete_notify_from_event() {
+        let mut data = b"\x23\x8c\x05\x00\x02\x00\x00\x00\x01\x00\x00\x01".to_vec();
+        data.extend(b"\x01\x00\x00\x04\x01\x02\x00\x00\x07\x00\x00\x00");
+        data.extend(b"\x40\x42\x0f\x00\x00\x00\x00\x00\x65\x00\x00\x00\x00\x00\x00\x00");
+        let event = Event::Generic(GenericEvent {
+            extension: 0x8c,
+            event_type: 1,
+            data,
+        });
+
+        assert_eq!(
+            PresentCompleteNotifyEvent::from_event(&event, 0x8c)
+                .unwrap()
+                .unwrap(),
+            PresentCompleteNotifyEvent {
+                kind: PresentCompleteKind::Pixmap,
+                mode: PresentCompleteMode::Flip,
+                eid: 0x04000001,
+                window: 0x201,
+                serial: 7,
+                ust: 1_000_000,
+                msc: 101,
+            }
+        );
+        assert!(PresentCompleteNotifyEvent::from_event(&event, 0x8d)
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,447 @@
+use super::{skip, Deserialize, ReplyRequest, Request};
+use crate::io::{self, Read, Write};
+use crate::utils::{ReadBytesExt, WriteBytesExt};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// How ChangeProperty combines new data with the current value.
+///
+/// Prepended or appended data must have type and format of the existing property,
+/// otherwise X server responds with Match error. Missing property is created as with Replace.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PropertyMode {
+    Replace = 0,
+    Prepend = 1,
+    Append = 2,
+}
+
+/// Change window property value, property is created if it does not exist.
+pub struct ChangeProperty {
+    pub mode: PropertyMode,
+    pub window: u32,
+    pub property: u32,
+    pub property_type: u32,
+    /// Unit size of data in bits: 8, 16 or 32.
+    pub format: u8,
+    /// Data in native byte order, length is multiple of format unit size.
+    pub data: Vec<u8>,
+}
+
+// 1     18                              opcode
+// 1                                     mode
+//       0     Replace
+//       1     Prepend
+//       2     Append
+// 2     6+(n+p)/4                       request length
+// 4     WINDOW                          window
+// 4     ATOM                            property
+// 4     ATOM                            type
+// 1     CARD8                           format
+// 3                                     unused
+// 4     CARD32                          length of data in format units
+//                                       (= n for format = 8)
+//                                       (= n/2 for format = 16)
+//                                       (= n/4 for format = 32)
+// n     LISTofBYTE                      data
+//                                       (n is a multiple of 2 for format = 16)
+//                                       (n is a multiple of 4 for format = 32)
+// p                                     unused, p=pad(n)
+impl Request for ChangeProperty {
+    const OPCODE: u8 = 18;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        let unit_size = usize::from(self.format / 8);
+        if unit_size == 0 || !self.data.len().is_multiple_of(unit_size) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Property data does not match its format",
+            ));
+        }
+
+        writer.write_u32_ne(self.window)?;
+        writer.write_u32_ne(self.property)?;
+        writer.write_u32_ne(self.property_type)?;
+        writer.write_u8(self.format)?;
+        writer.write_all(&[0; 3])?; // pad
+        writer.write_u32_ne((self.data.len() / unit_size) as u32)?;
+        writer.write_all(&self.data)?;
+        Ok(self.mode as u8)
+    }
+}
+
+/// Delete window property, PropertyNotify is generated only if it existed.
+pub struct DeleteProperty {
+    pub window: u32,
+    pub property: u32,
+}
+
+// 1     19                              opcode
+// 1                                     unused
+// 2     3                               request length
+// 4     WINDOW                          window
+// 4     ATOM                            property
+impl Request for DeleteProperty {
+    const OPCODE: u8 = 19;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.window)?;
+        writer.write_u32_ne(self.property)?;
+        Ok(0)
+    }
+}
+
+/// Get part of window property value.
+pub struct GetProperty {
+    pub delete: bool,
+    pub window: u32,
+    pub property: u32,
+    /// 0 (AnyPropertyType) matches any type.
+    pub property_type: u32,
+    /// Offset in 4-byte units.
+    pub long_offset: u32,
+    /// Maximum length in 4-byte units.
+    pub long_length: u32,
+}
+
+// 1     20                              opcode
+// 1     BOOL                            delete
+// 2     6                               request length
+// 4     WINDOW                          window
+// 4     ATOM                            property
+// 4     ATOM                            type
+//       0     AnyPropertyType
+// 4     CARD32                          long-offset
+// 4     CARD32                          long-length
+impl Request for GetProperty {
+    const OPCODE: u8 = 20;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.window)?;
+        writer.write_u32_ne(self.property)?;
+        writer.write_u32_ne(self.property_type)?;
+        writer.write_u32_ne(self.long_offset)?;
+        writer.write_u32_ne(self.long_length)?;
+        Ok(self.delete.into())
+    }
+}
+
+/// Part of window property value returned by GetProperty.
+///
+/// Property which does not exist has type 0 (None) and format 0.
+/// If type does not match the requested one, value is empty
+/// and `bytes_after` contains the whole property length.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Property {
+    pub property_type: u32,
+    /// Unit size of value in bits: 8, 16 or 32.
+    pub format: u8,
+    /// Bytes of property left after the returned value.
+    pub bytes_after: u32,
+    pub value: Vec<u8>,
+}
+
+// 1     1                               Reply
+// 1     CARD8                           format
+// 2     CARD16                          sequence number
+// 4     (n+p)/4                         reply length
+// 4     ATOM                            type
+//       0     None
+// 4     CARD32                          bytes-after
+// 4     CARD32                          length of value in format units
+//                                       (= 0 for format = 0)
+//                                       (= n for format = 8)
+//                                       (= n/2 for format = 16)
+//                                       (= n/4 for format = 32)
+// 12                                    unused
+// n     LISTofBYTE                      value
+// p                                     unused, p=pad(n)
+impl Deserialize for Property {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        reader.read_u8()?; // Reply
+        let format = reader.read_u8()?;
+        skip(&mut reader, 6)?;
+        let property_type = reader.read_u32_ne()?;
+        let bytes_after = reader.read_u32_ne()?;
+        let value_len = (reader.read_u32_ne()? as usize)
+            .checked_mul(usize::from(format / 8))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Property is too long"))?;
+        skip(&mut reader, 12)?;
+
+        let value = reader.read_string8(value_len)?;
+
+        Ok(Some(Property {
+            property_type,
+            format,
+            bytes_after,
+            value,
+        }))
+    }
+}
+
+impl ReplyRequest for GetProperty {
+    type Reply = Property;
+}
+
+/// List properties defined on the window.
+pub struct ListProperties {
+    pub window: u32,
+}
+
+// 1     21                              opcode
+// 1                                     unused
+// 2     2                               request length
+// 4     WINDOW                          window
+impl Request for ListProperties {
+    const OPCODE: u8 = 21;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.window)?;
+        Ok(0)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ListPropertiesReply {
+    pub atoms: Vec<u32>,
+}
+
+// 1     1                               Reply
+// 1                                     unused
+// 2     CARD16                          sequence number
+// 4     n                               reply length
+// 2     n                               number of ATOMs in atoms
+// 22                                    unused
+// 4n    LISTofATOM                      atoms
+impl Deserialize for ListPropertiesReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        skip(&mut reader, 8)?; // reply header
+        let atoms_len = reader.read_u16_ne()?;
+        skip(&mut reader, 22)?;
+
+        let atoms = (0..atoms_len)
+            .map(|_| reader.read_u32_ne())
+            .collect::<io::Result<_>>()?;
+
+        Ok(Some(ListPropertiesReply { atoms }))
+    }
+}
+
+impl ReplyRequest for ListProperties {
+    type Reply = ListPropertiesReply;
+}
+
+/// Rotate values of window properties, value of `properties[i]` moves to
+/// `properties[(i + delta) mod n]`. All properties have to exist and be distinct.
+pub struct RotateProperties {
+    pub window: u32,
+    pub delta: i16,
+    pub properties: Vec<u32>,
+}
+
+// 1     114                             opcode
+// 1                                     unused
+// 2     3+n                             request length
+// 4     WINDOW                          window
+// 2     n                               number of properties
+// 2     INT16                           delta
+// 4n    LISTofATOM                      properties
+impl Request for RotateProperties {
+    const OPCODE: u8 = 114;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        let properties_len = u16::try_from(self.properties.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Too many properties"))?;
+
+        writer.write_u32_ne(self.window)?;
+        writer.write_u16_ne(properties_len)?;
+        writer.write_i16_ne(self.delta)?;
+        for property in &self.properties {
+            writer.write_u32_ne(*property)?;
+        }
+        Ok(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ChangeProperty, GetProperty, ListProperties, ListPropertiesReply, Property, PropertyMode,
+        RotateProperties,
+    };
+    use crate::protocol::{Deserialize, Serialize};
+
+    #[test]
+    fn test_serialize_rotate_properties() {
+        const EXPECTED: &[u8] = b"\x72\x00\x06\x00\x01\x02\x00\x00\x03\x00\x01\x00\
+                                  \x30\x01\x00\x00\x31\x01\x00\x00\x32\x01\x00\x00";
+        let mut write_buf = vec![];
+
+        RotateProperties {
+            window: 0x201,
+            delta: 1,
+            properties: vec![0x130, 0x131, 0x132],
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+        assert_eq!(write_buf.len(), 6 * 4);
+    }
+
+    #[test]
+    fn test_serialize_rotate_properties_negative_delta() {
+        let mut write_buf = vec![];
+
+        RotateProperties {
+            window: 0x201,
+            delta: -2,
+            properties: vec![0x130, 0x131, 0x132],
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(&write_buf[8..12], b"\x03\x00\xfe\xff");
+    }
+
+    #[test]
+    fn test_serialize_change_property() {
+        const EXPECTED: &[u8] = b"\x12\x02\x08\x00\x01\x02\x00\x00\x27\x00\x00\x00\
+                                  \x1f\x00\x00\x00\x10\x00\x00\x00\x03\x00\x00\x00\
+                                  \x01\x00\x02\x00\x03\x00\x00\x00";
+        let mut write_buf = vec![];
+
+        ChangeProperty {
+            mode: PropertyMode::Append,
+            window: 0x201,
+            property: 39,
+            property_type: 31,
+            format: 16,
+            data: b"\x01\x00\x02\x00\x03\x00".to_vec(),
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_serialize_change_property_prepend() {
+        const EXPECTED: &[u8] = b"\x12\x01\x07\x00\x01\x02\x00\x00\x27\x00\x00\x00\
+                                  \x1f\x00\x00\x00\x08\x00\x00\x00\x02\x00\x00\x00\
+                                  ab\0\0";
+        let mut write_buf = vec![];
+
+        ChangeProperty {
+            mode: PropertyMode::Prepend,
+            window: 0x201,
+            property: 39,
+            property_type: 31,
+            format: 8,
+            data: b"ab".to_vec(),
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_serialize_change_property_invalid_format() {
+        let mut write_buf = vec![];
+
+        let result = ChangeProperty {
+            mode: PropertyMode::Replace,
+            window: 0x201,
+            property: 39,
+            property_type: 6,
+            format: 32,
+            data: b"\x01\x00".to_vec(),
+        }
+        .serialize(&mut write_buf);
+
+        assert!(result.is_err());
+        assert!(write_buf.is_empty());
+    }
+
+    #[test]
+    fn test_serialize_get_property() {
+        const EXPECTED: &[u8] = b"\x14\x01\x06\x00\x01\x02\x00\x00\x27\x00\x00\x00\
+                                  \x1f\x00\x00\x00\x02\x00\x00\x00\x00\x04\x00\x00";
+        let mut write_buf = vec![];
+
+        GetProperty {
+            delete: true,
+            window: 0x201,
+            property: 39,
+            property_type: 31,
+            long_offset: 2,
+            long_length: 1024,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_deserialize_property_format_16() {
+        let mut reply = b"\x01\x10\x01\x00\x01\x00\x00\x00\x13\x00\x00\x00\
+                          \x04\x00\x00\x00\x03\x00\x00\x00"
+            .to_vec();
+        reply.extend(vec![0; 12]);
+        reply.extend(b"\x01\x00\x02\x00\x03\x00\x00\x00");
+
+        assert_eq!(
+            Property::deserialize(&reply[..]).unwrap().unwrap(),
+            Property {
+                property_type: 19,
+                format: 16,
+                bytes_after: 4,
+                value: b"\x01\x00\x02\x00\x03\x00".to_vec(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_deserialize_property_bogus_length() {
+        // Value length far beyond the reply must not be allocated upfront
+        let mut reply = b"\x01\x20\x01\x00\x00\x00\x00\x00\x06\x00\x00\x00\
+                          \x00\x00\x00\x00\xff\xff\xff\xff"
+            .to_vec();
+        reply.extend(vec![0; 12]);
+
+        assert!(Property::deserialize(&reply[..]).is_err());
+    }
+
+    #[test]
+    fn test_serialize_list_properties() {
+        const EXPECTED: &[u8] = b"\x15\x00\x02\x00\x01\x02\x00\x00";
+        let mut write_buf = vec![];
+
+        ListProperties { window: 0x201 }
+            .serialize(&mut write_buf)
+            .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_deserialize_list_properties_reply() {
+        let mut reply = b"\x01\x00\x01\x00\x03\x00\x00\x00\x03\x00".to_vec();
+        reply.extend(vec![0; 22]);
+        reply.extend(b"\x27\x00\x00\x00\x43\x00\x00\x00\x2a\x01\x00\x00");
+        // Next message must not be consumed
+        reply.extend(b"\xff");
+        let mut reader = &reply[..];
+
+        assert_eq!(
+            ListPropertiesReply::deserialize(&mut reader)
+                .unwrap()
+                .unwrap(),
+            ListPropertiesReply {
+                atoms: vec![39, 67, 0x12a],
+            }
+        );
+        assert_eq!(reader, b"\xff");
+    }
+}
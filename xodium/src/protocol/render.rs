@@ -0,0 +1,301 @@
+//! RENDER extension, compositing of pictures with alpha blending.
+
+use super::{request_length, skip, Deserialize, Rectangle, ReplyRequest, Serialize};
+use crate::io::{self, Read, Write};
+use crate::utils::{ReadBytesExt, WriteBytesExt};
+use alloc::vec::Vec;
+
+pub const RENDER_EXTENSION_NAME: &str = "RENDER";
+
+const RENDER_QUERY_VERSION_OPCODE: u8 = 0;
+const RENDER_COMPOSITE_OPCODE: u8 = 8;
+const RENDER_FILL_RECTANGLES_OPCODE: u8 = 26;
+
+/// Porter-Duff operator combining source with destination.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PictOp {
+    Clear = 0,
+    /// Replace destination with source.
+    Src = 1,
+    Dst = 2,
+    /// Blend source on top of destination using source alpha.
+    Over = 3,
+    OverReverse = 4,
+    In = 5,
+    InReverse = 6,
+    Out = 7,
+    OutReverse = 8,
+    Atop = 9,
+    AtopReverse = 10,
+    Xor = 11,
+    Add = 12,
+    Saturate = 13,
+}
+
+/// Color with 16 bit channels, alpha is not premultiplied.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Rgba16 {
+    pub red: u16,
+    pub green: u16,
+    pub blue: u16,
+    /// 0 is fully transparent, 0xffff is opaque.
+    pub alpha: u16,
+}
+
+// 2     CARD16                          red
+// 2     CARD16                          green
+// 2     CARD16                          blue
+// 2     CARD16                          alpha
+impl Serialize for Rgba16 {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u16_ne(self.red)?;
+        writer.write_u16_ne(self.green)?;
+        writer.write_u16_ne(self.blue)?;
+        writer.write_u16_ne(self.alpha)?;
+        Ok(())
+    }
+}
+
+/// Negotiate RENDER version, client sends highest version it supports.
+pub struct RenderQueryVersion {
+    pub major_opcode: u8,
+    pub major_version: u32,
+    pub minor_version: u32,
+}
+
+// 1     CARD8                           major opcode
+// 1     0                               Render opcode
+// 2     3                               request length
+// 4     CARD32                          major version
+// 4     CARD32                          minor version
+impl Serialize for RenderQueryVersion {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(RENDER_QUERY_VERSION_OPCODE)?;
+        writer.write_u16_ne(3)?;
+        writer.write_u32_ne(self.major_version)?;
+        writer.write_u32_ne(self.minor_version)?;
+        Ok(())
+    }
+}
+
+/// RENDER version supported by both X server and client.
+#[derive(Debug, PartialEq)]
+pub struct RenderQueryVersionReply {
+    pub major_version: u32,
+    pub minor_version: u32,
+}
+
+// 1     1                               Reply
+// 1                                     unused
+// 2     CARD16                          sequence number
+// 4     0                               reply length
+// 4     CARD32                          major version
+// 4     CARD32                          minor version
+// 16                                    unused
+impl Deserialize for RenderQueryVersionReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        skip(&mut reader, 8)?; // reply header
+        let major_version = reader.read_u32_ne()?;
+        let minor_version = reader.read_u32_ne()?;
+        skip(&mut reader, 16)?;
+
+        Ok(Some(RenderQueryVersionReply {
+            major_version,
+            minor_version,
+        }))
+    }
+}
+
+impl ReplyRequest for RenderQueryVersion {
+    type Reply = RenderQueryVersionReply;
+}
+
+/// Combine rectangle of source picture, multiplied by alpha of mask picture,
+/// with rectangle of destination picture.
+pub struct RenderComposite {
+    pub major_opcode: u8,
+    pub op: PictOp,
+    pub src: u32,
+    /// 0 (None) to use source as is.
+    pub mask: u32,
+    pub dst: u32,
+    pub src_x: i16,
+    pub src_y: i16,
+    pub mask_x: i16,
+    pub mask_y: i16,
+    pub dst_x: i16,
+    pub dst_y: i16,
+    pub width: u16,
+    pub height: u16,
+}
+
+// 1     CARD8                           major opcode
+// 1     8                               Render opcode
+// 2     9                               request length
+// 1     PICTOP                          op
+// 3                                     unused
+// 4     PICTURE                         src
+// 4     PICTURE                         mask
+//       0     None
+// 4     PICTURE                         dst
+// 2     INT16                           src-x
+// 2     INT16                           src-y
+// 2     INT16                           mask-x
+// 2     INT16                           mask-y
+// 2     INT16                           dst-x
+// 2     INT16                           dst-y
+// 2     CARD16                          width
+// 2     CARD16                          height
+impl Serialize for RenderComposite {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(RENDER_COMPOSITE_OPCODE)?;
+        writer.write_u16_ne(9)?;
+        writer.write_u8(self.op as u8)?;
+        writer.write_all(&[0; 3])?;
+        writer.write_u32_ne(self.src)?;
+        writer.write_u32_ne(self.mask)?;
+        writer.write_u32_ne(self.dst)?;
+        writer.write_i16_ne(self.src_x)?;
+        writer.write_i16_ne(self.src_y)?;
+        writer.write_i16_ne(self.mask_x)?;
+        writer.write_i16_ne(self.mask_y)?;
+        writer.write_i16_ne(self.dst_x)?;
+        writer.write_i16_ne(self.dst_y)?;
+        writer.write_u16_ne(self.width)?;
+        writer.write_u16_ne(self.height)?;
+        Ok(())
+    }
+}
+
+/// Combine solid color with rectangles of destination picture.
+pub struct RenderFillRectangles {
+    pub major_opcode: u8,
+    pub op: PictOp,
+    pub dst: u32,
+    pub color: Rgba16,
+    pub rectangles: Vec<Rectangle>,
+}
+
+// 1     CARD8                           major opcode
+// 1     26                              Render opcode
+// 2     5+2n                            request length
+// 1     PICTOP                          op
+// 3                                     unused
+// 4     PICTURE                         dst
+// 8     COLOR                           color
+// 8n    LISTofRECTANGLE                 rects
+impl Serialize for RenderFillRectangles {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(RENDER_FILL_RECTANGLES_OPCODE)?;
+        writer.write_u16_ne(request_length(5 + 2 * self.rectangles.len())?)?;
+        writer.write_u8(self.op as u8)?;
+        writer.write_all(&[0; 3])?;
+        writer.write_u32_ne(self.dst)?;
+        self.color.serialize(&mut writer)?;
+        for rectangle in &self.rectangles {
+            rectangle.serialize(&mut writer)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        PictOp, RenderComposite, RenderFillRectangles, RenderQueryVersion, RenderQueryVersionReply,
+        Rgba16,
+    };
+    use crate::protocol::{Deserialize, Rectangle, Serialize};
+
+    #[test]
+    fn test_serialize_render_query_version() {
+        const EXPECTED: &[u8] = b"\x8b\x00\x03\x00\x00\x00\x00\x00\x0b\x00\x00\x00";
+        let mut write_buf = vec![];
+
+        RenderQueryVersion {
+            major_opcode: 139,
+            major_version: 0,
+            minor_version: 11,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_deserialize_render_query_version_reply() {
+        let mut reply =
+            b"\x01\x00\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x0b\x00\x00\x00".to_vec();
+        reply.extend(vec![0; 16]);
+
+        assert_eq!(
+            RenderQueryVersionReply::deserialize(&reply[..])
+                .unwrap()
+                .unwrap(),
+            RenderQueryVersionReply {
+                major_version: 0,
+                minor_version: 11,
+            }
+        );
+    }
+
+    #[test]
+    fn test_serialize_render_composite_over() {
+        const EXPECTED: &[u8] = b"\x8b\x08\x09\x00\x03\x00\x00\x00\x01\x00\x00\x04\
+                                  \x00\x00\x00\x00\x02\x00\x00\x04\x00\x00\x00\x00\
+                                  \x00\x00\x00\x00\x0a\x00\x14\x00\x40\x00\x20\x00";
+        let mut write_buf = vec![];
+
+        RenderComposite {
+            major_opcode: 139,
+            op: PictOp::Over,
+            src: 0x0400_0001,
+            mask: 0,
+            dst: 0x0400_0002,
+            src_x: 0,
+            src_y: 0,
+            mask_x: 0,
+            mask_y: 0,
+            dst_x: 10,
+            dst_y: 20,
+            width: 64,
+            height: 32,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_serialize_render_fill_rectangles_translucent() {
+        const EXPECTED: &[u8] = b"\x8b\x1a\x09\x00\x03\x00\x00\x00\x02\x00\x00\x04\
+                                  \xff\xff\x00\x00\x00\x00\x00\x80\
+                                  \x00\x00\x00\x00\x10\x00\x10\x00\x20\x00\x20\x00\x08\x00\x08\x00";
+        let mut write_buf = vec![];
+
+        RenderFillRectangles {
+            major_opcode: 139,
+            op: PictOp::Over,
+            dst: 0x0400_0002,
+            color: Rgba16 {
+                red: 0xffff,
+                green: 0,
+                blue: 0,
+                alpha: 0x8000,
+            },
+            rectangles: vec![
+                Rectangle::from((0, 0, 16, 16)),
+                Rectangle::from((32, 32, 8, 8)),
+            ],
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+}
@@ -0,0 +1,194 @@
+use super::{skip, Deserialize, ReplyRequest, Request};
+use crate::io::{self, Read, Write};
+use crate::utils::{ReadBytesExt, WriteBytesExt};
+
+/// Screen saver option which may be left to X server default.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScreenSaverPreference {
+    No = 0,
+    Yes = 1,
+    Default = 2,
+}
+
+/// Configure screen saver.
+pub struct SetScreenSaver {
+    /// Seconds of inactivity before screen saver activates,
+    /// 0 disables screen saver and -1 restores the default.
+    pub timeout: i16,
+    /// Seconds between screen saver pattern changes,
+    /// 0 disables changes and -1 restores the default.
+    pub interval: i16,
+    /// Blank the screen if hardware supports it, instead of drawing a pattern.
+    pub prefer_blanking: ScreenSaverPreference,
+    /// Allow screen saver which can't restore window contents without Expose events.
+    pub allow_exposures: ScreenSaverPreference,
+}
+
+// 1     107                             opcode
+// 1                                     unused
+// 2     3                               request length
+// 2     INT16                           timeout
+// 2     INT16                           interval
+// 1                                     prefer-blanking
+//       0     No
+//       1     Yes
+//       2     Default
+// 1                                     allow-exposures
+//       0     No
+//       1     Yes
+//       2     Default
+// 2                                     unused
+impl Request for SetScreenSaver {
+    const OPCODE: u8 = 107;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_i16_ne(self.timeout)?;
+        writer.write_i16_ne(self.interval)?;
+        writer.write_u8(self.prefer_blanking as u8)?;
+        writer.write_u8(self.allow_exposures as u8)?;
+        writer.write_u16_ne(0)?; // pad
+        Ok(0)
+    }
+}
+
+/// Query screen saver configuration.
+pub struct GetScreenSaver;
+
+// 1     108                             opcode
+// 1                                     unused
+// 2     1                               request length
+impl Request for GetScreenSaver {
+    const OPCODE: u8 = 108;
+
+    fn serialize_body<W: Write>(&self, _writer: W) -> io::Result<u8> {
+        Ok(0)
+    }
+}
+
+/// Screen saver configuration, timeout and interval are in seconds.
+#[derive(Debug, PartialEq)]
+pub struct GetScreenSaverReply {
+    pub timeout: u16,
+    pub interval: u16,
+    pub prefer_blanking: bool,
+    pub allow_exposures: bool,
+}
+
+// 1     1                               Reply
+// 1                                     unused
+// 2     CARD16                          sequence number
+// 4     0                               reply length
+// 2     CARD16                          timeout
+// 2     CARD16                          interval
+// 1                                     prefer-blanking
+//       0     No
+//       1     Yes
+// 1                                     allow-exposures
+//       0     No
+//       1     Yes
+// 18                                    unused
+impl Deserialize for GetScreenSaverReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        skip(&mut reader, 8)?; // reply header
+        let timeout = reader.read_u16_ne()?;
+        let interval = reader.read_u16_ne()?;
+        let prefer_blanking = reader.read_u8()? != 0;
+        let allow_exposures = reader.read_u8()? != 0;
+        skip(&mut reader, 18)?;
+
+        Ok(Some(GetScreenSaverReply {
+            timeout,
+            interval,
+            prefer_blanking,
+            allow_exposures,
+        }))
+    }
+}
+
+impl ReplyRequest for GetScreenSaver {
+    type Reply = GetScreenSaverReply;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScreenSaverMode {
+    /// Deactivate screen saver if it's active and restart inactivity timer.
+    Reset = 0,
+    /// Activate screen saver even if it's disabled.
+    Activate = 1,
+}
+
+/// Activate or deactivate screen saver.
+pub struct ForceScreenSaver {
+    pub mode: ScreenSaverMode,
+}
+
+// 1     115                             opcode
+// 1                                     mode
+//       0     Reset
+//       1     Activate
+// 2     1                               request length
+impl Request for ForceScreenSaver {
+    const OPCODE: u8 = 115;
+
+    fn serialize_body<W: Write>(&self, _writer: W) -> io::Result<u8> {
+        Ok(self.mode as u8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ForceScreenSaver, GetScreenSaverReply, ScreenSaverMode, ScreenSaverPreference,
+        SetScreenSaver,
+    };
+    use crate::protocol::{Deserialize, Serialize};
+
+    #[test]
+    fn test_serialize_force_screen_saver() {
+        const EXPECTED: &[u8] = b"\x73\x00\x01\x00";
+        let mut write_buf = vec![];
+
+        ForceScreenSaver {
+            mode: ScreenSaverMode::Reset,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_serialize_set_screen_saver() {
+        const EXPECTED: &[u8] = b"\x6b\x00\x03\x00\xff\xff\x58\x02\x00\x02\x00\x00";
+        let mut write_buf = vec![];
+
+        SetScreenSaver {
+            timeout: -1,
+            interval: 600,
+            prefer_blanking: ScreenSaverPreference::No,
+            allow_exposures: ScreenSaverPreference::Default,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_deserialize_get_screen_saver_reply() {
+        let mut reply = b"\x01\x00\x01\x00\x00\x00\x00\x00\x58\x02\x58\x02\x01\x00".to_vec();
+        reply.extend(vec![0; 18]);
+
+        assert_eq!(
+            GetScreenSaverReply::deserialize(&reply[..])
+                .unwrap()
+                .unwrap(),
+            GetScreenSaverReply {
+                timeout: 600,
+                interval: 600,
+                prefer_blanking: true,
+                allow_exposures: false,
+            }
+        );
+    }
+}
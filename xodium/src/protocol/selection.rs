@@ -0,0 +1,64 @@
+use super::{Request, Serialize, Timestamp};
+use crate::io::{self, Write};
+use crate::utils::WriteBytesExt;
+
+/// Ask selection owner to convert selection to `target` type
+/// and store it in `property` of `requestor` window.
+/// Requestor gets SelectionNotify once conversion is done or failed.
+pub struct ConvertSelection {
+    pub requestor: u32,
+    pub selection: u32,
+    pub target: u32,
+    /// 0 (None) is obsolete, owner then uses `target` as property.
+    pub property: u32,
+    /// CurrentTime or timestamp of the event triggered conversion.
+    pub time: Timestamp,
+}
+
+// 1     24                              opcode
+// 1                                     unused
+// 2     6                               request length
+// 4     WINDOW                          requestor
+// 4     ATOM                            selection
+// 4     ATOM                            target
+// 4     ATOM                            property
+//       0     None
+// 4     TIMESTAMP                       time
+//       0     CurrentTime
+impl Request for ConvertSelection {
+    const OPCODE: u8 = 24;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.requestor)?;
+        writer.write_u32_ne(self.selection)?;
+        writer.write_u32_ne(self.target)?;
+        writer.write_u32_ne(self.property)?;
+        self.time.serialize(&mut writer)?;
+        Ok(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ConvertSelection;
+    use crate::protocol::{Serialize, Timestamp};
+
+    #[test]
+    fn test_serialize_convert_selection() {
+        const EXPECTED: &[u8] = b"\x18\x00\x06\x00\x00\x00\x00\x04\x01\x00\x00\x00\
+                                  \x2a\x01\x00\x00\x01\x00\x00\x00\x00\x00\x00\x00";
+        let mut write_buf = vec![];
+
+        ConvertSelection {
+            requestor: 0x0400_0000,
+            selection: 1,
+            target: 0x12a,
+            property: 1,
+            time: Timestamp::current(),
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+}
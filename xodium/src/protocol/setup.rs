@@ -0,0 +1,470 @@
+use super::{skip, ByteOrder, Deserialize};
+use crate::io::{self, Read};
+use crate::utils::ReadBytesExt;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// Pixmap format supported by X server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Format {
+    pub depth: u8,
+    pub bits_per_pixel: u8,
+    pub scanline_pad: u8,
+}
+
+/// Visual type available for a depth.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VisualType {
+    pub visual_id: u32,
+    /// 0 StaticGray, 1 GrayScale, 2 StaticColor,
+    /// 3 PseudoColor, 4 TrueColor, 5 DirectColor
+    pub class: u8,
+    pub bits_per_rgb_value: u8,
+    pub colormap_entries: u16,
+    pub red_mask: u32,
+    pub green_mask: u32,
+    pub blue_mask: u32,
+}
+
+/// Depth allowed on a screen, along with its visuals.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Depth {
+    pub depth: u8,
+    pub visuals: Vec<VisualType>,
+}
+
+/// Screen (root window) information sent by X server during setup.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Screen {
+    pub root: u32,
+    pub default_colormap: u32,
+    pub white_pixel: u32,
+    pub black_pixel: u32,
+    pub current_input_masks: u32,
+    pub width_in_pixels: u16,
+    pub height_in_pixels: u16,
+    pub width_in_millimeters: u16,
+    pub height_in_millimeters: u16,
+    pub min_installed_maps: u16,
+    pub max_installed_maps: u16,
+    pub root_visual: u32,
+    /// 0 Never, 1 WhenMapped, 2 Always
+    pub backing_stores: u8,
+    pub save_unders: bool,
+    pub root_depth: u8,
+    pub allowed_depths: Vec<Depth>,
+}
+
+/// Information sent by X server on successful connection setup.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Setup {
+    pub protocol_major_version: u16,
+    pub protocol_minor_version: u16,
+    pub release_number: u32,
+    pub resource_id_base: u32,
+    pub resource_id_mask: u32,
+    pub motion_buffer_size: u32,
+    pub maximum_request_length: u16,
+    /// 0 LSBFirst, 1 MSBFirst
+    pub image_byte_order: u8,
+    /// 0 LeastSignificant, 1 MostSignificant
+    pub bitmap_format_bit_order: u8,
+    pub bitmap_format_scanline_unit: u8,
+    pub bitmap_format_scanline_pad: u8,
+    pub min_keycode: u8,
+    pub max_keycode: u8,
+    pub vendor: String,
+    pub pixmap_formats: Vec<Format>,
+    pub roots: Vec<Screen>,
+}
+
+/// X server response to setup request.
+#[derive(Debug, PartialEq)]
+pub enum SetupResponse {
+    Success(Setup),
+    Failed {
+        protocol_major_version: u16,
+        protocol_minor_version: u16,
+        reason: String,
+    },
+    Authenticate {
+        reason: String,
+    },
+}
+
+/// Size of setup response header, which contains additional data length.
+pub const SETUP_RESPONSE_HEADER_SIZE: usize = 8;
+
+// 1     CARD8                           depth
+// 1     CARD8                           bits-per-pixel
+// 1     CARD8                           scanline-pad
+// 5                                     unused
+impl Deserialize for Format {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        let depth = reader.read_u8()?;
+        let bits_per_pixel = reader.read_u8()?;
+        let scanline_pad = reader.read_u8()?;
+        skip(&mut reader, 5)?;
+
+        Ok(Some(Format {
+            depth,
+            bits_per_pixel,
+            scanline_pad,
+        }))
+    }
+}
+
+// 4     VISUALID                        visual-id
+// 1                                     class
+// 1     CARD8                           bits-per-rgb-value
+// 2     CARD16                          colormap-entries
+// 4     CARD32                          red-mask
+// 4     CARD32                          green-mask
+// 4     CARD32                          blue-mask
+// 4                                     unused
+impl VisualType {
+    /// Deserialize using byte order announced in setup request.
+    pub(crate) fn deserialize_with_order<R: Read>(
+        mut reader: R,
+        order: ByteOrder,
+    ) -> io::Result<Option<Self>> {
+        let visual_id = reader.read_u32_with(order)?;
+        let class = reader.read_u8()?;
+        let bits_per_rgb_value = reader.read_u8()?;
+        let colormap_entries = reader.read_u16_with(order)?;
+        let red_mask = reader.read_u32_with(order)?;
+        let green_mask = reader.read_u32_with(order)?;
+        let blue_mask = reader.read_u32_with(order)?;
+        skip(&mut reader, 4)?;
+
+        Ok(Some(VisualType {
+            visual_id,
+            class,
+            bits_per_rgb_value,
+            colormap_entries,
+            red_mask,
+            green_mask,
+            blue_mask,
+        }))
+    }
+}
+
+// 1     CARD8                           depth
+// 1                                     unused
+// 2     n                               number of VISUALTYPES in visuals
+// 4                                     unused
+// 24n   LISTofVISUALTYPE                visuals
+impl Depth {
+    /// Deserialize using byte order announced in setup request.
+    pub(crate) fn deserialize_with_order<R: Read>(
+        mut reader: R,
+        order: ByteOrder,
+    ) -> io::Result<Option<Self>> {
+        let depth = reader.read_u8()?;
+        skip(&mut reader, 1)?;
+        let visuals_len = reader.read_u16_with(order)?;
+        skip(&mut reader, 4)?;
+
+        let mut visuals = Vec::with_capacity(visuals_len.into());
+        for _ in 0..visuals_len {
+            visuals.extend(VisualType::deserialize_with_order(&mut reader, order)?);
+        }
+
+        Ok(Some(Depth { depth, visuals }))
+    }
+}
+
+// 4     WINDOW                          root
+// 4     COLORMAP                        default-colormap
+// 4     CARD32                          white-pixel
+// 4     CARD32                          black-pixel
+// 4     SETofEVENT                      current-input-masks
+// 2     CARD16                          width-in-pixels
+// 2     CARD16                          height-in-pixels
+// 2     CARD16                          width-in-millimeters
+// 2     CARD16                          height-in-millimeters
+// 2     CARD16                          min-installed-maps
+// 2     CARD16                          max-installed-maps
+// 4     VISUALID                        root-visual
+// 1                                     backing-stores
+// 1     BOOL                            save-unders
+// 1     CARD8                           root-depth
+// 1     CARD8                           number of DEPTHs in allowed-depths
+// n     LISTofDEPTH                     allowed-depths
+impl Screen {
+    /// Deserialize using byte order announced in setup request.
+    pub(crate) fn deserialize_with_order<R: Read>(
+        mut reader: R,
+        order: ByteOrder,
+    ) -> io::Result<Option<Self>> {
+        let root = reader.read_u32_with(order)?;
+        let default_colormap = reader.read_u32_with(order)?;
+        let white_pixel = reader.read_u32_with(order)?;
+        let black_pixel = reader.read_u32_with(order)?;
+        let current_input_masks = reader.read_u32_with(order)?;
+        let width_in_pixels = reader.read_u16_with(order)?;
+        let height_in_pixels = reader.read_u16_with(order)?;
+        let width_in_millimeters = reader.read_u16_with(order)?;
+        let height_in_millimeters = reader.read_u16_with(order)?;
+        let min_installed_maps = reader.read_u16_with(order)?;
+        let max_installed_maps = reader.read_u16_with(order)?;
+        let root_visual = reader.read_u32_with(order)?;
+        let backing_stores = reader.read_u8()?;
+        let save_unders = reader.read_u8()? != 0;
+        let root_depth = reader.read_u8()?;
+        let depths_len = reader.read_u8()?;
+
+        let mut allowed_depths = Vec::with_capacity(depths_len.into());
+        for _ in 0..depths_len {
+            allowed_depths.extend(Depth::deserialize_with_order(&mut reader, order)?);
+        }
+
+        Ok(Some(Screen {
+            root,
+            default_colormap,
+            white_pixel,
+            black_pixel,
+            current_input_masks,
+            width_in_pixels,
+            height_in_pixels,
+            width_in_millimeters,
+            height_in_millimeters,
+            min_installed_maps,
+            max_installed_maps,
+            root_visual,
+            backing_stores,
+            save_unders,
+            root_depth,
+            allowed_depths,
+        }))
+    }
+}
+
+// Success:
+// 1     1                               Success
+// 1                                     unused
+// 2     CARD16                          protocol-major-version
+// 2     CARD16                          protocol-minor-version
+// 2     8+2n+(v+p+m)/4                  length in 4-byte units of "additional data"
+// 4     CARD32                          release-number
+// 4     CARD32                          resource-id-base
+// 4     CARD32                          resource-id-mask
+// 4     CARD32                          motion-buffer-size
+// 2     v                               length of vendor
+// 2     CARD16                          maximum-request-length
+// 1     CARD8                           number of SCREENs in roots
+// 1     n                               number for FORMATs in pixmap-formats
+// 1                                     image-byte-order
+// 1                                     bitmap-format-bit-order
+// 1     CARD8                           bitmap-format-scanline-unit
+// 1     CARD8                           bitmap-format-scanline-pad
+// 1     KEYCODE                         min-keycode
+// 1     KEYCODE                         max-keycode
+// 4                                     unused
+// v     STRING8                         vendor
+// p                                     unused, p=pad(v)
+// 8n    LISTofFORMAT                    pixmap-formats
+// m     LISTofSCREEN                    roots
+//
+// Failed:
+// 1     0                               Failed
+// 1     n                               length of reason in bytes
+// 2     CARD16                          protocol-major-version
+// 2     CARD16                          protocol-minor-version
+// 2     (n+p)/4                         length in 4-byte units of "additional data"
+// n     STRING8                         reason
+// p                                     unused, p=pad(n)
+//
+// Authenticate:
+// 1     2                               Authenticate
+// 5                                     unused
+// 2     (n+p)/4                         length in 4-byte units of "additional data"
+// n     STRING8                         reason
+// p                                     unused, p=pad(n)
+impl SetupResponse {
+    /// Deserialize using byte order announced in setup request.
+    pub(crate) fn deserialize_with_order<R: Read>(
+        mut reader: R,
+        order: ByteOrder,
+    ) -> io::Result<Option<Self>> {
+        match reader.read_u8()? {
+            0 => {
+                let reason_len = reader.read_u8()?;
+                let protocol_major_version = reader.read_u16_with(order)?;
+                let protocol_minor_version = reader.read_u16_with(order)?;
+                let _additional_data_len = reader.read_u16_with(order)?;
+                let reason = reader.read_utf8_lossy_string8(reason_len.into())?;
+
+                Ok(Some(SetupResponse::Failed {
+                    protocol_major_version,
+                    protocol_minor_version,
+                    reason,
+                }))
+            }
+            1 => {
+                skip(&mut reader, 1)?;
+                let protocol_major_version = reader.read_u16_with(order)?;
+                let protocol_minor_version = reader.read_u16_with(order)?;
+                let _additional_data_len = reader.read_u16_with(order)?;
+                let release_number = reader.read_u32_with(order)?;
+                let resource_id_base = reader.read_u32_with(order)?;
+                let resource_id_mask = reader.read_u32_with(order)?;
+                let motion_buffer_size = reader.read_u32_with(order)?;
+                let vendor_len = reader.read_u16_with(order)?;
+                let maximum_request_length = reader.read_u16_with(order)?;
+                let roots_len = reader.read_u8()?;
+                if roots_len == 0 {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "Setup has no screens",
+                    ));
+                }
+                let pixmap_formats_len = reader.read_u8()?;
+                let image_byte_order = reader.read_u8()?;
+                let bitmap_format_bit_order = reader.read_u8()?;
+                let bitmap_format_scanline_unit = reader.read_u8()?;
+                let bitmap_format_scanline_pad = reader.read_u8()?;
+                let min_keycode = reader.read_u8()?;
+                let max_keycode = reader.read_u8()?;
+                skip(&mut reader, 4)?;
+                let vendor = reader.read_utf8_lossy_string8(vendor_len.into())?;
+
+                let mut pixmap_formats = Vec::with_capacity(pixmap_formats_len.into());
+                for _ in 0..pixmap_formats_len {
+                    pixmap_formats.extend(Format::deserialize(&mut reader)?);
+                }
+
+                let mut roots = Vec::with_capacity(roots_len.into());
+                for _ in 0..roots_len {
+                    roots.extend(Screen::deserialize_with_order(&mut reader, order)?);
+                }
+
+                Ok(Some(SetupResponse::Success(Setup {
+                    protocol_major_version,
+                    protocol_minor_version,
+                    release_number,
+                    resource_id_base,
+                    resource_id_mask,
+                    motion_buffer_size,
+                    maximum_request_length,
+                    image_byte_order,
+                    bitmap_format_bit_order,
+                    bitmap_format_scanline_unit,
+                    bitmap_format_scanline_pad,
+                    min_keycode,
+                    max_keycode,
+                    vendor,
+                    pixmap_formats,
+                    roots,
+                })))
+            }
+            2 => {
+                skip(&mut reader, 5)?;
+                let additional_data_len = reader.read_u16_with(order)?;
+                let reason =
+                    reader.read_utf8_lossy_string8(usize::from(additional_data_len) * 4)?;
+                // Reason is padded with zeros up to 4 bytes
+                let reason = reason.trim_end_matches('\0').to_string();
+
+                Ok(Some(SetupResponse::Authenticate { reason }))
+            }
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Unknown setup response status",
+            )),
+        }
+    }
+}
+
+impl Deserialize for VisualType {
+    fn deserialize<R: Read>(reader: R) -> io::Result<Option<Self>> {
+        Self::deserialize_with_order(reader, ByteOrder::NATIVE)
+    }
+}
+
+impl Deserialize for Depth {
+    fn deserialize<R: Read>(reader: R) -> io::Result<Option<Self>> {
+        Self::deserialize_with_order(reader, ByteOrder::NATIVE)
+    }
+}
+
+impl Deserialize for Screen {
+    fn deserialize<R: Read>(reader: R) -> io::Result<Option<Self>> {
+        Self::deserialize_with_order(reader, ByteOrder::NATIVE)
+    }
+}
+
+impl Deserialize for SetupResponse {
+    fn deserialize<R: Read>(reader: R) -> io::Result<Option<Self>> {
+        Self::deserialize_with_order(reader, ByteOrder::NATIVE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SetupResponse;
+    use crate::io;
+    use crate::protocol::Deserialize;
+    use crate::test_utils::setup_response;
+
+    #[test]
+    fn test_deserialize_success() {
+        let response = SetupResponse::deserialize(&setup_response(2)[..])
+            .unwrap()
+            .unwrap();
+
+        let setup = match response {
+            SetupResponse::Success(setup) => setup,
+            other => panic!("Unexpected setup response: {:?}", other),
+        };
+
+        assert_eq!(setup.protocol_major_version, 11);
+        assert_eq!(setup.resource_id_base, 0x0400_0000);
+        assert_eq!(setup.resource_id_mask, 0x001f_ffff);
+        assert_eq!(setup.maximum_request_length, 0xffff);
+        assert_eq!(setup.vendor, "xodium");
+        assert_eq!(setup.pixmap_formats.len(), 1);
+        assert_eq!(setup.pixmap_formats[0].bits_per_pixel, 32);
+        assert_eq!(setup.roots.len(), 2);
+        assert_eq!(setup.roots[0].root, 0x100);
+        assert_eq!(setup.roots[1].root, 0x101);
+        assert_eq!(setup.roots[1].width_in_pixels, 1920);
+        assert_eq!(setup.roots[1].allowed_depths[0].depth, 24);
+        assert_eq!(
+            setup.roots[1].allowed_depths[0].visuals[0].red_mask,
+            0x00ff_0000
+        );
+    }
+
+    #[test]
+    fn test_deserialize_no_screens() {
+        let err = SetupResponse::deserialize(&setup_response(0)[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_deserialize_failed() {
+        const RESPONSE: &[u8] = b"\x00\x06\x0b\x00\x00\x00\x02\x00reason\0\0";
+
+        assert_eq!(
+            SetupResponse::deserialize(RESPONSE).unwrap().unwrap(),
+            SetupResponse::Failed {
+                protocol_major_version: 11,
+                protocol_minor_version: 0,
+                reason: "reason".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_deserialize_authenticate() {
+        const RESPONSE: &[u8] = b"\x02\0\0\0\0\0\x02\0reason\0\0";
+
+        assert_eq!(
+            SetupResponse::deserialize(RESPONSE).unwrap().unwrap(),
+            SetupResponse::Authenticate {
+                reason: "reason".into(),
+            }
+        );
+    }
+}
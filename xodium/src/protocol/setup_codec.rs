@@ -1,23 +1,128 @@
+use super::{ByteOrder, Serialize, SetupRequest, SetupResponse, SETUP_RESPONSE_HEADER_SIZE};
 use crate::framed::{Decoder, Encoder};
-use std::io;
+use crate::io;
 
-#[derive(Default)]
-pub struct SetupCodec {}
+/// Codec used during connection setup.
+/// Sends [SetupRequest] and receives [SetupResponse].
+pub struct SetupCodec {
+    /// Byte order announced in setup request, X server replies using it.
+    byte_order: ByteOrder,
+}
+
+impl SetupCodec {
+    pub fn new(byte_order: ByteOrder) -> Self {
+        SetupCodec { byte_order }
+    }
+}
+
+impl Default for SetupCodec {
+    fn default() -> Self {
+        SetupCodec::new(ByteOrder::NATIVE)
+    }
+}
 
-impl Encoder for SetupCodec {
-    type Item = ();
+impl Encoder<SetupRequest> for SetupCodec {
     type Error = io::Error;
 
-    fn encode(&mut self, _item: Self::Item, _dst: &mut Vec<u8>) -> Result<(), Self::Error> {
-        Ok(())
+    fn encode(&mut self, item: SetupRequest, dst: &mut Vec<u8>) -> Result<(), Self::Error> {
+        item.serialize(dst)
     }
 }
 
 impl Decoder for SetupCodec {
-    type Item = ();
+    type Item = SetupResponse;
     type Error = io::Error;
 
-    fn decode(&mut self, _src: &mut Vec<u8>) -> Result<Option<Self::Item>, Self::Error> {
-        Ok(None)
+    fn decode(&mut self, src: &mut Vec<u8>) -> Result<Option<Self::Item>, Self::Error> {
+        if src.len() < SETUP_RESPONSE_HEADER_SIZE {
+            return Ok(None);
+        }
+
+        // Additional data length is stored in bytes 6-7 in 4-byte units.
+        let additional_data_len = match self.byte_order {
+            ByteOrder::MsbFirst => u16::from_be_bytes([src[6], src[7]]),
+            ByteOrder::LsbFirst => u16::from_le_bytes([src[6], src[7]]),
+        };
+        let response_len = SETUP_RESPONSE_HEADER_SIZE + usize::from(additional_data_len) * 4;
+
+        if src.len() < response_len {
+            return Ok(None);
+        }
+
+        let response: Vec<u8> = src.drain(0..response_len).collect();
+        SetupResponse::deserialize_with_order(&response[..], self.byte_order)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SetupCodec;
+    use crate::framed::{Decoder, Framed};
+    use crate::protocol::{ByteOrder, SetupResponse};
+    use crate::test_utils::setup_response;
+    use std::io::{self, Read, Write};
+
+    /// Stream returning at most one byte per read, like a slow link.
+    struct ByteStream(io::Cursor<Vec<u8>>);
+
+    impl Read for ByteStream {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = buf.len().min(1);
+            self.0.read(&mut buf[..len])
+        }
+    }
+
+    impl Write for ByteStream {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_decode_byte_at_a_time() {
+        let stream = ByteStream(io::Cursor::new(setup_response(2)));
+        let mut framed = Framed::new(Box::new(stream), SetupCodec::default());
+
+        match framed.next().unwrap() {
+            SetupResponse::Success(setup) => assert_eq!(setup.roots.len(), 2),
+            other => panic!("Unexpected setup response: {:?}", other),
+        }
+        assert!(framed.next_buffered().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_decode_big_endian() {
+        let mut response = b"\x01\x00\x00\x0b\x00\x00\x00\x14".to_vec();
+        response.extend(b"\x00\x00\x00\x01\x04\x00\x00\x00\x00\x1f\xff\xff\x00\x00\x01\x00");
+        response.extend(b"\x00\x06\xff\xff\x01\x00\x00\x00\x08\xff\x00\x00");
+        response.extend(b"\x00\x00\x00\x00xodium\x00\x00");
+        // Screen without depths
+        response.extend(b"\x00\x00\x01\x00\x00\x00\x00\x20\x00\xff\xff\xff\x00\x00\x00\x00");
+        response.extend(b"\x00\x00\x00\x00\x07\x80\x04\x38\x02\x1c\x01\x30\x00\x01\x00\x01");
+        response.extend(b"\x00\x00\x00\x21\x00\x00\x18\x00");
+
+        let mut codec = SetupCodec::new(ByteOrder::MsbFirst);
+        let setup = match codec.decode(&mut response).unwrap().unwrap() {
+            SetupResponse::Success(setup) => setup,
+            other => panic!("Unexpected setup response: {:?}", other),
+        };
+
+        assert!(response.is_empty());
+        assert_eq!(setup.protocol_major_version, 11);
+        assert_eq!(setup.release_number, 1);
+        assert_eq!(setup.resource_id_base, 0x0400_0000);
+        assert_eq!(setup.resource_id_mask, 0x001f_ffff);
+        assert_eq!(setup.motion_buffer_size, 256);
+        assert_eq!(setup.maximum_request_length, 0xffff);
+        assert_eq!(setup.vendor, "xodium");
+        assert_eq!(setup.roots.len(), 1);
+        assert_eq!(setup.roots[0].root, 0x100);
+        assert_eq!(setup.roots[0].width_in_pixels, 1920);
+        assert_eq!(setup.roots[0].height_in_pixels, 1080);
+        assert_eq!(setup.roots[0].root_depth, 24);
     }
 }
@@ -1,11 +1,13 @@
-use super::{pad, Serialize, BYTE_ORDER, PROTOCOL_MAJOR_VERSION, PROTOCOL_MINOR_VERSION};
+use super::{pad, ByteOrder, Serialize, PROTOCOL_MAJOR_VERSION, PROTOCOL_MINOR_VERSION};
+use crate::io::{self, Write};
 use crate::utils::WriteBytesExt;
-use std::convert::TryFrom;
-use std::io::{self, Write};
-use std::num::TryFromIntError;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::num::TryFromIntError;
 
-/// Request a connection to X server
-pub(crate) struct SetupRequest {
+/// Request a connection to X server, using native byte order.
+pub struct SetupRequest {
     auth_protocol_name: String,
     auth_protocol_data: Vec<u8>,
 }
@@ -44,7 +46,7 @@ impl SetupRequest {
 // q                       unused, q=pad(d)
 impl Serialize for SetupRequest {
     fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
-        writer.write_u8(BYTE_ORDER)?;
+        writer.write_u8(ByteOrder::NATIVE as u8)?;
         writer.write_u8(0)?; // pad
         writer.write_u16_ne(PROTOCOL_MAJOR_VERSION)?;
         writer.write_u16_ne(PROTOCOL_MINOR_VERSION)?;
@@ -73,7 +75,6 @@ impl Serialize for SetupRequest {
 mod tests {
     use super::SetupRequest;
     use crate::protocol::Serialize;
-    use std::io::Cursor;
 
     #[test]
     fn test_serialize_empty() {
@@ -82,7 +83,7 @@ mod tests {
 
         SetupRequest::new("", b"")
             .expect("Empty vecs always pass")
-            .serialize(&mut Cursor::new(&mut write_buf))
+            .serialize(&mut write_buf)
             .unwrap();
 
         assert_eq!(write_buf, EXPECTED_AUTHORIZATION_BUF);
@@ -96,7 +97,7 @@ mod tests {
 
         SetupRequest::new("auth_name", b"auth_data")
             .expect("Specified values always pass")
-            .serialize(&mut Cursor::new(&mut write_buf))
+            .serialize(&mut write_buf)
             .unwrap();
 
         assert_eq!(write_buf, EXPECTED_AUTHORIZATION_BUF);
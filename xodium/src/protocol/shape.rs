@@ -0,0 +1,251 @@
+//! SHAPE extension, non-rectangular windows.
+
+use super::{request_length, skip, Deserialize, Rectangle, ReplyRequest, Serialize};
+use crate::io::{self, Read, Write};
+use crate::utils::{ReadBytesExt, WriteBytesExt};
+use alloc::vec::Vec;
+
+pub const SHAPE_EXTENSION_NAME: &str = "SHAPE";
+
+const SHAPE_QUERY_VERSION_OPCODE: u8 = 0;
+const SHAPE_RECTANGLES_OPCODE: u8 = 1;
+const SHAPE_MASK_OPCODE: u8 = 2;
+
+/// Kind of window shape.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShapeKind {
+    /// Outer shape of window, including border.
+    Bounding = 0,
+    /// Inner shape of window, excluding border.
+    Clip = 1,
+    /// Area window receives pointer input in.
+    Input = 2,
+}
+
+/// How new shape is combined with the current one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShapeOperation {
+    Set = 0,
+    Union = 1,
+    Intersect = 2,
+    /// Current shape minus new one.
+    Subtract = 3,
+    /// New shape minus current one.
+    Invert = 4,
+}
+
+/// Promise of rectangles order made by client, lets X server skip sorting.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RectangleOrdering {
+    UnSorted = 0,
+    YSorted = 1,
+    YXSorted = 2,
+    YXBanded = 3,
+}
+
+/// Query SHAPE version supported by X server.
+pub struct ShapeQueryVersion {
+    pub major_opcode: u8,
+}
+
+// 1     CARD8                           major opcode
+// 1     0                               shape opcode
+// 2     1                               request length
+impl Serialize for ShapeQueryVersion {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(SHAPE_QUERY_VERSION_OPCODE)?;
+        writer.write_u16_ne(1)?;
+        Ok(())
+    }
+}
+
+/// SHAPE version supported by X server.
+#[derive(Debug, PartialEq)]
+pub struct ShapeQueryVersionReply {
+    pub major_version: u16,
+    pub minor_version: u16,
+}
+
+// 1     1                               Reply
+// 1                                     unused
+// 2     CARD16                          sequence number
+// 4     0                               reply length
+// 2     CARD16                          major version
+// 2     CARD16                          minor version
+// 20                                    unused
+impl Deserialize for ShapeQueryVersionReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        skip(&mut reader, 8)?; // reply header
+        let major_version = reader.read_u16_ne()?;
+        let minor_version = reader.read_u16_ne()?;
+        skip(&mut reader, 20)?;
+
+        Ok(Some(ShapeQueryVersionReply {
+            major_version,
+            minor_version,
+        }))
+    }
+}
+
+impl ReplyRequest for ShapeQueryVersion {
+    type Reply = ShapeQueryVersionReply;
+}
+
+/// Combine window shape with union of rectangles.
+pub struct ShapeRectangles {
+    pub major_opcode: u8,
+    pub operation: ShapeOperation,
+    pub kind: ShapeKind,
+    pub ordering: RectangleOrdering,
+    pub dest_window: u32,
+    pub x_offset: i16,
+    pub y_offset: i16,
+    pub rectangles: Vec<Rectangle>,
+}
+
+// 1     CARD8                           major opcode
+// 1     1                               shape opcode
+// 2     4+2n                            request length
+// 1     SHAPE_OP                        operation
+// 1     SHAPE_KIND                      destination kind
+// 1     BYTE                            ordering
+//       0     UnSorted
+//       1     YSorted
+//       2     YXSorted
+//       3     YXBanded
+// 1                                     unused
+// 4     WINDOW                          destination window
+// 2     INT16                           x-offset
+// 2     INT16                           y-offset
+// 8n    LISTofRECTANGLE                 rectangles
+impl Serialize for ShapeRectangles {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(SHAPE_RECTANGLES_OPCODE)?;
+        writer.write_u16_ne(request_length(4 + 2 * self.rectangles.len())?)?;
+        writer.write_u8(self.operation as u8)?;
+        writer.write_u8(self.kind as u8)?;
+        writer.write_u8(self.ordering as u8)?;
+        writer.write_u8(0)?; // pad
+        writer.write_u32_ne(self.dest_window)?;
+        writer.write_i16_ne(self.x_offset)?;
+        writer.write_i16_ne(self.y_offset)?;
+        for rectangle in &self.rectangles {
+            rectangle.serialize(&mut writer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Combine window shape with set bits of 1-bit depth pixmap.
+pub struct ShapeMask {
+    pub major_opcode: u8,
+    pub operation: ShapeOperation,
+    pub kind: ShapeKind,
+    pub dest_window: u32,
+    pub x_offset: i16,
+    pub y_offset: i16,
+    /// 0 (None) resets shape to the default rectangle.
+    pub source_bitmap: u32,
+}
+
+// 1     CARD8                           major opcode
+// 1     2                               shape opcode
+// 2     5                               request length
+// 1     SHAPE_OP                        operation
+// 1     SHAPE_KIND                      destination kind
+// 2                                     unused
+// 4     WINDOW                          destination window
+// 2     INT16                           x-offset
+// 2     INT16                           y-offset
+// 4     PIXMAP                          source bitmap
+//       0     None
+impl Serialize for ShapeMask {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(SHAPE_MASK_OPCODE)?;
+        writer.write_u16_ne(5)?;
+        writer.write_u8(self.operation as u8)?;
+        writer.write_u8(self.kind as u8)?;
+        writer.write_u16_ne(0)?; // pad
+        writer.write_u32_ne(self.dest_window)?;
+        writer.write_i16_ne(self.x_offset)?;
+        writer.write_i16_ne(self.y_offset)?;
+        writer.write_u32_ne(self.source_bitmap)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        RectangleOrdering, ShapeKind, ShapeMask, ShapeOperation, ShapeQueryVersionReply,
+        ShapeRectangles,
+    };
+    use crate::protocol::{Deserialize, Rectangle, Serialize};
+
+    #[test]
+    fn test_deserialize_query_version_reply() {
+        let mut reply = b"\x01\x00\x01\x00\x00\x00\x00\x00\x01\x00\x01\x00".to_vec();
+        reply.extend(vec![0; 20]);
+
+        assert_eq!(
+            ShapeQueryVersionReply::deserialize(&reply[..])
+                .unwrap()
+                .unwrap(),
+            ShapeQueryVersionReply {
+                major_version: 1,
+                minor_version: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_serialize_shape_rectangles() {
+        const EXPECTED: &[u8] = b"\x81\x01\x08\x00\x00\x00\x00\x00\x01\x02\x00\x00\
+                                  \x00\x00\x00\x00\x02\x00\x00\x00\x7c\x01\x2c\x01\
+                                  \x00\x00\x02\x00\x80\x01\x28\x01";
+        let mut write_buf = vec![];
+
+        // Rectangle with cut off top corners
+        ShapeRectangles {
+            major_opcode: 129,
+            operation: ShapeOperation::Set,
+            kind: ShapeKind::Bounding,
+            ordering: RectangleOrdering::UnSorted,
+            dest_window: 0x201,
+            x_offset: 0,
+            y_offset: 0,
+            rectangles: vec![
+                Rectangle::from((2, 0, 380, 300)),
+                Rectangle::from((0, 2, 384, 296)),
+            ],
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_serialize_shape_mask() {
+        const EXPECTED: &[u8] = b"\x81\x02\x05\x00\x01\x02\x00\x00\x01\x02\x00\x00\
+                                  \x0a\x00\xf6\xff\x05\x00\x00\x04";
+        let mut write_buf = vec![];
+
+        ShapeMask {
+            major_opcode: 129,
+            operation: ShapeOperation::Union,
+            kind: ShapeKind::Input,
+            dest_window: 0x201,
+            x_offset: 10,
+            y_offset: -10,
+            source_bitmap: 0x04000005,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+}
@@ -0,0 +1,115 @@
+//! LISTofSTR encoding shared by requests and replies carrying lists of names,
+//! every STR is prefixed with its length and the whole list is padded.
+
+use super::pad;
+use crate::io::{self, Read, Write};
+use crate::utils::{ReadBytesExt, WriteBytesExt};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// Length of LISTofSTR in bytes, excluding padding.
+pub(crate) fn str_list_len(strings: &[String]) -> usize {
+    strings.iter().map(|string| 1 + string.len()).sum()
+}
+
+// 1     n                               length of name in bytes
+// n     STRING8                         name
+/// Write LISTofSTR followed by padding, error if any string is longer than 255 bytes.
+pub(crate) fn write_str_list<W: Write>(mut writer: W, strings: &[String]) -> io::Result<()> {
+    for string in strings {
+        let len = u8::try_from(string.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "STR is too long"))?;
+        writer.write_u8(len)?;
+        writer.write_all(string.as_bytes())?;
+    }
+
+    for _ in 0..pad(str_list_len(strings)) {
+        writer.write_u8(0)?;
+    }
+    Ok(())
+}
+
+/// Read LISTofSTR of `count` strings, consuming padding following it.
+/// Invalid UTF-8 sequences are replaced.
+pub(crate) fn read_str_list<R: Read>(mut reader: R, count: usize) -> io::Result<Vec<String>> {
+    let mut strings = Vec::with_capacity(count);
+    let mut len = 0;
+
+    for _ in 0..count {
+        let string_len = usize::from(reader.read_u8()?);
+        let mut buf = vec![0; string_len];
+        reader.read_exact(&mut buf)?;
+        strings.push(String::from_utf8_lossy(&buf).to_string());
+        len += 1 + string_len;
+    }
+
+    super::skip(&mut reader, pad(len))?;
+    Ok(strings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_str_list, str_list_len, write_str_list};
+
+    #[test]
+    fn test_str_list_round_trip() {
+        let strings = vec!["built-ins".to_string(), "/usr/share/fonts".to_string()];
+        let mut write_buf = vec![];
+
+        write_str_list(&mut write_buf, &strings).unwrap();
+        assert_eq!(str_list_len(&strings), 27);
+        assert_eq!(write_buf.len(), 28);
+        assert_eq!(&write_buf[..10], b"\x09built-ins");
+
+        let mut reader = &write_buf[..];
+        assert_eq!(read_str_list(&mut reader, 2).unwrap(), strings);
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn test_write_too_long_str() {
+        assert!(write_str_list(vec![], &["x".repeat(256)]).is_err());
+    }
+
+    #[test]
+    fn test_empty_str_list() {
+        let mut write_buf = vec![];
+
+        write_str_list(&mut write_buf, &[]).unwrap();
+        assert!(write_buf.is_empty());
+
+        let mut reader = &b"\x05fixed"[..];
+        assert!(read_str_list(&mut reader, 0).unwrap().is_empty());
+        assert_eq!(reader.len(), 6);
+    }
+
+    #[test]
+    fn test_single_str() {
+        let strings = vec!["abc".to_string()];
+        let mut write_buf = vec![];
+
+        // Length prefix and string fill 4 bytes exactly, no padding
+        write_str_list(&mut write_buf, &strings).unwrap();
+        assert_eq!(write_buf, b"\x03abc");
+
+        let mut reader = &write_buf[..];
+        assert_eq!(read_str_list(&mut reader, 1).unwrap(), strings);
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn test_str_list_padded_as_whole() {
+        let strings = vec!["a".to_string(), "bc".to_string(), "".to_string()];
+        let mut write_buf = vec![];
+
+        // STRs are packed and only the whole list is padded
+        write_str_list(&mut write_buf, &strings).unwrap();
+        assert_eq!(str_list_len(&strings), 6);
+        assert_eq!(write_buf, b"\x01a\x02bc\x00\x00\x00");
+
+        let mut reader = &write_buf[..];
+        assert_eq!(read_str_list(&mut reader, 3).unwrap(), strings);
+        assert!(reader.is_empty());
+    }
+}
@@ -0,0 +1,71 @@
+use super::{Deserialize, Serialize};
+use crate::io::{self, Read, Write};
+use crate::utils::{ReadBytesExt, WriteBytesExt};
+
+/// X server time in milliseconds, wrapping around every ~49.7 days.
+/// [current](Timestamp::current) (0, CurrentTime) is substituted
+/// with X server time once request is processed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Timestamp(u32);
+
+impl Timestamp {
+    /// CurrentTime, X server time once request is processed.
+    pub const fn current() -> Self {
+        Timestamp(0)
+    }
+
+    /// Time in milliseconds, e.g. `time` of event which triggered the request.
+    pub const fn from_millis(millis: u32) -> Self {
+        Timestamp(millis)
+    }
+
+    pub const fn millis(self) -> u32 {
+        self.0
+    }
+
+    pub const fn is_current(self) -> bool {
+        self.0 == 0
+    }
+}
+
+// 4     TIMESTAMP                       time
+//       0     CurrentTime
+impl Serialize for Timestamp {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u32_ne(self.0)
+    }
+}
+
+impl Deserialize for Timestamp {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        Ok(Some(Timestamp(reader.read_u32_ne()?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Timestamp;
+    use crate::protocol::{Deserialize, Serialize};
+
+    #[test]
+    fn test_current_time() {
+        let mut write_buf = vec![];
+        Timestamp::current().serialize(&mut write_buf).unwrap();
+
+        assert_eq!(write_buf, b"\x00\x00\x00\x00");
+        assert!(Timestamp::current().is_current());
+        assert_eq!(Timestamp::default(), Timestamp::current());
+    }
+
+    #[test]
+    fn test_timestamp_round_trip() {
+        let time = Timestamp::from_millis(10000);
+        let mut write_buf = vec![];
+        time.serialize(&mut write_buf).unwrap();
+
+        assert_eq!(write_buf, b"\x10\x27\x00\x00");
+        assert_eq!(Timestamp::deserialize(&write_buf[..]).unwrap(), Some(time));
+        assert_eq!(time.millis(), 10000);
+        assert!(!time.is_current());
+    }
+}
@@ -0,0 +1,1174 @@
+use super::flags::define_flags;
+use super::{skip, Deserialize, EventMask, Rectangle, ReplyRequest, Request, Serialize};
+use crate::io::{self, Read, Write};
+use crate::utils::{ReadBytesExt, WriteBytesExt};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// Stacking operation applied by ConfigureWindow.
+///
+/// With a sibling specified the window is restacked relative to it,
+/// without a sibling - relative to all of its siblings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StackMode {
+    Above = 0,
+    Below = 1,
+    TopIf = 2,
+    BottomIf = 3,
+    Opposite = 4,
+}
+
+impl StackMode {
+    pub(crate) fn try_from(raw_stack_mode: u8) -> Option<StackMode> {
+        match raw_stack_mode {
+            0 => Some(StackMode::Above),
+            1 => Some(StackMode::Below),
+            2 => Some(StackMode::TopIf),
+            3 => Some(StackMode::BottomIf),
+            4 => Some(StackMode::Opposite),
+            _ => None,
+        }
+    }
+}
+
+/// Direction used by CirculateWindow.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CirculateDirection {
+    /// Raise the lowest mapped child that is occluded by another child.
+    RaiseLowest = 0,
+    /// Lower the highest mapped child that occludes another child.
+    LowerHighest = 1,
+}
+
+/// Change applied to client save-set by ChangeSaveSet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SaveSetMode {
+    Insert = 0,
+    Delete = 1,
+}
+
+/// Window class, InputOnly windows are invisible and only receive input.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WindowClass {
+    CopyFromParent = 0,
+    InputOutput = 1,
+    InputOnly = 2,
+}
+
+impl WindowClass {
+    pub(crate) fn try_from(raw_class: u8) -> Option<WindowClass> {
+        match raw_class {
+            0 => Some(WindowClass::CopyFromParent),
+            1 => Some(WindowClass::InputOutput),
+            2 => Some(WindowClass::InputOnly),
+            _ => None,
+        }
+    }
+}
+
+/// Where window contents are kept when window is resized.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BitGravity {
+    /// Contents are discarded.
+    Forget = 0,
+    NorthWest = 1,
+    North = 2,
+    NorthEast = 3,
+    West = 4,
+    Center = 5,
+    East = 6,
+    SouthWest = 7,
+    South = 8,
+    SouthEast = 9,
+    /// Contents stay at the same place relative to the root.
+    Static = 10,
+}
+
+impl BitGravity {
+    pub(crate) fn try_from(raw_gravity: u8) -> Option<BitGravity> {
+        use BitGravity::*;
+        [
+            Forget, NorthWest, North, NorthEast, West, Center, East, SouthWest, South, SouthEast,
+            Static,
+        ]
+        .get(usize::from(raw_gravity))
+        .copied()
+    }
+}
+
+/// How window is moved when its parent is resized.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WinGravity {
+    /// Window is unmapped instead of moving.
+    Unmap = 0,
+    NorthWest = 1,
+    North = 2,
+    NorthEast = 3,
+    West = 4,
+    Center = 5,
+    East = 6,
+    SouthWest = 7,
+    South = 8,
+    SouthEast = 9,
+    /// Window stays at the same place relative to the root.
+    Static = 10,
+}
+
+impl WinGravity {
+    pub(crate) fn try_from(raw_gravity: u8) -> Option<WinGravity> {
+        use WinGravity::*;
+        [
+            Unmap, NorthWest, North, NorthEast, West, Center, East, SouthWest, South, SouthEast,
+            Static,
+        ]
+        .get(usize::from(raw_gravity))
+        .copied()
+    }
+}
+
+/// Hint on when X server should maintain obscured window contents.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BackingStore {
+    NotUseful = 0,
+    WhenMapped = 1,
+    Always = 2,
+}
+
+impl BackingStore {
+    pub(crate) fn try_from(raw_backing_store: u8) -> Option<BackingStore> {
+        match raw_backing_store {
+            0 => Some(BackingStore::NotUseful),
+            1 => Some(BackingStore::WhenMapped),
+            2 => Some(BackingStore::Always),
+            _ => None,
+        }
+    }
+}
+
+/// Whether window is mapped and visible.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MapState {
+    Unmapped = 0,
+    /// Window is mapped, but one of its ancestors is not.
+    Unviewable = 1,
+    Viewable = 2,
+}
+
+/// Attributes of window set by CreateWindow and ChangeWindowAttributes.
+/// Only the attributes which were set are sent to the server.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WindowAttributes {
+    background_pixmap: Option<u32>,
+    background_pixel: Option<u32>,
+    border_pixmap: Option<u32>,
+    border_pixel: Option<u32>,
+    bit_gravity: Option<BitGravity>,
+    win_gravity: Option<WinGravity>,
+    backing_store: Option<BackingStore>,
+    backing_planes: Option<u32>,
+    backing_pixel: Option<u32>,
+    override_redirect: Option<bool>,
+    save_under: Option<bool>,
+    event_mask: Option<u32>,
+    do_not_propagate_mask: Option<u32>,
+    colormap: Option<u32>,
+    cursor: Option<u32>,
+}
+
+/// Attributes InputOnly window may have:
+/// win-gravity, override-redirect, event-mask, do-not-propagate-mask and cursor.
+const INPUT_ONLY_ATTRIBUTES: u32 = 0x0020 | 0x0200 | 0x0800 | 0x1000 | 0x4000;
+
+impl WindowAttributes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pixmap, 0 (None) or 1 (ParentRelative).
+    pub fn background_pixmap(mut self, background_pixmap: u32) -> Self {
+        self.background_pixmap = Some(background_pixmap);
+        self
+    }
+
+    pub fn background_pixel(mut self, background_pixel: u32) -> Self {
+        self.background_pixel = Some(background_pixel);
+        self
+    }
+
+    /// Pixmap or 0 (CopyFromParent).
+    pub fn border_pixmap(mut self, border_pixmap: u32) -> Self {
+        self.border_pixmap = Some(border_pixmap);
+        self
+    }
+
+    pub fn border_pixel(mut self, border_pixel: u32) -> Self {
+        self.border_pixel = Some(border_pixel);
+        self
+    }
+
+    pub fn bit_gravity(mut self, bit_gravity: BitGravity) -> Self {
+        self.bit_gravity = Some(bit_gravity);
+        self
+    }
+
+    pub fn win_gravity(mut self, win_gravity: WinGravity) -> Self {
+        self.win_gravity = Some(win_gravity);
+        self
+    }
+
+    pub fn backing_store(mut self, backing_store: BackingStore) -> Self {
+        self.backing_store = Some(backing_store);
+        self
+    }
+
+    pub fn backing_planes(mut self, backing_planes: u32) -> Self {
+        self.backing_planes = Some(backing_planes);
+        self
+    }
+
+    pub fn backing_pixel(mut self, backing_pixel: u32) -> Self {
+        self.backing_pixel = Some(backing_pixel);
+        self
+    }
+
+    pub fn override_redirect(mut self, override_redirect: bool) -> Self {
+        self.override_redirect = Some(override_redirect);
+        self
+    }
+
+    pub fn save_under(mut self, save_under: bool) -> Self {
+        self.save_under = Some(save_under);
+        self
+    }
+
+    pub fn event_mask(mut self, event_mask: EventMask) -> Self {
+        self.event_mask = Some(event_mask.bits());
+        self
+    }
+
+    pub fn do_not_propagate_mask(mut self, do_not_propagate_mask: EventMask) -> Self {
+        self.do_not_propagate_mask = Some(do_not_propagate_mask.bits());
+        self
+    }
+
+    /// Colormap or 0 (CopyFromParent).
+    pub fn colormap(mut self, colormap: u32) -> Self {
+        self.colormap = Some(colormap);
+        self
+    }
+
+    /// Cursor or 0 (None).
+    pub fn cursor(mut self, cursor: u32) -> Self {
+        self.cursor = Some(cursor);
+        self
+    }
+
+    /// Values in protocol order, paired with their value-mask bit.
+    fn values(&self) -> Vec<(u32, u32)> {
+        [
+            (0x0001, self.background_pixmap),
+            (0x0002, self.background_pixel),
+            (0x0004, self.border_pixmap),
+            (0x0008, self.border_pixel),
+            (0x0010, self.bit_gravity.map(|v| v as u32)),
+            (0x0020, self.win_gravity.map(|v| v as u32)),
+            (0x0040, self.backing_store.map(|v| v as u32)),
+            (0x0080, self.backing_planes),
+            (0x0100, self.backing_pixel),
+            (0x0200, self.override_redirect.map(u32::from)),
+            (0x0400, self.save_under.map(u32::from)),
+            (0x0800, self.event_mask),
+            (0x1000, self.do_not_propagate_mask),
+            (0x2000, self.colormap),
+            (0x4000, self.cursor),
+        ]
+        .iter()
+        .filter_map(|(bit, value)| value.map(|v| (*bit, v)))
+        .collect()
+    }
+
+    /// Check attributes are allowed for window of the class,
+    /// X server responds with Match error otherwise.
+    pub fn check_class(&self, class: WindowClass) -> io::Result<()> {
+        if class != WindowClass::InputOnly {
+            return Ok(());
+        }
+
+        match self
+            .values()
+            .iter()
+            .find(|(bit, _)| bit & INPUT_ONLY_ATTRIBUTES == 0)
+        {
+            Some((bit, _)) => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Attribute with mask {:#06x} is not allowed for InputOnly window",
+                    bit
+                ),
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Value-mask of value list, error unless list has a value per mask bit in bit order.
+/// X server closes connection if value-list does not match value-mask.
+fn value_mask(values: &[(u32, u32)]) -> io::Result<u32> {
+    let mut value_mask = 0u32;
+
+    for (bit, _) in values {
+        if !bit.is_power_of_two() || *bit <= value_mask {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Value list does not match value-mask",
+            ));
+        }
+        value_mask |= bit;
+    }
+
+    debug_assert_eq!(value_mask.count_ones() as usize, values.len());
+    Ok(value_mask)
+}
+
+define_flags! {
+    /// Values set in ConfigureWindow request or ConfigureRequest event.
+    pub struct ConfigWindowMask: u16 {
+        const X = 0x0001;
+        const Y = 0x0002;
+        const WIDTH = 0x0004;
+        const HEIGHT = 0x0008;
+        const BORDER_WIDTH = 0x0010;
+        const SIBLING = 0x0020;
+        const STACK_MODE = 0x0040;
+    }
+}
+
+/// Values changed by ConfigureWindow.
+/// Only the values which were set are sent to the server.
+///
+/// Sibling is only meaningful along with stack mode: window is restacked relative
+/// to the sibling instead of all of its siblings, e.g. TopIf raises it only if
+/// the sibling occludes it. Sibling without stack mode is rejected on serialization,
+/// X server would respond with Match error.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConfigureValues {
+    x: Option<i16>,
+    y: Option<i16>,
+    width: Option<u16>,
+    height: Option<u16>,
+    border_width: Option<u16>,
+    sibling: Option<u32>,
+    stack_mode: Option<StackMode>,
+}
+
+impl ConfigureValues {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn x(mut self, x: i16) -> Self {
+        self.x = Some(x);
+        self
+    }
+
+    pub fn y(mut self, y: i16) -> Self {
+        self.y = Some(y);
+        self
+    }
+
+    pub fn width(mut self, width: u16) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    pub fn height(mut self, height: u16) -> Self {
+        self.height = Some(height);
+        self
+    }
+
+    pub fn border_width(mut self, border_width: u16) -> Self {
+        self.border_width = Some(border_width);
+        self
+    }
+
+    /// Sibling to restack relative to, requires [stack_mode](ConfigureValues::stack_mode).
+    pub fn sibling(mut self, sibling: u32) -> Self {
+        self.sibling = Some(sibling);
+        self
+    }
+
+    pub fn stack_mode(mut self, stack_mode: StackMode) -> Self {
+        self.stack_mode = Some(stack_mode);
+        self
+    }
+
+    /// Error if sibling is set without stack mode.
+    fn check_sibling(&self) -> io::Result<()> {
+        if self.sibling.is_some() && self.stack_mode.is_none() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Sibling requires stack mode to be set",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Values in protocol order, paired with their value-mask bit.
+    fn values(&self) -> Vec<(ConfigWindowMask, u32)> {
+        [
+            (ConfigWindowMask::X, self.x.map(|v| i32::from(v) as u32)),
+            (ConfigWindowMask::Y, self.y.map(|v| i32::from(v) as u32)),
+            (ConfigWindowMask::WIDTH, self.width.map(u32::from)),
+            (ConfigWindowMask::HEIGHT, self.height.map(u32::from)),
+            (
+                ConfigWindowMask::BORDER_WIDTH,
+                self.border_width.map(u32::from),
+            ),
+            (ConfigWindowMask::SIBLING, self.sibling),
+            (
+                ConfigWindowMask::STACK_MODE,
+                self.stack_mode.map(|v| v as u32),
+            ),
+        ]
+        .iter()
+        .filter_map(|(bit, value)| value.map(|v| (*bit, v)))
+        .collect()
+    }
+
+    /// Value-mask of values which were set.
+    pub fn value_mask(&self) -> ConfigWindowMask {
+        self.values()
+            .iter()
+            .fold(ConfigWindowMask::empty(), |mask, (bit, _)| mask | *bit)
+    }
+}
+
+/// Create unmapped window.
+pub struct CreateWindow {
+    /// 0 means depth of the parent.
+    pub depth: u8,
+    pub wid: u32,
+    pub parent: u32,
+    pub geometry: Rectangle,
+    pub border_width: u16,
+    pub class: WindowClass,
+    /// 0 (CopyFromParent) means visual of the parent.
+    pub visual: u32,
+    pub attributes: WindowAttributes,
+}
+
+// 1     1                               opcode
+// 1     CARD8                           depth
+// 2     8+n                             request length
+// 4     WINDOW                          wid
+// 4     WINDOW                          parent
+// 2     INT16                           x
+// 2     INT16                           y
+// 2     CARD16                          width
+// 2     CARD16                          height
+// 2     CARD16                          border-width
+// 2                                     class
+//       0     CopyFromParent
+//       1     InputOutput
+//       2     InputOnly
+// 4     VISUALID                        visual
+//       0     CopyFromParent
+// 4     BITMASK                         value-mask
+// 4n    LISTofVALUE                     value-list
+impl Request for CreateWindow {
+    const OPCODE: u8 = 1;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        let values = self.attributes.values();
+        let value_mask = value_mask(&values)?;
+
+        writer.write_u32_ne(self.wid)?;
+        writer.write_u32_ne(self.parent)?;
+        self.geometry.serialize(&mut writer)?;
+        writer.write_u16_ne(self.border_width)?;
+        writer.write_u16_ne(self.class as u16)?;
+        writer.write_u32_ne(self.visual)?;
+        writer.write_u32_ne(value_mask)?;
+
+        for (_, value) in values {
+            writer.write_u32_ne(value)?;
+        }
+
+        Ok(self.depth)
+    }
+}
+
+/// Destroy window along with all its subwindows.
+pub struct DestroyWindow {
+    pub window: u32,
+}
+
+// 1     4                               opcode
+// 1                                     unused
+// 2     2                               request length
+// 4     WINDOW                          window
+impl Request for DestroyWindow {
+    const OPCODE: u8 = 4;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.window)?;
+        Ok(0)
+    }
+}
+
+/// Change attributes of existing window.
+pub struct ChangeWindowAttributes {
+    pub window: u32,
+    pub attributes: WindowAttributes,
+}
+
+// 1     2                               opcode
+// 1                                     unused
+// 2     3+n                             request length
+// 4     WINDOW                          window
+// 4     BITMASK                         value-mask
+// 4n    LISTofVALUE                     value-list
+impl Request for ChangeWindowAttributes {
+    const OPCODE: u8 = 2;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        let values = self.attributes.values();
+        let value_mask = value_mask(&values)?;
+
+        writer.write_u32_ne(self.window)?;
+        writer.write_u32_ne(value_mask)?;
+
+        for (_, value) in values {
+            writer.write_u32_ne(value)?;
+        }
+
+        Ok(0)
+    }
+}
+
+/// Add or remove window of another client from the save-set.
+/// Windows in the save-set are reparented to the closest ancestor
+/// not created by the client and mapped when its connection is closed.
+pub struct ChangeSaveSet {
+    pub mode: SaveSetMode,
+    pub window: u32,
+}
+
+// 1     6                               opcode
+// 1                                     mode
+//       0     Insert
+//       1     Delete
+// 2     2                               request length
+// 4     WINDOW                          window
+impl Request for ChangeSaveSet {
+    const OPCODE: u8 = 6;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.window)?;
+        Ok(self.mode as u8)
+    }
+}
+
+/// Map window, for override-redirect or unmanaged windows it is shown right away,
+/// otherwise window manager redirecting its parent gets MapRequest instead.
+pub struct MapWindow {
+    pub window: u32,
+}
+
+// 1     8                               opcode
+// 1                                     unused
+// 2     2                               request length
+// 4     WINDOW                          window
+impl Request for MapWindow {
+    const OPCODE: u8 = 8;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.window)?;
+        Ok(0)
+    }
+}
+
+/// Change window size, position, border or stacking order.
+pub struct ConfigureWindow {
+    pub window: u32,
+    pub values: ConfigureValues,
+}
+
+// 1     12                              opcode
+// 1                                     unused
+// 2     3+n                             request length
+// 4     WINDOW                          window
+// 2     BITMASK                         value-mask
+// 2                                     unused
+// 4n    LISTofVALUE                     value-list
+impl Request for ConfigureWindow {
+    const OPCODE: u8 = 12;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        self.values.check_sibling()?;
+        let values = self.values.values();
+
+        writer.write_u32_ne(self.window)?;
+        writer.write_u16_ne(self.values.value_mask().bits())?;
+        writer.write_u16_ne(0)?; // pad
+
+        for (_, value) in values {
+            writer.write_u32_ne(value)?;
+        }
+
+        Ok(0)
+    }
+}
+
+/// Raise or lower child windows of the window.
+pub struct CirculateWindow {
+    pub direction: CirculateDirection,
+    pub window: u32,
+}
+
+// 1     13                              opcode
+// 1                                     direction
+//       0     RaiseLowest
+//       1     LowerHighest
+// 2     2                               request length
+// 4     WINDOW                          window
+impl Request for CirculateWindow {
+    const OPCODE: u8 = 13;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.window)?;
+        Ok(self.direction as u8)
+    }
+}
+
+/// Query geometry of window or pixmap.
+pub struct GetGeometry {
+    pub drawable: u32,
+}
+
+// 1     14                              opcode
+// 1                                     unused
+// 2     2                               request length
+// 4     DRAWABLE                        drawable
+impl Request for GetGeometry {
+    const OPCODE: u8 = 14;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.drawable)?;
+        Ok(0)
+    }
+}
+
+/// Drawable geometry, position is relative to parent and 0 for pixmaps.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GetGeometryReply {
+    pub depth: u8,
+    pub root: u32,
+    /// Position of the top left corner outside of border, size is inside of it.
+    pub geometry: Rectangle,
+    pub border_width: u16,
+}
+
+// 1     1                               Reply
+// 1     CARD8                           depth
+// 2     CARD16                          sequence number
+// 4     0                               reply length
+// 4     WINDOW                          root
+// 2     INT16                           x
+// 2     INT16                           y
+// 2     CARD16                          width
+// 2     CARD16                          height
+// 2     CARD16                          border-width
+// 10                                    unused
+impl Deserialize for GetGeometryReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        reader.read_u8()?; // Reply
+        let depth = reader.read_u8()?;
+        skip(&mut reader, 6)?;
+        let root = reader.read_u32_ne()?;
+        let x = reader.read_i16_ne()?;
+        let y = reader.read_i16_ne()?;
+        let width = reader.read_u16_ne()?;
+        let height = reader.read_u16_ne()?;
+        let border_width = reader.read_u16_ne()?;
+        skip(&mut reader, 10)?;
+
+        Ok(Some(GetGeometryReply {
+            depth,
+            root,
+            geometry: Rectangle {
+                x,
+                y,
+                width,
+                height,
+            },
+            border_width,
+        }))
+    }
+}
+
+impl ReplyRequest for GetGeometry {
+    type Reply = GetGeometryReply;
+}
+
+/// Query window attributes along with its map state.
+pub struct GetWindowAttributes {
+    pub window: u32,
+}
+
+// 1     3                               opcode
+// 1                                     unused
+// 2     2                               request length
+// 4     WINDOW                          window
+impl Request for GetWindowAttributes {
+    const OPCODE: u8 = 3;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.window)?;
+        Ok(0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GetWindowAttributesReply {
+    pub backing_store: BackingStore,
+    pub visual: u32,
+    pub class: WindowClass,
+    pub bit_gravity: BitGravity,
+    pub win_gravity: WinGravity,
+    pub backing_planes: u32,
+    pub backing_pixel: u32,
+    pub save_under: bool,
+    pub map_is_installed: bool,
+    pub map_state: MapState,
+    pub override_redirect: bool,
+    /// 0 (None) if colormap was freed.
+    pub colormap: u32,
+    /// Events selected by all clients.
+    pub all_event_masks: u32,
+    /// Events selected by this client.
+    pub your_event_mask: u32,
+    pub do_not_propagate_mask: u16,
+}
+
+fn invalid_attribute(what: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Invalid {} in GetWindowAttributes reply", what),
+    )
+}
+
+// 1     1                               Reply
+// 1                                     backing-store
+//       0     NotUseful
+//       1     WhenMapped
+//       2     Always
+// 2     CARD16                          sequence number
+// 4     3                               reply length
+// 4     VISUALID                        visual
+// 2                                     class
+//       1     InputOutput
+//       2     InputOnly
+// 1     BITGRAVITY                      bit-gravity
+// 1     WINGRAVITY                      win-gravity
+// 4     CARD32                          backing-planes
+// 4     CARD32                          backing-pixel
+// 1     BOOL                            save-under
+// 1     BOOL                            map-is-installed
+// 1                                     map-state
+//       0     Unmapped
+//       1     Unviewable
+//       2     Viewable
+// 1     BOOL                            override-redirect
+// 4     COLORMAP                        colormap
+//       0     None
+// 4     SETofEVENT                      all-event-masks
+// 4     SETofEVENT                      your-event-mask
+// 2     SETofDEVICEEVENT                do-not-propagate-mask
+// 2                                     unused
+impl Deserialize for GetWindowAttributesReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        reader.read_u8()?; // Reply
+        let backing_store = BackingStore::try_from(reader.read_u8()?)
+            .ok_or_else(|| invalid_attribute("backing-store"))?;
+        skip(&mut reader, 6)?;
+        let visual = reader.read_u32_ne()?;
+        let class = u8::try_from(reader.read_u16_ne()?)
+            .ok()
+            .and_then(WindowClass::try_from)
+            .ok_or_else(|| invalid_attribute("class"))?;
+        let bit_gravity = BitGravity::try_from(reader.read_u8()?)
+            .ok_or_else(|| invalid_attribute("bit-gravity"))?;
+        let win_gravity = WinGravity::try_from(reader.read_u8()?)
+            .ok_or_else(|| invalid_attribute("win-gravity"))?;
+        let backing_planes = reader.read_u32_ne()?;
+        let backing_pixel = reader.read_u32_ne()?;
+        let save_under = reader.read_u8()? != 0;
+        let map_is_installed = reader.read_u8()? != 0;
+        let map_state = match reader.read_u8()? {
+            0 => MapState::Unmapped,
+            1 => MapState::Unviewable,
+            2 => MapState::Viewable,
+            _ => return Err(invalid_attribute("map-state")),
+        };
+        let override_redirect = reader.read_u8()? != 0;
+        let colormap = reader.read_u32_ne()?;
+        let all_event_masks = reader.read_u32_ne()?;
+        let your_event_mask = reader.read_u32_ne()?;
+        let do_not_propagate_mask = reader.read_u16_ne()?;
+        skip(&mut reader, 2)?;
+
+        Ok(Some(GetWindowAttributesReply {
+            backing_store,
+            visual,
+            class,
+            bit_gravity,
+            win_gravity,
+            backing_planes,
+            backing_pixel,
+            save_under,
+            map_is_installed,
+            map_state,
+            override_redirect,
+            colormap,
+            all_event_masks,
+            your_event_mask,
+            do_not_propagate_mask,
+        }))
+    }
+}
+
+impl ReplyRequest for GetWindowAttributes {
+    type Reply = GetWindowAttributesReply;
+}
+
+/// Query root, parent and children of window.
+pub struct QueryTree {
+    pub window: u32,
+}
+
+// 1     15                              opcode
+// 1                                     unused
+// 2     2                               request length
+// 4     WINDOW                          window
+impl Request for QueryTree {
+    const OPCODE: u8 = 15;
+
+    fn serialize_body<W: Write>(&self, mut writer: W) -> io::Result<u8> {
+        writer.write_u32_ne(self.window)?;
+        Ok(0)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct QueryTreeReply {
+    pub root: u32,
+    /// 0 (None) for root window.
+    pub parent: u32,
+    /// Children in bottom-to-top stacking order.
+    pub children: Vec<u32>,
+}
+
+// 1     1                               Reply
+// 1                                     unused
+// 2     CARD16                          sequence number
+// 4     n                               reply length
+// 4     WINDOW                          root
+// 4     WINDOW                          parent
+//       0     None
+// 2     n                               number of WINDOWs in children
+// 14                                    unused
+// 4n    LISTofWINDOW                    children
+impl Deserialize for QueryTreeReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        skip(&mut reader, 8)?; // reply header
+        let root = reader.read_u32_ne()?;
+        let parent = reader.read_u32_ne()?;
+        let children_len = reader.read_u16_ne()?;
+        skip(&mut reader, 14)?;
+
+        let children = (0..children_len)
+            .map(|_| reader.read_u32_ne())
+            .collect::<io::Result<_>>()?;
+
+        Ok(Some(QueryTreeReply {
+            root,
+            parent,
+            children,
+        }))
+    }
+}
+
+impl ReplyRequest for QueryTree {
+    type Reply = QueryTreeReply;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        value_mask, BitGravity, ChangeSaveSet, ChangeWindowAttributes, CirculateDirection,
+        CirculateWindow, ConfigureValues, ConfigureWindow, CreateWindow, GetGeometry,
+        GetGeometryReply, MapWindow, SaveSetMode, StackMode, WinGravity, WindowAttributes,
+        WindowClass,
+    };
+    use crate::protocol::{Deserialize, EventMask, Rectangle, Serialize};
+
+    #[test]
+    fn test_serialize_create_window() {
+        const EXPECTED: &[u8] = b"\x01\x00\x0a\x00\x01\x00\x40\x00\x00\x01\x00\x00\
+                                  \x0a\x00\x14\x00\x20\x03\x58\x02\x01\x00\x01\x00\
+                                  \x00\x00\x00\x00\x12\x00\x00\x00\xff\xff\xff\x00\x07\x00\x00\x00";
+        let mut write_buf = vec![];
+
+        CreateWindow {
+            depth: 0,
+            wid: 0x0040_0001,
+            parent: 0x100,
+            geometry: Rectangle::from((10, 20, 800, 600)),
+            border_width: 1,
+            class: WindowClass::InputOutput,
+            visual: 0,
+            attributes: WindowAttributes::new()
+                .bit_gravity(BitGravity::SouthWest)
+                .background_pixel(0x00ff_ffff),
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_serialize_change_window_attributes() {
+        const EXPECTED: &[u8] =
+            b"\x02\x00\x05\x00\x01\x02\x00\x00\x00\x0a\x00\x00\x01\x00\x00\x00\x00\x00\x42\x00";
+        let mut write_buf = vec![];
+
+        ChangeWindowAttributes {
+            window: 0x201,
+            attributes: WindowAttributes::new()
+                .event_mask(EventMask::PROPERTY_CHANGE | EventMask::STRUCTURE_NOTIFY)
+                .override_redirect(true),
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_gravity_and_class_values() {
+        assert_eq!(BitGravity::Forget as u8, 0);
+        assert_eq!(BitGravity::NorthWest as u8, 1);
+        assert_eq!(BitGravity::Center as u8, 5);
+        assert_eq!(BitGravity::SouthEast as u8, 9);
+        assert_eq!(BitGravity::Static as u8, 10);
+        assert_eq!(WinGravity::Unmap as u8, 0);
+        assert_eq!(WinGravity::NorthWest as u8, 1);
+        assert_eq!(WinGravity::Static as u8, 10);
+        assert_eq!(WindowClass::CopyFromParent as u8, 0);
+        assert_eq!(WindowClass::InputOutput as u8, 1);
+        assert_eq!(WindowClass::InputOnly as u8, 2);
+    }
+
+    #[test]
+    fn test_input_only_attributes() {
+        let attributes = WindowAttributes::new()
+            .win_gravity(WinGravity::Static)
+            .override_redirect(true)
+            .event_mask(EventMask::BUTTON_PRESS)
+            .cursor(0x0040_0002);
+        assert!(attributes.check_class(WindowClass::InputOnly).is_ok());
+
+        let attributes = attributes.background_pixel(0);
+        assert!(attributes.check_class(WindowClass::InputOutput).is_ok());
+        assert!(attributes.check_class(WindowClass::InputOnly).is_err());
+
+        let attributes = WindowAttributes::new().cursor(0).colormap(0x20);
+        assert!(attributes.check_class(WindowClass::InputOnly).is_err());
+    }
+
+    #[test]
+    fn test_value_mask() {
+        let attributes = WindowAttributes::new()
+            .cursor(0x0040_0002)
+            .background_pixel(0)
+            .event_mask(EventMask::BUTTON_PRESS);
+        assert_eq!(value_mask(&attributes.values()).unwrap(), 0x4802);
+
+        // Values out of order, duplicate and without a single bit
+        assert!(value_mask(&[(0x0800, 0x4), (0x0002, 0)]).is_err());
+        assert!(value_mask(&[(0x0002, 0), (0x0002, 0)]).is_err());
+        assert!(value_mask(&[(0x0003, 0)]).is_err());
+        assert!(value_mask(&[(0, 0)]).is_err());
+    }
+
+    #[test]
+    fn test_serialize_change_save_set_insert() {
+        const EXPECTED: &[u8] = b"\x06\x00\x02\x00\x01\x00\x60\x00";
+        let mut write_buf = vec![];
+
+        ChangeSaveSet {
+            mode: SaveSetMode::Insert,
+            window: 0x0060_0001,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_serialize_change_save_set_delete() {
+        const EXPECTED: &[u8] = b"\x06\x01\x02\x00\x01\x00\x60\x00";
+        let mut write_buf = vec![];
+
+        ChangeSaveSet {
+            mode: SaveSetMode::Delete,
+            window: 0x0060_0001,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_serialize_map_window() {
+        const EXPECTED: &[u8] = b"\x08\x00\x02\x00\x03\x04\x00\x00";
+        let mut write_buf = vec![];
+
+        MapWindow { window: 0x403 }
+            .serialize(&mut write_buf)
+            .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_serialize_circulate_window_raise_lowest() {
+        const EXPECTED: &[u8] = b"\x0d\x00\x02\x00\x01\x02\x00\x00";
+        let mut write_buf = vec![];
+
+        CirculateWindow {
+            direction: CirculateDirection::RaiseLowest,
+            window: 0x201,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_serialize_circulate_window_lower_highest() {
+        const EXPECTED: &[u8] = b"\x0d\x01\x02\x00\x01\x02\x00\x00";
+        let mut write_buf = vec![];
+
+        CirculateWindow {
+            direction: CirculateDirection::LowerHighest,
+            window: 0x201,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_serialize_configure_window_sibling_stack_mode() {
+        const EXPECTED: &[u8] =
+            b"\x0c\x00\x05\x00\x01\x02\x00\x00\x60\x00\x00\x00\x02\x03\x00\x00\x01\x00\x00\x00";
+        let mut write_buf = vec![];
+
+        ConfigureWindow {
+            window: 0x201,
+            values: ConfigureValues::new()
+                .sibling(0x302)
+                .stack_mode(StackMode::Below),
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_serialize_configure_window_sibling_without_stack_mode() {
+        let mut write_buf = vec![];
+
+        let configure = ConfigureWindow {
+            window: 0x201,
+            values: ConfigureValues::new().x(10).sibling(0x302),
+        };
+        assert!(configure.serialize(&mut write_buf).is_err());
+
+        // Stack mode alone restacks relative to all siblings
+        ConfigureWindow {
+            window: 0x201,
+            values: ConfigureValues::new().stack_mode(StackMode::TopIf),
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+        assert_eq!(
+            write_buf,
+            b"\x0c\x00\x04\x00\x01\x02\x00\x00\x40\x00\x00\x00\x02\x00\x00\x00"
+        );
+    }
+
+    #[test]
+    fn test_serialize_configure_window_geometry() {
+        const EXPECTED: &[u8] =
+            b"\x0c\x00\x05\x00\x01\x02\x00\x00\x03\x00\x00\x00\xf6\xff\xff\xff\x14\x00\x00\x00";
+        let mut write_buf = vec![];
+
+        ConfigureWindow {
+            window: 0x201,
+            values: ConfigureValues::new().y(20).x(-10),
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_stack_mode_values() {
+        assert_eq!(StackMode::Above as u8, 0);
+        assert_eq!(StackMode::Below as u8, 1);
+        assert_eq!(StackMode::TopIf as u8, 2);
+        assert_eq!(StackMode::BottomIf as u8, 3);
+        assert_eq!(StackMode::Opposite as u8, 4);
+    }
+
+    #[test]
+    fn test_serialize_get_geometry() {
+        const EXPECTED: &[u8] = b"\x0e\x00\x02\x00\x01\x00\x00\x04";
+        let mut write_buf = vec![];
+
+        GetGeometry {
+            drawable: 0x0400_0001,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_deserialize_get_geometry_reply() {
+        let mut reply = b"\x01\x18\x01\x00\x00\x00\x00\x00\x00\x01\x00\x00".to_vec();
+        reply.extend(b"\x0a\x00\xec\xff\x20\x03\x58\x02\x02\x00");
+        reply.extend(vec![0; 10]);
+
+        assert_eq!(
+            GetGeometryReply::deserialize(&reply[..]).unwrap().unwrap(),
+            GetGeometryReply {
+                depth: 24,
+                root: 0x100,
+                geometry: Rectangle::from((10, -20, 800, 600)),
+                border_width: 2,
+            }
+        );
+    }
+}
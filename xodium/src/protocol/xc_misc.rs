@@ -0,0 +1,169 @@
+//! XC-MISC extension, lets client reuse resource ids freed by X server.
+
+use super::{skip, Deserialize, ReplyRequest, Serialize};
+use crate::io::{self, Read, Write};
+use crate::utils::{ReadBytesExt, WriteBytesExt};
+use alloc::vec::Vec;
+
+pub const XC_MISC_EXTENSION_NAME: &str = "XC-MISC";
+
+const XC_MISC_GET_XID_RANGE_OPCODE: u8 = 1;
+const XC_MISC_GET_XID_LIST_OPCODE: u8 = 2;
+
+/// Request range of resource ids not used by X server.
+pub struct GetXIDRange {
+    pub major_opcode: u8,
+}
+
+// 1     CARD8                           major opcode
+// 1     1                               minor opcode
+// 2     1                               request length
+impl Serialize for GetXIDRange {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(XC_MISC_GET_XID_RANGE_OPCODE)?;
+        writer.write_u16_ne(1)?;
+        Ok(())
+    }
+}
+
+/// Contiguous range of free resource ids, `count` is 0 if there are none left.
+#[derive(Debug, PartialEq)]
+pub struct GetXIDRangeReply {
+    pub start_id: u32,
+    pub count: u32,
+}
+
+// 1     1                               Reply
+// 1                                     unused
+// 2     CARD16                          sequence number
+// 4     0                               reply length
+// 4     CARD32                          start-id
+// 4     CARD32                          count
+// 16                                    unused
+impl Deserialize for GetXIDRangeReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        skip(&mut reader, 8)?; // reply header
+        let start_id = reader.read_u32_ne()?;
+        let count = reader.read_u32_ne()?;
+        skip(&mut reader, 16)?;
+
+        Ok(Some(GetXIDRangeReply { start_id, count }))
+    }
+}
+
+impl ReplyRequest for GetXIDRange {
+    type Reply = GetXIDRangeReply;
+}
+
+/// Request up to `count` resource ids not used by X server.
+pub struct GetXIDList {
+    pub major_opcode: u8,
+    pub count: u32,
+}
+
+// 1     CARD8                           major opcode
+// 1     2                               minor opcode
+// 2     2                               request length
+// 4     CARD32                          count
+impl Serialize for GetXIDList {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(XC_MISC_GET_XID_LIST_OPCODE)?;
+        writer.write_u16_ne(2)?;
+        writer.write_u32_ne(self.count)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct GetXIDListReply {
+    pub ids: Vec<u32>,
+}
+
+// 1     1                               Reply
+// 1                                     unused
+// 2     CARD16                          sequence number
+// 4     n                               reply length
+// 4     n                               number of XIDs in ids
+// 20                                    unused
+// 4n    LISTofCARD32                    ids
+impl Deserialize for GetXIDListReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        skip(&mut reader, 8)?; // reply header
+        let ids_len = reader.read_u32_ne()?;
+        skip(&mut reader, 20)?;
+
+        let ids = (0..ids_len)
+            .map(|_| reader.read_u32_ne())
+            .collect::<io::Result<_>>()?;
+
+        Ok(Some(GetXIDListReply { ids }))
+    }
+}
+
+impl ReplyRequest for GetXIDList {
+    type Reply = GetXIDListReply;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GetXIDList, GetXIDListReply, GetXIDRange, GetXIDRangeReply};
+    use crate::protocol::{Deserialize, Serialize};
+
+    #[test]
+    fn test_serialize_get_xid_range() {
+        const EXPECTED: &[u8] = b"\x82\x01\x01\x00";
+        let mut write_buf = vec![];
+
+        GetXIDRange { major_opcode: 130 }
+            .serialize(&mut write_buf)
+            .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_deserialize_get_xid_range_reply() {
+        let mut reply =
+            b"\x01\x00\x01\x00\x00\x00\x00\x00\x00\x01\x00\x04\x10\x00\x00\x00".to_vec();
+        reply.extend(vec![0; 16]);
+
+        assert_eq!(
+            GetXIDRangeReply::deserialize(&reply[..]).unwrap().unwrap(),
+            GetXIDRangeReply {
+                start_id: 0x04000100,
+                count: 16,
+            }
+        );
+    }
+
+    #[test]
+    fn test_serialize_get_xid_list() {
+        const EXPECTED: &[u8] = b"\x82\x02\x02\x00\x08\x00\x00\x00";
+        let mut write_buf = vec![];
+
+        GetXIDList {
+            major_opcode: 130,
+            count: 8,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_deserialize_get_xid_list_reply() {
+        let mut reply = b"\x01\x00\x01\x00\x02\x00\x00\x00\x02\x00\x00\x00".to_vec();
+        reply.extend(vec![0; 20]);
+        reply.extend(b"\x05\x00\x00\x04\x09\x00\x00\x04");
+
+        assert_eq!(
+            GetXIDListReply::deserialize(&reply[..]).unwrap().unwrap(),
+            GetXIDListReply {
+                ids: vec![0x04000005, 0x04000009],
+            }
+        );
+    }
+}
@@ -0,0 +1,229 @@
+//! XFIXES extension, regions used e.g. for input shape of click-through windows.
+
+use super::{request_length, skip, Deserialize, Rectangle, ReplyRequest, Serialize, ShapeKind};
+use crate::io::{self, Read, Write};
+use crate::utils::{ReadBytesExt, WriteBytesExt};
+use alloc::vec::Vec;
+
+pub const XFIXES_EXTENSION_NAME: &str = "XFIXES";
+
+const XFIXES_QUERY_VERSION_OPCODE: u8 = 0;
+const XFIXES_CREATE_REGION_OPCODE: u8 = 5;
+const XFIXES_DESTROY_REGION_OPCODE: u8 = 10;
+const XFIXES_SET_WINDOW_SHAPE_REGION_OPCODE: u8 = 21;
+
+/// Negotiate XFIXES version, client sends highest version it supports.
+/// X server rejects other XFIXES requests until version is negotiated.
+pub struct XFixesQueryVersion {
+    pub major_opcode: u8,
+    pub major_version: u32,
+    pub minor_version: u32,
+}
+
+// 1     CARD8                           major opcode
+// 1     0                               XFixes opcode
+// 2     3                               request length
+// 4     CARD32                          major version
+// 4     CARD32                          minor version
+impl Serialize for XFixesQueryVersion {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(XFIXES_QUERY_VERSION_OPCODE)?;
+        writer.write_u16_ne(3)?;
+        writer.write_u32_ne(self.major_version)?;
+        writer.write_u32_ne(self.minor_version)?;
+        Ok(())
+    }
+}
+
+/// XFIXES version supported by both X server and client.
+#[derive(Debug, PartialEq)]
+pub struct XFixesQueryVersionReply {
+    pub major_version: u32,
+    pub minor_version: u32,
+}
+
+// 1     1                               Reply
+// 1                                     unused
+// 2     CARD16                          sequence number
+// 4     0                               reply length
+// 4     CARD32                          major version
+// 4     CARD32                          minor version
+// 16                                    unused
+impl Deserialize for XFixesQueryVersionReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        skip(&mut reader, 8)?; // reply header
+        let major_version = reader.read_u32_ne()?;
+        let minor_version = reader.read_u32_ne()?;
+        skip(&mut reader, 16)?;
+
+        Ok(Some(XFixesQueryVersionReply {
+            major_version,
+            minor_version,
+        }))
+    }
+}
+
+impl ReplyRequest for XFixesQueryVersion {
+    type Reply = XFixesQueryVersionReply;
+}
+
+/// Create region which is union of rectangles.
+pub struct CreateRegion {
+    pub major_opcode: u8,
+    pub region: u32,
+    pub rectangles: Vec<Rectangle>,
+}
+
+// 1     CARD8                           major opcode
+// 1     5                               XFixes opcode
+// 2     2+2n                            request length
+// 4     REGION                          region
+// 8n    LISTofRECTANGLE                 rectangles
+impl Serialize for CreateRegion {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(XFIXES_CREATE_REGION_OPCODE)?;
+        writer.write_u16_ne(request_length(2 + 2 * self.rectangles.len())?)?;
+        writer.write_u32_ne(self.region)?;
+        for rectangle in &self.rectangles {
+            rectangle.serialize(&mut writer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Free region.
+pub struct DestroyRegion {
+    pub major_opcode: u8,
+    pub region: u32,
+}
+
+// 1     CARD8                           major opcode
+// 1     10                              XFixes opcode
+// 2     2                               request length
+// 4     REGION                          region
+impl Serialize for DestroyRegion {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(XFIXES_DESTROY_REGION_OPCODE)?;
+        writer.write_u16_ne(2)?;
+        writer.write_u32_ne(self.region)?;
+        Ok(())
+    }
+}
+
+/// Set window shape to region.
+pub struct SetWindowShapeRegion {
+    pub major_opcode: u8,
+    pub window: u32,
+    pub kind: ShapeKind,
+    pub x_offset: i16,
+    pub y_offset: i16,
+    /// 0 (None) resets shape to the default rectangle.
+    pub region: u32,
+}
+
+// 1     CARD8                           major opcode
+// 1     21                              XFixes opcode
+// 2     5                               request length
+// 4     WINDOW                          dest
+// 1     SHAPE_KIND                      destKind
+// 3                                     unused
+// 2     INT16                           xOff
+// 2     INT16                           yOff
+// 4     REGION or None                  region
+impl Serialize for SetWindowShapeRegion {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(XFIXES_SET_WINDOW_SHAPE_REGION_OPCODE)?;
+        writer.write_u16_ne(5)?;
+        writer.write_u32_ne(self.window)?;
+        writer.write_u8(self.kind as u8)?;
+        writer.write_all(&[0; 3])?; // pad
+        writer.write_i16_ne(self.x_offset)?;
+        writer.write_i16_ne(self.y_offset)?;
+        writer.write_u32_ne(self.region)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CreateRegion, SetWindowShapeRegion, XFixesQueryVersion, XFixesQueryVersionReply};
+    use crate::protocol::{Deserialize, Rectangle, Serialize, ShapeKind};
+
+    #[test]
+    fn test_serialize_query_version() {
+        const EXPECTED: &[u8] = b"\x8a\x00\x03\x00\x05\x00\x00\x00\x00\x00\x00\x00";
+        let mut write_buf = vec![];
+
+        XFixesQueryVersion {
+            major_opcode: 138,
+            major_version: 5,
+            minor_version: 0,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_deserialize_query_version_reply() {
+        let mut reply =
+            b"\x01\x00\x01\x00\x00\x00\x00\x00\x05\x00\x00\x00\x00\x00\x00\x00".to_vec();
+        reply.extend(vec![0; 16]);
+
+        assert_eq!(
+            XFixesQueryVersionReply::deserialize(&reply[..])
+                .unwrap()
+                .unwrap(),
+            XFixesQueryVersionReply {
+                major_version: 5,
+                minor_version: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_serialize_create_region() {
+        const EXPECTED: &[u8] = b"\x8a\x05\x06\x00\x01\x00\x00\x04\
+                                  \x00\x00\x00\x00\x0a\x00\x14\x00\
+                                  \xfb\xff\x1e\x00\x28\x00\x32\x00";
+        let mut write_buf = vec![];
+
+        CreateRegion {
+            major_opcode: 138,
+            region: 0x04000001,
+            rectangles: vec![
+                Rectangle::from((0, 0, 10, 20)),
+                Rectangle::from((-5, 30, 40, 50)),
+            ],
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_serialize_set_window_shape_region() {
+        const EXPECTED: &[u8] = b"\x8a\x15\x05\x00\x01\x02\x00\x00\x02\x00\x00\x00\
+                                  \x00\x00\x00\x00\x01\x00\x00\x04";
+        let mut write_buf = vec![];
+
+        SetWindowShapeRegion {
+            major_opcode: 138,
+            window: 0x201,
+            kind: ShapeKind::Input,
+            x_offset: 0,
+            y_offset: 0,
+            region: 0x04000001,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+}
@@ -0,0 +1,286 @@
+//! XINERAMA extension, geometry of monitors combined into a single screen.
+
+use super::{skip, Deserialize, ReplyRequest, Serialize};
+use crate::io::{self, Read, Write};
+use crate::utils::{ReadBytesExt, WriteBytesExt};
+use alloc::vec::Vec;
+
+pub const XINERAMA_EXTENSION_NAME: &str = "XINERAMA";
+
+const XINERAMA_QUERY_VERSION_OPCODE: u8 = 0;
+const XINERAMA_IS_ACTIVE_OPCODE: u8 = 4;
+const XINERAMA_QUERY_SCREENS_OPCODE: u8 = 5;
+
+/// Negotiate XINERAMA version, client sends highest version it supports.
+pub struct XineramaQueryVersion {
+    pub major_opcode: u8,
+    pub major_version: u8,
+    pub minor_version: u8,
+}
+
+// 1     CARD8                           major opcode
+// 1     0                               Xinerama opcode
+// 2     2                               request length
+// 1     CARD8                           major version
+// 1     CARD8                           minor version
+// 2                                     unused
+impl Serialize for XineramaQueryVersion {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(XINERAMA_QUERY_VERSION_OPCODE)?;
+        writer.write_u16_ne(2)?;
+        writer.write_u8(self.major_version)?;
+        writer.write_u8(self.minor_version)?;
+        writer.write_u16_ne(0)?;
+        Ok(())
+    }
+}
+
+/// XINERAMA version supported by X server.
+#[derive(Debug, PartialEq)]
+pub struct XineramaQueryVersionReply {
+    pub major_version: u16,
+    pub minor_version: u16,
+}
+
+// 1     1                               Reply
+// 1                                     unused
+// 2     CARD16                          sequence number
+// 4     0                               reply length
+// 2     CARD16                          major version
+// 2     CARD16                          minor version
+// 20                                    unused
+impl Deserialize for XineramaQueryVersionReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        skip(&mut reader, 8)?; // reply header
+        let major_version = reader.read_u16_ne()?;
+        let minor_version = reader.read_u16_ne()?;
+        skip(&mut reader, 20)?;
+
+        Ok(Some(XineramaQueryVersionReply {
+            major_version,
+            minor_version,
+        }))
+    }
+}
+
+impl ReplyRequest for XineramaQueryVersion {
+    type Reply = XineramaQueryVersionReply;
+}
+
+/// Query whether screen is combined from several monitors.
+pub struct XineramaIsActive {
+    pub major_opcode: u8,
+}
+
+// 1     CARD8                           major opcode
+// 1     4                               Xinerama opcode
+// 2     1                               request length
+impl Serialize for XineramaIsActive {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(XINERAMA_IS_ACTIVE_OPCODE)?;
+        writer.write_u16_ne(1)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct XineramaIsActiveReply {
+    pub active: bool,
+}
+
+// 1     1                               Reply
+// 1                                     unused
+// 2     CARD16                          sequence number
+// 4     0                               reply length
+// 4     CARD32                          state
+// 20                                    unused
+impl Deserialize for XineramaIsActiveReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        skip(&mut reader, 8)?; // reply header
+        let active = reader.read_u32_ne()? != 0;
+        skip(&mut reader, 20)?;
+
+        Ok(Some(XineramaIsActiveReply { active }))
+    }
+}
+
+impl ReplyRequest for XineramaIsActive {
+    type Reply = XineramaIsActiveReply;
+}
+
+/// Query geometry of monitors the screen is combined from.
+pub struct XineramaQueryScreens {
+    pub major_opcode: u8,
+}
+
+// 1     CARD8                           major opcode
+// 1     5                               Xinerama opcode
+// 2     1                               request length
+impl Serialize for XineramaQueryScreens {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(XINERAMA_QUERY_SCREENS_OPCODE)?;
+        writer.write_u16_ne(1)?;
+        Ok(())
+    }
+}
+
+/// Monitor area of the screen, in screen coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScreenInfo {
+    pub x_org: i16,
+    pub y_org: i16,
+    pub width: u16,
+    pub height: u16,
+}
+
+// 2     INT16                           x_org
+// 2     INT16                           y_org
+// 2     CARD16                          width
+// 2     CARD16                          height
+impl Deserialize for ScreenInfo {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        let x_org = reader.read_i16_ne()?;
+        let y_org = reader.read_i16_ne()?;
+        let width = reader.read_u16_ne()?;
+        let height = reader.read_u16_ne()?;
+
+        Ok(Some(ScreenInfo {
+            x_org,
+            y_org,
+            width,
+            height,
+        }))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct XineramaQueryScreensReply {
+    pub screens: Vec<ScreenInfo>,
+}
+
+// 1     1                               Reply
+// 1                                     unused
+// 2     CARD16                          sequence number
+// 4     2n                              reply length
+// 4     n                               number of SCREENINFOs in screens
+// 20                                    unused
+// 8n    LISTofSCREENINFO                screens
+impl Deserialize for XineramaQueryScreensReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        skip(&mut reader, 8)?; // reply header
+        let screens_len = reader.read_u32_ne()?;
+        skip(&mut reader, 20)?;
+
+        let mut screens = Vec::new();
+        for _ in 0..screens_len {
+            if let Some(screen) = ScreenInfo::deserialize(&mut reader)? {
+                screens.push(screen);
+            }
+        }
+
+        Ok(Some(XineramaQueryScreensReply { screens }))
+    }
+}
+
+impl ReplyRequest for XineramaQueryScreens {
+    type Reply = XineramaQueryScreensReply;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ScreenInfo, XineramaIsActiveReply, XineramaQueryScreens, XineramaQueryScreensReply,
+        XineramaQueryVersion, XineramaQueryVersionReply,
+    };
+    use crate::protocol::{Deserialize, Serialize};
+
+    #[test]
+    fn test_serialize_xinerama_query_version() {
+        const EXPECTED: &[u8] = b"\x8a\x00\x02\x00\x01\x01\x00\x00";
+        let mut write_buf = vec![];
+
+        XineramaQueryVersion {
+            major_opcode: 138,
+            major_version: 1,
+            minor_version: 1,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_deserialize_xinerama_query_version_reply() {
+        let mut reply = b"\x01\x00\x01\x00\x00\x00\x00\x00\x01\x00\x01\x00".to_vec();
+        reply.extend(vec![0; 20]);
+
+        assert_eq!(
+            XineramaQueryVersionReply::deserialize(&reply[..])
+                .unwrap()
+                .unwrap(),
+            XineramaQueryVersionReply {
+                major_version: 1,
+                minor_version: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_deserialize_xinerama_is_active_reply() {
+        let mut reply = b"\x01\x00\x01\x00\x00\x00\x00\x00\x01\x00\x00\x00".to_vec();
+        reply.extend(vec![0; 20]);
+
+        assert_eq!(
+            XineramaIsActiveReply::deserialize(&reply[..])
+                .unwrap()
+                .unwrap(),
+            XineramaIsActiveReply { active: true }
+        );
+    }
+
+    #[test]
+    fn test_serialize_xinerama_query_screens() {
+        const EXPECTED: &[u8] = b"\x8a\x05\x01\x00";
+        let mut write_buf = vec![];
+
+        XineramaQueryScreens { major_opcode: 138 }
+            .serialize(&mut write_buf)
+            .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_deserialize_xinerama_query_screens_reply() {
+        let mut reply = b"\x01\x00\x01\x00\x04\x00\x00\x00\x02\x00\x00\x00".to_vec();
+        reply.extend(vec![0; 20]);
+        reply.extend(b"\x00\x00\x00\x00\x80\x07\x38\x04");
+        reply.extend(b"\x80\x07\x00\x00\x00\x0a\xa0\x05");
+
+        assert_eq!(
+            XineramaQueryScreensReply::deserialize(&reply[..])
+                .unwrap()
+                .unwrap(),
+            XineramaQueryScreensReply {
+                screens: vec![
+                    ScreenInfo {
+                        x_org: 0,
+                        y_org: 0,
+                        width: 1920,
+                        height: 1080,
+                    },
+                    ScreenInfo {
+                        x_org: 1920,
+                        y_org: 0,
+                        width: 2560,
+                        height: 1440,
+                    },
+                ],
+            }
+        );
+    }
+}
@@ -0,0 +1,345 @@
+//! X Input extension version 2 (XI2), per-device and sub-pixel input.
+//!
+//! Only version negotiation, event selection and device events
+//! (key, button, motion and touch) are covered, raw events and
+//! valuators of device events are not decoded yet.
+
+use super::{request_length, skip, Deserialize, Event, ReplyRequest, Serialize};
+use crate::io::{self, Read, Write};
+use crate::utils::{ReadBytesExt, WriteBytesExt};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+pub const XINPUT_EXTENSION_NAME: &str = "XInputExtension";
+
+const XI_SELECT_EVENTS_OPCODE: u8 = 46;
+const XI_QUERY_VERSION_OPCODE: u8 = 47;
+
+/// Device ids selecting events of all devices or all master devices.
+pub const XI_ALL_DEVICES: u16 = 0;
+pub const XI_ALL_MASTER_DEVICES: u16 = 1;
+
+/// XI2 generic event types, event mask bit of event type is `1 << type`.
+pub const XI_KEY_PRESS: u16 = 2;
+pub const XI_KEY_RELEASE: u16 = 3;
+pub const XI_BUTTON_PRESS: u16 = 4;
+pub const XI_BUTTON_RELEASE: u16 = 5;
+pub const XI_MOTION: u16 = 6;
+pub const XI_TOUCH_BEGIN: u16 = 18;
+pub const XI_TOUCH_UPDATE: u16 = 19;
+pub const XI_TOUCH_END: u16 = 20;
+
+/// Negotiate XI2 version, client sends highest version it supports.
+/// Required before any other XI2 request.
+pub struct XIQueryVersion {
+    pub major_opcode: u8,
+    pub major_version: u16,
+    pub minor_version: u16,
+}
+
+// 1     CARD8                           major opcode
+// 1     47                              XI opcode
+// 2     2                               request length
+// 2     CARD16                          major version
+// 2     CARD16                          minor version
+impl Serialize for XIQueryVersion {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(XI_QUERY_VERSION_OPCODE)?;
+        writer.write_u16_ne(2)?;
+        writer.write_u16_ne(self.major_version)?;
+        writer.write_u16_ne(self.minor_version)?;
+        Ok(())
+    }
+}
+
+/// XI version supported by both X server and client.
+#[derive(Debug, PartialEq)]
+pub struct XIQueryVersionReply {
+    pub major_version: u16,
+    pub minor_version: u16,
+}
+
+// 1     1                               Reply
+// 1                                     unused
+// 2     CARD16                          sequence number
+// 4     0                               reply length
+// 2     CARD16                          major version
+// 2     CARD16                          minor version
+// 20                                    unused
+impl Deserialize for XIQueryVersionReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        skip(&mut reader, 8)?; // reply header
+        let major_version = reader.read_u16_ne()?;
+        let minor_version = reader.read_u16_ne()?;
+        skip(&mut reader, 20)?;
+
+        Ok(Some(XIQueryVersionReply {
+            major_version,
+            minor_version,
+        }))
+    }
+}
+
+impl ReplyRequest for XIQueryVersion {
+    type Reply = XIQueryVersionReply;
+}
+
+/// Select XI2 events of window per device, events are delivered as generic events.
+pub struct XISelectEvents {
+    pub major_opcode: u8,
+    pub window: u32,
+    /// Device id and event mask, e.g. `(XI_ALL_MASTER_DEVICES, 1 << XI_MOTION)`.
+    /// Empty mask clears selection of the device.
+    pub masks: Vec<(u16, u32)>,
+}
+
+// 1     CARD8                           major opcode
+// 1     46                              XI opcode
+// 2     3+n                             request length
+// 4     WINDOW                          window
+// 2     CARD16                          num masks
+// 2                                     unused
+// n     LISTofEVMASK                    masks
+//
+// EVMASK
+// 2     DEVICEID                        device id
+// 2     CARD16                          mask length in 4-byte units
+// 4m    SETofEVENTMASK                  mask
+impl Serialize for XISelectEvents {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let num_masks = u16::try_from(self.masks.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Too many event masks"))?;
+
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(XI_SELECT_EVENTS_OPCODE)?;
+        writer.write_u16_ne(request_length(3 + 2 * self.masks.len())?)?;
+        writer.write_u32_ne(self.window)?;
+        writer.write_u16_ne(num_masks)?;
+        writer.write_u16_ne(0)?; // pad
+        for &(device_id, mask) in &self.masks {
+            writer.write_u16_ne(device_id)?;
+            writer.write_u16_ne(1)?;
+            writer.write_u32_ne(mask)?;
+        }
+        Ok(())
+    }
+}
+
+/// Key, button, motion or touch event of a device.
+#[derive(Debug, Clone, PartialEq)]
+pub struct XIDeviceEvent {
+    /// One of device event types, e.g. [XI_MOTION].
+    pub event_type: u16,
+    pub device_id: u16,
+    /// Physical device which generated the event, `device_id` may be its master.
+    pub source_id: u16,
+    pub time: u32,
+    /// Keycode for key events, button for button events,
+    /// touch id for touch events, 0 for motion events.
+    pub detail: u32,
+    pub root: u32,
+    pub event: u32,
+    /// Child of event window containing pointer, 0 (None) if there is none.
+    pub child: u32,
+    pub root_x: f64,
+    pub root_y: f64,
+    pub event_x: f64,
+    pub event_y: f64,
+    pub flags: u32,
+    /// Effective modifiers state.
+    pub mods: u32,
+    /// Effective keyboard group.
+    pub group: u8,
+    /// Buttons pressed right before the event, bit n is set if button n is pressed.
+    pub buttons: Vec<u32>,
+}
+
+impl XIDeviceEvent {
+    /// Decode event if it is a device event of XI with specified major opcode.
+    pub fn from_event(event: &Event, major_opcode: u8) -> io::Result<Option<Self>> {
+        match event {
+            Event::Generic(event)
+                if event.extension == major_opcode
+                    && ((XI_KEY_PRESS..=XI_MOTION).contains(&event.event_type)
+                        || (XI_TOUCH_BEGIN..=XI_TOUCH_END).contains(&event.event_type)) =>
+            {
+                XIDeviceEvent::deserialize(&event.data[..])
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Fixed point number with 16 bits of integral and 16 bits of fractional part.
+fn read_fp1616<R: Read>(mut reader: R) -> io::Result<f64> {
+    Ok(f64::from(reader.read_i32_ne()?) / 65536.0)
+}
+
+// 1     35                              XGE event type
+// 1     CARD8                           XI extension opcode
+// 2     CARD16                          sequence number
+// 4     CARD32                          length
+// 2     CARD16                          event type
+// 2     DEVICEID                        device id
+// 4     TIMESTAMP                       time
+// 4     CARD32                          detail
+// 4     WINDOW                          root
+// 4     WINDOW                          event
+// 4     WINDOW                          child
+// 4     FP1616                          root x
+// 4     FP1616                          root y
+// 4     FP1616                          event x
+// 4     FP1616                          event y
+// 2     CARD16                          buttons length in 4-byte units
+// 2     CARD16                          valuators length in 4-byte units
+// 2     DEVICEID                        source id
+// 2                                     unused
+// 4     SETofDEVICEEVENTFLAGS           flags
+// 16    MODIFIERINFO                    mods (base, latched, locked, effective)
+// 4     GROUPINFO                       group (base, latched, locked, effective)
+// 4b    SETofBUTTONMASK                 buttons
+// 4v    SETofVALUATORMASK               valuators
+//       LISTofFP3232                    axis values
+impl Deserialize for XIDeviceEvent {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        skip(&mut reader, 8)?; // generic event header
+        let event_type = reader.read_u16_ne()?;
+        let device_id = reader.read_u16_ne()?;
+        let time = reader.read_u32_ne()?;
+        let detail = reader.read_u32_ne()?;
+        let root = reader.read_u32_ne()?;
+        let event = reader.read_u32_ne()?;
+        let child = reader.read_u32_ne()?;
+        let root_x = read_fp1616(&mut reader)?;
+        let root_y = read_fp1616(&mut reader)?;
+        let event_x = read_fp1616(&mut reader)?;
+        let event_y = read_fp1616(&mut reader)?;
+        let buttons_len = reader.read_u16_ne()?;
+        skip(&mut reader, 2)?; // valuators length
+        let source_id = reader.read_u16_ne()?;
+        skip(&mut reader, 2)?;
+        let flags = reader.read_u32_ne()?;
+        skip(&mut reader, 12)?; // base, latched and locked mods
+        let mods = reader.read_u32_ne()?;
+        skip(&mut reader, 3)?; // base, latched and locked group
+        let group = reader.read_u8()?;
+
+        let buttons = (0..buttons_len)
+            .map(|_| reader.read_u32_ne())
+            .collect::<io::Result<_>>()?;
+
+        Ok(Some(XIDeviceEvent {
+            event_type,
+            device_id,
+            source_id,
+            time,
+            detail,
+            root,
+            event,
+            child,
+            root_x,
+            root_y,
+            event_x,
+            event_y,
+            flags,
+            mods,
+            group,
+            buttons,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        XIDeviceEvent, XIQueryVersion, XIQueryVersionReply, XISelectEvents, XI_ALL_MASTER_DEVICES,
+        XI_BUTTON_PRESS, XI_MOTION,
+    };
+    use crate::protocol::{Deserialize, Event, GenericEvent, Serialize};
+
+    #[test]
+    fn test_query_version() {
+        const EXPECTED: &[u8] = b"\x83\x2f\x02\x00\x02\x00\x02\x00";
+        let mut write_buf = vec![];
+
+        XIQueryVersion {
+            major_opcode: 131,
+            major_version: 2,
+            minor_version: 2,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+        assert_eq!(write_buf, EXPECTED);
+
+        let mut reply = b"\x01\x00\x01\x00\x00\x00\x00\x00\x02\x00\x01\x00".to_vec();
+        reply.extend(vec![0; 20]);
+
+        assert_eq!(
+            XIQueryVersionReply::deserialize(&reply[..])
+                .unwrap()
+                .unwrap(),
+            XIQueryVersionReply {
+                major_version: 2,
+                minor_version: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_serialize_select_events() {
+        const EXPECTED: &[u8] = b"\x83\x2e\x05\x00\x01\x02\x00\x00\x01\x00\x00\x00\
+                                  \x01\x00\x01\x00\x50\x00\x00\x00";
+        let mut write_buf = vec![];
+
+        XISelectEvents {
+            major_opcode: 131,
+            window: 0x201,
+            masks: vec![(XI_ALL_MASTER_DEVICES, 1 << XI_MOTION | 1 << XI_BUTTON_PRESS)],
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_device_event_from_event() {
+        let mut data = b"\x23\x83\x05\x00\x0d\x00\x00\x00\x06\x00\x02\x00".to_vec();
+        data.extend(b"\x10\x27\x00\x00\x00\x00\x00\x00\x00\x01\x00\x00");
+        data.extend(b"\x01\x02\x00\x00\x00\x00\x00\x00");
+        // root (100.5, 200.25), event (10.5, -0.25)
+        data.extend(b"\x00\x80\x64\x00\x00\x40\xc8\x00\x00\x80\x0a\x00\x00\xc0\xff\xff");
+        data.extend(b"\x01\x00\x00\x00\x0b\x00\x00\x00");
+        data.extend(vec![0; 16]);
+        data.extend(b"\x04\x00\x00\x00\x00\x00\x00\x01\x02\x00\x00\x00");
+        let event = Event::Generic(GenericEvent {
+            extension: 0x83,
+            event_type: XI_MOTION,
+            data,
+        });
+
+        assert_eq!(
+            XIDeviceEvent::from_event(&event, 0x83).unwrap().unwrap(),
+            XIDeviceEvent {
+                event_type: XI_MOTION,
+                device_id: 2,
+                source_id: 11,
+                time: 10000,
+                detail: 0,
+                root: 0x100,
+                event: 0x201,
+                child: 0,
+                root_x: 100.5,
+                root_y: 200.25,
+                event_x: 10.5,
+                event_y: -0.25,
+                flags: 0,
+                mods: 4,
+                group: 1,
+                buttons: vec![0x2],
+            }
+        );
+        assert!(XIDeviceEvent::from_event(&event, 0x84).unwrap().is_none());
+    }
+}
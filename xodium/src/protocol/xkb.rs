@@ -0,0 +1,487 @@
+//! XKEYBOARD (XKB) extension, keyboard mapping with groups and shift levels.
+//!
+//! Only version negotiation and key types and key symbols parts of the keyboard
+//! map are implemented, which is enough to translate keycode, group and modifiers
+//! to keysym. Key actions, behaviors, explicit components, modifier maps,
+//! indicators, names, compatibility map and XKB events are not covered yet.
+
+use super::{skip, Deserialize, ReplyRequest, Serialize};
+use crate::io::{self, Read, Write};
+use crate::utils::{ReadBytesExt, WriteBytesExt};
+use alloc::vec::Vec;
+
+pub const XKB_EXTENSION_NAME: &str = "XKEYBOARD";
+
+/// Device spec of the core keyboard.
+pub const XKB_USE_CORE_KBD: u16 = 0x0100;
+
+const XKB_USE_EXTENSION_OPCODE: u8 = 0;
+const XKB_GET_MAP_OPCODE: u8 = 8;
+
+const XKB_MAP_PART_KEY_TYPES: u16 = 1 << 0;
+const XKB_MAP_PART_KEY_SYMS: u16 = 1 << 1;
+
+/// Negotiate XKB version, client sends version it implements.
+/// X server rejects other XKB requests until extension is used.
+pub struct XkbUseExtension {
+    pub major_opcode: u8,
+    pub wanted_major: u16,
+    pub wanted_minor: u16,
+}
+
+// 1     CARD8                           major opcode
+// 1     0                               xkb-opcode
+// 2     2                               request-length
+// 2     CARD16                          wantedMajor
+// 2     CARD16                          wantedMinor
+impl Serialize for XkbUseExtension {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(XKB_USE_EXTENSION_OPCODE)?;
+        writer.write_u16_ne(2)?;
+        writer.write_u16_ne(self.wanted_major)?;
+        writer.write_u16_ne(self.wanted_minor)?;
+        Ok(())
+    }
+}
+
+/// Whether X server supports XKB version client wants, and its own version.
+#[derive(Debug, PartialEq)]
+pub struct XkbUseExtensionReply {
+    pub supported: bool,
+    pub server_major: u16,
+    pub server_minor: u16,
+}
+
+// 1     1                               Reply
+// 1     BOOL                            supported
+// 2     CARD16                          sequence number
+// 4     0                               reply length
+// 2     CARD16                          serverMajor
+// 2     CARD16                          serverMinor
+// 20                                    unused
+impl Deserialize for XkbUseExtensionReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        reader.read_u8()?; // Reply
+        let supported = reader.read_u8()? != 0;
+        skip(&mut reader, 6)?;
+        let server_major = reader.read_u16_ne()?;
+        let server_minor = reader.read_u16_ne()?;
+        skip(&mut reader, 20)?;
+
+        Ok(Some(XkbUseExtensionReply {
+            supported,
+            server_major,
+            server_minor,
+        }))
+    }
+}
+
+impl ReplyRequest for XkbUseExtension {
+    type Reply = XkbUseExtensionReply;
+}
+
+/// Query all key types and key symbols of keyboard.
+pub struct XkbGetMap {
+    pub major_opcode: u8,
+    /// Keyboard device, e.g. [XKB_USE_CORE_KBD].
+    pub device_spec: u16,
+}
+
+// 1     CARD8                           major opcode
+// 1     8                               xkb-opcode
+// 2     7                               request-length
+// 2     KB_DEVICESPEC                   deviceSpec
+// 2     SETofMAPPART                    full
+// 2     SETofMAPPART                    partial
+// 1     CARD8                           firstType
+// 1     CARD8                           nTypes
+// 1     KEYCODE                         firstKeySym
+// 1     CARD8                           nKeySyms
+// 1     KEYCODE                         firstKeyAction
+// 1     CARD8                           nKeyActions
+// 1     KEYCODE                         firstKeyBehavior
+// 1     CARD8                           nKeyBehaviors
+// 2     SETofVMOD                       virtualMods
+// 1     KEYCODE                         firstKeyExplicit
+// 1     CARD8                           nKeyExplicit
+// 1     KEYCODE                         firstModMapKey
+// 1     CARD8                           nModMapKeys
+// 1     KEYCODE                         firstVModMapKey
+// 1     CARD8                           nVModMapKeys
+// 2                                     unused
+impl Serialize for XkbGetMap {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(XKB_GET_MAP_OPCODE)?;
+        writer.write_u16_ne(7)?;
+        writer.write_u16_ne(self.device_spec)?;
+        writer.write_u16_ne(XKB_MAP_PART_KEY_TYPES | XKB_MAP_PART_KEY_SYMS)?;
+        // Ranges are ignored for parts requested in full
+        writer.write_u16_ne(0)?;
+        writer.write_all(&[0; 8])?;
+        writer.write_u16_ne(0)?;
+        writer.write_all(&[0; 6])?;
+        writer.write_u16_ne(0)?; // pad
+        Ok(())
+    }
+}
+
+/// Modifiers combination selecting shift level of key type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct XkbKeyTypeMapEntry {
+    pub active: bool,
+    /// Real modifiers, including ones virtual modifiers are bound to.
+    pub mods_mask: u8,
+    pub level: u8,
+}
+
+/// Key type, mapping modifiers to shift levels, e.g. `TWO_LEVEL` for letters.
+#[derive(Debug, Clone, PartialEq)]
+pub struct XkbKeyType {
+    /// Modifiers key type depends on.
+    pub mods_mask: u8,
+    pub num_levels: u8,
+    pub map: Vec<XkbKeyTypeMapEntry>,
+}
+
+impl XkbKeyType {
+    /// Shift level selected by modifiers state, level 0 if no entry matches.
+    pub fn level(&self, state: u16) -> u8 {
+        let mods = state as u8 & self.mods_mask;
+        self.map
+            .iter()
+            .find(|entry| entry.active && entry.mods_mask == mods)
+            .map_or(0, |entry| entry.level)
+    }
+}
+
+// 1     CARD8                           mods.mask
+// 1     CARD8                           mods.realMods
+// 2     SETofVMOD                       mods.vmods
+// 1     CARD8                           numLevels
+// 1     m                               nMapEntries
+// 1     BOOL                            hasPreserve
+// 1                                     unused
+// 8m    LISTofKTMAPENTRY                map
+// p*4m  LISTofMODDEF                    preserve, p=1 if hasPreserve
+//
+// KTMAPENTRY
+// 1     BOOL                            active
+// 1     CARD8                           mods.mask
+// 1     CARD8                           level
+// 1     CARD8                           mods.realMods
+// 2     SETofVMOD                       mods.vmods
+// 2                                     unused
+impl Deserialize for XkbKeyType {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        let mods_mask = reader.read_u8()?;
+        skip(&mut reader, 3)?;
+        let num_levels = reader.read_u8()?;
+        let map_len = reader.read_u8()?;
+        let has_preserve = reader.read_u8()? != 0;
+        skip(&mut reader, 1)?;
+
+        let map = (0..map_len)
+            .map(|_| {
+                let active = reader.read_u8()? != 0;
+                let mods_mask = reader.read_u8()?;
+                let level = reader.read_u8()?;
+                skip(&mut reader, 5)?;
+                Ok(XkbKeyTypeMapEntry {
+                    active,
+                    mods_mask,
+                    level,
+                })
+            })
+            .collect::<io::Result<_>>()?;
+
+        if has_preserve {
+            skip(&mut reader, 4 * usize::from(map_len))?;
+        }
+
+        Ok(Some(XkbKeyType {
+            mods_mask,
+            num_levels,
+            map,
+        }))
+    }
+}
+
+/// Keysyms of single key, `width` levels for each group.
+#[derive(Debug, Clone, PartialEq)]
+pub struct XkbKeySymMap {
+    /// Key type index of each group.
+    pub kt_index: [u8; 4],
+    /// Number of groups in low 4 bits, out of range group handling in high ones.
+    pub group_info: u8,
+    pub width: u8,
+    pub syms: Vec<u32>,
+}
+
+impl XkbKeySymMap {
+    pub fn num_groups(&self) -> u8 {
+        self.group_info & 0x0f
+    }
+}
+
+// 4     LISTofCARD8                     ktIndex
+// 1     CARD8                           groupInfo
+// 1     CARD8                           width
+// 2     n                               nSyms
+// 4n    LISTofKEYSYM                    syms
+impl Deserialize for XkbKeySymMap {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        let mut kt_index = [0; 4];
+        reader.read_exact(&mut kt_index)?;
+        let group_info = reader.read_u8()?;
+        let width = reader.read_u8()?;
+        let syms_len = reader.read_u16_ne()?;
+        let syms = (0..syms_len)
+            .map(|_| reader.read_u32_ne())
+            .collect::<io::Result<_>>()?;
+
+        Ok(Some(XkbKeySymMap {
+            kt_index,
+            group_info,
+            width,
+            syms,
+        }))
+    }
+}
+
+/// Key types and key symbols of keyboard.
+#[derive(Debug, Clone, PartialEq)]
+pub struct XkbGetMapReply {
+    pub device_id: u8,
+    pub min_key_code: u8,
+    pub max_key_code: u8,
+    pub types: Vec<XkbKeyType>,
+    /// Keycode of the first element of `key_sym_maps`.
+    pub first_key_sym: u8,
+    pub key_sym_maps: Vec<XkbKeySymMap>,
+}
+
+impl XkbGetMapReply {
+    /// Keysym of key at group and shift level, `None` if key has no symbol there.
+    /// Out of range groups wrap around, like X server does by default.
+    pub fn keysym_for(&self, keycode: u8, group: u8, level: u8) -> Option<u32> {
+        let key = self
+            .key_sym_maps
+            .get(usize::from(keycode.checked_sub(self.first_key_sym)?))?;
+        if key.num_groups() == 0 || level >= key.width {
+            return None;
+        }
+
+        let group = group % key.num_groups();
+        let index = usize::from(group) * usize::from(key.width) + usize::from(level);
+        key.syms.get(index).copied().filter(|&keysym| keysym != 0)
+    }
+
+    /// Keysym of key at group, with shift level selected by modifiers state
+    /// according to key type of the group.
+    pub fn keysym_for_state(&self, keycode: u8, group: u8, state: u16) -> Option<u32> {
+        let key = self
+            .key_sym_maps
+            .get(usize::from(keycode.checked_sub(self.first_key_sym)?))?;
+        if key.num_groups() == 0 {
+            return None;
+        }
+
+        let group = group % key.num_groups();
+        let key_type = self
+            .types
+            .get(usize::from(*key.kt_index.get(usize::from(group))?))?;
+        self.keysym_for(keycode, group, key_type.level(state))
+    }
+}
+
+// 1     1                               Reply
+// 1     CARD8                           deviceID
+// 2     CARD16                          sequence number
+// 4     2+(I/4)                         reply length
+// 2                                     unused
+// 1     KEYCODE                         minKeyCode
+// 1     KEYCODE                         maxKeyCode
+// 2     SETofMAPPART                    present
+// 1     CARD8                           firstType
+// 1     t                               nTypes
+// 1     CARD8                           totalTypes
+// 1     KEYCODE                         firstKeySym
+// 2     S                               totalSyms
+// 1     s                               nKeySyms
+// 1     KEYCODE                         firstKeyAction
+// 2     A                               totalActions
+// 1     CARD8                           nKeyActions
+// 1     KEYCODE                         firstKeyBehavior
+// 1     CARD8                           nKeyBehaviors
+// 1     CARD8                           totalKeyBehaviors
+// 1     KEYCODE                         firstKeyExplicit
+// 1     CARD8                           nKeyExplicit
+// 1     CARD8                           totalKeyExplicit
+// 1     KEYCODE                         firstModMapKey
+// 1     CARD8                           nModMapKeys
+// 1     CARD8                           totalModMapKeys
+// 1     KEYCODE                         firstVModMapKey
+// 1     CARD8                           nVModMapKeys
+// 1     CARD8                           totalVModMapKeys
+// 1                                     unused
+// 2     SETofVMOD                       virtualMods
+// v     LISTofKEYTYPE                   typesRtrn
+// 8s+4S LISTofKEYSYMMAP                 symsRtrn
+//       other parts, not requested
+impl Deserialize for XkbGetMapReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        reader.read_u8()?; // Reply
+        let device_id = reader.read_u8()?;
+        skip(&mut reader, 8)?;
+        let min_key_code = reader.read_u8()?;
+        let max_key_code = reader.read_u8()?;
+        let present = reader.read_u16_ne()?;
+        skip(&mut reader, 1)?;
+        let types_len = reader.read_u8()?;
+        skip(&mut reader, 1)?;
+        let first_key_sym = reader.read_u8()?;
+        skip(&mut reader, 2)?;
+        let key_syms_len = reader.read_u8()?;
+        skip(&mut reader, 19)?;
+
+        let mut types = vec![];
+        if present & XKB_MAP_PART_KEY_TYPES != 0 {
+            for _ in 0..types_len {
+                types.extend(XkbKeyType::deserialize(&mut reader)?);
+            }
+        }
+
+        let mut key_sym_maps = vec![];
+        if present & XKB_MAP_PART_KEY_SYMS != 0 {
+            for _ in 0..key_syms_len {
+                key_sym_maps.extend(XkbKeySymMap::deserialize(&mut reader)?);
+            }
+        }
+
+        Ok(Some(XkbGetMapReply {
+            device_id,
+            min_key_code,
+            max_key_code,
+            types,
+            first_key_sym,
+            key_sym_maps,
+        }))
+    }
+}
+
+impl ReplyRequest for XkbGetMap {
+    type Reply = XkbGetMapReply;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        XkbGetMap, XkbGetMapReply, XkbKeySymMap, XkbUseExtension, XkbUseExtensionReply,
+        XKB_USE_CORE_KBD,
+    };
+    use crate::protocol::{Deserialize, Serialize};
+
+    #[test]
+    fn test_use_extension() {
+        const EXPECTED: &[u8] = b"\x87\x00\x02\x00\x01\x00\x00\x00";
+        let mut write_buf = vec![];
+
+        XkbUseExtension {
+            major_opcode: 135,
+            wanted_major: 1,
+            wanted_minor: 0,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+
+        let mut reply = b"\x01\x01\x01\x00\x00\x00\x00\x00\x01\x00\x00\x00".to_vec();
+        reply.extend(vec![0; 20]);
+
+        assert_eq!(
+            XkbUseExtensionReply::deserialize(&reply[..])
+                .unwrap()
+                .unwrap(),
+            XkbUseExtensionReply {
+                supported: true,
+                server_major: 1,
+                server_minor: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_serialize_get_map() {
+        let mut write_buf = vec![];
+
+        XkbGetMap {
+            major_opcode: 135,
+            device_spec: XKB_USE_CORE_KBD,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf.len(), 28);
+        assert_eq!(&write_buf[..8], b"\x87\x08\x07\x00\x00\x01\x03\x00");
+    }
+
+    #[test]
+    fn test_get_map_keysym_for() {
+        let mut reply = b"\x01\x03\x01\x00\x00\x00\x00\x00\x00\x00\x08\xff\x03\x00".to_vec();
+        // Two types, two keys starting at keycode 38
+        reply.extend(b"\x00\x02\x02\x26\x05\x00\x02\x00\x00\x00\x00");
+        reply.extend(vec![0; 13]);
+        reply.extend(b"\x00\x00");
+        // ONE_LEVEL type
+        reply.extend(b"\x00\x00\x00\x00\x01\x00\x00\x00");
+        // TWO_LEVEL type, Shift selects level 1
+        reply.extend(b"\x01\x01\x00\x00\x02\x01\x00\x00");
+        reply.extend(b"\x01\x01\x01\x01\x00\x00\x00\x00");
+        // Key `a` with two groups of two levels, `a` `A` and `ф` `Ф`
+        reply.extend(b"\x01\x01\x00\x00\x02\x02\x04\x00");
+        reply.extend(b"\x61\x00\x00\x00\x41\x00\x00\x00\xc6\x06\x00\x00\xe6\x06\x00\x00");
+        // Key with single keysym of ONE_LEVEL type
+        reply.extend(b"\x00\x00\x00\x00\x01\x01\x01\x00\x20\x00\x00\x00");
+
+        let map = XkbGetMapReply::deserialize(&reply[..]).unwrap().unwrap();
+        assert_eq!(map.types.len(), 2);
+        assert_eq!(map.types[1].num_levels, 2);
+        assert_eq!(map.key_sym_maps.len(), 2);
+
+        assert_eq!(map.keysym_for(38, 0, 0), Some(0x61));
+        assert_eq!(map.keysym_for(38, 0, 1), Some(0x41));
+        assert_eq!(map.keysym_for(38, 1, 1), Some(0x6e6));
+        // Out of range group wraps around
+        assert_eq!(map.keysym_for(38, 2, 0), Some(0x61));
+        assert_eq!(map.keysym_for(39, 0, 1), None);
+        assert_eq!(map.keysym_for(37, 0, 0), None);
+
+        // Shift
+        assert_eq!(map.keysym_for_state(38, 1, 0x0001), Some(0x6e6));
+        assert_eq!(map.keysym_for_state(38, 0, 0x0000), Some(0x61));
+        assert_eq!(map.keysym_for_state(39, 0, 0x0001), Some(0x20));
+    }
+
+    #[test]
+    fn test_get_map_keysym_for_state_too_many_groups() {
+        // Server claims 15 groups, but key types are only known for 4
+        let map = XkbGetMapReply {
+            device_id: 3,
+            min_key_code: 8,
+            max_key_code: 255,
+            types: vec![],
+            first_key_sym: 38,
+            key_sym_maps: vec![XkbKeySymMap {
+                kt_index: [0; 4],
+                group_info: 0x0f,
+                width: 1,
+                syms: vec![0x61; 15],
+            }],
+        };
+
+        assert_eq!(map.keysym_for_state(38, 5, 0x0000), None);
+    }
+}
@@ -0,0 +1,329 @@
+//! XTEST extension, synthesizes input as if it came from real devices.
+
+use super::{skip, Deserialize, ReplyRequest, Serialize};
+use crate::io::{self, Read, Write};
+use crate::utils::{ReadBytesExt, WriteBytesExt};
+
+pub const XTEST_EXTENSION_NAME: &str = "XTEST";
+
+const XTEST_GET_VERSION_OPCODE: u8 = 0;
+const XTEST_COMPARE_CURSOR_OPCODE: u8 = 1;
+const XTEST_FAKE_INPUT_OPCODE: u8 = 2;
+const XTEST_GRAB_CONTROL_OPCODE: u8 = 3;
+
+/// Negotiate XTEST version, client sends highest version it supports.
+pub struct XTestGetVersion {
+    pub major_opcode: u8,
+    pub major_version: u8,
+    pub minor_version: u16,
+}
+
+// 1     CARD8                           major opcode
+// 1     0                               minor opcode
+// 2     2                               request length
+// 1     CARD8                           major version
+// 1                                     unused
+// 2     CARD16                          minor version
+impl Serialize for XTestGetVersion {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(XTEST_GET_VERSION_OPCODE)?;
+        writer.write_u16_ne(2)?;
+        writer.write_u8(self.major_version)?;
+        writer.write_u8(0)?; // pad
+        writer.write_u16_ne(self.minor_version)?;
+        Ok(())
+    }
+}
+
+/// XTEST version supported by X server.
+#[derive(Debug, PartialEq)]
+pub struct XTestGetVersionReply {
+    pub major_version: u8,
+    pub minor_version: u16,
+}
+
+// 1     1                               Reply
+// 1     CARD8                           major version
+// 2     CARD16                          sequence number
+// 4     0                               reply length
+// 2     CARD16                          minor version
+// 22                                    unused
+impl Deserialize for XTestGetVersionReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        reader.read_u8()?; // Reply
+        let major_version = reader.read_u8()?;
+        skip(&mut reader, 6)?;
+        let minor_version = reader.read_u16_ne()?;
+        skip(&mut reader, 22)?;
+
+        Ok(Some(XTestGetVersionReply {
+            major_version,
+            minor_version,
+        }))
+    }
+}
+
+impl ReplyRequest for XTestGetVersion {
+    type Reply = XTestGetVersionReply;
+}
+
+/// Check whether window has the cursor, e.g. to verify cursor of application under test.
+pub struct XTestCompareCursor {
+    pub major_opcode: u8,
+    pub window: u32,
+    /// Cursor, 0 (None) or 1 (CurrentCursor) to compare with the one currently displayed.
+    pub cursor: u32,
+}
+
+// 1     CARD8                           major opcode
+// 1     1                               minor opcode
+// 2     3                               request length
+// 4     WINDOW                          window
+// 4     CURSOR                          cursor
+//       0     None
+//       1     CurrentCursor
+impl Serialize for XTestCompareCursor {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(XTEST_COMPARE_CURSOR_OPCODE)?;
+        writer.write_u16_ne(3)?;
+        writer.write_u32_ne(self.window)?;
+        writer.write_u32_ne(self.cursor)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct XTestCompareCursorReply {
+    pub same: bool,
+}
+
+// 1     1                               Reply
+// 1     BOOL                            same
+// 2     CARD16                          sequence number
+// 4     0                               reply length
+// 24                                    unused
+impl Deserialize for XTestCompareCursorReply {
+    fn deserialize<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        reader.read_u8()?; // Reply
+        let same = reader.read_u8()? != 0;
+        skip(&mut reader, 30)?;
+
+        Ok(Some(XTestCompareCursorReply { same }))
+    }
+}
+
+impl ReplyRequest for XTestCompareCursor {
+    type Reply = XTestCompareCursorReply;
+}
+
+/// Type of input synthesized by XTestFakeInput.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FakeInputType {
+    KeyPress = 2,
+    KeyRelease = 3,
+    ButtonPress = 4,
+    ButtonRelease = 5,
+    /// Detail 0 moves pointer to absolute position, 1 - relative to current one.
+    MotionNotify = 6,
+}
+
+/// Synthesize key, button or pointer motion input.
+pub struct XTestFakeInput {
+    pub major_opcode: u8,
+    pub event_type: FakeInputType,
+    /// Keycode, button or motion kind depending on type.
+    pub detail: u8,
+    /// Delay in milliseconds before input is processed, 0 is immediate.
+    pub time: u32,
+    /// Root window of motion, 0 (None) means the root pointer is on.
+    pub root: u32,
+    pub root_x: i16,
+    pub root_y: i16,
+}
+
+// 1     CARD8                           major opcode
+// 1     2                               minor opcode
+// 2     9                               request length
+// 1     BYTE                            type
+// 1     BYTE                            detail
+// 2                                     unused
+// 4     CARD32                          time
+// 4     WINDOW                          root
+// 8                                     unused
+// 2     INT16                           rootX
+// 2     INT16                           rootY
+// 7                                     unused
+// 1     CARD8                           deviceid
+impl Serialize for XTestFakeInput {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(XTEST_FAKE_INPUT_OPCODE)?;
+        writer.write_u16_ne(9)?;
+        writer.write_u8(self.event_type as u8)?;
+        writer.write_u8(self.detail)?;
+        writer.write_u16_ne(0)?; // pad
+        writer.write_u32_ne(self.time)?;
+        writer.write_u32_ne(self.root)?;
+        writer.write_all(&[0; 8])?; // pad
+        writer.write_i16_ne(self.root_x)?;
+        writer.write_i16_ne(self.root_y)?;
+        writer.write_all(&[0; 7])?; // pad
+        writer.write_u8(0)?; // deviceid, core devices only
+        Ok(())
+    }
+}
+
+/// Make the client impervious to server grabs, so its requests,
+/// e.g. fake input, are processed even while another client grabs the server.
+pub struct XTestGrabControl {
+    pub major_opcode: u8,
+    pub impervious: bool,
+}
+
+// 1     CARD8                           major opcode
+// 1     3                               minor opcode
+// 2     2                               request length
+// 1     BOOL                            impervious
+// 3                                     unused
+impl Serialize for XTestGrabControl {
+    fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_u8(self.major_opcode)?;
+        writer.write_u8(XTEST_GRAB_CONTROL_OPCODE)?;
+        writer.write_u16_ne(2)?;
+        writer.write_u8(self.impervious.into())?;
+        writer.write_all(&[0; 3])?; // pad
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        FakeInputType, XTestCompareCursor, XTestCompareCursorReply, XTestFakeInput,
+        XTestGetVersion, XTestGetVersionReply, XTestGrabControl,
+    };
+    use crate::protocol::{Deserialize, Serialize};
+
+    #[test]
+    fn test_serialize_get_version() {
+        const EXPECTED: &[u8] = b"\x84\x00\x02\x00\x02\x00\x02\x00";
+        let mut write_buf = vec![];
+
+        XTestGetVersion {
+            major_opcode: 132,
+            major_version: 2,
+            minor_version: 2,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_deserialize_get_version_reply() {
+        let mut reply = b"\x01\x02\x01\x00\x00\x00\x00\x00\x02\x00".to_vec();
+        reply.extend(vec![0; 22]);
+
+        assert_eq!(
+            XTestGetVersionReply::deserialize(&reply[..])
+                .unwrap()
+                .unwrap(),
+            XTestGetVersionReply {
+                major_version: 2,
+                minor_version: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_serialize_fake_key_press() {
+        let mut expected = b"\x84\x02\x09\x00\x02\x26\x00\x00".to_vec();
+        expected.extend(vec![0; 28]);
+        let mut write_buf = vec![];
+
+        XTestFakeInput {
+            major_opcode: 132,
+            event_type: FakeInputType::KeyPress,
+            detail: 38,
+            time: 0,
+            root: 0,
+            root_x: 0,
+            root_y: 0,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, expected);
+    }
+
+    #[test]
+    fn test_serialize_fake_motion() {
+        let mut expected = b"\x84\x02\x09\x00\x06\x00\x00\x00\x0a\x00\x00\x00".to_vec();
+        expected.extend(b"\x00\x01\x00\x00");
+        expected.extend(vec![0; 8]);
+        expected.extend(b"\x64\x00\xc8\x00");
+        expected.extend(vec![0; 8]);
+        let mut write_buf = vec![];
+
+        XTestFakeInput {
+            major_opcode: 132,
+            event_type: FakeInputType::MotionNotify,
+            detail: 0,
+            time: 10,
+            root: 0x100,
+            root_x: 100,
+            root_y: 200,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, expected);
+    }
+
+    #[test]
+    fn test_serialize_compare_cursor() {
+        const EXPECTED: &[u8] = b"\x84\x01\x03\x00\x01\x02\x00\x00\x01\x00\x00\x00";
+        let mut write_buf = vec![];
+
+        XTestCompareCursor {
+            major_opcode: 132,
+            window: 0x201,
+            cursor: 1,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+
+    #[test]
+    fn test_deserialize_compare_cursor_reply() {
+        let mut reply = b"\x01\x01\x01\x00\x00\x00\x00\x00".to_vec();
+        reply.extend(vec![0; 24]);
+
+        assert_eq!(
+            XTestCompareCursorReply::deserialize(&reply[..])
+                .unwrap()
+                .unwrap(),
+            XTestCompareCursorReply { same: true }
+        );
+    }
+
+    #[test]
+    fn test_serialize_grab_control() {
+        const EXPECTED: &[u8] = b"\x84\x03\x02\x00\x01\x00\x00\x00";
+        let mut write_buf = vec![];
+
+        XTestGrabControl {
+            major_opcode: 132,
+            impervious: true,
+        }
+        .serialize(&mut write_buf)
+        .unwrap();
+
+        assert_eq!(write_buf, EXPECTED);
+    }
+}
@@ -0,0 +1,188 @@
+//! Fixtures shared by unit tests.
+//! Everything but [setup_response], [query_font_reply] and [event] requires `std` feature.
+
+#[cfg(feature = "std")]
+use crate::connection::Connection;
+use crate::protocol::pad;
+#[cfg(feature = "std")]
+use crate::utils::ReadBytesExt;
+use crate::utils::WriteBytesExt;
+#[cfg(feature = "std")]
+use std::cell::RefCell;
+#[cfg(feature = "std")]
+use std::io::{self, Read, Write};
+#[cfg(feature = "std")]
+use std::rc::Rc;
+
+/// Build successful setup response with specified number of screens.
+/// Screen `i` has root window `0x100 + i` and default colormap `0x20 + i`.
+pub(crate) fn setup_response(screens: usize) -> Vec<u8> {
+    const VENDOR: &[u8] = b"xodium";
+    let mut data = vec![];
+
+    data.write_u32_ne(0).unwrap(); // release-number
+    data.write_u32_ne(0x0400_0000).unwrap(); // resource-id-base
+    data.write_u32_ne(0x001f_ffff).unwrap(); // resource-id-mask
+    data.write_u32_ne(256).unwrap(); // motion-buffer-size
+    data.write_u16_ne(VENDOR.len() as u16).unwrap();
+    data.write_u16_ne(0xffff).unwrap(); // maximum-request-length
+    data.write_u8(screens as u8).unwrap();
+    data.write_u8(1).unwrap(); // pixmap formats
+    data.write_u8(0).unwrap(); // image-byte-order
+    data.write_u8(0).unwrap(); // bitmap-format-bit-order
+    data.write_u8(32).unwrap(); // bitmap-format-scanline-unit
+    data.write_u8(32).unwrap(); // bitmap-format-scanline-pad
+    data.write_u8(8).unwrap(); // min-keycode
+    data.write_u8(255).unwrap(); // max-keycode
+    data.write_u32_ne(0).unwrap(); // unused
+    data.extend(VENDOR);
+    data.extend(vec![0; pad(VENDOR.len())]);
+
+    // Pixmap format
+    data.extend(&[24, 32, 32, 0, 0, 0, 0, 0]);
+
+    for i in 0..screens {
+        data.write_u32_ne(0x100 + i as u32).unwrap(); // root
+        data.write_u32_ne(0x20 + i as u32).unwrap(); // default-colormap
+        data.write_u32_ne(0x00ff_ffff).unwrap(); // white-pixel
+        data.write_u32_ne(0).unwrap(); // black-pixel
+        data.write_u32_ne(0).unwrap(); // current-input-masks
+        data.write_u16_ne(1920).unwrap();
+        data.write_u16_ne(1080).unwrap();
+        data.write_u16_ne(508).unwrap();
+        data.write_u16_ne(285).unwrap();
+        data.write_u16_ne(1).unwrap(); // min-installed-maps
+        data.write_u16_ne(1).unwrap(); // max-installed-maps
+        data.write_u32_ne(0x21).unwrap(); // root-visual
+        data.write_u8(0).unwrap(); // backing-stores
+        data.write_u8(0).unwrap(); // save-unders
+        data.write_u8(24).unwrap(); // root-depth
+        data.write_u8(1).unwrap(); // allowed depths
+
+        data.write_u8(24).unwrap(); // depth
+        data.write_u8(0).unwrap();
+        data.write_u16_ne(1).unwrap(); // visuals
+        data.write_u32_ne(0).unwrap();
+
+        data.write_u32_ne(0x21).unwrap(); // visual-id
+        data.write_u8(4).unwrap(); // TrueColor
+        data.write_u8(8).unwrap(); // bits-per-rgb-value
+        data.write_u16_ne(256).unwrap(); // colormap-entries
+        data.write_u32_ne(0x00ff_0000).unwrap();
+        data.write_u32_ne(0x0000_ff00).unwrap();
+        data.write_u32_ne(0x0000_00ff).unwrap();
+        data.write_u32_ne(0).unwrap();
+    }
+
+    let mut response = vec![];
+    response.write_u8(1).unwrap(); // Success
+    response.write_u8(0).unwrap();
+    response.write_u16_ne(11).unwrap();
+    response.write_u16_ne(0).unwrap();
+    response.write_u16_ne((data.len() / 4) as u16).unwrap();
+    response.extend(data);
+    response
+}
+
+/// Build QueryFont reply of font with characters from space (5 pixels wide)
+/// to quote, `!` is 7 pixels wide and quote is nonexistent.
+pub(crate) fn query_font_reply() -> Vec<u8> {
+    let mut reply = b"\x01\x00\x01\x00\x12\x00\x00\x00".to_vec();
+    reply.extend(b"\x00\x00\x05\x00\x05\x00\x0a\x00\x02\x00\x00\x00\x00\x00\x00\x00");
+    reply.extend(b"\x00\x00\x07\x00\x07\x00\x0a\x00\x02\x00\x00\x00\x00\x00\x00\x00");
+    reply.extend(b"\x20\x00\x22\x00\x20\x00\x01\x00\x00\x00\x00\x01\x0a\x00\x02\x00");
+    reply.extend(b"\x03\x00\x00\x00\x41\x00\x00\x00\x0c\x00\x00\x00");
+    reply.extend(b"\x00\x00\x05\x00\x05\x00\x0a\x00\x02\x00\x00\x00");
+    reply.extend(b"\x00\x00\x07\x00\x07\x00\x0a\x00\x02\x00\x00\x00");
+    reply.extend(vec![0; 12]);
+    reply
+}
+
+#[cfg(feature = "std")]
+/// Build InternAtom reply with `atom` for request of `sequence_number`.
+pub(crate) fn intern_atom_reply(sequence_number: u8, atom: u32) -> Vec<u8> {
+    let mut reply = vec![1, 0, sequence_number, 0, 0, 0, 0, 0];
+    reply.write_u32_ne(atom).unwrap();
+    reply.extend(vec![0; 20]);
+    reply
+}
+
+/// Event of `bytes` padded with zeros to 32 bytes.
+pub(crate) fn event(bytes: &[u8]) -> Vec<u8> {
+    let mut event = bytes.to_vec();
+    event.resize(32, 0);
+    event
+}
+
+#[cfg(feature = "std")]
+/// Read setup request on the server side,
+/// returning authorization protocol name and data.
+pub(crate) fn read_setup_request<R: Read>(mut reader: R) -> io::Result<(Vec<u8>, Vec<u8>)> {
+    let mut header = [0; 6];
+    reader.read_exact(&mut header)?;
+    let name_len = usize::from(reader.read_u16_ne()?);
+    let data_len = usize::from(reader.read_u16_ne()?);
+    reader.read_u16_ne()?;
+
+    let mut name = vec![0; name_len + pad(name_len)];
+    reader.read_exact(&mut name)?;
+    name.truncate(name_len);
+
+    let mut data = vec![0; data_len + pad(data_len)];
+    reader.read_exact(&mut data)?;
+    data.truncate(data_len);
+
+    Ok((name, data))
+}
+
+#[cfg(feature = "std")]
+/// In-memory stream: reads from predefined input and records everything written.
+pub(crate) struct MockStream {
+    input: io::Cursor<Vec<u8>>,
+    output: Rc<RefCell<Vec<u8>>>,
+}
+
+#[cfg(feature = "std")]
+impl Read for MockStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.input.read(buf)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Write for MockStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.output.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+/// Connection to mock server which sends single screen setup followed by `input`.
+/// Returns connection and buffer with everything written after setup.
+pub(crate) fn mock_connection(input: Vec<u8>) -> (Connection, Rc<RefCell<Vec<u8>>>) {
+    mock_connection_with_screens(1, input)
+}
+
+#[cfg(feature = "std")]
+/// Same as [mock_connection], but setup has specified number of screens.
+pub(crate) fn mock_connection_with_screens(
+    screens: usize,
+    input: Vec<u8>,
+) -> (Connection, Rc<RefCell<Vec<u8>>>) {
+    let mut server_input = setup_response(screens);
+    server_input.extend(input);
+
+    let output = Rc::new(RefCell::new(vec![]));
+    let stream = MockStream {
+        input: io::Cursor::new(server_input),
+        output: output.clone(),
+    };
+
+    let connection = Connection::setup(Box::new(stream)).expect("Mock setup always succeeds");
+    output.borrow_mut().clear();
+    (connection, output)
+}
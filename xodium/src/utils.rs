@@ -1,153 +1,212 @@
-use std::io::{self, Read, Write};
+use crate::io;
+use crate::protocol::{pad, ByteOrder};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// Largest allocation made before reading data of length received from peer.
+const READ_CHUNK_SIZE: usize = 64 * 1024;
 
 /// Auto trait for any type implementing
 /// [Read](std::io::Read) + [Write](std::io::Write).
 /// Sole purpose of this trait is to
 /// make dyn [Read](std::io::Read) + [Write](std::io::Write) kind of possible.
-pub trait StreamMarker: Read + Write {}
-impl<T: Read + Write> StreamMarker for T {}
+#[cfg(feature = "std")]
+pub trait StreamMarker: io::Read + io::Write {}
+#[cfg(feature = "std")]
+impl<T: io::Read + io::Write> StreamMarker for T {}
 
 /// Adopted from `byteorder` crate.
 pub(crate) trait ReadBytesExt: io::Read {
     #[inline]
     fn read_u8(&mut self) -> io::Result<u8> {
-        let mut buf = [0; std::mem::size_of::<u8>()];
+        let mut buf = [0; core::mem::size_of::<u8>()];
         self.read_exact(&mut buf)?;
         Ok(buf[0])
     }
 
+    #[allow(dead_code)]
     #[inline]
     fn read_i8(&mut self) -> io::Result<i8> {
-        let mut buf = [0; std::mem::size_of::<i8>()];
+        let mut buf = [0; core::mem::size_of::<i8>()];
         self.read_exact(&mut buf)?;
         Ok(buf[0] as i8)
     }
 
     #[inline]
     fn read_u16_be(&mut self) -> io::Result<u16> {
-        let mut buf = [0; std::mem::size_of::<u16>()];
+        let mut buf = [0; core::mem::size_of::<u16>()];
         self.read_exact(&mut buf)?;
         Ok(u16::from_be_bytes(buf))
     }
 
     #[inline]
     fn read_u16_le(&mut self) -> io::Result<u16> {
-        let mut buf = [0; std::mem::size_of::<u16>()];
+        let mut buf = [0; core::mem::size_of::<u16>()];
         self.read_exact(&mut buf)?;
         Ok(u16::from_le_bytes(buf))
     }
 
     #[inline]
     fn read_u16_ne(&mut self) -> io::Result<u16> {
-        let mut buf = [0; std::mem::size_of::<u16>()];
+        let mut buf = [0; core::mem::size_of::<u16>()];
         self.read_exact(&mut buf)?;
         Ok(u16::from_ne_bytes(buf))
     }
 
+    #[inline]
+    fn read_u16_with(&mut self, order: ByteOrder) -> io::Result<u16> {
+        match order {
+            ByteOrder::MsbFirst => self.read_u16_be(),
+            ByteOrder::LsbFirst => self.read_u16_le(),
+        }
+    }
+
+    #[allow(dead_code)]
     #[inline]
     fn read_i16_be(&mut self) -> io::Result<i16> {
-        let mut buf = [0; std::mem::size_of::<i16>()];
+        let mut buf = [0; core::mem::size_of::<i16>()];
         self.read_exact(&mut buf)?;
         Ok(i16::from_be_bytes(buf))
     }
 
+    #[allow(dead_code)]
     #[inline]
     fn read_i16_le(&mut self) -> io::Result<i16> {
-        let mut buf = [0; std::mem::size_of::<i16>()];
+        let mut buf = [0; core::mem::size_of::<i16>()];
         self.read_exact(&mut buf)?;
         Ok(i16::from_le_bytes(buf))
     }
 
     #[inline]
     fn read_i16_ne(&mut self) -> io::Result<i16> {
-        let mut buf = [0; std::mem::size_of::<i16>()];
+        let mut buf = [0; core::mem::size_of::<i16>()];
         self.read_exact(&mut buf)?;
         Ok(i16::from_ne_bytes(buf))
     }
 
     #[inline]
     fn read_u32_be(&mut self) -> io::Result<u32> {
-        let mut buf = [0; std::mem::size_of::<u32>()];
+        let mut buf = [0; core::mem::size_of::<u32>()];
         self.read_exact(&mut buf)?;
         Ok(u32::from_be_bytes(buf))
     }
 
     #[inline]
     fn read_u32_le(&mut self) -> io::Result<u32> {
-        let mut buf = [0; std::mem::size_of::<u32>()];
+        let mut buf = [0; core::mem::size_of::<u32>()];
         self.read_exact(&mut buf)?;
         Ok(u32::from_le_bytes(buf))
     }
 
     #[inline]
     fn read_u32_ne(&mut self) -> io::Result<u32> {
-        let mut buf = [0; std::mem::size_of::<u32>()];
+        let mut buf = [0; core::mem::size_of::<u32>()];
         self.read_exact(&mut buf)?;
         Ok(u32::from_ne_bytes(buf))
     }
 
+    #[inline]
+    fn read_u32_with(&mut self, order: ByteOrder) -> io::Result<u32> {
+        match order {
+            ByteOrder::MsbFirst => self.read_u32_be(),
+            ByteOrder::LsbFirst => self.read_u32_le(),
+        }
+    }
+
+    #[allow(dead_code)]
     #[inline]
     fn read_i32_be(&mut self) -> io::Result<i32> {
-        let mut buf = [0; std::mem::size_of::<i32>()];
+        let mut buf = [0; core::mem::size_of::<i32>()];
         self.read_exact(&mut buf)?;
         Ok(i32::from_be_bytes(buf))
     }
 
+    #[allow(dead_code)]
     #[inline]
     fn read_i32_le(&mut self) -> io::Result<i32> {
-        let mut buf = [0; std::mem::size_of::<i32>()];
+        let mut buf = [0; core::mem::size_of::<i32>()];
         self.read_exact(&mut buf)?;
         Ok(i32::from_le_bytes(buf))
     }
 
     #[inline]
     fn read_i32_ne(&mut self) -> io::Result<i32> {
-        let mut buf = [0; std::mem::size_of::<i32>()];
+        let mut buf = [0; core::mem::size_of::<i32>()];
         self.read_exact(&mut buf)?;
         Ok(i32::from_ne_bytes(buf))
     }
 
+    #[allow(dead_code)]
     #[inline]
     fn read_u64_be(&mut self) -> io::Result<u64> {
-        let mut buf = [0; std::mem::size_of::<u64>()];
+        let mut buf = [0; core::mem::size_of::<u64>()];
         self.read_exact(&mut buf)?;
         Ok(u64::from_be_bytes(buf))
     }
 
+    #[allow(dead_code)]
     #[inline]
     fn read_u64_le(&mut self) -> io::Result<u64> {
-        let mut buf = [0; std::mem::size_of::<u64>()];
+        let mut buf = [0; core::mem::size_of::<u64>()];
         self.read_exact(&mut buf)?;
         Ok(u64::from_le_bytes(buf))
     }
 
     #[inline]
     fn read_u64_ne(&mut self) -> io::Result<u64> {
-        let mut buf = [0; std::mem::size_of::<u64>()];
+        let mut buf = [0; core::mem::size_of::<u64>()];
         self.read_exact(&mut buf)?;
         Ok(u64::from_ne_bytes(buf))
     }
 
+    #[allow(dead_code)]
     #[inline]
     fn read_i64_be(&mut self) -> io::Result<i64> {
-        let mut buf = [0; std::mem::size_of::<i64>()];
+        let mut buf = [0; core::mem::size_of::<i64>()];
         self.read_exact(&mut buf)?;
         Ok(i64::from_be_bytes(buf))
     }
 
+    #[allow(dead_code)]
     #[inline]
     fn read_i64_le(&mut self) -> io::Result<i64> {
-        let mut buf = [0; std::mem::size_of::<i64>()];
+        let mut buf = [0; core::mem::size_of::<i64>()];
         self.read_exact(&mut buf)?;
         Ok(i64::from_le_bytes(buf))
     }
 
+    #[allow(dead_code)]
     #[inline]
     fn read_i64_ne(&mut self) -> io::Result<i64> {
-        let mut buf = [0; std::mem::size_of::<i64>()];
+        let mut buf = [0; core::mem::size_of::<i64>()];
         self.read_exact(&mut buf)?;
         Ok(i64::from_ne_bytes(buf))
     }
+
+    /// Read STRING8 of `n` bytes, consuming `pad(n)` bytes following it.
+    /// Buffer grows as bytes arrive, so bogus `n` fails with UnexpectedEof
+    /// instead of allocating it upfront.
+    fn read_string8(&mut self, n: usize) -> io::Result<Vec<u8>> {
+        let len = n
+            .checked_add(pad(n))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "STRING8 is too long"))?;
+
+        let mut buf = Vec::new();
+        while buf.len() < len {
+            let start = buf.len();
+            buf.resize(start + (len - start).min(READ_CHUNK_SIZE), 0);
+            self.read_exact(&mut buf[start..])?;
+        }
+        buf.truncate(n);
+        Ok(buf)
+    }
+
+    /// Same as [read_string8](ReadBytesExt::read_string8),
+    /// invalid UTF-8 sequences are replaced.
+    fn read_utf8_lossy_string8(&mut self, n: usize) -> io::Result<String> {
+        let buf = self.read_string8(n)?;
+        Ok(String::from_utf8_lossy(&buf).to_string())
+    }
 }
 
 // TODO: Tests
@@ -170,6 +229,7 @@ pub trait WriteBytesExt: io::Write {
         self.write_all(&n.to_be_bytes())
     }
 
+    #[allow(dead_code)]
     #[inline]
     fn write_u16_le(&mut self, n: u16) -> io::Result<()> {
         self.write_all(&n.to_le_bytes())
@@ -180,11 +240,13 @@ pub trait WriteBytesExt: io::Write {
         self.write_all(&n.to_ne_bytes())
     }
 
+    #[allow(dead_code)]
     #[inline]
     fn write_i16_be(&mut self, n: i16) -> io::Result<()> {
         self.write_all(&n.to_be_bytes())
     }
 
+    #[allow(dead_code)]
     #[inline]
     fn write_i16_le(&mut self, n: i16) -> io::Result<()> {
         self.write_all(&n.to_le_bytes())
@@ -195,11 +257,13 @@ pub trait WriteBytesExt: io::Write {
         self.write_all(&n.to_ne_bytes())
     }
 
+    #[allow(dead_code)]
     #[inline]
     fn write_u32_be(&mut self, n: u32) -> io::Result<()> {
         self.write_all(&n.to_be_bytes())
     }
 
+    #[allow(dead_code)]
     #[inline]
     fn write_u32_le(&mut self, n: u32) -> io::Result<()> {
         self.write_all(&n.to_le_bytes())
@@ -210,26 +274,31 @@ pub trait WriteBytesExt: io::Write {
         self.write_all(&n.to_ne_bytes())
     }
 
+    #[allow(dead_code)]
     #[inline]
     fn write_i32_be(&mut self, n: i32) -> io::Result<()> {
         self.write_all(&n.to_be_bytes())
     }
 
+    #[allow(dead_code)]
     #[inline]
     fn write_i32_le(&mut self, n: i32) -> io::Result<()> {
         self.write_all(&n.to_le_bytes())
     }
 
+    #[allow(dead_code)]
     #[inline]
     fn write_i32_ne(&mut self, n: i32) -> io::Result<()> {
         self.write_all(&n.to_ne_bytes())
     }
 
+    #[allow(dead_code)]
     #[inline]
     fn write_u64_be(&mut self, n: u64) -> io::Result<()> {
         self.write_all(&n.to_be_bytes())
     }
 
+    #[allow(dead_code)]
     #[inline]
     fn write_u64_le(&mut self, n: u64) -> io::Result<()> {
         self.write_all(&n.to_le_bytes())
@@ -240,16 +309,19 @@ pub trait WriteBytesExt: io::Write {
         self.write_all(&n.to_ne_bytes())
     }
 
+    #[allow(dead_code)]
     #[inline]
     fn write_i64_be(&mut self, n: i64) -> io::Result<()> {
         self.write_all(&n.to_be_bytes())
     }
 
+    #[allow(dead_code)]
     #[inline]
     fn write_i64_le(&mut self, n: i64) -> io::Result<()> {
         self.write_all(&n.to_le_bytes())
     }
 
+    #[allow(dead_code)]
     #[inline]
     fn write_i64_ne(&mut self, n: i64) -> io::Result<()> {
         self.write_all(&n.to_ne_bytes())
@@ -258,3 +330,45 @@ pub trait WriteBytesExt: io::Write {
 
 // TODO: Tests
 impl<W: io::Write + ?Sized> WriteBytesExt for W {}
+
+#[cfg(test)]
+mod tests {
+    use super::ReadBytesExt;
+
+    #[test]
+    fn test_read_string8_consumes_pad() {
+        let cases: &[(usize, &[u8], &[u8])] = &[
+            (0, b"", b"\xff"),
+            (1, b"a", b"\0\0\0\xff"),
+            (3, b"abc", b"\0\xff"),
+            (4, b"abcd", b"\xff"),
+        ];
+
+        for &(n, string, rest) in cases {
+            let mut input = string.to_vec();
+            input.extend(rest);
+            let mut reader = &input[..];
+
+            assert_eq!(reader.read_string8(n).unwrap(), string);
+            assert_eq!(reader, b"\xff");
+        }
+    }
+
+    #[test]
+    fn test_read_string8_length_beyond_input() {
+        let mut reader = &b"abcd"[..];
+        assert!(reader.read_string8(usize::MAX).is_err());
+
+        let mut reader = &b"abcd"[..];
+        let err = reader.read_string8(1 << 40).unwrap_err();
+        assert_eq!(err.kind(), crate::io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_read_utf8_lossy_string8() {
+        let mut reader = &b"ab\xffc\0\0\0\0"[..];
+
+        assert_eq!(reader.read_utf8_lossy_string8(4).unwrap(), "ab\u{fffd}c");
+        assert_eq!(reader.len(), 4);
+    }
+}
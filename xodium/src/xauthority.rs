@@ -1,23 +1,31 @@
+#[cfg(feature = "serde")]
+mod base64;
 mod connection_family;
 
 use self::connection_family::ConnectionFamily;
+use crate::display::Display;
 use crate::utils::ReadBytesExt;
 use std::env;
+use std::error::Error;
 use std::fmt;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::ErrorKind::UnexpectedEof;
 use std::io::{self, Read};
 use std::path::PathBuf;
 
 const DEFAULT_XAUTHORITY_FILE_NAME: &str = ".Xauthority";
+const HOSTNAME_PATHS: &[&str] = &["/proc/sys/kernel/hostname", "/etc/hostname"];
 
 /// Single entry from Xauthority file
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) struct XAuthEntry {
     pub connection_family: ConnectionFamily,
     pub display_name: String,
     pub display_number: u16,
     pub protocol_name: String,
+    /// Serialized as base64 string.
+    #[cfg_attr(feature = "serde", serde(with = "base64"))]
     pub protocol_data: Vec<u8>,
 }
 
@@ -42,10 +50,26 @@ impl fmt::Display for ParseError {
     }
 }
 
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParseError::Io(e) => Some(e),
+            ParseError::InvalidFile => None,
+        }
+    }
+}
+
 fn read_sized_string<R: Read>(mut reader: R) -> io::Result<Vec<u8>> {
     let len = reader.read_u16_be()?;
     let mut string = vec![];
     reader.take(u64::from(len)).read_to_end(&mut string)?;
+    // Truncated file would otherwise yield entry with partial cookie
+    if string.len() != usize::from(len) {
+        return Err(io::Error::new(
+            UnexpectedEof,
+            "Xauthority entry is truncated",
+        ));
+    }
     Ok(string)
 }
 
@@ -64,7 +88,9 @@ fn read_entry<R: Read>(mut reader: R) -> Result<Option<XAuthEntry>, ParseError>
     let protocol_data = read_sized_string(&mut reader)?;
 
     let display_name = String::from_utf8_lossy(&raw_display_name).to_string();
-    let display_number: u16 = String::from_utf8_lossy(&raw_display_number).parse().map_err(|_| ParseError::InvalidFile)?;
+    let display_number: u16 = String::from_utf8_lossy(&raw_display_number)
+        .parse()
+        .map_err(|_| ParseError::InvalidFile)?;
     let protocol_name = String::from_utf8_lossy(&raw_protocol_name).to_string();
 
     Ok(Some(XAuthEntry {
@@ -109,13 +135,50 @@ pub(crate) fn read_default() -> Result<Vec<XAuthEntry>, ParseError> {
         },
     };
 
-    Ok(from_reader(File::open(xauthority_path)?)?)
+    from_reader(File::open(xauthority_path)?)
+}
+
+/// Name of the machine, used to match local Xauthority entries.
+fn local_hostname() -> Option<String> {
+    HOSTNAME_PATHS
+        .iter()
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .map(|hostname| hostname.trim().to_string())
+        .find(|hostname| !hostname.is_empty())
+}
+
+/// Find entries authorizing connection to local display, in Xauthority file order.
+pub(crate) fn find_entries<'a>(
+    entries: &'a [XAuthEntry],
+    display: &Display,
+) -> Vec<&'a XAuthEntry> {
+    // TODO: Match entries for remote displays
+    if display.hostname.is_some() && !display.is_unix_socket_path() {
+        return vec![];
+    }
+
+    let hostname = local_hostname();
+
+    entries
+        .iter()
+        .filter(|entry| {
+            let family = entry.connection_family;
+            let address_matches = family.is_wild()
+                || (family.is_local() && Some(&entry.display_name) == hostname.as_ref());
+
+            address_matches && entry.display_number == display.display
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{from_reader, read_default, ConnectionFamily, XAuthEntry};
+    use super::{
+        find_entries, from_reader, local_hostname, read_default, ConnectionFamily, XAuthEntry,
+    };
+    use crate::display::Display;
     use std::env;
+    use std::error::Error;
     use std::fs::{create_dir_all, File};
     use std::io::{self, Write};
 
@@ -171,6 +234,73 @@ mod tests {
         assert!(from_reader(b"\x01\0\0\x08hostname".as_ref()).is_err());
     }
 
+    #[test]
+    fn test_parse_error_source() {
+        let error = from_reader(b"\x01\0\0\x08host".as_ref()).unwrap_err();
+        let source = error.source().unwrap();
+        assert_eq!(
+            source.downcast_ref::<io::Error>().unwrap().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+
+        let error = from_reader(b"\x12\x34".as_ref()).unwrap_err();
+        assert!(error.source().is_none());
+    }
+
+    #[test]
+    fn test_read_truncated_protocol_data() {
+        let data = b"\x01\0\0\x08hostname\0\x010\0\x12MIT-MAGIC-COOKIE-1\0\x10abcd";
+        assert!(from_reader(data.as_ref()).is_err());
+    }
+
+    #[test]
+    fn test_find_entries() {
+        let entries = vec![
+            XAuthEntry {
+                connection_family: ConnectionFamily::Local,
+                display_name: "other-host".into(),
+                display_number: 1,
+                protocol_name: "MIT-MAGIC-COOKIE-1".into(),
+                protocol_data: b"\x01".to_vec(),
+            },
+            XAuthEntry {
+                connection_family: ConnectionFamily::Wild,
+                display_name: "".into(),
+                display_number: 0,
+                protocol_name: "MIT-MAGIC-COOKIE-1".into(),
+                protocol_data: b"\x02".to_vec(),
+            },
+            XAuthEntry {
+                connection_family: ConnectionFamily::Local,
+                display_name: local_hostname().unwrap_or_default(),
+                display_number: 1,
+                protocol_name: "MIT-MAGIC-COOKIE-1".into(),
+                protocol_data: b"\x03".to_vec(),
+            },
+            XAuthEntry {
+                connection_family: ConnectionFamily::Wild,
+                display_name: "".into(),
+                display_number: 0,
+                protocol_name: "MIT-MAGIC-COOKIE-1".into(),
+                protocol_data: b"\x04".to_vec(),
+            },
+        ];
+
+        let display = Display::new(None, 0, None);
+        let found = find_entries(&entries, &display);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].protocol_data, b"\x02");
+        assert_eq!(found[1].protocol_data, b"\x04");
+
+        let display = Display::new(None, 1, None);
+        let found = find_entries(&entries, &display);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].protocol_data, b"\x03");
+
+        let display = Display::new(None, 2, None);
+        assert!(find_entries(&entries, &display).is_empty());
+    }
+
     #[test]
     fn test_read_default() -> io::Result<()> {
         let xauth_path = env::temp_dir().join("xodium-tests-xauth");
@@ -236,4 +366,19 @@ mod tests {
 
         Ok(())
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let entry = from_reader(XAUTH_FILE_SINGLE_ENTRY_EXAMPLE)
+            .unwrap()
+            .remove(0);
+
+        let json = serde_json::to_string(&entry).unwrap();
+        assert_eq!(
+            json,
+            r#"{"connection_family":"Local","display_name":"hostname","display_number":0,"protocol_name":"MIT-MAGIC-COOKIE-1","protocol_data":"q83v"}"#
+        );
+        assert_eq!(serde_json::from_str::<XAuthEntry>(&json).unwrap(), entry);
+    }
 }
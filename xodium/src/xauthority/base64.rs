@@ -0,0 +1,91 @@
+//! Base64 (RFC 4648, padded) encoding of binary Xauthority data for serde.
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serializer};
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn encode(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let triple = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let index = (triple >> (18 - 6 * i)) & 0x3f;
+                encoded.push(char::from(ALPHABET[index as usize]));
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+fn decode(encoded: &str) -> Option<Vec<u8>> {
+    let encoded = encoded.as_bytes();
+    if !encoded.len().is_multiple_of(4) {
+        return None;
+    }
+
+    let mut data = Vec::with_capacity(encoded.len() / 4 * 3);
+    for (n, chunk) in encoded.chunks(4).enumerate() {
+        let is_last = n == encoded.len() / 4 - 1;
+        let padding = chunk.iter().rev().take_while(|&&c| c == b'=').count();
+        if padding > 2 || (padding > 0 && !is_last) {
+            return None;
+        }
+
+        let mut triple = 0u32;
+        for &c in &chunk[..4 - padding] {
+            let value = ALPHABET.iter().position(|&a| a == c)? as u32;
+            triple = (triple << 6) | value;
+        }
+        triple <<= 6 * padding as u32;
+
+        data.extend(&triple.to_be_bytes()[1..4 - padding]);
+    }
+
+    Some(data)
+}
+
+pub(crate) fn serialize<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&encode(data))
+}
+
+pub(crate) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    let encoded = String::deserialize(deserializer)?;
+    decode(&encoded).ok_or_else(|| D::Error::custom("invalid base64"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, encode};
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(encode(b""), "");
+        assert_eq!(encode(b"\xab"), "qw==");
+        assert_eq!(encode(b"\xab\xcd"), "q80=");
+        assert_eq!(encode(b"\xab\xcd\xef"), "q83v");
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode("").unwrap(), b"");
+        assert_eq!(decode("qw==").unwrap(), b"\xab");
+        assert_eq!(decode("q80=").unwrap(), b"\xab\xcd");
+        assert_eq!(decode("q83v").unwrap(), b"\xab\xcd\xef");
+        assert!(decode("q8").is_none());
+        assert!(decode("q=3v").is_none());
+        assert!(decode("qw==q83v").is_none());
+        assert!(decode("q8!v").is_none());
+    }
+}
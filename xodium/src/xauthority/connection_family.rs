@@ -1,5 +1,6 @@
 // https://gitlab.freedesktop.org/xorg/lib/libxau/blob/master/include/X11/Xauth.h#L61-65
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) enum ConnectionFamily {
     /// for local non-net authentication
     LocalHost = 252,
@@ -12,6 +13,7 @@ pub(crate) enum ConnectionFamily {
     Wild = 65535,
 }
 
+#[allow(dead_code)]
 impl ConnectionFamily {
     pub fn try_from(raw_family: u16) -> Option<ConnectionFamily> {
         match raw_family {
@@ -25,38 +27,23 @@ impl ConnectionFamily {
     }
 
     pub fn is_localhost(&self) -> bool {
-        match *self {
-            ConnectionFamily::LocalHost => true,
-            _ => false,
-        }
+        matches!(*self, ConnectionFamily::LocalHost)
     }
 
     pub fn is_krb5principal(&self) -> bool {
-        match *self {
-            ConnectionFamily::Krb5Principal => true,
-            _ => false,
-        }
+        matches!(*self, ConnectionFamily::Krb5Principal)
     }
 
     pub fn is_netname(&self) -> bool {
-        match *self {
-            ConnectionFamily::Netname => true,
-            _ => false,
-        }
+        matches!(*self, ConnectionFamily::Netname)
     }
 
     pub fn is_local(&self) -> bool {
-        match *self {
-            ConnectionFamily::Local => true,
-            _ => false,
-        }
+        matches!(*self, ConnectionFamily::Local)
     }
 
     pub fn is_wild(&self) -> bool {
-        match *self {
-            ConnectionFamily::Wild => true,
-            _ => false,
-        }
+        matches!(*self, ConnectionFamily::Wild)
     }
 }
 